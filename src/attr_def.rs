@@ -0,0 +1,191 @@
+//! $AttrDef (FILE record 4) defines the attribute types of the volume: their names, whether they
+//! can be indexed or have to stay resident, and the smallest and largest value they may hold.
+//! It's a list of 0xA0 byte entries ending with an entry whose type is zero.
+//!
+//!| Offset | Size | Description                                  |
+//!|--------|------|----------------------------------------------|
+//!| 0x00   | 128  | Name in UTF-16, padded with zeros            |
+//!| 0x80   | 4    | Attribute type                               |
+//!| 0x84   | 4    | Display rule                                 |
+//!| 0x88   | 4    | Collation rule                               |
+//!| 0x8C   | 4    | Flags                                        |
+//!| 0x90   | 8    | Minimum size                                 |
+//!| 0x98   | 8    | Maximum size, -1 when unlimited              |
+//!
+//!<https://flatcap.github.io/linux-ntfs/ntfs/files/attrdef.html>
+use crate::{
+    bytes::{u32_at, u64_at},
+    Attribute, AttributeType, FileRecord, Ntfs, NtfsError, Residency, SystemFile,
+};
+use std::io::{Read, Seek};
+
+///The size of an entry in $AttrDef.
+pub const ATTR_DEF_ENTRY_SIZE: usize = 0xA0;
+
+///Unknown bits are kept as they are, so the raw value can always be recovered with `bits`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttrDefFlags(pub u32);
+
+impl AttrDefFlags {
+    pub const INDEXABLE: AttrDefFlags = AttrDefFlags(0x0002);
+    ///More than one attribute of the type can be in a FILE record.
+    pub const MULTIPLE: AttrDefFlags = AttrDefFlags(0x0004);
+    pub const NOT_ZERO: AttrDefFlags = AttrDefFlags(0x0008);
+    pub const INDEXED_UNIQUE: AttrDefFlags = AttrDefFlags(0x0010);
+    pub const NAMED_UNIQUE: AttrDefFlags = AttrDefFlags(0x0020);
+    pub const RESIDENT: AttrDefFlags = AttrDefFlags(0x0040);
+    ///Changes are logged even when the attribute is non-resident.
+    pub const ALWAYS_LOG: AttrDefFlags = AttrDefFlags(0x0080);
+
+    const NAMES: [(AttrDefFlags, &'static str); 7] = [
+        (AttrDefFlags::INDEXABLE, "INDEXABLE"),
+        (AttrDefFlags::MULTIPLE, "MULTIPLE"),
+        (AttrDefFlags::NOT_ZERO, "NOT_ZERO"),
+        (AttrDefFlags::INDEXED_UNIQUE, "INDEXED_UNIQUE"),
+        (AttrDefFlags::NAMED_UNIQUE, "NAMED_UNIQUE"),
+        (AttrDefFlags::RESIDENT, "RESIDENT"),
+        (AttrDefFlags::ALWAYS_LOG, "ALWAYS_LOG"),
+    ];
+
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    pub fn contains(&self, other: AttrDefFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    ///The names of the flags that are set, e.g. ["INDEXABLE", "RESIDENT"]. Unknown bits are left out.
+    pub fn names(&self) -> Vec<&'static str> {
+        Self::NAMES
+            .iter()
+            .filter(|(flag, _)| self.contains(*flag))
+            .map(|(_, name)| *name)
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttrDef {
+    ///The name of the type, e.g. "$STANDARD_INFORMATION".
+    pub name: String,
+    pub type_code: AttributeType,
+    pub display_rule: u32,
+    pub collation_rule: u32,
+    pub flags: AttrDefFlags,
+    pub minimum_size: u64,
+    ///`u64::MAX` when the size isn't limited.
+    pub maximum_size: u64,
+}
+
+impl AttrDef {
+    pub fn parse(entry: &[u8]) -> Result<AttrDef, NtfsError> {
+        if entry.len() < ATTR_DEF_ENTRY_SIZE {
+            return Err(NtfsError::BufferTooSmall {
+                needed: ATTR_DEF_ENTRY_SIZE,
+                available: entry.len(),
+            });
+        }
+        let units: Vec<u16> = entry[..0x80]
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+            .take_while(|unit| *unit != 0)
+            .collect();
+        Ok(AttrDef {
+            name: String::from_utf16_lossy(&units),
+            type_code: AttributeType::from(u32_at(entry, 0x80)),
+            display_rule: u32_at(entry, 0x84),
+            collation_rule: u32_at(entry, 0x88),
+            flags: AttrDefFlags(u32_at(entry, 0x8C)),
+            minimum_size: u64_at(entry, 0x90),
+            maximum_size: u64_at(entry, 0x98),
+        })
+    }
+}
+
+///The attribute definitions of a volume, in the order $AttrDef lists them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttrDefTable(pub Vec<AttrDef>);
+
+impl AttrDefTable {
+    ///Parses entries up to the first one whose type is zero, or the end of `bytes`.
+    pub fn parse(bytes: &[u8]) -> Result<AttrDefTable, NtfsError> {
+        bytes
+            .chunks_exact(ATTR_DEF_ENTRY_SIZE)
+            .take_while(|entry| u32_at(entry, 0x80) != 0)
+            .map(AttrDef::parse)
+            .collect::<Result<_, _>>()
+            .map(AttrDefTable)
+    }
+
+    ///The definition of `type_code`, if the volume defines it.
+    pub fn get(&self, type_code: AttributeType) -> Option<&AttrDef> {
+        self.0.iter().find(|def| def.type_code == type_code)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, AttrDef> {
+        self.0.iter()
+    }
+
+    ///Checks `attribute` against the definition of its type: the type has to be defined,
+    ///attributes that have to be resident can't be non-resident, and the value has to be within
+    ///the minimum and maximum size. Since only the first extent of a non-resident attribute
+    ///holds its real size, the size of later extents isn't checked.
+    pub fn check(&self, attribute: &Attribute) -> Result<(), NtfsError> {
+        let type_code = attribute.type_code();
+        let violation = |reason| NtfsError::AttrDefViolation { type_code, reason };
+        let def = self
+            .get(type_code)
+            .ok_or(violation("type isn't defined in $AttrDef"))?;
+
+        let size = match attribute.header().residency {
+            Residency::Resident { value_length, .. } => Some(value_length as u64),
+            _ if def.flags.contains(AttrDefFlags::RESIDENT) => {
+                return Err(violation("attribute has to be resident"));
+            }
+            Residency::NonResident {
+                start_vcn: 0,
+                real_size,
+                ..
+            } => Some(real_size),
+            Residency::NonResident { .. } => None,
+        };
+        match size {
+            Some(size) if size < def.minimum_size => {
+                Err(violation("value is below the minimum size"))
+            }
+            Some(size) if size > def.maximum_size => {
+                Err(violation("value is above the maximum size"))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    ///Checks every attribute of `record`, stopping at the first violation.
+    pub fn check_record(&self, record: &FileRecord) -> Result<(), NtfsError> {
+        for attribute in record.attributes() {
+            self.check(&attribute?)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> IntoIterator for &'a AttrDefTable {
+    type Item = &'a AttrDef;
+    type IntoIter = std::slice::Iter<'a, AttrDef>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///Reads the attribute definition table of the volume.
+    pub fn attr_def_table(&mut self) -> Result<AttrDefTable, NtfsError> {
+        let record = self.file_record(SystemFile::AttrDef.record_number())?;
+        AttrDefTable::parse(&self.read_data(&record, None)?)
+    }
+}
@@ -0,0 +1,222 @@
+//! When the attributes of a file don't fit in one FILE record, they spill into extension records.
+//! The $ATTRIBUTE_LIST (0x20) attribute in the base record lists every attribute and the record holding it.
+//! A large non-resident attribute can itself be split across several records,
+//! each piece covering a different range of VCNs.
+//!
+//!| Offset | Size | Description                           |
+//!|--------|------|---------------------------------------|
+//!| 0x00   | 4    | Type                                  |
+//!| 0x04   | 2    | Record length                         |
+//!| 0x06   | 1    | Name length (N)                       |
+//!| 0x07   | 1    | Offset to the name                    |
+//!| 0x08   | 8    | Starting VCN                          |
+//!| 0x10   | 8    | Base / extension file reference       |
+//!| 0x18   | 2    | Attribute Id                          |
+//!| 0x1A   | 2N   | Name in Unicode (if N > 0)            |
+//!
+//!<https://flatcap.github.io/linux-ntfs/ntfs/attributes/attribute_list.html>
+use crate::{
+    bytes::{u16_at, u32_at, u64_at},
+    AttributeHeader, AttributeType, DataRun, FileRecord, FileReference, Ntfs, NtfsError, Residency,
+};
+use std::io::{Read, Seek};
+
+///The size of an attribute list entry without its name.
+pub const ATTRIBUTE_LIST_ENTRY_SIZE: usize = 0x1A;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AttributeListEntry {
+    pub type_code: AttributeType,
+    pub record_length: u16,
+    pub name_length: u8,
+    pub name_offset: u8,
+    pub starting_vcn: u64,
+    ///The FILE record holding the attribute, which may be the base record itself.
+    pub file_reference: FileReference,
+    pub attribute_id: u16,
+    pub name: Option<String>,
+}
+
+///Parses the value of an $ATTRIBUTE_LIST attribute.
+pub fn parse_attribute_list(value: &[u8]) -> Result<Vec<AttributeListEntry>, NtfsError> {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    while offset + ATTRIBUTE_LIST_ENTRY_SIZE <= value.len() {
+        let entry = &value[offset..];
+        let record_length = u16_at(entry, 0x04);
+        if (record_length as usize) < ATTRIBUTE_LIST_ENTRY_SIZE
+            || offset + record_length as usize > value.len()
+        {
+            return Err(NtfsError::Corrupt(
+                "attribute list entry runs past the attribute list",
+            ));
+        }
+
+        let name_length = entry[0x06] as usize;
+        let name = if name_length > 0 {
+            let start = entry[0x07] as usize;
+            let end = start + name_length * 2;
+            if end > record_length as usize {
+                return Err(NtfsError::Corrupt(
+                    "attribute list name runs past the entry",
+                ));
+            }
+            let units: Vec<u16> = entry[start..end]
+                .chunks_exact(2)
+                .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                .collect();
+            Some(String::from_utf16_lossy(&units))
+        } else {
+            None
+        };
+
+        entries.push(AttributeListEntry {
+            type_code: AttributeType::from(u32_at(entry, 0x00)),
+            record_length,
+            name_length: entry[0x06],
+            name_offset: entry[0x07],
+            starting_vcn: u64_at(entry, 0x08),
+            file_reference: FileReference::from_raw(u64_at(entry, 0x10)),
+            attribute_id: u16_at(entry, 0x18),
+            name,
+        });
+        offset += record_length as usize;
+    }
+    Ok(entries)
+}
+
+///An attribute with all of its pieces put back together.
+///Non-resident attributes split across extension records have their runs combined in VCN order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MergedAttribute {
+    pub type_code: AttributeType,
+    pub name: Option<String>,
+    ///The header of the first piece, which holds the sizes of the whole attribute.
+    pub header: AttributeHeader,
+    ///The value of a resident attribute.
+    pub value: Option<Vec<u8>>,
+    ///The combined runs of a non-resident attribute.
+    pub runs: Vec<DataRun>,
+}
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///The entries of the $ATTRIBUTE_LIST in `record`, reading it from disk if it's non-resident.
+    ///Returns `None` if the record has no attribute list.
+    pub fn attribute_list(
+        &mut self,
+        record: &FileRecord,
+    ) -> Result<Option<Vec<AttributeListEntry>>, NtfsError> {
+        let Some(attribute) = record.find_attribute(AttributeType::AttributeList)? else {
+            return Ok(None);
+        };
+        let value = self.read_attribute(&attribute)?;
+        parse_attribute_list(&value).map(Some)
+    }
+
+    ///All the attributes of FILE record `record_number`, including the ones in its extension records.
+    pub fn attributes_of(&mut self, record_number: u64) -> Result<Vec<MergedAttribute>, NtfsError> {
+        let base = self.file_record(record_number)?;
+        self.merged_attributes(record_number, &base)
+    }
+
+    ///Merges the attributes of `base` with the ones in the extension records its attribute list points at.
+    pub(crate) fn merged_attributes(
+        &mut self,
+        record_number: u64,
+        base: &FileRecord,
+    ) -> Result<Vec<MergedAttribute>, NtfsError> {
+        let mut extensions: Vec<FileReference> = Vec::new();
+        for entry in self.attribute_list(base)?.unwrap_or_default() {
+            let reference = entry.file_reference;
+            if reference.record_number != record_number && !extensions.contains(&reference) {
+                extensions.push(reference);
+            }
+        }
+
+        let mut records = Vec::new();
+        for reference in extensions {
+            let record = self.file_record(reference.record_number)?;
+            if record.sequence_number != reference.sequence_number {
+                return Err(NtfsError::Corrupt(
+                    "attribute list points at a reused extension record",
+                ));
+            }
+            records.push(record);
+        }
+        merge_attributes(std::iter::once(base).chain(&records))
+    }
+
+    ///Reads the value of a merged attribute, see [`Ntfs::read_attribute`].
+    pub fn read_merged_attribute(
+        &mut self,
+        attribute: &MergedAttribute,
+    ) -> Result<Vec<u8>, NtfsError> {
+        match &attribute.value {
+            Some(value) => Ok(value.clone()),
+            None => self.read_runs(&attribute.header, attribute.runs.clone()),
+        }
+    }
+}
+
+///Puts the attributes of `records` back together, skipping the $ATTRIBUTE_LIST itself.
+pub(crate) fn merge_attributes<'a>(
+    records: impl Iterator<Item = &'a FileRecord>,
+) -> Result<Vec<MergedAttribute>, NtfsError> {
+    //Every piece along with its starting VCN.
+    let mut pieces = Vec::new();
+    for record in records {
+        for attribute in record.attributes() {
+            let attribute = attribute?;
+            if attribute.type_code() == AttributeType::AttributeList {
+                continue;
+            }
+            let (start_vcn, runs) = match attribute.header().residency {
+                Residency::Resident { .. } => (0, Vec::new()),
+                Residency::NonResident { start_vcn, .. } => (start_vcn, attribute.data_runs()?),
+            };
+            let piece = MergedAttribute {
+                type_code: attribute.type_code(),
+                name: attribute.name().map(str::to_string),
+                header: attribute.header().clone(),
+                value: attribute.value().map(<[u8]>::to_vec),
+                runs,
+            };
+            pieces.push((start_vcn, piece));
+        }
+    }
+    pieces.sort_by(|(a_vcn, a), (b_vcn, b)| {
+        (a.type_code, &a.name, a_vcn).cmp(&(b.type_code, &b.name, b_vcn))
+    });
+
+    let mut merged: Vec<MergedAttribute> = Vec::new();
+    for (start_vcn, piece) in pieces {
+        match merged.last_mut() {
+            //Resident attributes can't be split, so a repeated one is a separate attribute,
+            //like the Win32 and DOS $FILE_NAME of a file.
+            Some(last)
+                if last.type_code == piece.type_code
+                    && last.name == piece.name
+                    && last.header.non_resident
+                    && piece.header.non_resident =>
+            {
+                let (
+                    Residency::NonResident { end_vcn, .. },
+                    Residency::NonResident {
+                        end_vcn: piece_end_vcn,
+                        ..
+                    },
+                ) = (&mut last.header.residency, &piece.header.residency)
+                else {
+                    return Err(NtfsError::Corrupt("resident attribute is split"));
+                };
+                if start_vcn != end_vcn.wrapping_add(1) {
+                    return Err(NtfsError::Corrupt("attribute pieces are not contiguous"));
+                }
+                *end_vcn = *piece_end_vcn;
+                last.runs.extend(piece.runs);
+            }
+            _ => merged.push(piece),
+        }
+    }
+    Ok(merged)
+}
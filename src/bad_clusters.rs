@@ -0,0 +1,104 @@
+//! $BadClus (FILE record 8) keeps chkdsk from reusing defective clusters by allocating them to itself.
+//! Its $DATA attribute named "$Bad" is as large as the volume and sparse everywhere except
+//! the bad clusters, whose runs point at themselves. The unnamed $DATA attribute is empty.
+use crate::{AttributeType, MasterFileTable, Ntfs, NtfsError, SystemFile};
+use std::{
+    io::{Read, Seek},
+    ops::Range,
+};
+
+///An attribute whose clusters overlap the bad clusters of the volume.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BadClusterFile {
+    ///The base record, even when the attribute is in an extension record.
+    pub record_number: u64,
+    pub type_code: AttributeType,
+    pub name: Option<String>,
+    ///The bad clusters the attribute's runs point at, in run order.
+    pub bad_clusters: Vec<Range<u64>>,
+}
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///The bad clusters of the volume as LCN ranges, read from the run list of $BadClus:$Bad.
+    pub fn bad_clusters(&mut self) -> Result<Vec<Range<u64>>, NtfsError> {
+        let record_number = SystemFile::BadClus.record_number();
+        let record = self.file_record(record_number)?;
+        let bad = self
+            .merged_attributes(record_number, &record)?
+            .into_iter()
+            .find(|a| a.type_code == AttributeType::Data && a.name.as_deref() == Some("$Bad"))
+            .ok_or_else(|| NtfsError::NotFound(String::from("$BadClus:$Bad")))?;
+        if bad.value.is_some() {
+            return Err(NtfsError::Corrupt("$BadClus:$Bad is resident"));
+        }
+        Ok(bad
+            .runs
+            .into_iter()
+            .filter_map(|run| Some(run.lcn?..run.lcn? + run.length_clusters))
+            .collect())
+    }
+
+    ///Returns true if $BadClus:$Bad marks any cluster as bad.
+    pub fn has_bad_clusters(&mut self) -> Result<bool, NtfsError> {
+        Ok(!self.bad_clusters()?.is_empty())
+    }
+}
+
+impl<R: Read + Seek> MasterFileTable<'_, R> {
+    ///Finds the attributes whose data sits on a bad cluster, by intersecting the run list of every
+    ///non-resident attribute in the $MFT with [`Ntfs::bad_clusters`]. Records that fail to parse
+    ///are skipped, since a damaged volume is what this is for. $BadClus itself isn't reported.
+    pub fn files_on_bad_clusters(&mut self) -> Result<Vec<BadClusterFile>, NtfsError> {
+        let bad = self.ntfs.bad_clusters()?;
+        let mut files: Vec<BadClusterFile> = Vec::new();
+        if bad.is_empty() {
+            return Ok(files);
+        }
+        for record in self.ntfs.file_records() {
+            let record = match record {
+                Ok(record) => record,
+                Err(err @ NtfsError::Io(_)) => return Err(err),
+                Err(_) => continue,
+            };
+            let record_number = match record.base_record_reference.record_number {
+                0 => record.mft_record_number as u64,
+                base => base,
+            };
+            if !record.is_in_use() || record_number == SystemFile::BadClus.record_number() {
+                continue;
+            }
+            for attribute in record.attributes().map_while(Result::ok) {
+                let Ok(runs) = attribute.data_runs() else {
+                    continue;
+                };
+                let overlaps: Vec<Range<u64>> = runs
+                    .iter()
+                    .filter_map(|run| Some(run.lcn?..run.lcn? + run.length_clusters))
+                    .flat_map(|run| {
+                        bad.iter()
+                            .map(move |bad| run.start.max(bad.start)..run.end.min(bad.end))
+                    })
+                    .filter(|overlap| !overlap.is_empty())
+                    .collect();
+                if overlaps.is_empty() {
+                    continue;
+                }
+                let name = attribute.name().map(str::to_string);
+                match files.iter_mut().find(|file| {
+                    file.record_number == record_number
+                        && file.type_code == attribute.type_code()
+                        && file.name == name
+                }) {
+                    Some(file) => file.bad_clusters.extend(overlaps),
+                    None => files.push(BadClusterFile {
+                        record_number,
+                        type_code: attribute.type_code(),
+                        name,
+                        bad_clusters: overlaps,
+                    }),
+                }
+            }
+        }
+        Ok(files)
+    }
+}
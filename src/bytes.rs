@@ -0,0 +1,24 @@
+//! Little-endian helpers for reading fields out of on-disk structures.
+//! Callers are responsible for making sure the field lies within the buffer.
+
+pub(crate) fn u16_at(buf: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([buf[offset], buf[offset + 1]])
+}
+
+pub(crate) fn u32_at(buf: &[u8], offset: usize) -> u32 {
+    let mut bytes = [0u8; 4];
+    bytes.copy_from_slice(&buf[offset..offset + 4]);
+    u32::from_le_bytes(bytes)
+}
+
+pub(crate) fn u64_at(buf: &[u8], offset: usize) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&buf[offset..offset + 8]);
+    u64::from_le_bytes(bytes)
+}
+
+pub(crate) fn guid_at(buf: &[u8], offset: usize) -> crate::Guid {
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&buf[offset..offset + 16]);
+    crate::Guid(bytes)
+}
@@ -0,0 +1,302 @@
+//! $Bitmap (FILE record 6) tracks which clusters of the volume are in use.
+//! Its unnamed $DATA attribute holds one bit per cluster, least significant bit first,
+//! set when the cluster is allocated. The bitmap is padded to a multiple of 8 bytes,
+//! so it can describe a few clusters past the end of the volume.
+//!
+//! The bitmap of a 100 TB volume with 4K clusters takes over 3 GB, so [`Ntfs::free_extents`]
+//! and [`Ntfs::usage`] read it in chunks of [`BITMAP_CHUNK_SIZE`] instead of loading it whole.
+use crate::{Ntfs, NtfsError, NtfsStreamReader, SystemFile};
+use std::io::{Read, Seek};
+
+///The number of bitmap bytes read at a time by [`Ntfs::free_extents`] and [`Ntfs::usage`].
+pub const BITMAP_CHUNK_SIZE: usize = 0x10000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClusterBitmap {
+    bytes: Vec<u8>,
+    cluster_count: u64,
+}
+
+impl ClusterBitmap {
+    ///Wraps the raw bitmap of a volume with `cluster_count` clusters.
+    ///Bits past the end of the volume are ignored, and clusters missing from a short bitmap are not counted.
+    pub fn new(bytes: Vec<u8>, cluster_count: u64) -> ClusterBitmap {
+        let cluster_count = cluster_count.min((bytes.len() as u64).saturating_mul(8));
+        ClusterBitmap {
+            bytes,
+            cluster_count,
+        }
+    }
+
+    ///The number of clusters described by the bitmap.
+    pub fn cluster_count(&self) -> u64 {
+        self.cluster_count
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    ///Returns true if cluster `lcn` is in use. Clusters past the end of the volume can't be used,
+    ///so they're reported as allocated.
+    pub fn is_allocated(&self, lcn: u64) -> bool {
+        if lcn >= self.cluster_count {
+            return true;
+        }
+        self.bytes[(lcn / 8) as usize] & (1 << (lcn % 8)) != 0
+    }
+
+    pub fn free_cluster_count(&self) -> u64 {
+        let full_bytes = (self.cluster_count / 8) as usize;
+        let mut free: u64 = self.bytes[..full_bytes]
+            .iter()
+            .map(|byte| byte.count_zeros() as u64)
+            .sum();
+        for lcn in full_bytes as u64 * 8..self.cluster_count {
+            if !self.is_allocated(lcn) {
+                free += 1;
+            }
+        }
+        free
+    }
+
+    ///Same as `free_cluster_count`.
+    pub fn free_clusters(&self) -> u64 {
+        self.free_cluster_count()
+    }
+
+    pub fn allocated_cluster_count(&self) -> u64 {
+        self.cluster_count - self.free_cluster_count()
+    }
+
+    ///Same as `allocated_cluster_count`.
+    pub fn used_clusters(&self) -> u64 {
+        self.allocated_cluster_count()
+    }
+
+    ///Iterates over the runs of free clusters as (first LCN, length in clusters), in LCN order.
+    pub fn free_runs(&self) -> FreeRuns<'_> {
+        FreeRuns {
+            bitmap: self,
+            next: 0,
+        }
+    }
+}
+
+///Iterates over the free runs of a [`ClusterBitmap`]. Created with [`ClusterBitmap::free_runs`].
+pub struct FreeRuns<'a> {
+    bitmap: &'a ClusterBitmap,
+    next: u64,
+}
+
+impl FreeRuns<'_> {
+    ///The first cluster from `lcn` onwards that is allocated if `allocated` is set, or free if not.
+    fn next_matching(&self, mut lcn: u64, allocated: bool) -> u64 {
+        //Skip whole bytes that can't hold a match.
+        let skip = if allocated { 0x00 } else { 0xFF };
+        while lcn < self.bitmap.cluster_count {
+            if lcn.is_multiple_of(8) && self.bitmap.bytes[(lcn / 8) as usize] == skip {
+                lcn += 8;
+                continue;
+            }
+            if self.bitmap.is_allocated(lcn) == allocated {
+                return lcn;
+            }
+            lcn += 1;
+        }
+        self.bitmap.cluster_count
+    }
+}
+
+impl Iterator for FreeRuns<'_> {
+    type Item = (u64, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.next_matching(self.next, false);
+        if start >= self.bitmap.cluster_count {
+            self.next = start;
+            return None;
+        }
+        let end = self
+            .next_matching(start, true)
+            .min(self.bitmap.cluster_count);
+        self.next = end;
+        Some((start, end - start))
+    }
+}
+
+///How much of the volume is in use, according to $Bitmap. Created with [`Ntfs::usage`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VolumeUsage {
+    pub cluster_size: u64,
+    pub total_clusters: u64,
+    pub free_clusters: u64,
+}
+
+impl VolumeUsage {
+    pub fn used_clusters(&self) -> u64 {
+        self.total_clusters - self.free_clusters
+    }
+
+    pub fn total_bytes(&self) -> u64 {
+        self.total_clusters.saturating_mul(self.cluster_size)
+    }
+
+    pub fn free_bytes(&self) -> u64 {
+        self.free_clusters.saturating_mul(self.cluster_size)
+    }
+
+    pub fn used_bytes(&self) -> u64 {
+        self.used_clusters().saturating_mul(self.cluster_size)
+    }
+}
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///Reads the whole cluster allocation bitmap from $Bitmap into memory.
+    ///See [`Ntfs::free_extents`] and [`Ntfs::usage`] for large volumes.
+    pub fn cluster_bitmap(&mut self) -> Result<ClusterBitmap, NtfsError> {
+        let record = self.file_record(SystemFile::Bitmap.record_number())?;
+        let bytes = self.read_data(&record, None)?;
+        Ok(ClusterBitmap::new(bytes, self.total_clusters()))
+    }
+
+    ///Iterates over the runs of free clusters as (first LCN, length in clusters), reading $Bitmap
+    ///a chunk at a time. Runs crossing a chunk boundary are yielded whole.
+    pub fn free_extents(&mut self) -> Result<FreeExtents<'_, R>, NtfsError> {
+        Ok(FreeExtents {
+            chunks: self.bitmap_chunks()?,
+            chunk: None,
+            pending: None,
+        })
+    }
+
+    ///Counts the used and free clusters, reading $Bitmap a chunk at a time.
+    pub fn usage(&mut self) -> Result<VolumeUsage, NtfsError> {
+        let cluster_size = self.geometry().bytes_per_cluster;
+        let mut chunks = self.bitmap_chunks()?;
+        let mut usage = VolumeUsage {
+            cluster_size,
+            total_clusters: 0,
+            free_clusters: 0,
+        };
+        while let Some((_, chunk)) = chunks.next_chunk()? {
+            usage.total_clusters += chunk.cluster_count();
+            usage.free_clusters += chunk.free_cluster_count();
+        }
+        Ok(usage)
+    }
+
+    ///The number of clusters in the volume, from the boot sector.
+    fn total_clusters(&self) -> u64 {
+        let boot_sector = self.boot_sector();
+        boot_sector.total_sectors / boot_sector.sectors_per_cluster as u64
+    }
+
+    fn bitmap_chunks(&mut self) -> Result<BitmapChunks<'_, R>, NtfsError> {
+        let total_clusters = self.total_clusters();
+        let stream = self
+            .data_streams(SystemFile::Bitmap.record_number())?
+            .into_iter()
+            .find(|stream| !stream.is_alternate())
+            .ok_or(NtfsError::Corrupt("$Bitmap has no $DATA attribute"))?;
+        Ok(BitmapChunks {
+            reader: self.open_stream(&stream)?,
+            first_lcn: 0,
+            total_clusters,
+        })
+    }
+}
+
+///Reads $Bitmap in chunks of [`BITMAP_CHUNK_SIZE`] bytes.
+struct BitmapChunks<'a, R: Read + Seek> {
+    reader: NtfsStreamReader<'a, R>,
+    ///The LCN of the first bit of the next chunk.
+    first_lcn: u64,
+    total_clusters: u64,
+}
+
+impl<R: Read + Seek> BitmapChunks<'_, R> {
+    ///Reads the next chunk, returning the LCN of its first bit along with it.
+    fn next_chunk(&mut self) -> Result<Option<(u64, ClusterBitmap)>, NtfsError> {
+        if self.first_lcn >= self.total_clusters {
+            return Ok(None);
+        }
+        let mut bytes = Vec::with_capacity(BITMAP_CHUNK_SIZE);
+        (&mut self.reader)
+            .take(BITMAP_CHUNK_SIZE as u64)
+            .read_to_end(&mut bytes)?;
+        if bytes.is_empty() {
+            return Ok(None);
+        }
+        let first_lcn = self.first_lcn;
+        let chunk = ClusterBitmap::new(bytes, self.total_clusters - first_lcn);
+        self.first_lcn += chunk.cluster_count();
+        Ok(Some((first_lcn, chunk)))
+    }
+}
+
+///Iterates over the free runs of the volume, reading $Bitmap in chunks. Created with [`Ntfs::free_extents`].
+pub struct FreeExtents<'a, R: Read + Seek> {
+    chunks: BitmapChunks<'a, R>,
+    ///The chunk being scanned, its first LCN and the position of the scan within it.
+    chunk: Option<(u64, ClusterBitmap, u64)>,
+    ///A run reaching the end of the previous chunk, which may continue in the next one.
+    pending: Option<(u64, u64)>,
+}
+
+impl<R: Read + Seek> FreeExtents<'_, R> {
+    fn step(&mut self) -> Result<Option<(u64, u64)>, NtfsError> {
+        loop {
+            let Some((first_lcn, chunk, next)) = &mut self.chunk else {
+                match self.chunks.next_chunk()? {
+                    Some((first_lcn, chunk)) => self.chunk = Some((first_lcn, chunk, 0)),
+                    None => return Ok(self.pending.take()),
+                }
+                continue;
+            };
+
+            let mut runs = FreeRuns {
+                bitmap: chunk,
+                next: *next,
+            };
+            let Some((start, length)) = runs.next() else {
+                self.chunk = None;
+                continue;
+            };
+            *next = runs.next;
+            let reaches_end = start + length == chunk.cluster_count();
+            let start = *first_lcn + start;
+
+            let run = match self.pending.take() {
+                Some((pending_start, pending_length))
+                    if pending_start + pending_length == start =>
+                {
+                    (pending_start, pending_length + length)
+                }
+                Some(pending) => {
+                    self.pending = Some((start, length));
+                    return Ok(Some(pending));
+                }
+                None => (start, length),
+            };
+            if reaches_end {
+                self.pending = Some(run);
+            } else {
+                return Ok(Some(run));
+            }
+        }
+    }
+}
+
+impl<R: Read + Seek> Iterator for FreeExtents<'_, R> {
+    type Item = Result<(u64, u64), NtfsError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.step();
+        if result.is_err() {
+            self.chunk = None;
+            self.chunks.first_lcn = self.chunks.total_clusters;
+        }
+        result.transpose()
+    }
+}
@@ -0,0 +1,233 @@
+//! Small files keep their content inside the FILE record in a resident $DATA attribute.
+//! Larger files store it in clusters, located through the run list of a non-resident $DATA attribute.
+//! Compressed files split their clusters into compression units, see [`decompress_lznt1`].
+use crate::{
+    decompress_lznt1, Attribute, AttributeHeader, AttributeType, DataRun, FileRecord, Flag,
+    MergedAttribute, Ntfs, NtfsError, Residency,
+};
+use std::io::{Read, Seek};
+
+///The compression unit Windows writes, 2^4 = 16 clusters.
+pub const COMPRESSION_UNIT: u16 = 4;
+
+///The largest compression unit, 16 clusters of 4 KB.
+pub const MAX_COMPRESSION_UNIT_SIZE: u64 = 64 * 1024;
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///Reads the content of the $DATA attribute of `record` named `stream_name`,
+    ///so `Some("Zone.Identifier")` reads "file.txt:Zone.Identifier" and `None` reads the file itself.
+    ///If the record has an $ATTRIBUTE_LIST, the pieces of $DATA in its extension records are merged first,
+    ///which relies on the record number stored in the header by Windows XP and later.
+    pub fn read_data(
+        &mut self,
+        record: &FileRecord,
+        stream_name: Option<&str>,
+    ) -> Result<Vec<u8>, NtfsError> {
+        let data = self.data_attribute(record, stream_name)?;
+        self.read_merged_attribute(&data)
+    }
+
+    ///Finds the $DATA attribute of `record` named `stream_name` for [`Ntfs::read_data`] and [`Ntfs::open_reader`],
+    ///merging its pieces if the record has an $ATTRIBUTE_LIST.
+    pub(crate) fn data_attribute(
+        &mut self,
+        record: &FileRecord,
+        stream_name: Option<&str>,
+    ) -> Result<MergedAttribute, NtfsError> {
+        let missing = || match stream_name {
+            Some(name) => NtfsError::NotFound(format!(":{name}")),
+            None => NtfsError::Corrupt("FILE record has no $DATA attribute"),
+        };
+        if record
+            .find_attribute(AttributeType::AttributeList)?
+            .is_some()
+        {
+            return self
+                .merged_attributes(record.mft_record_number as u64, record)?
+                .into_iter()
+                .find(|attribute| {
+                    attribute.type_code == AttributeType::Data
+                        && attribute.name.as_deref() == stream_name
+                })
+                .ok_or_else(missing);
+        }
+        let data = record
+            .data_stream_attribute(stream_name)?
+            .ok_or_else(missing)?;
+        let runs = match data.value() {
+            Some(_) => Vec::new(),
+            None => data.data_runs()?,
+        };
+        Ok(MergedAttribute {
+            type_code: data.type_code(),
+            name: data.name().map(str::to_string),
+            header: data.header().clone(),
+            value: data.value().map(<[u8]>::to_vec),
+            runs,
+        })
+    }
+
+    ///Reads the value of an attribute, wherever it is stored.
+    ///Resident attributes return their value. Non-resident attributes are read through their runs:
+    ///sparse runs read as zeros, bytes past the initialized size read as zeros
+    ///and the output is truncated to the real size, not the allocated size.
+    pub fn read_attribute(&mut self, attribute: &Attribute) -> Result<Vec<u8>, NtfsError> {
+        match attribute.value() {
+            Some(value) => Ok(value.to_vec()),
+            None => self.read_runs(attribute.header(), attribute.data_runs()?),
+        }
+    }
+
+    ///Reads the clusters of a non-resident attribute, using the sizes and flags from its `header`.
+    pub(crate) fn read_runs(
+        &mut self,
+        header: &AttributeHeader,
+        runs: Vec<DataRun>,
+    ) -> Result<Vec<u8>, NtfsError> {
+        let Residency::NonResident {
+            real_size,
+            initialized_size,
+            compression_unit,
+            allocated_size,
+            ..
+        } = header.residency
+        else {
+            return Err(NtfsError::Corrupt("resident attributes have no data runs"));
+        };
+        //The output is allocated up front, so a corrupt size mustn't be trusted.
+        let run_clusters = runs
+            .iter()
+            .try_fold(0u64, |total, run| total.checked_add(run.length_clusters))
+            .ok_or(NtfsError::Corrupt("data runs are too long"))?;
+        let run_bytes = self.geometry().clusters_to_bytes(run_clusters)?;
+        if real_size > allocated_size || real_size > run_bytes {
+            return Err(NtfsError::Corrupt(
+                "attribute is larger than the clusters allocated to it",
+            ));
+        }
+        let initialized_size = initialized_size.min(real_size);
+
+        if header.has_flag(Flag::Compressed) {
+            let unit_clusters = self.compression_unit_clusters(compression_unit)?;
+            let mut output = self.read_compressed(&runs, unit_clusters, real_size)?;
+            output.resize(real_size as usize, 0);
+            output[initialized_size as usize..].fill(0);
+            return Ok(output);
+        }
+
+        let geometry = *self.geometry();
+        let mut output = Vec::new();
+        for run in runs {
+            let remaining = initialized_size.saturating_sub(output.len() as u64);
+            if remaining == 0 {
+                break;
+            }
+            //Don't read the clusters past the initialized part of the file.
+            let clusters = run
+                .length_clusters
+                .min(geometry.vcn_count_for_bytes(remaining));
+            match run.lcn {
+                Some(lcn) => output.extend(self.read_clusters(lcn, clusters)?),
+                None => {
+                    let len = clusters
+                        .saturating_mul(geometry.bytes_per_cluster)
+                        .min(remaining);
+                    output.resize(output.len() + len as usize, 0);
+                }
+            }
+        }
+        output.truncate(initialized_size as usize);
+        output.resize(real_size as usize, 0);
+        Ok(output)
+    }
+
+    ///Reads data stored in compression units of `unit_clusters` clusters.
+    ///A unit with every cluster allocated is stored uncompressed, a unit with no clusters allocated is sparse,
+    ///and anything in between is LZNT1 compressed data followed by sparse clusters.
+    fn read_compressed(
+        &mut self,
+        runs: &[DataRun],
+        unit_clusters: u64,
+        real_size: u64,
+    ) -> Result<Vec<u8>, NtfsError> {
+        let geometry = *self.geometry();
+        //Whole units, so a partly used last unit is still recognised as uncompressed.
+        let total_clusters = geometry
+            .vcn_count_for_bytes(real_size)
+            .next_multiple_of(unit_clusters);
+
+        //The LCN of every cluster in the unit being gathered, or `None` if it's sparse.
+        let mut unit = Vec::with_capacity(unit_clusters as usize);
+        let mut remaining = total_clusters;
+        let mut output = Vec::new();
+        for run in runs {
+            let clusters = run.length_clusters.min(remaining);
+            remaining -= clusters;
+            for n in 0..clusters {
+                let lcn = run
+                    .lcn
+                    .map(|lcn| {
+                        lcn.checked_add(n)
+                            .ok_or(NtfsError::Corrupt("data run overflows the cluster number"))
+                    })
+                    .transpose()?;
+                unit.push(lcn);
+                if unit.len() as u64 == unit_clusters {
+                    output.extend(self.read_compression_unit(&unit, unit_clusters)?);
+                    unit.clear();
+                }
+            }
+        }
+        //The runs can end before the last unit does.
+        if !unit.is_empty() {
+            output.extend(self.read_compression_unit(&unit, unit_clusters)?);
+        }
+        output.truncate(real_size as usize);
+        Ok(output)
+    }
+
+    ///The number of clusters in a compression unit of 2^`compression_unit` clusters.
+    ///Windows only writes units of 16 clusters of at most 4 KB, and every other size is refused
+    ///so a corrupt header can't make a unit too large to allocate.
+    pub(crate) fn compression_unit_clusters(
+        &self,
+        compression_unit: u16,
+    ) -> Result<u64, NtfsError> {
+        if compression_unit != COMPRESSION_UNIT {
+            return Err(NtfsError::Unsupported(
+                "compression units other than 16 clusters",
+            ));
+        }
+        let unit_clusters = 1u64 << compression_unit;
+        if self.geometry().clusters_to_bytes(unit_clusters)? > MAX_COMPRESSION_UNIT_SIZE {
+            return Err(NtfsError::Unsupported(
+                "compressed files on clusters larger than 4 KB",
+            ));
+        }
+        Ok(unit_clusters)
+    }
+
+    ///Reads one compression unit of `unit_clusters` clusters from the LCNs of its clusters,
+    ///`None` for sparse ones, and returns its decompressed bytes.
+    pub(crate) fn read_compression_unit(
+        &mut self,
+        lcns: &[Option<u64>],
+        unit_clusters: u64,
+    ) -> Result<Vec<u8>, NtfsError> {
+        let unit_size = self.geometry().clusters_to_bytes(unit_clusters)? as usize;
+        let allocated: Vec<u64> = lcns.iter().flatten().copied().collect();
+        let mut bytes = Vec::new();
+        for lcn in &allocated {
+            bytes.extend(self.read_cluster(*lcn)?);
+        }
+        if allocated.len() as u64 == unit_clusters {
+            return Ok(bytes);
+        }
+        let mut output = Vec::with_capacity(unit_size);
+        if !allocated.is_empty() {
+            decompress_lznt1(&bytes, &mut output)?;
+        }
+        output.resize(unit_size, 0);
+        Ok(output)
+    }
+}
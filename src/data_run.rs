@@ -0,0 +1,112 @@
+//! Non-resident attributes store where their clusters are as a run list.
+//! Each run starts with a header byte: the low nibble is the size of the length field
+//! and the high nibble is the size of the offset field, both in bytes.
+//!
+//!| Header | Length     | Offset         |
+//!|--------|------------|----------------|
+//!| 0x21   | 0x18       | 0x34 0x56      |
+//!
+//! The offset is a signed delta from the previous run's LCN.
+//! An offset field size of 0 means the run is sparse and has no clusters on disk.
+//! The list ends with a 0x00 header.
+//!
+//!<https://flatcap.github.io/linux-ntfs/ntfs/concepts/data_runs.html>
+use crate::NtfsError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DataRun {
+    ///The logical cluster number of the first cluster, or `None` if the run is sparse.
+    pub lcn: Option<u64>,
+    ///The length of the run in clusters.
+    pub length_clusters: u64,
+}
+
+///Decodes a run list up to the terminating 0x00 header, or the end of `bytes` if it is missing.
+///Use [`Attribute::data_runs`](crate::Attribute::data_runs) to also check the runs against the attribute's VCN range.
+pub fn decode_runs(bytes: &[u8]) -> Result<Vec<DataRun>, NtfsError> {
+    let mut runs = Vec::new();
+    let mut lcn: i64 = 0;
+    let mut i = 0;
+    while let Some(&header) = bytes.get(i) {
+        if header == 0 {
+            break;
+        }
+        let length_size = (header & 0x0F) as usize;
+        let offset_size = (header >> 4) as usize;
+        if length_size == 0 || length_size > 8 || offset_size > 8 {
+            return Err(NtfsError::Corrupt("invalid data run header"));
+        }
+        let fields = i + 1..i + 1 + length_size + offset_size;
+        let Some(fields) = bytes.get(fields) else {
+            return Err(NtfsError::Corrupt("data run is truncated"));
+        };
+
+        let mut length = 0u64;
+        for (n, byte) in fields[..length_size].iter().enumerate() {
+            length |= (*byte as u64) << (n * 8);
+        }
+
+        let run_lcn = if offset_size == 0 {
+            None
+        } else {
+            let mut delta = 0i64;
+            for (n, byte) in fields[length_size..].iter().enumerate() {
+                delta |= (*byte as i64) << (n * 8);
+            }
+            //Sign extend the delta.
+            let shift = 64 - offset_size * 8;
+            delta = (delta << shift) >> shift;
+
+            lcn = lcn
+                .checked_add(delta)
+                .filter(|lcn| *lcn >= 0)
+                .ok_or(NtfsError::Corrupt(
+                    "data run points before the start of the volume",
+                ))?;
+            Some(lcn as u64)
+        };
+
+        runs.push(DataRun {
+            lcn: run_lcn,
+            length_clusters: length,
+        });
+        i += 1 + length_size + offset_size;
+    }
+    Ok(runs)
+}
+
+///The runs of a non-resident attribute along with the VCN each one starts at,
+///used by the readers to find the run behind a stream position.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RunTable {
+    runs: Vec<(u64, DataRun)>,
+}
+
+impl RunTable {
+    ///Lays out `runs` one after another starting at `start_vcn`.
+    pub(crate) fn new(start_vcn: u64, runs: Vec<DataRun>) -> Result<Self, NtfsError> {
+        let mut vcn = start_vcn;
+        let mut table = Vec::with_capacity(runs.len());
+        for run in runs {
+            table.push((vcn, run));
+            vcn = vcn
+                .checked_add(run.length_clusters)
+                .ok_or(NtfsError::Corrupt("data runs are too long"))?;
+        }
+        Ok(Self { runs: table })
+    }
+
+    ///The first VCN of the run that covers `vcn` and the run itself.
+    pub(crate) fn find(&self, vcn: u64) -> Option<(u64, DataRun)> {
+        self.runs
+            .iter()
+            .find(|(start, run)| vcn >= *start && vcn - start < run.length_clusters)
+            .copied()
+    }
+
+    ///The LCN `vcn` is stored at, or `None` if it's sparse or no run covers it.
+    pub(crate) fn lcn(&self, vcn: u64) -> Option<u64> {
+        let (start, run) = self.find(vcn)?;
+        run.lcn.map(|lcn| lcn + (vcn - start))
+    }
+}
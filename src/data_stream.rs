@@ -0,0 +1,144 @@
+//! A file can have more than one $DATA attribute. The unnamed one is the content of the file,
+//! every named one is an alternate data stream (ADS), opened on Windows as "file.txt:name".
+//! Browsers tag downloads with a "Zone.Identifier" stream, and since Explorer doesn't show streams
+//! they're also a common place to hide data.
+use crate::{
+    merge_attributes, AttributeHeader, AttributeType, DataRun, FileRecord, MasterFileTable,
+    MergedAttribute, Ntfs, NtfsError, NtfsStreamReader, Residency,
+};
+use std::io::{Read, Seek};
+
+///A $DATA attribute of a file, with every piece of it put back together.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DataStream {
+    ///`None` for the main stream, otherwise the name of the alternate data stream.
+    pub name: Option<String>,
+    pub real_size: u64,
+    ///The bytes reserved on disk, which for resident streams is the size of the value.
+    pub allocated_size: u64,
+    pub resident: bool,
+    header: AttributeHeader,
+    value: Option<Vec<u8>>,
+    runs: Vec<DataRun>,
+}
+
+impl DataStream {
+    fn from_merged(attribute: MergedAttribute) -> DataStream {
+        let (real_size, allocated_size) = match attribute.header.residency {
+            Residency::Resident { value_length, .. } => (value_length as u64, value_length as u64),
+            Residency::NonResident {
+                real_size,
+                allocated_size,
+                ..
+            } => (real_size, allocated_size),
+        };
+        DataStream {
+            name: attribute.name,
+            real_size,
+            allocated_size,
+            resident: !attribute.header.non_resident,
+            header: attribute.header,
+            value: attribute.value,
+            runs: attribute.runs,
+        }
+    }
+
+    ///Returns true if this is an alternate data stream rather than the main stream.
+    pub fn is_alternate(&self) -> bool {
+        self.name.is_some()
+    }
+
+    pub fn header(&self) -> &AttributeHeader {
+        &self.header
+    }
+
+    ///The runs of a non-resident stream in VCN order, empty for a resident one.
+    pub fn runs(&self) -> &[DataRun] {
+        &self.runs
+    }
+}
+
+fn data_streams(attributes: Vec<MergedAttribute>) -> Vec<DataStream> {
+    attributes
+        .into_iter()
+        .filter(|attribute| attribute.type_code == AttributeType::Data)
+        .map(DataStream::from_merged)
+        .collect()
+}
+
+impl FileRecord {
+    ///The $DATA attributes stored in this record, main stream first.
+    ///Streams split across extension records need [`Ntfs::data_streams`] instead.
+    pub fn data_streams(&self) -> Result<Vec<DataStream>, NtfsError> {
+        Ok(data_streams(merge_attributes(std::iter::once(self))?))
+    }
+
+    ///The names of the $DATA attributes in this record, with an empty string for the main stream.
+    pub fn stream_names(&self) -> Result<Vec<String>, NtfsError> {
+        Ok(self
+            .data_streams()?
+            .into_iter()
+            .map(|stream| stream.name.unwrap_or_default())
+            .collect())
+    }
+}
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///The $DATA attributes of FILE record `record_number`, following its $ATTRIBUTE_LIST
+    ///so that streams split across extension records come back as one descriptor.
+    pub fn data_streams(&mut self, record_number: u64) -> Result<Vec<DataStream>, NtfsError> {
+        let base = self.file_record(record_number)?;
+        Ok(data_streams(self.merged_attributes(record_number, &base)?))
+    }
+
+    ///Opens a stream for reading. Compressed streams have to be read with [`Ntfs::read_stream`].
+    pub fn open_stream(
+        &mut self,
+        stream: &DataStream,
+    ) -> Result<NtfsStreamReader<'_, R>, NtfsError> {
+        match &stream.value {
+            Some(value) => Ok(self.value_reader(value.clone())),
+            None => self.runs_reader(&stream.header, stream.runs.clone()),
+        }
+    }
+
+    ///Reads the whole stream into memory.
+    pub fn read_stream(&mut self, stream: &DataStream) -> Result<Vec<u8>, NtfsError> {
+        match &stream.value {
+            Some(value) => Ok(value.clone()),
+            None => self.read_runs(&stream.header, stream.runs.clone()),
+        }
+    }
+}
+
+impl<R: Read + Seek> MasterFileTable<'_, R> {
+    ///Scans every base record in use and returns the record numbers of the files
+    ///with at least one alternate data stream, along with all of their streams.
+    ///Records that fail to parse, or whose attributes can't be put back together, are skipped,
+    ///so one damaged record doesn't end the scan. Only I/O errors are returned.
+    pub fn files_with_alternate_streams(
+        &mut self,
+    ) -> Result<Vec<(u64, Vec<DataStream>)>, NtfsError> {
+        let mut files = Vec::new();
+        for n in 0..self.record_count() {
+            let record = match self.record(n) {
+                Ok(record) => record,
+                Err(err @ NtfsError::Io(_)) => return Err(err),
+                Err(_) => continue,
+            };
+            if !record.is_in_use() || record.base_record_reference.record_number != 0 {
+                continue;
+            }
+            let attributes = match self.ntfs.merged_attributes(n, &record) {
+                Ok(attributes) => attributes,
+                Err(err @ NtfsError::Io(_)) => return Err(err),
+                Err(_) => continue,
+            };
+            let streams = data_streams(attributes);
+            if streams.iter().any(DataStream::is_alternate) {
+                files.push((n, streams));
+            }
+        }
+        Ok(files)
+    }
+}
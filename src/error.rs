@@ -0,0 +1,172 @@
+//! Errors produced while parsing NTFS structures.
+use crate::AttributeType;
+use std::{fmt, io};
+
+#[derive(Debug)]
+pub enum NtfsError {
+    Io(io::Error),
+    ///A structure does not start with its magic number: the boot sector's 0xEB5290 jump instruction,
+    ///'FILE', 'INDX' or the GPT header's 'EFI PART'.
+    BadMagic,
+    ///The FILE record is marked 'BAAD' because its multi-sector check failed.
+    BadRecord,
+    ///The FILE record is all zeros and has never been used.
+    EmptyRecord,
+    ///The OEM ID is not 'NTFS    '.
+    BadOemId,
+    ///The boot sector or MBR does not end with 0xAA55.
+    BadEndMarker,
+    ///The buffer is too small to hold the structure being parsed.
+    BufferTooSmall {
+        needed: usize,
+        available: usize,
+    },
+    ///An on-disk structure is internally inconsistent.
+    Corrupt(&'static str),
+    ///The FILE record number lies past the end of the $MFT.
+    RecordOutOfRange(u64),
+    ///A sector's trailing bytes don't match the Update Sequence Number,
+    ///which usually means the sector was torn during a write.
+    FixupMismatch {
+        sector_index: usize,
+    },
+    ///The bytes per sector is not a power of two between 512 and 4096.
+    InvalidSectorSize(u16),
+    ///The encoded sectors per cluster does not describe a usable cluster size.
+    InvalidClusterSize(u8),
+    ///A "bytes or clusters" size field holds an unusable value.
+    InvalidSizeEncoding {
+        field: &'static str,
+        value: u8,
+    },
+    ///A path component doesn't exist. Holds the path up to and including the missing component.
+    NotFound(String),
+    ///A path goes through a file as if it were a directory. Holds the path of the file.
+    NotADirectory(String),
+    ///Resolving a path followed more than [`MAX_LINK_HOPS`](crate::MAX_LINK_HOPS) symbolic links
+    ///or junctions, which usually means they form a loop. Holds the path of the last link.
+    TooManyLinks(String),
+    ///A symbolic link or junction points at another volume, or a relative link climbs above the root.
+    LinkOutsideVolume {
+        link: String,
+        target: String,
+    },
+    ///Opening a raw volume was denied because the process isn't running as administrator,
+    ///or on Linux as root or a member of the disk group. Holds the path of the volume.
+    NotElevated(String),
+    ///The reader holds fewer bytes than the boot sector's total sectors say the volume has.
+    TruncatedVolume {
+        declared: u64,
+        available: u64,
+    },
+    ///The structure is valid but reading it isn't supported.
+    Unsupported(&'static str),
+    ///The string is not a SID in the "S-1-5-..." form. Holds the string.
+    InvalidSid(String),
+    ///The attribute breaks a constraint the volume's $AttrDef sets for its type.
+    AttrDefViolation {
+        type_code: AttributeType,
+        reason: &'static str,
+    },
+    ///The FILETIME is before 1970 and can't be represented as a `SystemTime`.
+    TimeBeforeUnixEpoch(u64),
+    ///A field did not contain the value required by NTFS.
+    UnexpectedValue {
+        field: &'static str,
+        offset: usize,
+        expected: u64,
+        found: u64,
+    },
+}
+
+impl fmt::Display for NtfsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NtfsError::Io(err) => write!(f, "I/O error: {err}"),
+            NtfsError::BadMagic => write!(f, "bad magic number"),
+            NtfsError::BadRecord => write!(f, "FILE record is marked BAAD"),
+            NtfsError::EmptyRecord => write!(f, "FILE record is empty"),
+            NtfsError::BadOemId => write!(f, "OEM ID is not 'NTFS    '"),
+            NtfsError::BadEndMarker => write!(f, "missing 0xAA55 end-of-sector marker"),
+            NtfsError::BufferTooSmall { needed, available } => {
+                write!(
+                    f,
+                    "buffer too small: needed {needed} bytes, found {available}"
+                )
+            }
+            NtfsError::Corrupt(reason) => write!(f, "corrupt structure: {reason}"),
+            NtfsError::RecordOutOfRange(n) => {
+                write!(f, "FILE record {n} is past the end of the $MFT")
+            }
+            NtfsError::FixupMismatch { sector_index } => {
+                write!(f, "update sequence mismatch in sector {sector_index}")
+            }
+            NtfsError::InvalidSectorSize(size) => write!(f, "invalid sector size: {size}"),
+            NtfsError::InvalidClusterSize(value) => {
+                write!(f, "invalid sectors per cluster: {value:#x}")
+            }
+            NtfsError::InvalidSizeEncoding { field, value } => {
+                write!(f, "invalid {field}: {value:#x}")
+            }
+            NtfsError::NotFound(path) => write!(f, "{path} not found"),
+            NtfsError::NotADirectory(path) => write!(f, "{path} is not a directory"),
+            NtfsError::TooManyLinks(path) => {
+                write!(f, "too many symbolic links or junctions at {path}")
+            }
+            NtfsError::LinkOutsideVolume { link, target } => {
+                write!(f, "{link} points outside the volume, at {target}")
+            }
+            NtfsError::NotElevated(path) => {
+                write!(f, "opening {path} requires elevated privileges")
+            }
+            NtfsError::TruncatedVolume {
+                declared,
+                available,
+            } => write!(
+                f,
+                "volume is truncated: {declared} bytes declared, {available} available"
+            ),
+            NtfsError::Unsupported(reason) => write!(f, "unsupported: {reason}"),
+            NtfsError::InvalidSid(sid) => write!(f, "invalid SID: {sid}"),
+            NtfsError::AttrDefViolation { type_code, reason } => {
+                write!(f, "{type_code} violates $AttrDef: {reason}")
+            }
+            NtfsError::TimeBeforeUnixEpoch(ft) => {
+                write!(f, "FILETIME {ft} is before the Unix epoch")
+            }
+            NtfsError::UnexpectedValue {
+                field,
+                offset,
+                expected,
+                found,
+            } => write!(
+                f,
+                "unexpected {field} at offset {offset:#x}: expected {expected:#x}, found {found:#x}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for NtfsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            NtfsError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for NtfsError {
+    fn from(err: io::Error) -> Self {
+        NtfsError::Io(err)
+    }
+}
+
+impl From<NtfsError> for io::Error {
+    fn from(err: NtfsError) -> Self {
+        match err {
+            NtfsError::Io(err) => err,
+            err => io::Error::new(io::ErrorKind::InvalidData, err),
+        }
+    }
+}
@@ -0,0 +1,281 @@
+//! Extended attributes are name/value pairs attached to a file, from OS/2 and kept for compatibility.
+//! WSL uses them to store the Linux owner, group, mode and device number of files in its root file system.
+//! $EA_INFORMATION (0xD0) is always resident and summarises the $EA (0xE0) attribute,
+//! which holds the pairs and is non-resident when they don't fit in the FILE record.
+//!
+//!| Offset | Size | Description                                   |
+//!|--------|------|-----------------------------------------------|
+//!| 0x00   | 2    | Size of the packed extended attributes        |
+//!| 0x02   | 2    | Number of attributes with NEED_EA set         |
+//!| 0x04   | 4    | Size of the $EA value                         |
+//!
+//! $EA is a list of entries, each starting on a 4 byte boundary. The name is ASCII and null terminated.
+//!
+//!| Offset | Size | Description                                   |
+//!|--------|------|-----------------------------------------------|
+//!| 0x00   | 4    | Offset to the next entry                      |
+//!| 0x04   | 1    | Flags                                         |
+//!| 0x05   | 1    | Name length (N), without the null             |
+//!| 0x06   | 2    | Value length (V)                              |
+//!| 0x08   | N+1  | Name                                          |
+//!| 0x09+N | V    | Value                                         |
+//!
+//! WSL keeps each field in its own attribute: $LXUID, $LXGID and $LXMOD hold a u32 and $LXDEV holds the
+//! major and minor device numbers as two u32s. Before that, the first WSL release kept them all in LXATTRB.
+//!
+//!| Offset | Size | Description                                   |
+//!|--------|------|-----------------------------------------------|
+//!| 0x00   | 2    | Flags                                         |
+//!| 0x02   | 2    | Version                                       |
+//!| 0x04   | 4    | Mode                                          |
+//!| 0x08   | 4    | User id                                       |
+//!| 0x0C   | 4    | Group id                                      |
+//!| 0x10   | 4    | Device                                        |
+//!| 0x14   | 4    | Access time, nanoseconds                      |
+//!| 0x18   | 4    | Modification time, nanoseconds                |
+//!| 0x1C   | 4    | Change time, nanoseconds                      |
+//!| 0x20   | 8    | Access time, seconds since 1970               |
+//!| 0x28   | 8    | Modification time, seconds since 1970         |
+//!| 0x30   | 8    | Change time, seconds since 1970               |
+//!
+//!<https://flatcap.github.io/linux-ntfs/ntfs/attributes/ea.html>
+//!
+//!<https://flatcap.github.io/linux-ntfs/ntfs/attributes/ea_information.html>
+use crate::{
+    bytes::{u16_at, u32_at, u64_at},
+    AttributeType, FileRecord, Ntfs, NtfsError,
+};
+use std::io::{Read, Seek};
+
+///The size of the $EA_INFORMATION value.
+pub const EA_INFORMATION_SIZE: usize = 0x08;
+
+///The size of an $EA entry before its name.
+pub const EA_ENTRY_HEADER_SIZE: usize = 0x08;
+
+///The size of an LXATTRB value.
+pub const LXATTRB_SIZE: usize = 0x38;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EaInformation {
+    pub packed_size: u16,
+    ///The number of extended attributes a program has to understand to use the file.
+    pub need_ea_count: u16,
+    ///The size of the $EA value.
+    pub unpacked_size: u32,
+}
+
+impl EaInformation {
+    pub fn parse(value: &[u8]) -> Result<EaInformation, NtfsError> {
+        if value.len() < EA_INFORMATION_SIZE {
+            return Err(NtfsError::BufferTooSmall {
+                needed: EA_INFORMATION_SIZE,
+                available: value.len(),
+            });
+        }
+        Ok(EaInformation {
+            packed_size: u16_at(value, 0x00),
+            need_ea_count: u16_at(value, 0x02),
+            unpacked_size: u32_at(value, 0x04),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ExtendedAttribute {
+    pub flags: u8,
+    pub name: String,
+    pub value: Vec<u8>,
+}
+
+impl ExtendedAttribute {
+    ///The file can't be used by a program that doesn't understand this attribute.
+    pub const NEED_EA: u8 = 0x80;
+
+    pub fn needs_ea(&self) -> bool {
+        self.flags & Self::NEED_EA != 0
+    }
+
+    ///Parses every entry of an $EA value. The list ends at the end of the value,
+    ///or at an entry whose offset to the next entry is zero.
+    pub fn parse_all(value: &[u8]) -> Result<Vec<ExtendedAttribute>, NtfsError> {
+        let mut attributes = Vec::new();
+        let mut offset = 0;
+        while offset + EA_ENTRY_HEADER_SIZE <= value.len() {
+            let entry = &value[offset..];
+            let next = u32_at(entry, 0x00) as usize;
+            let name_length = entry[0x05] as usize;
+            let value_length = u16_at(entry, 0x06) as usize;
+            let value_start = EA_ENTRY_HEADER_SIZE + name_length + 1;
+            if value_start + value_length > entry.len() {
+                return Err(NtfsError::Corrupt("$EA entry runs past the attribute"));
+            }
+            attributes.push(ExtendedAttribute {
+                flags: entry[0x04],
+                name: String::from_utf8_lossy(&entry[EA_ENTRY_HEADER_SIZE..value_start - 1])
+                    .into_owned(),
+                value: entry[value_start..value_start + value_length].to_vec(),
+            });
+            if next == 0 {
+                break;
+            }
+            if next < value_start + value_length {
+                return Err(NtfsError::Corrupt("$EA entry overlaps the next one"));
+            }
+            offset += next;
+        }
+        Ok(attributes)
+    }
+}
+
+///The LXATTRB extended attribute of the first WSL release, which holds all of a file's metadata.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LxAttrb {
+    pub flags: u16,
+    pub version: u16,
+    pub mode: u32,
+    pub uid: u32,
+    pub gid: u32,
+    pub device: u32,
+    pub access_time_nanoseconds: u32,
+    pub modified_time_nanoseconds: u32,
+    pub changed_time_nanoseconds: u32,
+    ///Seconds since the Unix epoch.
+    pub access_time: u64,
+    pub modified_time: u64,
+    pub changed_time: u64,
+}
+
+impl LxAttrb {
+    pub fn parse(value: &[u8]) -> Result<LxAttrb, NtfsError> {
+        if value.len() < LXATTRB_SIZE {
+            return Err(NtfsError::BufferTooSmall {
+                needed: LXATTRB_SIZE,
+                available: value.len(),
+            });
+        }
+        Ok(LxAttrb {
+            flags: u16_at(value, 0x00),
+            version: u16_at(value, 0x02),
+            mode: u32_at(value, 0x04),
+            uid: u32_at(value, 0x08),
+            gid: u32_at(value, 0x0C),
+            device: u32_at(value, 0x10),
+            access_time_nanoseconds: u32_at(value, 0x14),
+            modified_time_nanoseconds: u32_at(value, 0x18),
+            changed_time_nanoseconds: u32_at(value, 0x1C),
+            access_time: u64_at(value, 0x20),
+            modified_time: u64_at(value, 0x28),
+            changed_time: u64_at(value, 0x30),
+        })
+    }
+}
+
+///The Linux metadata WSL keeps in a file's extended attributes.
+///Fields the file has no attribute for are `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct WslMetadata {
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    ///The file type and permission bits, as in `st_mode`.
+    pub mode: Option<u32>,
+    ///The major and minor device numbers of a device file.
+    pub device: Option<(u32, u32)>,
+    ///The LXATTRB attribute, if the file was written by the first WSL release.
+    pub lxattrb: Option<LxAttrb>,
+}
+
+impl WslMetadata {
+    ///Collects the WSL attributes from `attributes`, returning `None` if there aren't any.
+    ///$LXUID, $LXGID, $LXMOD and $LXDEV take precedence over the fields of LXATTRB.
+    pub fn from_extended_attributes(
+        attributes: &[ExtendedAttribute],
+    ) -> Result<Option<WslMetadata>, NtfsError> {
+        let mut metadata = WslMetadata::default();
+        let mut found = false;
+        for attribute in attributes {
+            let value = &attribute.value;
+            let u32_value = || -> Result<u32, NtfsError> {
+                if value.len() < 4 {
+                    return Err(NtfsError::BufferTooSmall {
+                        needed: 4,
+                        available: value.len(),
+                    });
+                }
+                Ok(u32_at(value, 0))
+            };
+            match attribute.name.as_str() {
+                "$LXUID" => metadata.uid = Some(u32_value()?),
+                "$LXGID" => metadata.gid = Some(u32_value()?),
+                "$LXMOD" => metadata.mode = Some(u32_value()?),
+                "$LXDEV" => {
+                    if value.len() < 8 {
+                        return Err(NtfsError::BufferTooSmall {
+                            needed: 8,
+                            available: value.len(),
+                        });
+                    }
+                    metadata.device = Some((u32_at(value, 0), u32_at(value, 4)));
+                }
+                "LXATTRB" => metadata.lxattrb = Some(LxAttrb::parse(value)?),
+                _ => continue,
+            }
+            found = true;
+        }
+        if let Some(lxattrb) = metadata.lxattrb {
+            metadata.uid = metadata.uid.or(Some(lxattrb.uid));
+            metadata.gid = metadata.gid.or(Some(lxattrb.gid));
+            metadata.mode = metadata.mode.or(Some(lxattrb.mode));
+        }
+        Ok(found.then_some(metadata))
+    }
+}
+
+impl FileRecord {
+    ///The $EA_INFORMATION of the record, or `None` if the file has no extended attributes.
+    pub fn ea_information(&self) -> Result<Option<EaInformation>, NtfsError> {
+        let Some(attribute) = self.find_attribute(AttributeType::EaInformation)? else {
+            return Ok(None);
+        };
+        let value = attribute
+            .value()
+            .ok_or(NtfsError::Corrupt("$EA_INFORMATION is not resident"))?;
+        EaInformation::parse(value).map(Some)
+    }
+}
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///The extended attributes of a file, reading $EA from disk if it's non-resident
+    ///and from an extension record if the file has an $ATTRIBUTE_LIST.
+    ///Files without extended attributes have none.
+    pub fn extended_attributes(
+        &mut self,
+        record: &FileRecord,
+    ) -> Result<Vec<ExtendedAttribute>, NtfsError> {
+        let value = if record
+            .find_attribute(AttributeType::AttributeList)?
+            .is_some()
+        {
+            let Some(ea) = self
+                .merged_attributes(record.mft_record_number as u64, record)?
+                .into_iter()
+                .find(|attribute| attribute.type_code == AttributeType::Ea)
+            else {
+                return Ok(Vec::new());
+            };
+            self.read_merged_attribute(&ea)?
+        } else {
+            let Some(ea) = record.find_attribute(AttributeType::Ea)? else {
+                return Ok(Vec::new());
+            };
+            self.read_attribute(&ea)?
+        };
+        ExtendedAttribute::parse_all(&value)
+    }
+
+    ///The Linux owner, group, mode and device number WSL stored for a file,
+    ///or `None` if it has no WSL attributes.
+    pub fn wsl_metadata(&mut self, record: &FileRecord) -> Result<Option<WslMetadata>, NtfsError> {
+        WslMetadata::from_extended_attributes(&self.extended_attributes(record)?)
+    }
+}
@@ -0,0 +1,127 @@
+//! $FILE_NAME (0x30) stores the name of a file and a reference to its parent directory.
+//! A file has one $FILE_NAME for each hard link, and usually a second one holding its DOS 8.3 short name.
+//!
+//!| Offset | Size | Description                       |
+//!|--------|------|-----------------------------------|
+//!| 0x00   | 8    | File reference to the parent dir  |
+//!| 0x08   | 8    | File creation time                |
+//!| 0x10   | 8    | File altered time                 |
+//!| 0x18   | 8    | MFT changed time                  |
+//!| 0x20   | 8    | File read time                    |
+//!| 0x28   | 8    | Allocated size of the file        |
+//!| 0x30   | 8    | Real size of the file             |
+//!| 0x38   | 4    | Flags, e.g. Directory, compressed |
+//!| 0x3C   | 4    | Used by EAs and Reparse           |
+//!| 0x40   | 1    | Filename length in characters (L) |
+//!| 0x41   | 1    | Filename namespace                |
+//!| 0x42   | 2L   | File name in Unicode              |
+//!
+//!<https://flatcap.github.io/linux-ntfs/ntfs/attributes/file_name.html>
+use crate::{
+    bytes::{u32_at, u64_at},
+    FileAttributes, FileReference, NtfsError, NtfsTime,
+};
+
+///The size of the attribute value without the name.
+pub const FILE_NAME_HEADER_SIZE: usize = 0x42;
+
+///The set of rules a name follows.
+///A long name that is also a valid 8.3 name is stored once as `Win32AndDos`.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Namespace {
+    ///Case sensitive, allows any character except '\0' and '/'.
+    Posix = 0,
+    ///Case insensitive, the usual long name.
+    Win32 = 1,
+    ///The 8.3 short name.
+    Dos = 2,
+    ///The long name is a valid 8.3 name.
+    Win32AndDos = 3,
+}
+
+impl Namespace {
+    ///How strongly a name in this namespace is preferred when showing a file to the user.
+    ///Higher is better: Win32&DOS > Win32 > POSIX > DOS.
+    pub fn priority(&self) -> u8 {
+        match self {
+            Namespace::Win32AndDos => 3,
+            Namespace::Win32 => 2,
+            Namespace::Posix => 1,
+            Namespace::Dos => 0,
+        }
+    }
+}
+
+impl TryFrom<u8> for Namespace {
+    type Error = NtfsError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Namespace::Posix),
+            1 => Ok(Namespace::Win32),
+            2 => Ok(Namespace::Dos),
+            3 => Ok(Namespace::Win32AndDos),
+            _ => Err(NtfsError::Corrupt("unknown file name namespace")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileName {
+    pub parent_directory: FileReference,
+    ///Windows only updates the timestamps in $FILE_NAME when the file is renamed or moved,
+    ///so they often differ from the ones in $STANDARD_INFORMATION.
+    pub creation_time: NtfsTime,
+    pub modified_time: NtfsTime,
+    pub mft_changed_time: NtfsTime,
+    pub accessed_time: NtfsTime,
+    pub allocated_size: u64,
+    pub real_size: u64,
+    pub flags: FileAttributes,
+    ///The reparse tag when the file is a reparse point, otherwise used by extended attributes.
+    pub reparse_value: u32,
+    pub namespace: Namespace,
+    pub name: String,
+}
+
+impl FileName {
+    pub fn parse(value: &[u8]) -> Result<FileName, NtfsError> {
+        if value.len() < FILE_NAME_HEADER_SIZE {
+            return Err(NtfsError::BufferTooSmall {
+                needed: FILE_NAME_HEADER_SIZE,
+                available: value.len(),
+            });
+        }
+
+        //The length is in UTF-16 code units, not bytes or characters.
+        let name_end = FILE_NAME_HEADER_SIZE + value[0x40] as usize * 2;
+        if name_end > value.len() {
+            return Err(NtfsError::Corrupt("file name runs past the attribute"));
+        }
+        let units: Vec<u16> = value[FILE_NAME_HEADER_SIZE..name_end]
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+            .collect();
+
+        Ok(FileName {
+            parent_directory: FileReference::from_raw(u64_at(value, 0x00)),
+            creation_time: NtfsTime(u64_at(value, 0x08)),
+            modified_time: NtfsTime(u64_at(value, 0x10)),
+            mft_changed_time: NtfsTime(u64_at(value, 0x18)),
+            accessed_time: NtfsTime(u64_at(value, 0x20)),
+            allocated_size: u64_at(value, 0x28),
+            real_size: u64_at(value, 0x30),
+            flags: FileAttributes(u32_at(value, 0x38)),
+            reparse_value: u32_at(value, 0x3C),
+            namespace: Namespace::try_from(value[0x41])?,
+            name: String::from_utf16_lossy(&units),
+        })
+    }
+
+    pub fn is_directory(&self) -> bool {
+        self.flags.contains(FileAttributes::DIRECTORY)
+    }
+}
@@ -0,0 +1,171 @@
+//! [`NtfsFileReader`] reads the content of a file through `std::io::Read` and `Seek`,
+//! the streaming counterpart of [`Ntfs::read_data`]. Plain files are read with an [`NtfsStreamReader`],
+//! compressed files are decompressed one compression unit at a time as the reader reaches them.
+use crate::{
+    data_run::RunTable, stream::seek_position, AttributeHeader, DataRun, FileRecord, Flag,
+    MergedAttribute, Ntfs, NtfsError, NtfsStreamReader, Residency,
+};
+use std::io::{self, Read, Seek, SeekFrom};
+
+///Reads the content of a file with memory bounded by a compression unit.
+///Sparse runs and bytes past the initialized size read as zeros and reads stop at the real size.
+///Created with [`Ntfs::open_reader`].
+pub struct NtfsFileReader<'a, R: Read + Seek> {
+    inner: Inner<'a, R>,
+}
+
+enum Inner<'a, R: Read + Seek> {
+    Stream(NtfsStreamReader<'a, R>),
+    Compressed(CompressedReader<'a, R>),
+}
+
+struct CompressedReader<'a, R: Read + Seek> {
+    ntfs: &'a mut Ntfs<R>,
+    runs: RunTable,
+    unit_clusters: u64,
+    unit_size: u64,
+    real_size: u64,
+    initialized_size: u64,
+    position: u64,
+    ///The last unit that was read and its decompressed bytes.
+    unit: Option<(u64, Vec<u8>)>,
+}
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///Opens the $DATA attribute of `record` named `stream_name` for reading,
+    ///finding it the same way as [`Ntfs::read_data`] but only reading clusters as they're needed.
+    pub fn open_reader(
+        &mut self,
+        record: &FileRecord,
+        stream_name: Option<&str>,
+    ) -> Result<NtfsFileReader<'_, R>, NtfsError> {
+        let MergedAttribute {
+            header,
+            value,
+            runs,
+            ..
+        } = self.data_attribute(record, stream_name)?;
+
+        let inner = match value {
+            Some(value) => Inner::Stream(self.value_reader(value)),
+            None if header.has_flag(Flag::Compressed) => {
+                Inner::Compressed(self.compressed_reader(&header, runs)?)
+            }
+            None => Inner::Stream(self.runs_reader(&header, runs)?),
+        };
+        Ok(NtfsFileReader { inner })
+    }
+
+    fn compressed_reader(
+        &mut self,
+        header: &AttributeHeader,
+        data_runs: Vec<DataRun>,
+    ) -> Result<CompressedReader<'_, R>, NtfsError> {
+        let Residency::NonResident {
+            start_vcn,
+            real_size,
+            initialized_size,
+            compression_unit,
+            ..
+        } = header.residency
+        else {
+            return Err(NtfsError::Corrupt("resident attributes have no data runs"));
+        };
+        let unit_clusters = self.compression_unit_clusters(compression_unit)?;
+        let unit_size = self.geometry().clusters_to_bytes(unit_clusters)?;
+
+        let runs = RunTable::new(start_vcn, data_runs)?;
+
+        Ok(CompressedReader {
+            ntfs: self,
+            runs,
+            unit_clusters,
+            unit_size,
+            real_size,
+            initialized_size: initialized_size.min(real_size),
+            position: 0,
+            unit: None,
+        })
+    }
+}
+
+impl<R: Read + Seek> NtfsFileReader<'_, R> {
+    ///The size of the file.
+    pub fn len(&self) -> u64 {
+        match &self.inner {
+            Inner::Stream(reader) => reader.len(),
+            Inner::Compressed(reader) => reader.real_size,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn position(&self) -> u64 {
+        match &self.inner {
+            Inner::Stream(reader) => reader.position(),
+            Inner::Compressed(reader) => reader.position,
+        }
+    }
+}
+
+impl<R: Read + Seek> Read for NtfsFileReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match &mut self.inner {
+            Inner::Stream(reader) => reader.read(buf),
+            Inner::Compressed(reader) => reader.read(buf),
+        }
+    }
+}
+
+impl<R: Read + Seek> Seek for NtfsFileReader<'_, R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let reader = match &mut self.inner {
+            Inner::Stream(reader) => return reader.seek(pos),
+            Inner::Compressed(reader) => reader,
+        };
+        reader.position = seek_position(pos, reader.position, reader.real_size)?;
+        Ok(reader.position)
+    }
+}
+
+impl<R: Read + Seek> CompressedReader<'_, R> {
+    ///The decompressed bytes of compression unit `unit`, reading it unless it was the last one read.
+    fn unit(&mut self, unit: u64) -> Result<&[u8], NtfsError> {
+        if self.unit.as_ref().is_none_or(|(cached, _)| *cached != unit) {
+            //Clusters that no run covers are treated as sparse.
+            let first_vcn = unit * self.unit_clusters;
+            let lcns: Vec<Option<u64>> = (first_vcn..first_vcn + self.unit_clusters)
+                .map(|vcn| self.runs.lcn(vcn))
+                .collect();
+            let bytes = self.ntfs.read_compression_unit(&lcns, self.unit_clusters)?;
+            self.unit = Some((unit, bytes));
+        }
+        Ok(&self.unit.as_ref().expect("the unit was just read").1)
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.real_size || buf.is_empty() {
+            return Ok(0);
+        }
+        let len = (buf.len() as u64).min(self.real_size - self.position);
+
+        if self.position >= self.initialized_size {
+            buf[..len as usize].fill(0);
+            self.position += len;
+            return Ok(len as usize);
+        }
+
+        let unit = self.position / self.unit_size;
+        let offset = self.position % self.unit_size;
+        let len = len
+            .min(self.initialized_size - self.position)
+            .min(self.unit_size - offset);
+        let bytes = self.unit(unit)?;
+        let start = offset as usize;
+        buf[..len as usize].copy_from_slice(&bytes[start..start + len as usize]);
+        self.position += len;
+        Ok(len as usize)
+    }
+}
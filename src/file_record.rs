@@ -22,5 +22,355 @@
 //!|        | 2    |    | Update Sequence Number (a)                       |
 //!|        | 2S-2 |    | Update Sequence Array (a)                        |
 
+use crate::{
+    apply_fixup, apply_fixup_unverified,
+    bytes::{u16_at, u32_at, u64_at},
+    Attribute, AttributeType, FileName, FileReference, IndexEntry, IndexRoot, Namespace, NtfsError,
+    ObjectId, ReparseData, ReparsePoint, SecurityDescriptor, StandardInformation, FIXUP_STRIDE,
+};
 
-pub struct FileRecord {}
+///The flags at offset 0x16 of the FILE record header.
+///Unknown bits are kept as they are, so the raw value can always be recovered with `bits`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileRecordFlags(pub u16);
+
+impl FileRecordFlags {
+    ///The record is in use. Deleted records have this bit cleared.
+    pub const IN_USE: FileRecordFlags = FileRecordFlags(0x0001);
+    ///The record is a directory.
+    pub const DIRECTORY: FileRecordFlags = FileRecordFlags(0x0002);
+    ///The record is in $Extend, e.g. $ObjId, $Quota, $Reparse.
+    pub const EXTENSION: FileRecordFlags = FileRecordFlags(0x0004);
+    ///The record has an index other than $I30, e.g. $Secure.
+    pub const VIEW_INDEX: FileRecordFlags = FileRecordFlags(0x0008);
+
+    pub fn bits(self) -> u16 {
+        self.0
+    }
+
+    pub fn contains(self, other: FileRecordFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+///The size of the header up to and including the MFT record number.
+pub const FILE_RECORD_HEADER_SIZE: usize = 0x30;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileRecord {
+    pub magic: [u8; 4],
+    pub update_sequence_offset: u16,
+    ///Size in words of the Update Sequence.
+    pub update_sequence_size: u16,
+    ///$LogFile Sequence Number.
+    pub lsn: u64,
+    pub sequence_number: u16,
+    pub hard_link_count: u16,
+    pub first_attribute_offset: u16,
+    pub flags: u16,
+    pub real_size: u32,
+    pub allocated_size: u32,
+    ///Zero for base records, otherwise the base record this extension record belongs to.
+    pub base_record_reference: FileReference,
+    pub next_attribute_id: u16,
+    ///Only present on Windows XP and later.
+    pub mft_record_number: u32,
+
+    ///The record with the update sequence applied.
+    data: Vec<u8>,
+}
+
+impl FileRecord {
+    pub fn parse(bytes: &[u8]) -> Result<FileRecord, NtfsError> {
+        Self::parse_with(bytes, true)
+    }
+
+    ///Parses a record without verifying the Update Sequence Number of each sector.
+    ///Torn sectors are restored anyway, which is useful when carving damaged records.
+    pub fn parse_unverified(bytes: &[u8]) -> Result<FileRecord, NtfsError> {
+        Self::parse_with(bytes, false)
+    }
+
+    fn parse_with(bytes: &[u8], verify: bool) -> Result<FileRecord, NtfsError> {
+        if bytes.len() < FILE_RECORD_HEADER_SIZE {
+            return Err(NtfsError::BufferTooSmall {
+                needed: FILE_RECORD_HEADER_SIZE,
+                available: bytes.len(),
+            });
+        }
+
+        let magic = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        match &magic {
+            b"FILE" => {}
+            b"BAAD" => return Err(NtfsError::BadRecord),
+            [0, 0, 0, 0] => return Err(NtfsError::EmptyRecord),
+            _ => return Err(NtfsError::BadMagic),
+        }
+
+        let real_size = u32_at(bytes, 0x18);
+        let allocated_size = u32_at(bytes, 0x1C);
+        if real_size > allocated_size {
+            return Err(NtfsError::Corrupt(
+                "FILE record real size exceeds allocated size",
+            ));
+        }
+
+        let first_attribute_offset = u16_at(bytes, 0x14);
+        if first_attribute_offset as usize + 4 > bytes.len() {
+            return Err(NtfsError::Corrupt(
+                "first attribute lies outside the FILE record",
+            ));
+        }
+
+        let update_sequence_offset = u16_at(bytes, 0x04);
+        let update_sequence_size = u16_at(bytes, 0x06);
+
+        let mut data = bytes.to_vec();
+        if verify {
+            apply_fixup(
+                &mut data,
+                update_sequence_offset,
+                update_sequence_size,
+                FIXUP_STRIDE,
+            )?;
+        } else {
+            apply_fixup_unverified(
+                &mut data,
+                update_sequence_offset,
+                update_sequence_size,
+                FIXUP_STRIDE,
+            )?;
+        }
+
+        Ok(FileRecord {
+            magic,
+            update_sequence_offset,
+            update_sequence_size,
+            lsn: u64_at(bytes, 0x08),
+            sequence_number: u16_at(bytes, 0x10),
+            hard_link_count: u16_at(bytes, 0x12),
+            first_attribute_offset,
+            flags: u16_at(bytes, 0x16),
+            real_size,
+            allocated_size,
+            base_record_reference: FileReference::from_raw(u64_at(bytes, 0x20)),
+            next_attribute_id: u16_at(bytes, 0x28),
+            mft_record_number: u32_at(bytes, 0x2C),
+            data,
+        })
+    }
+
+    pub fn flags(&self) -> FileRecordFlags {
+        FileRecordFlags(self.flags)
+    }
+
+    pub fn is_in_use(&self) -> bool {
+        self.flags().contains(FileRecordFlags::IN_USE)
+    }
+
+    pub fn is_directory(&self) -> bool {
+        self.flags().contains(FileRecordFlags::DIRECTORY)
+    }
+
+    ///Walks the attributes from the first attribute offset until the 0xFFFFFFFF end marker,
+    ///or the real size of the record if the end marker is missing.
+    pub fn attributes(&self) -> Attributes<'_> {
+        Attributes {
+            data: &self.data[..(self.real_size as usize).min(self.data.len())],
+            offset: self.first_attribute_offset as usize,
+        }
+    }
+
+    ///Returns the first attribute of `attribute_type`.
+    pub fn find_attribute(
+        &self,
+        attribute_type: AttributeType,
+    ) -> Result<Option<Attribute<'_>>, NtfsError> {
+        for attribute in self.attributes() {
+            let attribute = attribute?;
+            if attribute.type_code() == attribute_type {
+                return Ok(Some(attribute));
+            }
+        }
+        Ok(None)
+    }
+
+    ///The $STANDARD_INFORMATION of the record.
+    ///Extension records don't have one, so a missing attribute is `None` rather than an error.
+    pub fn standard_information(&self) -> Result<Option<StandardInformation>, NtfsError> {
+        let Some(attribute) = self.find_attribute(AttributeType::StandardInformation)? else {
+            return Ok(None);
+        };
+        let value = attribute
+            .value()
+            .ok_or(NtfsError::Corrupt("$STANDARD_INFORMATION is not resident"))?;
+        StandardInformation::parse(value).map(Some)
+    }
+
+    ///The content of the unnamed $DATA attribute when it is stored inside the record.
+    ///Returns `None` if the $DATA attribute is non-resident or missing.
+    pub fn read_resident_data(&self) -> Option<&[u8]> {
+        self.data_attribute().ok()??.value()
+    }
+
+    ///The unnamed $DATA attribute, which holds the content of the file.
+    ///Named $DATA attributes are alternate data streams.
+    pub fn data_attribute(&self) -> Result<Option<Attribute<'_>>, NtfsError> {
+        self.data_stream_attribute(None)
+    }
+
+    ///The $DATA attribute named `name`, or the unnamed one if `name` is `None`.
+    pub fn data_stream_attribute(
+        &self,
+        name: Option<&str>,
+    ) -> Result<Option<Attribute<'_>>, NtfsError> {
+        for attribute in self.attributes() {
+            let attribute = attribute?;
+            if attribute.type_code() == AttributeType::Data && attribute.name() == name {
+                return Ok(Some(attribute));
+            }
+        }
+        Ok(None)
+    }
+
+    ///The $REPARSE_POINT of the record, or `None` if the file isn't a reparse point.
+    pub fn reparse_point(&self) -> Result<Option<ReparsePoint>, NtfsError> {
+        let Some(attribute) = self.find_attribute(AttributeType::ReparsePoint)? else {
+            return Ok(None);
+        };
+        let value = attribute
+            .value()
+            .ok_or(NtfsError::Unsupported("$REPARSE_POINT is not resident"))?;
+        ReparsePoint::parse(value).map(Some)
+    }
+
+    ///The target of a symbolic link or junction, see [`ReparsePoint::target`].
+    ///Returns `None` for other files, and for reparse points that can't be read.
+    pub fn reparse_target(&self) -> Option<String> {
+        self.reparse_point().ok()??.target().map(str::to_string)
+    }
+
+    ///The $OBJECT_ID of the record, set once link tracking has given the file an object id.
+    pub fn object_id(&self) -> Result<Option<ObjectId>, NtfsError> {
+        let Some(attribute) = self.find_attribute(AttributeType::ObjectId)? else {
+            return Ok(None);
+        };
+        let value = attribute
+            .value()
+            .ok_or(NtfsError::Corrupt("$OBJECT_ID is not resident"))?;
+        ObjectId::parse(value).map(Some)
+    }
+
+    ///The resident $SECURITY_DESCRIPTOR of the record, which NTFS 1.x files have instead of a security id.
+    ///Large descriptors are non-resident and have to be read with [`Ntfs::security_descriptor`](crate::Ntfs::security_descriptor).
+    pub fn security_descriptor(&self) -> Result<Option<SecurityDescriptor>, NtfsError> {
+        let Some(attribute) = self.find_attribute(AttributeType::SecurityDescriptor)? else {
+            return Ok(None);
+        };
+        let value = attribute.value().ok_or(NtfsError::Unsupported(
+            "$SECURITY_DESCRIPTOR is not resident",
+        ))?;
+        SecurityDescriptor::parse(value).map(Some)
+    }
+
+    ///The typed payload of the record's reparse point, see [`ReparseData`].
+    ///Returns `None` for other files, and for reparse points that can't be read.
+    pub fn reparse(&self) -> Option<ReparseData> {
+        self.reparse_point().ok()?.map(ReparseData::from)
+    }
+
+    ///Every $FILE_NAME attribute in the record, in the order they are stored.
+    pub fn file_names(&self) -> Result<Vec<FileName>, NtfsError> {
+        let mut names = Vec::new();
+        for attribute in self.attributes() {
+            let attribute = attribute?;
+            if attribute.type_code() != AttributeType::FileName {
+                continue;
+            }
+            let value = attribute
+                .value()
+                .ok_or(NtfsError::Corrupt("$FILE_NAME is not resident"))?;
+            names.push(FileName::parse(value)?);
+        }
+        Ok(names)
+    }
+
+    ///The name to show the user, preferring Win32&DOS, then Win32, POSIX and finally the DOS short name.
+    pub fn best_name(&self) -> Option<String> {
+        self.file_names()
+            .ok()?
+            .into_iter()
+            .max_by_key(|name| name.namespace.priority())
+            .map(|name| name.name)
+    }
+
+    ///The names of the file. DOS short names are left out unless `include_dos` is set,
+    ///since they duplicate a long name stored in the same record.
+    pub fn names(&self, include_dos: bool) -> Result<Vec<String>, NtfsError> {
+        Ok(self
+            .file_names()?
+            .into_iter()
+            .filter(|name| include_dos || name.namespace != Namespace::Dos)
+            .map(|name| name.name)
+            .collect())
+    }
+
+    ///The entries of the directory's $I30 index root.
+    ///Large directories keep most of their entries in $INDEX_ALLOCATION, which isn't read here.
+    ///Entries pointing at sub-nodes keep their VCN in [`IndexEntry::sub_node_vcn`],
+    ///use [`Ntfs::read_directory`](crate::Ntfs::read_directory) to read every entry.
+    pub fn index_entries(&self) -> Result<Vec<IndexEntry>, NtfsError> {
+        Ok(self.index_root()?.entries)
+    }
+
+    ///The directory's $I30 index root.
+    pub fn index_root(&self) -> Result<IndexRoot, NtfsError> {
+        for attribute in self.attributes() {
+            let attribute = attribute?;
+            if attribute.type_code() == AttributeType::IndexRoot && attribute.has_name("$I30") {
+                let value = attribute
+                    .value()
+                    .ok_or(NtfsError::Corrupt("$INDEX_ROOT is not resident"))?;
+                return IndexRoot::parse(value);
+            }
+        }
+        Err(NtfsError::Corrupt("FILE record has no $I30 index root"))
+    }
+
+    ///The raw bytes of the record, after the update sequence has been applied.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+///Iterates over the attributes of a FILE record. Created with [`FileRecord::attributes`].
+///Iteration stops after the first error, since the next attribute can't be located.
+pub struct Attributes<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Iterator for Attributes<'a> {
+    type Item = Result<Attribute<'a>, NtfsError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.offset;
+        if offset + 4 > self.data.len() || u32_at(self.data, offset) == 0xFFFF_FFFF {
+            return None;
+        }
+        //Stop on errors, there's no way to find the next attribute.
+        self.offset = self.data.len();
+
+        let attribute = match Attribute::parse(&self.data[offset..]) {
+            Ok(attribute) => attribute,
+            Err(err) => return Some(Err(err)),
+        };
+        if attribute.header().total_length == 0 {
+            return Some(Err(NtfsError::Corrupt("attribute has a length of zero")));
+        }
+
+        self.offset = offset + attribute.header().total_length as usize;
+        Some(Ok(attribute))
+    }
+}
@@ -0,0 +1,48 @@
+//! A file reference points at a FILE record.
+//! The low 48 bits are the MFT record number and the high 16 bits are the sequence number,
+//! which must match the record's own sequence number for the reference to be valid.
+//! The sequence number is incremented every time a record is reused,
+//! so a stale reference to a deleted file can be told apart from a reference to its replacement.
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileReference {
+    pub record_number: u64,
+    pub sequence_number: u16,
+}
+
+impl FileReference {
+    ///Splits the raw 8 byte reference into its record and sequence number.
+    pub fn from_raw(raw: u64) -> FileReference {
+        FileReference {
+            record_number: raw & 0x0000_FFFF_FFFF_FFFF,
+            sequence_number: (raw >> 48) as u16,
+        }
+    }
+
+    ///Packs the reference back into its on-disk form.
+    pub fn to_raw(&self) -> u64 {
+        (self.sequence_number as u64) << 48 | self.record_number
+    }
+
+    pub fn record_number(&self) -> u64 {
+        self.record_number
+    }
+
+    pub fn sequence_number(&self) -> u16 {
+        self.sequence_number
+    }
+}
+
+impl From<u64> for FileReference {
+    fn from(raw: u64) -> Self {
+        FileReference::from_raw(raw)
+    }
+}
+
+impl fmt::Display for FileReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.record_number, self.sequence_number)
+    }
+}
@@ -0,0 +1,95 @@
+//! Multi-sector structures (FILE and INDX records) are protected by an Update Sequence.
+//! When the structure is written, the last two bytes of every sector are saved in the Update Sequence Array
+//! and replaced with the Update Sequence Number. A sector whose trailing bytes don't match was torn during a write.
+//!
+//!| Offset     | Size | Description                         |
+//!|------------|------|-------------------------------------|
+//!| usn_offset | 2    | Update Sequence Number              |
+//!| +2         | 2S-2 | Saved bytes, one word per sector    |
+//!
+//!<https://flatcap.github.io/linux-ntfs/ntfs/concepts/fixup.html>
+use crate::{bytes::u16_at, NtfsError};
+
+///Update sequences protect 512 byte strides, even on drives with larger sectors.
+pub const FIXUP_STRIDE: u16 = 512;
+
+///Verifies each sector's trailing Update Sequence Number and restores the saved bytes.
+///`usn_count` is the size in words of the Update Sequence, including the number itself,
+///and has to cover every sector of `buffer`, which holds exactly one record.
+///The buffer is left untouched if any sector fails verification.
+pub fn apply_fixup(
+    buffer: &mut [u8],
+    usn_offset: u16,
+    usn_count: u16,
+    bytes_per_sector: u16,
+) -> Result<(), NtfsError> {
+    fixup(buffer, usn_offset, usn_count, bytes_per_sector, true)
+}
+
+///Restores the saved bytes without checking the Update Sequence Number.
+///Used when carving damaged records, where a torn sector is better than nothing.
+pub fn apply_fixup_unverified(
+    buffer: &mut [u8],
+    usn_offset: u16,
+    usn_count: u16,
+    bytes_per_sector: u16,
+) -> Result<(), NtfsError> {
+    fixup(buffer, usn_offset, usn_count, bytes_per_sector, false)
+}
+
+fn fixup(
+    buffer: &mut [u8],
+    usn_offset: u16,
+    usn_count: u16,
+    bytes_per_sector: u16,
+    verify: bool,
+) -> Result<(), NtfsError> {
+    if bytes_per_sector < FIXUP_STRIDE || !bytes_per_sector.is_power_of_two() {
+        return Err(NtfsError::Corrupt(
+            "update sequence stride is not a power of two of at least 512 bytes",
+        ));
+    }
+    let usn_offset = usn_offset as usize;
+    let sectors = (usn_count as usize).saturating_sub(1);
+    let bytes_per_sector = bytes_per_sector as usize;
+
+    //The array has to hold the number itself and sit in the first sector, before its trailing bytes.
+    if usn_count == 0 || usn_offset + usn_count as usize * 2 > bytes_per_sector - 2 {
+        return Err(NtfsError::Corrupt(
+            "update sequence array is empty or runs past the first sector",
+        ));
+    }
+
+    let needed = (usn_offset + usn_count as usize * 2).max(sectors * bytes_per_sector);
+    if needed > buffer.len() {
+        return Err(NtfsError::BufferTooSmall {
+            needed,
+            available: buffer.len(),
+        });
+    }
+
+    //Sectors past the update sequence could be torn without anyone noticing.
+    if verify && sectors * bytes_per_sector < buffer.len() {
+        return Err(NtfsError::Corrupt(
+            "update sequence doesn't cover every sector of the record",
+        ));
+    }
+
+    if verify {
+        let usn = u16_at(buffer, usn_offset);
+        for sector_index in 0..sectors {
+            let end = (sector_index + 1) * bytes_per_sector - 2;
+            if u16_at(buffer, end) != usn {
+                return Err(NtfsError::FixupMismatch { sector_index });
+            }
+        }
+    }
+
+    for sector_index in 0..sectors {
+        let end = (sector_index + 1) * bytes_per_sector - 2;
+        let saved = usn_offset + 2 + sector_index * 2;
+        buffer.copy_within(saved..saved + 2, end);
+    }
+
+    Ok(())
+}
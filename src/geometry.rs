@@ -0,0 +1,70 @@
+//! The sizes every other structure is measured in, decoded once from the boot sector.
+//! Sectors are 512 or 4096 bytes, clusters are a power of two sectors (4 KB on most volumes, up to 2 MB),
+//! and FILE records and index records are either a number of clusters or a power of two bytes.
+use crate::{NtfsError, PartitionBootSector, Size};
+use std::ops::RangeInclusive;
+
+///The sizes a FILE record or index record can have, in bytes.
+pub const RECORD_SIZES: RangeInclusive<u64> = 256..=65536;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Geometry {
+    pub bytes_per_sector: u64,
+    pub bytes_per_cluster: u64,
+    pub file_record_size: u64,
+    pub index_record_size: u64,
+}
+
+impl Geometry {
+    pub fn new(boot_sector: &PartitionBootSector) -> Result<Geometry, NtfsError> {
+        let bytes_per_sector = boot_sector.bytes_per_sector as u64;
+        let bytes_per_cluster = bytes_per_sector
+            .checked_mul(boot_sector.sectors_per_cluster as u64)
+            .ok_or(NtfsError::Corrupt("cluster size overflows"))?;
+        //Records are read whole, so a corrupt size mustn't ask for gigabytes per record.
+        let size = |size: &Size| {
+            match *size {
+                Size::Bytes(bytes) => Some(bytes as u64),
+                Size::Clusters(clusters) => bytes_per_cluster.checked_mul(clusters as u64),
+            }
+            .filter(|size| RECORD_SIZES.contains(size) && size.is_power_of_two())
+        };
+        Ok(Geometry {
+            bytes_per_sector,
+            bytes_per_cluster,
+            file_record_size: size(&boot_sector.file_record_segment).ok_or(NtfsError::Corrupt(
+                "FILE record size is not a power of two from 256 bytes to 64 KB",
+            ))?,
+            index_record_size: size(&boot_sector.index_buffer).ok_or(NtfsError::Corrupt(
+                "index record size is not a power of two from 256 bytes to 64 KB",
+            ))?,
+        })
+    }
+
+    ///The byte offset of the logical cluster number `lcn` from the start of the volume.
+    pub fn lcn_to_byte_offset(&self, lcn: u64) -> Result<u64, NtfsError> {
+        lcn.checked_mul(self.bytes_per_cluster)
+            .ok_or(NtfsError::Corrupt(
+                "cluster number overflows the volume offset",
+            ))
+    }
+
+    ///The number of clusters needed to hold `len` bytes.
+    pub fn vcn_count_for_bytes(&self, len: u64) -> u64 {
+        len.div_ceil(self.bytes_per_cluster)
+    }
+
+    ///The size in bytes of `clusters` clusters.
+    pub fn clusters_to_bytes(&self, clusters: u64) -> Result<u64, NtfsError> {
+        clusters
+            .checked_mul(self.bytes_per_cluster)
+            .ok_or(NtfsError::Corrupt("cluster count overflows"))
+    }
+}
+
+impl PartitionBootSector {
+    ///Decodes the sector, cluster and record sizes, see [`Geometry`].
+    pub fn geometry(&self) -> Result<Geometry, NtfsError> {
+        Geometry::new(self)
+    }
+}
@@ -0,0 +1,172 @@
+//! Disks partitioned with GPT keep their partition table in the sectors after the protective MBR.
+//! LBA 1 holds the header, which points at an array of partition entries, usually starting at LBA 2.
+//! Only 512 byte logical sectors are supported.
+//!
+//! GPT header
+//!| Offset | Size | Description                            |
+//!|--------|------|----------------------------------------|
+//!| 0x00   | 8    | Signature 'EFI PART'                   |
+//!| 0x08   | 4    | Revision                               |
+//!| 0x0C   | 4    | Header size                            |
+//!| 0x10   | 4    | CRC32 of the header                    |
+//!| 0x18   | 8    | LBA of this header                     |
+//!| 0x20   | 8    | LBA of the backup header               |
+//!| 0x28   | 8    | First usable LBA                       |
+//!| 0x30   | 8    | Last usable LBA                        |
+//!| 0x38   | 16   | Disk GUID                              |
+//!| 0x48   | 8    | LBA of the partition entries           |
+//!| 0x50   | 4    | Number of partition entries            |
+//!| 0x54   | 4    | Size of a partition entry              |
+//!| 0x58   | 4    | CRC32 of the partition entries         |
+//!
+//! Partition entry
+//!| Offset | Size | Description                            |
+//!|--------|------|----------------------------------------|
+//!| 0x00   | 16   | Partition type GUID, zero if unused    |
+//!| 0x10   | 16   | Unique partition GUID                  |
+//!| 0x20   | 8    | First LBA                              |
+//!| 0x28   | 8    | Last LBA, inclusive                    |
+//!| 0x30   | 8    | Attributes                             |
+//!| 0x38   | 72   | Name in UTF-16                         |
+//!
+//!<https://en.wikipedia.org/wiki/GUID_Partition_Table>
+use crate::{
+    bytes::{guid_at, u32_at, u64_at},
+    NtfsError, MBR_SECTOR_SIZE,
+};
+use std::{
+    fmt,
+    io::{Read, Seek, SeekFrom},
+};
+
+///The size of the partition entries this parser understands.
+pub const GPT_PARTITION_ENTRY_SIZE: usize = 0x80;
+
+///The largest partition entry accepted, so a corrupt header can't ask for a huge entry array.
+pub const MAX_GPT_PARTITION_ENTRY_SIZE: usize = 4096;
+
+///A GUID as stored on disk, with the first three fields little-endian.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Guid(pub [u8; 16]);
+
+impl Guid {
+    ///Basic Data Partition, used by Windows for NTFS, exFAT and FAT volumes.
+    pub const BASIC_DATA: Guid = Guid([
+        0xA2, 0xA0, 0xD0, 0xEB, 0xE5, 0xB9, 0x33, 0x44, 0x87, 0xC0, 0x68, 0xB6, 0xB7, 0x26, 0x99,
+        0xC7,
+    ]);
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == [0; 16]
+    }
+}
+
+impl fmt::Display for Guid {
+    ///Formats the GUID the way Windows does, e.g. EBD0A0A2-B9E5-4433-87C0-68B6B72699C7.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let b = &self.0;
+        write!(
+            f,
+            "{:08X}-{:04X}-{:04X}-",
+            u32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+            u16::from_le_bytes([b[4], b[5]]),
+            u16::from_le_bytes([b[6], b[7]])
+        )?;
+        for byte in &b[8..10] {
+            write!(f, "{byte:02X}")?;
+        }
+        write!(f, "-")?;
+        for byte in &b[10..16] {
+            write!(f, "{byte:02X}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GptPartition {
+    ///The position of the entry in the partition entry array.
+    pub index: usize,
+    pub type_guid: Guid,
+    pub unique_guid: Guid,
+    pub start_lba: u64,
+    ///The last sector of the partition, inclusive.
+    pub end_lba: u64,
+    pub attributes: u64,
+    pub name: String,
+}
+
+impl GptPartition {
+    ///Returns true for a Basic Data Partition. The boot sector tells NTFS apart from FAT and exFAT.
+    pub fn is_basic_data(&self) -> bool {
+        self.type_guid == Guid::BASIC_DATA
+    }
+
+    ///The byte offset of the partition, to pass to [`Ntfs::new_at_offset`](crate::Ntfs::new_at_offset).
+    pub fn byte_offset(&self) -> u64 {
+        self.start_lba.saturating_mul(MBR_SECTOR_SIZE)
+    }
+
+    ///The size of the partition in bytes.
+    pub fn size(&self) -> u64 {
+        (self.end_lba.saturating_sub(self.start_lba) + 1).saturating_mul(MBR_SECTOR_SIZE)
+    }
+}
+
+///Reads the GPT header at LBA 1 and returns the partitions in its entry array.
+///Unused entries, which have a zero type GUID, are left out. The CRC32 checksums aren't verified.
+pub fn scan_gpt<R: Read + Seek>(reader: &mut R) -> Result<Vec<GptPartition>, NtfsError> {
+    let mut header = [0u8; 512];
+    reader.seek(SeekFrom::Start(MBR_SECTOR_SIZE))?;
+    reader.read_exact(&mut header)?;
+    if &header[0x00..0x08] != b"EFI PART" {
+        return Err(NtfsError::BadMagic);
+    }
+
+    let entries_lba = u64_at(&header, 0x48);
+    let count = u32_at(&header, 0x50) as usize;
+    let entry_size = u32_at(&header, 0x54) as usize;
+    if !(GPT_PARTITION_ENTRY_SIZE..=MAX_GPT_PARTITION_ENTRY_SIZE).contains(&entry_size)
+        || !entry_size.is_power_of_two()
+    {
+        return Err(NtfsError::Corrupt("invalid GPT partition entry size"));
+    }
+    //The specification requires room for 128 entries, allow a generous amount more.
+    if count > 4096 {
+        return Err(NtfsError::Corrupt("too many GPT partition entries"));
+    }
+
+    let offset = entries_lba
+        .checked_mul(MBR_SECTOR_SIZE)
+        .ok_or(NtfsError::Corrupt("GPT partition entries are out of range"))?;
+    let len = count
+        .checked_mul(entry_size)
+        .ok_or(NtfsError::Corrupt("GPT partition entries are too large"))?;
+    let mut entries = vec![0u8; len];
+    reader.seek(SeekFrom::Start(offset))?;
+    reader.read_exact(&mut entries)?;
+
+    let mut partitions = Vec::new();
+    for (index, entry) in entries.chunks_exact(entry_size).enumerate() {
+        let type_guid = guid_at(entry, 0x00);
+        if type_guid.is_zero() {
+            continue;
+        }
+
+        let units: Vec<u16> = entry[0x38..0x80]
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+            .take_while(|unit| *unit != 0)
+            .collect();
+        partitions.push(GptPartition {
+            index,
+            type_guid,
+            unique_guid: guid_at(entry, 0x10),
+            start_lba: u64_at(entry, 0x20),
+            end_lba: u64_at(entry, 0x28),
+            attributes: u64_at(entry, 0x30),
+            name: String::from_utf16_lossy(&units),
+        });
+    }
+    Ok(partitions)
+}
@@ -0,0 +1,653 @@
+//! Directories are B-tree indexes of $FILE_NAME keys, stored in attributes named $I30.
+//! Small directories fit entirely in the resident $INDEX_ROOT (0x90) attribute.
+//!
+//! $INDEX_ROOT
+//!| Offset | Size | Description                         |
+//!|--------|------|-------------------------------------|
+//!| 0x00   | 4    | Attribute type of the key (0x30)    |
+//!| 0x04   | 4    | Collation rule                      |
+//!| 0x08   | 4    | Bytes per index record              |
+//!| 0x0C   | 1    | Clusters per index record           |
+//!| 0x0D   | 3    | Padding                             |
+//!| 0x10   | 16   | Index node header                   |
+//!
+//! Index node header
+//!| Offset | Size | Description                         |
+//!|--------|------|-------------------------------------|
+//!| 0x00   | 4    | Offset to the first index entry     |
+//!| 0x04   | 4    | Total size of the index entries     |
+//!| 0x08   | 4    | Allocated size of the index entries |
+//!| 0x0C   | 1    | Flags, 1 if there are sub-nodes     |
+//!| 0x0D   | 3    | Padding                             |
+//!
+//! The offsets are relative to the start of the node header.
+//!
+//! Index entry
+//!| Offset | Size | Description                         |
+//!|--------|------|-------------------------------------|
+//!| 0x00   | 8    | File reference                      |
+//!| 0x08   | 2    | Length of the index entry (L)       |
+//!| 0x0A   | 2    | Length of the stream                |
+//!| 0x0C   | 1    | Flags                               |
+//!| 0x0D   | 3    | Padding                             |
+//!| 0x10   | ?    | Stream, a copy of the $FILE_NAME    |
+//!| L - 8  | 8    | VCN of the sub-node, if flag 1 set  |
+//!
+//! The last entry in a node has flag 2 set and no stream.
+//!
+//! Larger directories keep their nodes in INDX blocks, stored in the non-resident $INDEX_ALLOCATION (0xA0) attribute.
+//! The $BITMAP (0xB0) attribute of the same name marks which blocks are in use.
+//! Like FILE records, INDX blocks are protected by an update sequence.
+//!
+//! INDX header
+//!| Offset | Size | Description                         |
+//!|--------|------|-------------------------------------|
+//!| 0x00   | 4    | Magic number 'INDX'                 |
+//!| 0x04   | 2    | Offset to the Update Sequence       |
+//!| 0x06   | 2    | Size in words of Update Sequence    |
+//!| 0x08   | 8    | $LogFile Sequence Number (LSN)      |
+//!| 0x10   | 8    | VCN of this INDX block              |
+//!| 0x18   | 16   | Index node header                   |
+//!
+//!<https://flatcap.github.io/linux-ntfs/ntfs/attributes/index_root.html>
+//!
+//!<https://flatcap.github.io/linux-ntfs/ntfs/concepts/index_record.html>
+use crate::{
+    apply_fixup,
+    bytes::{u16_at, u32_at, u64_at},
+    AttributeType, FileName, FileRecord, FileReference, Ntfs, NtfsError, UpcaseTable, FIXUP_STRIDE,
+};
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    io::{Read, Seek},
+};
+
+///The size of the $INDEX_ROOT header before the node header.
+pub const INDEX_ROOT_HEADER_SIZE: usize = 0x10;
+
+///The size of the index node header.
+pub const INDEX_NODE_HEADER_SIZE: usize = 0x10;
+
+///The size of an index entry without its stream.
+pub const INDEX_ENTRY_HEADER_SIZE: usize = 0x10;
+
+///The size of the INDX header before the node header.
+pub const INDEX_BLOCK_HEADER_SIZE: usize = 0x18;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IndexEntry {
+    pub file_reference: FileReference,
+    pub file_name: FileName,
+    pub flags: u8,
+    ///The VCN of the index record holding the entries that sort before this one.
+    pub sub_node_vcn: Option<u64>,
+}
+
+impl IndexEntry {
+    pub const SUB_NODE: u8 = 0x01;
+    pub const LAST_ENTRY: u8 = 0x02;
+
+    pub fn has_sub_node(&self) -> bool {
+        self.flags & Self::SUB_NODE != 0
+    }
+}
+
+///Parses the entries of an index node until the last entry.
+///`node` starts at the node header and the last entry itself is not returned.
+pub fn parse_index_entries(node: &[u8]) -> Result<Vec<IndexEntry>, NtfsError> {
+    Ok(parse_index_node(node)?.0)
+}
+
+///Parses the entries of an index node, along with the sub-node VCN of the last entry,
+///which points at the entries that sort after every other entry in the node.
+fn parse_index_node(node: &[u8]) -> Result<(Vec<IndexEntry>, Option<u64>), NtfsError> {
+    let (raw_entries, last_sub_node_vcn) = split_index_node(node)?;
+    let mut entries = Vec::new();
+    for entry in raw_entries {
+        let stream_length = u16_at(entry, 0x0A) as usize;
+        if INDEX_ENTRY_HEADER_SIZE + stream_length > entry.len() {
+            return Err(NtfsError::Corrupt("index entry stream runs past the entry"));
+        }
+        entries.push(IndexEntry {
+            file_reference: FileReference::from_raw(u64_at(entry, 0x00)),
+            file_name: FileName::parse(
+                &entry[INDEX_ENTRY_HEADER_SIZE..INDEX_ENTRY_HEADER_SIZE + stream_length],
+            )?,
+            flags: entry[0x0C],
+            sub_node_vcn: sub_node_vcn(entry)?,
+        });
+    }
+    Ok((entries, last_sub_node_vcn))
+}
+
+///Splits an index node into the bytes of each entry, whatever its key, checking they fit in the node.
+///The last entry has no key, so only its sub-node VCN is returned.
+fn split_index_node(node: &[u8]) -> Result<(Vec<&[u8]>, Option<u64>), NtfsError> {
+    if node.len() < INDEX_NODE_HEADER_SIZE {
+        return Err(NtfsError::BufferTooSmall {
+            needed: INDEX_NODE_HEADER_SIZE,
+            available: node.len(),
+        });
+    }
+    let mut offset = u32_at(node, 0x00) as usize;
+    let end = (u32_at(node, 0x04) as usize).min(node.len());
+
+    let mut entries = Vec::new();
+    loop {
+        if offset + INDEX_ENTRY_HEADER_SIZE > end {
+            return Err(NtfsError::Corrupt("index node has no last entry"));
+        }
+        let length = u16_at(node, offset + 0x08) as usize;
+        if length < INDEX_ENTRY_HEADER_SIZE || offset + length > end {
+            return Err(NtfsError::Corrupt("index entry runs past the index node"));
+        }
+        let entry = &node[offset..offset + length];
+        if entry[0x0C] & IndexEntry::LAST_ENTRY != 0 {
+            return Ok((entries, sub_node_vcn(entry)?));
+        }
+        entries.push(entry);
+        offset += length;
+    }
+}
+
+///The VCN at the end of an index entry that has a sub-node.
+fn sub_node_vcn(entry: &[u8]) -> Result<Option<u64>, NtfsError> {
+    if entry[0x0C] & IndexEntry::SUB_NODE == 0 {
+        return Ok(None);
+    }
+    if entry.len() < INDEX_ENTRY_HEADER_SIZE + 8 {
+        return Err(NtfsError::Corrupt("index entry has no room for a sub-node"));
+    }
+    Ok(Some(u64_at(entry, entry.len() - 8)))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IndexRoot {
+    ///The type of attribute being indexed, 0x30 ($FILE_NAME) for directories.
+    pub attribute_type: u32,
+    pub collation_rule: u32,
+    pub index_record_size: u32,
+    pub clusters_per_index_record: u8,
+    ///The flags of the node header.
+    pub flags: u8,
+    pub entries: Vec<IndexEntry>,
+    ///The sub-node of the last entry, holding the keys that sort after every entry.
+    pub last_sub_node_vcn: Option<u64>,
+}
+
+impl IndexRoot {
+    ///Set when the index doesn't fit in the root and continues in $INDEX_ALLOCATION.
+    pub const LARGE_INDEX: u8 = 0x01;
+
+    pub fn parse(value: &[u8]) -> Result<IndexRoot, NtfsError> {
+        let needed = INDEX_ROOT_HEADER_SIZE + INDEX_NODE_HEADER_SIZE;
+        if value.len() < needed {
+            return Err(NtfsError::BufferTooSmall {
+                needed,
+                available: value.len(),
+            });
+        }
+        let node = &value[INDEX_ROOT_HEADER_SIZE..];
+        let (entries, last_sub_node_vcn) = parse_index_node(node)?;
+
+        Ok(IndexRoot {
+            attribute_type: u32_at(value, 0x00),
+            collation_rule: u32_at(value, 0x04),
+            index_record_size: u32_at(value, 0x08),
+            clusters_per_index_record: value[0x0C],
+            flags: node[0x0C],
+            entries,
+            last_sub_node_vcn,
+        })
+    }
+
+    pub fn is_large_index(&self) -> bool {
+        self.flags & Self::LARGE_INDEX != 0
+    }
+}
+
+///An INDX block from $INDEX_ALLOCATION.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IndexBlock {
+    pub lsn: u64,
+    pub vcn: u64,
+    ///The flags of the node header, 1 if the entries have sub-nodes.
+    pub flags: u8,
+    pub entries: Vec<IndexEntry>,
+    ///The sub-node of the last entry, holding the keys that sort after every entry.
+    pub last_sub_node_vcn: Option<u64>,
+}
+
+impl IndexBlock {
+    ///Verifies the 'INDX' magic, applies the update sequence and parses the entries.
+    pub fn parse(bytes: &[u8]) -> Result<IndexBlock, NtfsError> {
+        let data = fixed_up_index_block(bytes)?;
+        let node = &data[INDEX_BLOCK_HEADER_SIZE..];
+        let (entries, last_sub_node_vcn) = parse_index_node(node)?;
+
+        Ok(IndexBlock {
+            lsn: u64_at(&data, 0x08),
+            vcn: u64_at(&data, 0x10),
+            flags: node[0x0C],
+            entries,
+            last_sub_node_vcn,
+        })
+    }
+}
+
+///Verifies the 'INDX' magic of an INDX block and applies its update sequence.
+fn fixed_up_index_block(bytes: &[u8]) -> Result<Vec<u8>, NtfsError> {
+    let needed = INDEX_BLOCK_HEADER_SIZE + INDEX_NODE_HEADER_SIZE;
+    if bytes.len() < needed {
+        return Err(NtfsError::BufferTooSmall {
+            needed,
+            available: bytes.len(),
+        });
+    }
+    if &bytes[0..4] != b"INDX" {
+        return Err(NtfsError::BadMagic);
+    }
+    let mut data = bytes.to_vec();
+    apply_fixup(
+        &mut data,
+        u16_at(bytes, 0x04),
+        u16_at(bytes, 0x06),
+        FIXUP_STRIDE,
+    )?;
+    Ok(data)
+}
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///Reads the entries in the INDX blocks of the directory's $I30 $INDEX_ALLOCATION.
+    ///Blocks marked as unused in the $I30 $BITMAP are skipped.
+    ///Returns no entries for a small directory that fits in its $INDEX_ROOT.
+    pub fn read_index_allocation(
+        &mut self,
+        record: &FileRecord,
+    ) -> Result<Vec<IndexEntry>, NtfsError> {
+        let block_size = record.index_root()?.index_record_size as usize;
+        if block_size == 0 {
+            return Err(NtfsError::Corrupt("index record size is zero"));
+        }
+
+        let Some(allocation) = self.index_allocation(record, "$I30")? else {
+            return Ok(Vec::new());
+        };
+
+        let mut entries = Vec::new();
+        for (i, block) in allocation.blocks.chunks_exact(block_size).enumerate() {
+            if !block_in_use(allocation.bitmap.as_deref(), i) {
+                continue;
+            }
+            entries.extend(IndexBlock::parse(block)?.entries);
+        }
+        Ok(entries)
+    }
+
+    ///Reads the $INDEX_ALLOCATION and $BITMAP attributes of the index named `name`, e.g. $I30 for a directory,
+    ///or returns `None` if it has no $INDEX_ALLOCATION.
+    fn index_allocation(
+        &mut self,
+        record: &FileRecord,
+        name: &str,
+    ) -> Result<Option<IndexAllocation>, NtfsError> {
+        let mut allocation = None;
+        let mut bitmap = None;
+        for attribute in record.attributes() {
+            let attribute = attribute?;
+            if !attribute.has_name(name) {
+                continue;
+            }
+            if attribute.type_code() == AttributeType::IndexAllocation {
+                allocation = Some(self.read_attribute(&attribute)?);
+            } else if attribute.type_code() == AttributeType::Bitmap {
+                bitmap = Some(self.read_attribute(&attribute)?);
+            }
+        }
+        Ok(allocation.map(|blocks| IndexAllocation { blocks, bitmap }))
+    }
+
+    ///Iterates over the entries of a directory in index order, which is sorted by name.
+    ///The B-tree is walked from $INDEX_ROOT down through the sub-node of each entry,
+    ///reading the INDX blocks from $INDEX_ALLOCATION.
+    pub fn directory_iter(&mut self, record: &FileRecord) -> Result<DirectoryIter, NtfsError> {
+        let root = record.index_root()?;
+        let block_size = root.index_record_size as usize;
+        let allocation = if root.is_large_index() {
+            self.index_allocation(record, "$I30")?
+        } else {
+            None
+        };
+        let vcn_size = self.index_vcn_size(block_size);
+
+        let mut entries = root.entries;
+        entries.reverse();
+        Ok(DirectoryIter {
+            allocation: allocation.unwrap_or_default(),
+            block_size,
+            vcn_size,
+            visited: HashSet::new(),
+            stack: vec![IndexNode {
+                entries,
+                descended: false,
+                last_sub_node_vcn: root.last_sub_node_vcn,
+            }],
+        })
+    }
+}
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///The unit of the sub-node VCNs of an index with `block_size` byte INDX blocks.
+    ///Blocks smaller than a cluster are addressed in 512 byte units instead of clusters.
+    fn index_vcn_size(&self, block_size: usize) -> u64 {
+        let cluster_size = self.geometry().bytes_per_cluster;
+        if block_size as u64 >= cluster_size {
+            cluster_size
+        } else {
+            512
+        }
+    }
+
+    ///Reads the $INDEX_ROOT named `name` and, for a large index, its $INDEX_ALLOCATION.
+    fn view_index(&mut self, record: &FileRecord, name: &str) -> Result<ViewIndex, NtfsError> {
+        let mut root = None;
+        for attribute in record.attributes() {
+            let attribute = attribute?;
+            if attribute.type_code() == AttributeType::IndexRoot && attribute.has_name(name) {
+                root = Some(
+                    attribute
+                        .value()
+                        .ok_or(NtfsError::Corrupt("$INDEX_ROOT is not resident"))?
+                        .to_vec(),
+                );
+                break;
+            }
+        }
+        let root = root.ok_or(NtfsError::Corrupt("FILE record has no such index root"))?;
+        let needed = INDEX_ROOT_HEADER_SIZE + INDEX_NODE_HEADER_SIZE;
+        if root.len() < needed {
+            return Err(NtfsError::BufferTooSmall {
+                needed,
+                available: root.len(),
+            });
+        }
+        let block_size = u32_at(&root, 0x08) as usize;
+        let vcn_size = self.index_vcn_size(block_size);
+        let allocation = if root[INDEX_ROOT_HEADER_SIZE + 0x0C] & IndexRoot::LARGE_INDEX != 0 {
+            self.index_allocation(record, name)?.unwrap_or_default()
+        } else {
+            IndexAllocation::default()
+        };
+        Ok(ViewIndex {
+            root: root[INDEX_ROOT_HEADER_SIZE..].to_vec(),
+            block_size,
+            vcn_size,
+            allocation,
+        })
+    }
+
+    ///Looks up a key in a view index like $O or $SII by descending its B-tree.
+    ///`compare` orders the key being looked for against an entry, given the raw bytes of the entry.
+    ///Returns the raw bytes of the matching entry.
+    pub(crate) fn search_view_index(
+        &mut self,
+        record: &FileRecord,
+        name: &str,
+        mut compare: impl FnMut(&[u8]) -> Result<Ordering, NtfsError>,
+    ) -> Result<Option<Vec<u8>>, NtfsError> {
+        let index = self.view_index(record, name)?;
+        let mut node = index.root.clone();
+        let mut visited = HashSet::new();
+        loop {
+            let (entries, mut sub_node) = split_index_node(&node)?;
+            for entry in entries {
+                match compare(entry)? {
+                    Ordering::Equal => return Ok(Some(entry.to_vec())),
+                    Ordering::Less => {
+                        sub_node = sub_node_vcn(entry)?;
+                        break;
+                    }
+                    Ordering::Greater => {}
+                }
+            }
+            let Some(vcn) = sub_node else {
+                return Ok(None);
+            };
+            if !visited.insert(vcn) {
+                return Err(NtfsError::Corrupt("index sub-nodes form a cycle"));
+            }
+            node = index.node(vcn)?;
+        }
+    }
+
+    ///The raw bytes of every entry in a view index like $O or $Q, in index order.
+    pub(crate) fn view_index_entries(
+        &mut self,
+        record: &FileRecord,
+        name: &str,
+    ) -> Result<Vec<Vec<u8>>, NtfsError> {
+        let index = self.view_index(record, name)?;
+        let mut entries = Vec::new();
+        index.collect_entries(&index.root, &mut HashSet::new(), &mut entries)?;
+        Ok(entries)
+    }
+
+    ///Looks up `name` in a directory, ignoring case, by descending its B-tree.
+    ///Names are compared with `upcase` the way NTFS collates them, so only the INDX blocks
+    ///on the way to the entry are parsed. The entry found can be a DOS short name.
+    pub fn find_in_directory(
+        &mut self,
+        record: &FileRecord,
+        name: &str,
+        upcase: &UpcaseTable,
+    ) -> Result<Option<IndexEntry>, NtfsError> {
+        let name: Vec<u16> = name.encode_utf16().collect();
+        self.directory_iter(record)?.search(&name, upcase)
+    }
+}
+
+///The root node of a view index, starting at its node header, and the INDX blocks below it.
+struct ViewIndex {
+    root: Vec<u8>,
+    block_size: usize,
+    vcn_size: u64,
+    allocation: IndexAllocation,
+}
+
+impl ViewIndex {
+    ///The node in the INDX block at `vcn`, starting at its node header.
+    fn node(&self, vcn: u64) -> Result<Vec<u8>, NtfsError> {
+        let block =
+            fixed_up_index_block(self.allocation.block(vcn, self.vcn_size, self.block_size)?)?;
+        if u64_at(&block, 0x10) != vcn {
+            return Err(NtfsError::Corrupt("INDX block has the wrong VCN"));
+        }
+        Ok(block[INDEX_BLOCK_HEADER_SIZE..].to_vec())
+    }
+
+    ///Adds the entries of `node` and its sub-nodes to `entries` in index order,
+    ///each sub-node coming before the entry that points at it.
+    fn collect_entries(
+        &self,
+        node: &[u8],
+        visited: &mut HashSet<u64>,
+        entries: &mut Vec<Vec<u8>>,
+    ) -> Result<(), NtfsError> {
+        let (node_entries, last_sub_node) = split_index_node(node)?;
+        for entry in node_entries {
+            if let Some(vcn) = sub_node_vcn(entry)? {
+                self.collect_sub_node(vcn, visited, entries)?;
+            }
+            entries.push(entry.to_vec());
+        }
+        if let Some(vcn) = last_sub_node {
+            self.collect_sub_node(vcn, visited, entries)?;
+        }
+        Ok(())
+    }
+
+    fn collect_sub_node(
+        &self,
+        vcn: u64,
+        visited: &mut HashSet<u64>,
+        entries: &mut Vec<Vec<u8>>,
+    ) -> Result<(), NtfsError> {
+        if !visited.insert(vcn) {
+            return Err(NtfsError::Corrupt("index sub-nodes form a cycle"));
+        }
+        self.collect_entries(&self.node(vcn)?, visited, entries)
+    }
+}
+
+///The INDX blocks of an index and the bitmap of the ones in use.
+#[derive(Default)]
+struct IndexAllocation {
+    blocks: Vec<u8>,
+    bitmap: Option<Vec<u8>>,
+}
+
+impl IndexAllocation {
+    ///The INDX block a sub-node VCN points at, which must be marked as used.
+    fn block(&self, vcn: u64, vcn_size: u64, block_size: usize) -> Result<&[u8], NtfsError> {
+        let offset = vcn
+            .checked_mul(vcn_size)
+            .and_then(|offset| usize::try_from(offset).ok())
+            .ok_or(NtfsError::Corrupt("index sub-node VCN is out of range"))?;
+        if block_size == 0 || offset % block_size != 0 {
+            return Err(NtfsError::Corrupt(
+                "index sub-node is not on a block boundary",
+            ));
+        }
+        if !block_in_use(self.bitmap.as_deref(), offset / block_size) {
+            return Err(NtfsError::Corrupt("index sub-node is not in use"));
+        }
+        self.blocks
+            .get(offset..offset + block_size)
+            .ok_or(NtfsError::Corrupt(
+                "index sub-node is past the end of $INDEX_ALLOCATION",
+            ))
+    }
+}
+
+///Returns true if block `i` is marked as used, or if there's no bitmap to say otherwise.
+fn block_in_use(bitmap: Option<&[u8]>, i: usize) -> bool {
+    match bitmap {
+        Some(bitmap) => bitmap
+            .get(i / 8)
+            .is_some_and(|byte| byte & (1 << (i % 8)) != 0),
+        None => true,
+    }
+}
+
+///A node of the B-tree being walked by [`DirectoryIter`].
+struct IndexNode {
+    ///The entries left to yield, in reverse order.
+    entries: Vec<IndexEntry>,
+    ///Set once the sub-node of the next entry has been visited.
+    descended: bool,
+    last_sub_node_vcn: Option<u64>,
+}
+
+///Walks the B-tree of a directory index in order. Created with [`Ntfs::directory_iter`].
+///The path from the root to the current node is kept on the heap, so deep trees can't overflow the stack.
+///Iteration stops after the first error.
+pub struct DirectoryIter {
+    allocation: IndexAllocation,
+    block_size: usize,
+    vcn_size: u64,
+    visited: HashSet<u64>,
+    stack: Vec<IndexNode>,
+}
+
+impl DirectoryIter {
+    ///Parses the INDX block at `vcn`.
+    fn node(&mut self, vcn: u64) -> Result<IndexNode, NtfsError> {
+        if !self.visited.insert(vcn) {
+            return Err(NtfsError::Corrupt("index sub-nodes form a cycle"));
+        }
+        let block = self.allocation.block(vcn, self.vcn_size, self.block_size)?;
+        let block = IndexBlock::parse(block)?;
+        if block.vcn != vcn {
+            return Err(NtfsError::Corrupt("INDX block has the wrong VCN"));
+        }
+        let mut entries = block.entries;
+        entries.reverse();
+        Ok(IndexNode {
+            entries,
+            descended: false,
+            last_sub_node_vcn: block.last_sub_node_vcn,
+        })
+    }
+
+    ///Descends from the root node to the entry named `name`.
+    fn search(
+        &mut self,
+        name: &[u16],
+        upcase: &UpcaseTable,
+    ) -> Result<Option<IndexEntry>, NtfsError> {
+        let Some(mut node) = self.stack.pop() else {
+            return Ok(None);
+        };
+        loop {
+            let mut sub_node = node.last_sub_node_vcn;
+            //The entries are stored in reverse, so this pops them in index order.
+            while let Some(entry) = node.entries.pop() {
+                let entry_name: Vec<u16> = entry.file_name.name.encode_utf16().collect();
+                match upcase.compare(name, &entry_name) {
+                    Ordering::Equal => return Ok(Some(entry)),
+                    Ordering::Less => {
+                        sub_node = entry.sub_node_vcn;
+                        break;
+                    }
+                    Ordering::Greater => {}
+                }
+            }
+            match sub_node {
+                Some(vcn) => node = self.node(vcn)?,
+                None => return Ok(None),
+            }
+        }
+    }
+
+    fn step(&mut self) -> Result<Option<IndexEntry>, NtfsError> {
+        loop {
+            let Some(node) = self.stack.last_mut() else {
+                return Ok(None);
+            };
+            //Every entry comes after the entries in its sub-node.
+            let sub_node = match node.entries.last() {
+                Some(entry) if !node.descended => entry.sub_node_vcn,
+                Some(_) => None,
+                None => node.last_sub_node_vcn.take(),
+            };
+            if let Some(vcn) = sub_node {
+                node.descended = true;
+                let child = self.node(vcn)?;
+                self.stack.push(child);
+                continue;
+            }
+            match node.entries.pop() {
+                Some(entry) => {
+                    node.descended = false;
+                    return Ok(Some(entry));
+                }
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+impl Iterator for DirectoryIter {
+    type Item = Result<IndexEntry, NtfsError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.step();
+        if result.is_err() {
+            self.stack.clear();
+        }
+        result.transpose()
+    }
+}
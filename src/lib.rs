@@ -0,0 +1,82 @@
+//! <https://flatcap.github.io/linux-ntfs/ntfs/index.html>
+//!
+//! <https://en.wikipedia.org/wiki/NTFS>
+pub use attr_def::*;
+pub use attribute_list::*;
+pub use bad_clusters::*;
+pub use cluster_bitmap::*;
+pub use data_run::*;
+pub use data_stream::*;
+pub use error::*;
+pub use extended_attribute::*;
+pub use file_name::*;
+pub use file_reader::*;
+pub use file_record::*;
+pub use file_reference::*;
+pub use fixup::*;
+pub use geometry::*;
+pub use gpt::*;
+pub use index::*;
+pub use log_file::*;
+pub use lznt1::*;
+pub use master_file_table::*;
+pub use mbr::*;
+pub use mft_bitmap::*;
+pub use mft_mirror::*;
+pub use object_id::*;
+pub use partition_boot_sector::*;
+pub use quota::*;
+pub use record_cache::*;
+pub use reparse_point::*;
+pub use security::*;
+pub use sid::*;
+pub use standard_information::*;
+pub use stream::*;
+pub use time::*;
+pub use undelete::*;
+pub use upcase::*;
+pub use usn_journal::*;
+pub use volume::*;
+pub use volume_information::*;
+pub use walk::*;
+
+pub mod attr_def;
+pub mod attribute_list;
+pub mod bad_clusters;
+mod bytes;
+pub mod cluster_bitmap;
+pub mod data;
+pub mod data_run;
+pub mod data_stream;
+pub mod error;
+pub mod extended_attribute;
+pub mod file_name;
+pub mod file_reader;
+pub mod file_record;
+pub mod file_reference;
+pub mod fixup;
+pub mod geometry;
+pub mod gpt;
+pub mod index;
+pub mod log_file;
+pub mod lznt1;
+pub mod master_file_table;
+pub mod mbr;
+pub mod mft_bitmap;
+pub mod mft_mirror;
+pub mod object_id;
+pub mod partition_boot_sector;
+pub mod quota;
+pub mod record_cache;
+pub mod reparse_point;
+pub mod security;
+pub mod sid;
+pub mod standard_information;
+pub mod stream;
+pub mod time;
+pub mod undelete;
+pub mod upcase;
+pub mod usn_journal;
+pub mod volume;
+pub mod volume_information;
+pub mod walk;
@@ -0,0 +1,802 @@
+//! $LogFile (FILE record 2) is the journal NTFS replays after a crash. It starts with two copies of
+//! the restart page, each protected by its own update sequence, followed by the circular log of
+//! RCRD pages holding the log records. Every change to the metadata is logged under a log sequence
+//! number (LSN), and FILE records keep the LSN of their last change at 0x08.
+//!
+//! Restart page
+//!| Offset | Size | Description                                   |
+//!|--------|------|-----------------------------------------------|
+//!| 0x00   | 4    | Magic 'RSTR', or 'CHKD' after chkdsk          |
+//!| 0x04   | 2    | Offset to the update sequence                 |
+//!| 0x06   | 2    | Size in words of the update sequence          |
+//!| 0x08   | 8    | LSN of the last chkdsk                        |
+//!| 0x10   | 4    | System page size                              |
+//!| 0x14   | 4    | Log page size                                 |
+//!| 0x18   | 2    | Offset to the restart area                    |
+//!| 0x1A   | 2    | Minor version, 1 or 0                         |
+//!| 0x1C   | 2    | Major version, 1 or 2                         |
+//!
+//! Restart area
+//!| Offset | Size | Description                                   |
+//!|--------|------|-----------------------------------------------|
+//!| 0x00   | 8    | Current LSN                                   |
+//!| 0x08   | 2    | Number of log clients                         |
+//!| 0x0A   | 2    | First free client                             |
+//!| 0x0C   | 2    | First client in use                           |
+//!| 0x0E   | 2    | Flags                                         |
+//!| 0x10   | 4    | Number of sequence number bits in an LSN      |
+//!| 0x14   | 2    | Size of the restart area                      |
+//!| 0x16   | 2    | Offset to the client array                    |
+//!| 0x18   | 8    | Size of the log file                          |
+//!| 0x20   | 4    | Size of the data of the last LSN              |
+//!| 0x24   | 2    | Size of a log record header                   |
+//!| 0x26   | 2    | Offset to the data of a log page              |
+//!| 0x28   | 4    | Restart log open count                        |
+//!
+//! Log record
+//!| Offset | Size | Description                                   |
+//!|--------|------|-----------------------------------------------|
+//!| 0x00   | 8    | This LSN                                      |
+//!| 0x08   | 8    | Previous LSN of the client                    |
+//!| 0x10   | 8    | Next LSN to undo                              |
+//!| 0x18   | 4    | Size of the client data                       |
+//!| 0x1C   | 2    | Client sequence number                        |
+//!| 0x1E   | 2    | Client index                                  |
+//!| 0x20   | 4    | Record type                                   |
+//!| 0x24   | 4    | Transaction id                                |
+//!| 0x28   | 2    | Flags                                         |
+//!| 0x30   | 2    | Redo operation                                |
+//!| 0x32   | 2    | Undo operation                                |
+//!| 0x34   | 2    | Offset to the redo data                       |
+//!| 0x36   | 2    | Size of the redo data                         |
+//!| 0x38   | 2    | Offset to the undo data                       |
+//!| 0x3A   | 2    | Size of the undo data                         |
+//!| 0x3C   | 2    | Target attribute                              |
+//!| 0x3E   | 2    | Number of LCNs to follow                      |
+//!| 0x40   | 2    | Record offset                                 |
+//!| 0x42   | 2    | Attribute offset                              |
+//!| 0x44   | 2    | Cluster index                                 |
+//!| 0x48   | 8    | Target VCN                                    |
+//!| 0x50   | 8N   | LCNs                                          |
+//!
+//! The fields from 0x30 on are the NTFS client's data, only present in standard records.
+//! Log records are 8 byte aligned and their data can run on into the following pages,
+//! but the header always fits in the page it starts in.
+//!
+//!<https://flatcap.github.io/linux-ntfs/ntfs/files/logfile.html>
+use crate::{
+    apply_fixup,
+    bytes::{u16_at, u32_at, u64_at},
+    DataStream, Ntfs, NtfsError, NtfsStreamReader, SystemFile, FIXUP_STRIDE,
+};
+use std::{
+    fmt,
+    io::{Read, Seek, SeekFrom},
+};
+
+///The size of the restart page header, up to the update sequence.
+pub const RESTART_PAGE_HEADER_SIZE: usize = 0x1E;
+
+///The size of the restart area.
+pub const RESTART_AREA_SIZE: usize = 0x30;
+
+///The size of a log client record in the restart area's client array.
+pub const LOG_CLIENT_RECORD_SIZE: usize = 0xA0;
+
+///The size of the RCRD page header, up to the update sequence.
+pub const RECORD_PAGE_HEADER_SIZE: usize = 0x28;
+
+///The size of a log record header, without the client data.
+pub const LOG_RECORD_HEADER_SIZE: usize = 0x30;
+
+///The size of the NTFS client's data at the start of a standard log record, without the LCNs.
+pub const LOG_OPERATION_HEADER_SIZE: usize = 0x20;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RestartPage {
+    ///Set when the magic is 'CHKD' rather than 'RSTR'.
+    pub is_chkdsk: bool,
+    pub chkdsk_lsn: u64,
+    pub system_page_size: u32,
+    pub log_page_size: u32,
+    pub restart_area_offset: u16,
+    pub minor_version: u16,
+    pub major_version: u16,
+    pub restart_area: RestartArea,
+}
+
+impl RestartPage {
+    ///Parses a restart page, applying its update sequence to a copy of `page`.
+    ///Versions 1.1 and 2.0 are read, others are `Unsupported`.
+    pub fn parse(page: &[u8]) -> Result<RestartPage, NtfsError> {
+        if page.len() < RESTART_PAGE_HEADER_SIZE {
+            return Err(NtfsError::BufferTooSmall {
+                needed: RESTART_PAGE_HEADER_SIZE,
+                available: page.len(),
+            });
+        }
+        let is_chkdsk = match &page[0..4] {
+            b"RSTR" => false,
+            b"CHKD" => true,
+            _ => return Err(NtfsError::BadMagic),
+        };
+        let system_page_size = u32_at(page, 0x10);
+        if system_page_size as usize != page.len() {
+            return Err(NtfsError::Corrupt(
+                "restart page size doesn't match the page",
+            ));
+        }
+        let (usn_offset, usn_count) = (u16_at(page, 0x04), u16_at(page, 0x06));
+        let mut page = page.to_vec();
+        apply_fixup(&mut page, usn_offset, usn_count, FIXUP_STRIDE)?;
+
+        let minor_version = u16_at(&page, 0x1A);
+        let major_version = u16_at(&page, 0x1C);
+        if !matches!((major_version, minor_version), (1, 1) | (2, 0)) {
+            return Err(NtfsError::Unsupported("$LogFile version"));
+        }
+        let log_page_size = u32_at(&page, 0x14);
+        if !log_page_size.is_power_of_two() || log_page_size < FIXUP_STRIDE as u32 {
+            return Err(NtfsError::Corrupt("$LogFile page size is invalid"));
+        }
+        //The restart area follows the update sequence, which is longer on larger pages,
+        //so it's found through its offset rather than at a fixed place.
+        let restart_area_offset = u16_at(&page, 0x18);
+        let restart_area = page
+            .get(restart_area_offset as usize..)
+            .ok_or(NtfsError::Corrupt("restart area is past the restart page"))
+            .and_then(RestartArea::parse)?;
+        if restart_area.log_page_data_offset as u32 >= log_page_size {
+            return Err(NtfsError::Corrupt("log page data offset is past the page"));
+        }
+
+        Ok(RestartPage {
+            is_chkdsk,
+            chkdsk_lsn: u64_at(&page, 0x08),
+            system_page_size,
+            log_page_size,
+            restart_area_offset,
+            minor_version,
+            major_version,
+            restart_area,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RestartArea {
+    pub current_lsn: u64,
+    pub log_clients: u16,
+    pub client_free_list: u16,
+    pub client_in_use_list: u16,
+    pub flags: u16,
+    ///The number of high bits of an LSN that hold the sequence number, the rest is the offset.
+    pub seq_number_bits: u32,
+    pub restart_area_length: u16,
+    pub client_array_offset: u16,
+    pub file_size: u64,
+    pub last_lsn_data_length: u32,
+    pub log_record_header_length: u16,
+    pub log_page_data_offset: u16,
+    ///Missing from restart areas shorter than 0x30 bytes.
+    pub restart_log_open_count: Option<u32>,
+    pub clients: Vec<LogClient>,
+}
+
+impl RestartArea {
+    ///The volume was cleanly unmounted, so there's nothing to replay.
+    pub const VOLUME_IS_CLEAN: u16 = 0x0002;
+
+    pub fn parse(area: &[u8]) -> Result<RestartArea, NtfsError> {
+        if area.len() < RESTART_AREA_SIZE {
+            return Err(NtfsError::BufferTooSmall {
+                needed: RESTART_AREA_SIZE,
+                available: area.len(),
+            });
+        }
+        let seq_number_bits = u32_at(area, 0x10);
+        if !(3..64).contains(&seq_number_bits) {
+            return Err(NtfsError::Corrupt("LSN sequence number bits are invalid"));
+        }
+        let restart_area_length = u16_at(area, 0x14);
+        let client_array_offset = u16_at(area, 0x16);
+        let log_clients = u16_at(area, 0x08);
+        let mut clients = Vec::new();
+        for client in 0..log_clients as usize {
+            let offset = client_array_offset as usize + client * LOG_CLIENT_RECORD_SIZE;
+            let record = area
+                .get(offset..offset + LOG_CLIENT_RECORD_SIZE)
+                .ok_or(NtfsError::Corrupt("log client is past the restart page"))?;
+            clients.push(LogClient::parse(record)?);
+        }
+
+        Ok(RestartArea {
+            current_lsn: u64_at(area, 0x00),
+            log_clients,
+            client_free_list: u16_at(area, 0x0A),
+            client_in_use_list: u16_at(area, 0x0C),
+            flags: u16_at(area, 0x0E),
+            seq_number_bits,
+            restart_area_length,
+            client_array_offset,
+            file_size: u64_at(area, 0x18),
+            last_lsn_data_length: u32_at(area, 0x20),
+            log_record_header_length: u16_at(area, 0x24),
+            log_page_data_offset: u16_at(area, 0x26),
+            restart_log_open_count: (restart_area_length as usize >= RESTART_AREA_SIZE)
+                .then(|| u32_at(area, 0x28)),
+            clients,
+        })
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.flags & Self::VOLUME_IS_CLEAN != 0
+    }
+
+    ///The byte offset in $LogFile of the record with `lsn`.
+    pub fn lsn_to_offset(&self, lsn: u64) -> u64 {
+        (lsn << self.seq_number_bits) >> (self.seq_number_bits - 3)
+    }
+
+    ///How many times the log had wrapped around when `lsn` was written.
+    pub fn lsn_sequence_number(&self, lsn: u64) -> u64 {
+        lsn >> (64 - self.seq_number_bits)
+    }
+}
+
+///A client of the log. NTFS is the only one in practice.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LogClient {
+    pub oldest_lsn: u64,
+    ///The LSN of the client's last checkpoint, where replay starts.
+    pub client_restart_lsn: u64,
+    pub prev_client: u16,
+    pub next_client: u16,
+    pub seq_number: u16,
+    pub name: String,
+}
+
+impl LogClient {
+    pub fn parse(record: &[u8]) -> Result<LogClient, NtfsError> {
+        if record.len() < LOG_CLIENT_RECORD_SIZE {
+            return Err(NtfsError::BufferTooSmall {
+                needed: LOG_CLIENT_RECORD_SIZE,
+                available: record.len(),
+            });
+        }
+        let name_length = (u32_at(record, 0x1C) as usize).min(LOG_CLIENT_RECORD_SIZE - 0x20);
+        let units: Vec<u16> = record[0x20..0x20 + name_length]
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+            .collect();
+        Ok(LogClient {
+            oldest_lsn: u64_at(record, 0x00),
+            client_restart_lsn: u64_at(record, 0x08),
+            prev_client: u16_at(record, 0x10),
+            next_client: u16_at(record, 0x12),
+            seq_number: u16_at(record, 0x14),
+            name: String::from_utf16_lossy(&units),
+        })
+    }
+}
+
+///The header of an RCRD page.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RecordPageHeader {
+    ///The last LSN that starts in the page.
+    pub last_lsn: u64,
+    pub flags: u32,
+    pub page_count: u16,
+    pub page_position: u16,
+    ///The offset just past the last record that ends in the page.
+    pub next_record_offset: u16,
+    ///The LSN of the last record that ends in the page.
+    pub last_end_lsn: u64,
+}
+
+impl RecordPageHeader {
+    ///A log record ends in the page, so `next_record_offset` and `last_end_lsn` are set.
+    pub const RECORD_END: u32 = 0x0001;
+
+    ///Parses the header of an RCRD page whose update sequence has already been applied.
+    pub fn parse(page: &[u8]) -> Result<RecordPageHeader, NtfsError> {
+        if page.len() < RECORD_PAGE_HEADER_SIZE {
+            return Err(NtfsError::BufferTooSmall {
+                needed: RECORD_PAGE_HEADER_SIZE,
+                available: page.len(),
+            });
+        }
+        if &page[0..4] != b"RCRD" {
+            return Err(NtfsError::BadMagic);
+        }
+        Ok(RecordPageHeader {
+            last_lsn: u64_at(page, 0x08),
+            flags: u32_at(page, 0x10),
+            page_count: u16_at(page, 0x14),
+            page_position: u16_at(page, 0x16),
+            next_record_offset: u16_at(page, 0x18),
+            last_end_lsn: u64_at(page, 0x20),
+        })
+    }
+
+    pub fn has_record_end(&self) -> bool {
+        self.flags & Self::RECORD_END != 0
+    }
+}
+
+///A redo or undo operation of the NTFS log client.
+///Unknown codes are kept as they are, since newer versions of Windows add more.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LogOperation(pub u16);
+
+impl LogOperation {
+    pub const NOOP: LogOperation = LogOperation(0x00);
+    pub const COMPENSATION_LOG_RECORD: LogOperation = LogOperation(0x01);
+    pub const INITIALIZE_FILE_RECORD_SEGMENT: LogOperation = LogOperation(0x02);
+    pub const DEALLOCATE_FILE_RECORD_SEGMENT: LogOperation = LogOperation(0x03);
+    pub const WRITE_END_OF_FILE_RECORD_SEGMENT: LogOperation = LogOperation(0x04);
+    pub const CREATE_ATTRIBUTE: LogOperation = LogOperation(0x05);
+    pub const DELETE_ATTRIBUTE: LogOperation = LogOperation(0x06);
+    pub const UPDATE_RESIDENT_VALUE: LogOperation = LogOperation(0x07);
+    pub const UPDATE_NONRESIDENT_VALUE: LogOperation = LogOperation(0x08);
+    pub const UPDATE_MAPPING_PAIRS: LogOperation = LogOperation(0x09);
+    pub const DELETE_DIRTY_CLUSTERS: LogOperation = LogOperation(0x0A);
+    pub const SET_NEW_ATTRIBUTE_SIZES: LogOperation = LogOperation(0x0B);
+    pub const ADD_INDEX_ENTRY_ROOT: LogOperation = LogOperation(0x0C);
+    pub const DELETE_INDEX_ENTRY_ROOT: LogOperation = LogOperation(0x0D);
+    pub const ADD_INDEX_ENTRY_ALLOCATION: LogOperation = LogOperation(0x0E);
+    pub const DELETE_INDEX_ENTRY_ALLOCATION: LogOperation = LogOperation(0x0F);
+    pub const WRITE_END_OF_INDEX_BUFFER: LogOperation = LogOperation(0x10);
+    pub const SET_INDEX_ENTRY_VCN_ROOT: LogOperation = LogOperation(0x11);
+    pub const SET_INDEX_ENTRY_VCN_ALLOCATION: LogOperation = LogOperation(0x12);
+    pub const UPDATE_FILE_NAME_ROOT: LogOperation = LogOperation(0x13);
+    pub const UPDATE_FILE_NAME_ALLOCATION: LogOperation = LogOperation(0x14);
+    pub const SET_BITS_IN_NONRESIDENT_BITMAP: LogOperation = LogOperation(0x15);
+    pub const CLEAR_BITS_IN_NONRESIDENT_BITMAP: LogOperation = LogOperation(0x16);
+    pub const HOT_FIX: LogOperation = LogOperation(0x17);
+    pub const END_TOP_LEVEL_ACTION: LogOperation = LogOperation(0x18);
+    pub const PREPARE_TRANSACTION: LogOperation = LogOperation(0x19);
+    pub const COMMIT_TRANSACTION: LogOperation = LogOperation(0x1A);
+    pub const FORGET_TRANSACTION: LogOperation = LogOperation(0x1B);
+    pub const OPEN_NONRESIDENT_ATTRIBUTE: LogOperation = LogOperation(0x1C);
+    pub const OPEN_ATTRIBUTE_TABLE_DUMP: LogOperation = LogOperation(0x1D);
+    pub const ATTRIBUTE_NAMES_DUMP: LogOperation = LogOperation(0x1E);
+    pub const DIRTY_PAGE_TABLE_DUMP: LogOperation = LogOperation(0x1F);
+    pub const TRANSACTION_TABLE_DUMP: LogOperation = LogOperation(0x20);
+    pub const UPDATE_RECORD_DATA_ROOT: LogOperation = LogOperation(0x21);
+    pub const UPDATE_RECORD_DATA_ALLOCATION: LogOperation = LogOperation(0x22);
+    pub const UPDATE_RELATIVE_DATA_IN_INDEX: LogOperation = LogOperation(0x23);
+    pub const UPDATE_RELATIVE_DATA_IN_INDEX2: LogOperation = LogOperation(0x24);
+    pub const ZERO_END_OF_FILE_RECORD: LogOperation = LogOperation(0x25);
+
+    const NAMES: [&'static str; 0x26] = [
+        "Noop",
+        "CompensationLogRecord",
+        "InitializeFileRecordSegment",
+        "DeallocateFileRecordSegment",
+        "WriteEndOfFileRecordSegment",
+        "CreateAttribute",
+        "DeleteAttribute",
+        "UpdateResidentValue",
+        "UpdateNonresidentValue",
+        "UpdateMappingPairs",
+        "DeleteDirtyClusters",
+        "SetNewAttributeSizes",
+        "AddIndexEntryRoot",
+        "DeleteIndexEntryRoot",
+        "AddIndexEntryAllocation",
+        "DeleteIndexEntryAllocation",
+        "WriteEndOfIndexBuffer",
+        "SetIndexEntryVcnRoot",
+        "SetIndexEntryVcnAllocation",
+        "UpdateFileNameRoot",
+        "UpdateFileNameAllocation",
+        "SetBitsInNonresidentBitMap",
+        "ClearBitsInNonresidentBitMap",
+        "HotFix",
+        "EndTopLevelAction",
+        "PrepareTransaction",
+        "CommitTransaction",
+        "ForgetTransaction",
+        "OpenNonresidentAttribute",
+        "OpenAttributeTableDump",
+        "AttributeNamesDump",
+        "DirtyPageTableDump",
+        "TransactionTableDump",
+        "UpdateRecordDataRoot",
+        "UpdateRecordDataAllocation",
+        "UpdateRelativeDataInIndex",
+        "UpdateRelativeDataInIndex2",
+        "ZeroEndOfFileRecord",
+    ];
+
+    ///The name Windows gives the operation, or `None` for an unknown code.
+    pub fn name(&self) -> Option<&'static str> {
+        Self::NAMES.get(self.0 as usize).copied()
+    }
+}
+
+impl fmt::Display for LogOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "{:#x}", self.0),
+        }
+    }
+}
+
+///The NTFS client's part of a standard log record: what to redo and undo, and where.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LogOperationHeader {
+    pub redo_operation: LogOperation,
+    pub undo_operation: LogOperation,
+    pub redo_offset: u16,
+    pub redo_length: u16,
+    pub undo_offset: u16,
+    pub undo_length: u16,
+    ///The index of the attribute in the open attribute table.
+    pub target_attribute: u16,
+    pub lcns_to_follow: u16,
+    pub record_offset: u16,
+    pub attribute_offset: u16,
+    pub cluster_index: u16,
+    pub target_vcn: u64,
+    ///The clusters the operation changes, as many as `lcns_to_follow`.
+    pub lcns: Vec<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LogRecord {
+    pub this_lsn: u64,
+    pub client_previous_lsn: u64,
+    pub client_undo_next_lsn: u64,
+    pub client_data_length: u32,
+    pub client_seq_number: u16,
+    pub client_index: u16,
+    pub record_type: u32,
+    pub transaction_id: u32,
+    pub flags: u16,
+    ///Set for standard records, `None` for checkpoints.
+    pub operation: Option<LogOperationHeader>,
+}
+
+impl LogRecord {
+    pub const STANDARD: u32 = 1;
+    pub const CHECKPOINT: u32 = 2;
+    ///The record's data runs on into the following pages.
+    pub const MULTI_PAGE: u16 = 0x0001;
+
+    ///Parses a log record from its header on. `record` needs to hold the client data up to the
+    ///end of the LCNs for the operation header to be read.
+    pub fn parse(record: &[u8]) -> Result<LogRecord, NtfsError> {
+        if record.len() < LOG_RECORD_HEADER_SIZE {
+            return Err(NtfsError::BufferTooSmall {
+                needed: LOG_RECORD_HEADER_SIZE,
+                available: record.len(),
+            });
+        }
+        let record_type = u32_at(record, 0x20);
+        let client_data_length = u32_at(record, 0x18);
+        let operation = if record_type == Self::STANDARD
+            && client_data_length as usize >= LOG_OPERATION_HEADER_SIZE
+        {
+            Some(Self::parse_operation(&record[LOG_RECORD_HEADER_SIZE..])?)
+        } else {
+            None
+        };
+        Ok(LogRecord {
+            this_lsn: u64_at(record, 0x00),
+            client_previous_lsn: u64_at(record, 0x08),
+            client_undo_next_lsn: u64_at(record, 0x10),
+            client_data_length,
+            client_seq_number: u16_at(record, 0x1C),
+            client_index: u16_at(record, 0x1E),
+            record_type,
+            transaction_id: u32_at(record, 0x24),
+            flags: u16_at(record, 0x28),
+            operation,
+        })
+    }
+
+    fn parse_operation(data: &[u8]) -> Result<LogOperationHeader, NtfsError> {
+        if data.len() < LOG_OPERATION_HEADER_SIZE {
+            return Err(NtfsError::BufferTooSmall {
+                needed: LOG_RECORD_HEADER_SIZE + LOG_OPERATION_HEADER_SIZE,
+                available: LOG_RECORD_HEADER_SIZE + data.len(),
+            });
+        }
+        let lcns_to_follow = u16_at(data, 0x0E);
+        let lcns = data[LOG_OPERATION_HEADER_SIZE..]
+            .chunks_exact(8)
+            .take(lcns_to_follow as usize)
+            .map(|lcn| u64_at(lcn, 0))
+            .collect();
+        Ok(LogOperationHeader {
+            redo_operation: LogOperation(u16_at(data, 0x00)),
+            undo_operation: LogOperation(u16_at(data, 0x02)),
+            redo_offset: u16_at(data, 0x04),
+            redo_length: u16_at(data, 0x06),
+            undo_offset: u16_at(data, 0x08),
+            undo_length: u16_at(data, 0x0A),
+            target_attribute: u16_at(data, 0x0C),
+            lcns_to_follow,
+            record_offset: u16_at(data, 0x10),
+            attribute_offset: u16_at(data, 0x12),
+            cluster_index: u16_at(data, 0x14),
+            target_vcn: u64_at(data, 0x18),
+            lcns,
+        })
+    }
+
+    pub fn is_multi_page(&self) -> bool {
+        self.flags & Self::MULTI_PAGE != 0
+    }
+}
+
+///The journal of a volume. Created with [`Ntfs::log_file`].
+pub struct LogFile<'a, R: Read + Seek> {
+    ntfs: &'a mut Ntfs<R>,
+    stream: DataStream,
+    restart_pages: Vec<RestartPage>,
+}
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///Opens $LogFile and reads both of its restart pages.
+    ///Fails if neither restart page is valid.
+    pub fn log_file(&mut self) -> Result<LogFile<'_, R>, NtfsError> {
+        let stream = self
+            .data_streams(SystemFile::LogFile.record_number())?
+            .into_iter()
+            .find(|stream| !stream.is_alternate())
+            .ok_or(NtfsError::Corrupt("$LogFile has no $DATA attribute"))?;
+        let mut reader = self.open_stream(&stream)?;
+        let mut header = [0u8; RESTART_PAGE_HEADER_SIZE];
+        reader.read_exact(&mut header)?;
+        let page_size = u32_at(&header, 0x10) as u64;
+        if !page_size.is_power_of_two() || !(0x200..=0x10000).contains(&page_size) {
+            return Err(NtfsError::Corrupt("restart page size is invalid"));
+        }
+
+        let mut restart_pages = Vec::new();
+        let mut first_error = None;
+        for offset in [0, page_size] {
+            let mut page = vec![0u8; page_size as usize];
+            reader.seek(SeekFrom::Start(offset))?;
+            reader.read_exact(&mut page)?;
+            match RestartPage::parse(&page) {
+                Ok(restart_page) => restart_pages.push(restart_page),
+                Err(err @ NtfsError::Io(_)) => return Err(err),
+                Err(err) => {
+                    first_error.get_or_insert(err);
+                }
+            }
+        }
+        if restart_pages.is_empty() {
+            return Err(first_error.unwrap_or(NtfsError::Corrupt("$LogFile has no restart page")));
+        }
+        Ok(LogFile {
+            ntfs: self,
+            stream,
+            restart_pages,
+        })
+    }
+}
+
+impl<R: Read + Seek> LogFile<'_, R> {
+    ///The valid restart pages, at most two.
+    pub fn restart_pages(&self) -> &[RestartPage] {
+        &self.restart_pages
+    }
+
+    ///The restart page NTFS wrote last, the one with the highest current LSN.
+    pub fn restart_page(&self) -> &RestartPage {
+        self.restart_pages
+            .iter()
+            .max_by_key(|page| page.restart_area.current_lsn)
+            .expect("LogFile always has a restart page")
+    }
+
+    ///The restart area of [`LogFile::restart_page`].
+    pub fn restart_area(&self) -> &RestartArea {
+        &self.restart_page().restart_area
+    }
+
+    ///Iterates over the log records in the order of the pages holding them, skipping the
+    ///restart pages, pages that fail their update sequence check and data that doesn't belong
+    ///to a record where its LSN says. Sort by `this_lsn` for the order they were written in.
+    pub fn records(&mut self) -> Result<LogRecords<'_, R>, NtfsError> {
+        let restart_page = self.restart_page().clone();
+        let page_size = restart_page.log_page_size as u64;
+        let area = restart_page.restart_area;
+        let first_page = 2 * restart_page.system_page_size as u64;
+        let reader = self.ntfs.open_stream(&self.stream)?;
+        Ok(LogRecords {
+            pages: LogPages::new(reader, page_size, area.log_page_data_offset as u64),
+            offset: first_page,
+            area,
+        })
+    }
+
+    ///Reads the record with `lsn`, e.g. the LSN of a FILE record's last change.
+    ///Returns `None` if it has been overwritten since.
+    pub fn record_at(&mut self, lsn: u64) -> Result<Option<LogRecord>, NtfsError> {
+        let restart_page = self.restart_page().clone();
+        let area = restart_page.restart_area;
+        let reader = self.ntfs.open_stream(&self.stream)?;
+        let mut pages = LogPages::new(
+            reader,
+            restart_page.log_page_size as u64,
+            area.log_page_data_offset as u64,
+        );
+        let offset = area.lsn_to_offset(lsn);
+        if offset < 2 * restart_page.system_page_size as u64 {
+            return Ok(None);
+        }
+        pages.record(offset, &area)
+    }
+}
+
+///Reads RCRD pages one at a time, keeping the last one.
+struct LogPages<'a, R: Read + Seek> {
+    reader: NtfsStreamReader<'a, R>,
+    page_size: u64,
+    data_offset: u64,
+    ///The offset of the page in `page`, and the page if it's a valid RCRD page.
+    cached: Option<(u64, Option<Vec<u8>>)>,
+}
+
+impl<'a, R: Read + Seek> LogPages<'a, R> {
+    fn new(reader: NtfsStreamReader<'a, R>, page_size: u64, data_offset: u64) -> Self {
+        LogPages {
+            reader,
+            page_size,
+            data_offset,
+            cached: None,
+        }
+    }
+
+    ///The RCRD page at `offset` with its update sequence applied,
+    ///or `None` if it isn't one or fails the check.
+    fn page(&mut self, offset: u64) -> Result<Option<&[u8]>, NtfsError> {
+        if self
+            .cached
+            .as_ref()
+            .is_none_or(|(cached, _)| *cached != offset)
+        {
+            let mut page = vec![0u8; self.page_size as usize];
+            self.reader.seek(SeekFrom::Start(offset))?;
+            self.reader.read_exact(&mut page)?;
+            let (usn_offset, usn_count) = (u16_at(&page, 0x04), u16_at(&page, 0x06));
+            let valid = &page[0..4] == b"RCRD"
+                && apply_fixup(&mut page, usn_offset, usn_count, FIXUP_STRIDE).is_ok();
+            self.cached = Some((offset, valid.then_some(page)));
+        }
+        Ok(self.cached.as_ref().and_then(|(_, page)| page.as_deref()))
+    }
+
+    ///Reads `len` bytes of log data from `offset` on, continuing after the header of each following page.
+    fn data(&mut self, mut offset: u64, len: usize) -> Result<Option<Vec<u8>>, NtfsError> {
+        let mut data = Vec::with_capacity(len);
+        while data.len() < len {
+            let page_offset = offset - offset % self.page_size;
+            if page_offset + self.page_size > self.reader.len() {
+                return Ok(None);
+            }
+            let start = (offset - page_offset) as usize;
+            let Some(page) = self.page(page_offset)? else {
+                return Ok(None);
+            };
+            let end = page.len().min(start + len - data.len());
+            data.extend_from_slice(&page[start..end]);
+            offset = page_offset + self.page_size + self.data_offset;
+        }
+        Ok(Some(data))
+    }
+
+    ///The record at `offset`, if a record with an LSN pointing at `offset` starts there.
+    fn record(&mut self, offset: u64, area: &RestartArea) -> Result<Option<LogRecord>, NtfsError> {
+        let page_offset = offset - offset % self.page_size;
+        let start = offset - page_offset;
+        if start < self.data_offset || start + LOG_RECORD_HEADER_SIZE as u64 > self.page_size {
+            return Ok(None);
+        }
+        let Some(header) = self.data(offset, LOG_RECORD_HEADER_SIZE)? else {
+            return Ok(None);
+        };
+        let this_lsn = u64_at(&header, 0x00);
+        if this_lsn == 0 || area.lsn_to_offset(this_lsn) != offset {
+            return Ok(None);
+        }
+        let client_data_length = u32_at(&header, 0x18) as usize;
+        let mut len = LOG_RECORD_HEADER_SIZE + client_data_length.min(LOG_OPERATION_HEADER_SIZE);
+        let Some(mut record) = self.data(offset, len)? else {
+            return Ok(None);
+        };
+        if record.len() >= LOG_RECORD_HEADER_SIZE + LOG_OPERATION_HEADER_SIZE {
+            let lcns_to_follow = u16_at(&record, LOG_RECORD_HEADER_SIZE + 0x0E) as usize;
+            len = (len + lcns_to_follow * 8).min(LOG_RECORD_HEADER_SIZE + client_data_length);
+            match self.data(offset, len)? {
+                Some(full) => record = full,
+                None => return Ok(None),
+            }
+        }
+        LogRecord::parse(&record).map(Some)
+    }
+
+    ///The offset of the first byte after `len` bytes of log data starting at `offset`.
+    fn advance(&self, offset: u64, len: u64) -> u64 {
+        let page_offset = offset - offset % self.page_size;
+        let in_page = page_offset + self.page_size - offset;
+        if len <= in_page {
+            return offset + len;
+        }
+        let per_page = self.page_size - self.data_offset;
+        let rest = len - in_page;
+        let pages = rest.div_ceil(per_page);
+        let last = rest - (pages - 1) * per_page;
+        page_offset + pages * self.page_size + self.data_offset + last
+    }
+}
+
+///Iterates over the records of $LogFile. Created with [`LogFile::records`].
+pub struct LogRecords<'a, R: Read + Seek> {
+    pages: LogPages<'a, R>,
+    area: RestartArea,
+    ///The offset in $LogFile of the next record to try.
+    offset: u64,
+}
+
+impl<R: Read + Seek> LogRecords<'_, R> {
+    ///Moves on to the first record of the page after the one holding `offset`.
+    fn next_page(&mut self) {
+        let page_size = self.pages.page_size;
+        self.offset = self.offset - self.offset % page_size + page_size + self.pages.data_offset;
+    }
+}
+
+impl<R: Read + Seek> Iterator for LogRecords<'_, R> {
+    type Item = Result<LogRecord, NtfsError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let page_size = self.pages.page_size;
+        loop {
+            let page_offset = self.offset - self.offset % page_size;
+            if page_offset + page_size > self.pages.reader.len() {
+                return None;
+            }
+            //Records after the last one starting in the page are left over from before the log wrapped.
+            let last_lsn = match self.pages.page(page_offset) {
+                Ok(Some(page)) => RecordPageHeader::parse(page).map(|header| header.last_lsn),
+                Ok(None) => Err(NtfsError::BadMagic),
+                Err(err) => {
+                    self.offset = self.pages.reader.len();
+                    return Some(Err(err));
+                }
+            };
+            let Ok(last_lsn) = last_lsn else {
+                self.next_page();
+                continue;
+            };
+            if self.offset - page_offset < self.pages.data_offset {
+                self.offset = page_offset + self.pages.data_offset;
+            }
+            match self.pages.record(self.offset, &self.area) {
+                Ok(Some(record)) if record.this_lsn <= last_lsn => {
+                    let len = LOG_RECORD_HEADER_SIZE as u64 + record.client_data_length as u64;
+                    self.offset = self.pages.advance(self.offset, len).next_multiple_of(8);
+                    if record.this_lsn == last_lsn
+                        && self.offset - self.offset % page_size == page_offset
+                    {
+                        self.next_page();
+                    }
+                    return Some(Ok(record));
+                }
+                Ok(_) => self.next_page(),
+                Err(err) => {
+                    self.offset = self.pages.reader.len();
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+//! Compressed attributes are stored in compression units of 16 clusters, each encoded with LZNT1.
+//! The compressed data is a sequence of chunks, each holding up to 4096 bytes of output.
+//!
+//! Chunk header
+//!| Bits  | Description                                   |
+//!|-------|-----------------------------------------------|
+//!| 0-11  | Size of the chunk, including the header, - 3  |
+//!| 12-14 | Signature, always 3                           |
+//!| 15    | Set if the chunk is compressed                |
+//!
+//! A header of 0 ends the data. An uncompressed chunk holds its 4096 bytes as they are.
+//! A compressed chunk is a series of groups: a flag byte followed by eight tokens.
+//! Bit `n` of the flag byte is clear if token `n` is a literal byte,
+//! or set if it's a 2 byte back-reference into the output of the chunk.
+//! The back-reference splits its 16 bits between an offset and a length.
+//! The further into the chunk, the more bits go to the offset:
+//! 12 length bits while the position is at most 16, down to 4 length bits at the end of the chunk.
+//!
+//!<https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-xca/5655f4a3-6ba4-489b-959f-e1f407c52f15>
+use crate::NtfsError;
+
+///The amount of output a single chunk decompresses to.
+pub const LZNT1_CHUNK_SIZE: usize = 4096;
+
+///Decompresses `compressed` and appends the result to `output`.
+///Chunks that decompress to less than 4096 bytes are padded with zeros when another chunk follows.
+pub fn decompress_lznt1(compressed: &[u8], output: &mut Vec<u8>) -> Result<(), NtfsError> {
+    //Chunks line up with the start of this call's output, not of the whole buffer.
+    let start = output.len();
+    let mut i = 0;
+    let mut first_chunk = true;
+    while i + 2 <= compressed.len() {
+        let header = u16::from_le_bytes([compressed[i], compressed[i + 1]]);
+        if header == 0 {
+            break;
+        }
+        let size = (header & 0x0FFF) as usize + 1;
+        let Some(chunk) = compressed.get(i + 2..i + 2 + size) else {
+            return Err(NtfsError::Corrupt("LZNT1 chunk is truncated"));
+        };
+        i += 2 + size;
+
+        if !first_chunk {
+            let padded = start + (output.len() - start).next_multiple_of(LZNT1_CHUNK_SIZE);
+            output.resize(padded, 0);
+        }
+        first_chunk = false;
+
+        if header & 0x8000 == 0 {
+            output.extend_from_slice(chunk);
+        } else {
+            decompress_chunk(chunk, output)?;
+        }
+    }
+    Ok(())
+}
+
+fn decompress_chunk(chunk: &[u8], output: &mut Vec<u8>) -> Result<(), NtfsError> {
+    let start = output.len();
+    let mut i = 0;
+    while i < chunk.len() {
+        let flags = chunk[i];
+        i += 1;
+        for bit in 0..8 {
+            if i >= chunk.len() {
+                break;
+            }
+            if flags & (1 << bit) == 0 {
+                output.push(chunk[i]);
+                i += 1;
+                continue;
+            }
+
+            let Some(token) = chunk.get(i..i + 2) else {
+                return Err(NtfsError::Corrupt("LZNT1 back-reference is truncated"));
+            };
+            let token = u16::from_le_bytes([token[0], token[1]]) as usize;
+            i += 2;
+
+            let position = output.len() - start;
+            let mut length_bits = 12;
+            let mut n = position.saturating_sub(1);
+            while n >= 0x10 {
+                n >>= 1;
+                length_bits -= 1;
+            }
+            let offset = (token >> length_bits) + 1;
+            let length = (token & ((1 << length_bits) - 1)) + 3;
+            if offset > position {
+                return Err(NtfsError::Corrupt(
+                    "LZNT1 back-reference points before the start of the chunk",
+                ));
+            }
+            //The source may overlap the bytes being written, so copy one byte at a time.
+            for _ in 0..length {
+                output.push(output[output.len() - offset]);
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,23 +1,10 @@
-//! <https://flatcap.github.io/linux-ntfs/ntfs/index.html>
-//!
-//! <https://en.wikipedia.org/wiki/NTFS>
-pub use std::{
-    fs::File,
-    io::{BufReader, Read, Seek, SeekFrom},
-};
-
-pub use file_record::*;
-pub use master_file_table::*;
-pub use partition_boot_sector::*;
-
-pub mod file_record;
-pub mod master_file_table;
-pub mod partition_boot_sector;
-
+#[cfg(windows)]
 fn main() {
-    let file = File::open("\\\\.\\C:").expect("Run as Admin");
-    let mut reader = BufReader::new(file);
+    let ntfs = ntfs::open_volume('C').unwrap_or_else(|err| panic!("{err}"));
+    dbg!(ntfs.boot_sector());
+}
 
-    let pbs = pbs(&mut reader);
-    dbg!(&pbs);
+#[cfg(not(windows))]
+fn main() {
+    eprintln!("Opening a volume by drive letter is only supported on Windows");
 }
@@ -2,8 +2,9 @@
 //! The MFT is a collection of FILE records.
 //! Each file in the MFT has a `fileID`, this is analogous to an inode in unix.
 //! The description of each file is packed into [FILE records](struct@crate::file_record::FileRecord).
-//TODO: Explain $ATTRIBUTE_LIST
 //! If one FILE record is not large enough (this is unusual), then an $ATTRIBUTE_LIST attribute is needed.
+//! It lists every attribute of the file along with the extension FILE record that holds it,
+//! see [`attribute_list`](crate::attribute_list).
 //! The first 24 FILE records are reserved for the system files.
 //!
 //!| fileID | Filename  | OS  | Description                                            |
@@ -33,6 +34,15 @@
 //!| > 24   | A_File    |     | An ordinary file                                       |
 //!| > 24   | A_Dir     |     | An ordinary directory                                  |
 //!| ...    | ...       |     | ...                                                    |
+use crate::{
+    bytes::{u16_at, u32_at, u64_at},
+    decode_runs, DataRun, FileRecord, Ntfs, NtfsError,
+};
+use std::{
+    fmt,
+    io::{self, Read, Seek},
+    path::PathBuf,
+};
 
 ///In NTFS, everything on disk is a file. Even the metadata is stored as a set of files.
 ///The Master File Table (MFT) is an index of every file on the volume.
@@ -40,32 +50,854 @@
 ///
 ///<https://flatcap.github.io/linux-ntfs/ntfs/attributes/index.html>
 pub mod attribute {
+    #[deprecated(note = "use `AttributeType::StandardInformation`")]
     pub const STANDARD_INFORMATION_OFFSET: u64 = 0x10;
+    #[deprecated(note = "use `AttributeType::AttributeList`")]
     pub const ATTRIBUTE_LIST_OFFSET: u64 = 0x20;
+    #[deprecated(note = "use `AttributeType::FileName`")]
     pub const FILE_NAME_OFFSET: u64 = 0x30;
+    #[deprecated(note = "use `AttributeType::ObjectId`")]
     pub const VOLUME_VERSION_OFFSET: u64 = 0x40; //Windows NT
+    #[deprecated(note = "use `AttributeType::ObjectId`")]
     pub const OBJECT_ID_OFFSET: u64 = 0x40; //Windows 2000
+    #[deprecated(note = "use `AttributeType::SecurityDescriptor`")]
     pub const SECURITY_DESCRIPTOR_OFFSET: u64 = 0x50;
+    #[deprecated(note = "use `AttributeType::VolumeName`")]
     pub const VOLUME_NAME_OFFSET: u64 = 0x60;
+    #[deprecated(note = "use `AttributeType::VolumeInformation`")]
     pub const VOLUME_INFORMATION_OFFSET: u64 = 0x70;
+    #[deprecated(note = "use `AttributeType::Data`")]
     pub const DATA_OFFSET: u64 = 0x80;
+    #[deprecated(note = "use `AttributeType::IndexRoot`")]
     pub const INDEX_ROOT_OFFSET: u64 = 0x90;
+    #[deprecated(note = "use `AttributeType::IndexAllocation`")]
     pub const INDEX_ALLOCATION_OFFSET: u64 = 0xA0;
+    #[deprecated(note = "use `AttributeType::Bitmap`")]
     pub const BITMAP_OFFSET: u64 = 0xB0;
+    #[deprecated(note = "use `AttributeType::ReparsePoint`")]
     pub const SYMBOLIC_LINK_OFFSET: u64 = 0xC0; //Windows NT
+    #[deprecated(note = "use `AttributeType::ReparsePoint`")]
     pub const REPARSE_POINT_OFFSET: u64 = 0xC0; //Windows 2000
+    #[deprecated(note = "use `AttributeType::EaInformation`")]
     pub const EA_INFORMATION_OFFSET: u64 = 0xD0;
+    #[deprecated(note = "use `AttributeType::Ea`")]
     pub const EA_OFFSET: u64 = 0xE0;
+    #[deprecated(note = "use `AttributeType::PropertySet`")]
     pub const PROPERTY_SET_OFFSET: u64 = 0xF0; //Windows NT
+    #[deprecated(note = "use `AttributeType::LoggedUtilityStream`")]
     pub const LOGGED_UTILITY_STREAM_OFFSET: u64 = 0x100; //Windows 2000
 }
 
-/// Every attribute in every MFT record has a standard header.
-/// The header stores information about the attribute's type, size, name (optional) and whether it is resident, or not.
-/// <https://flatcap.github.io/linux-ntfs/ntfs/concepts/attribute_header.html>
-//TODO: Attribute Headers
+///The type of an attribute, stored in the first four bytes of its header.
+///
+///| Type  | Name                    | OS  |
+///|-------|-------------------------|-----|
+///| 0x10  | $STANDARD_INFORMATION   |     |
+///| 0x20  | $ATTRIBUTE_LIST         |     |
+///| 0x30  | $FILE_NAME              |     |
+///| 0x40  | $VOLUME_VERSION         | NT  |
+///| 0x40  | $OBJECT_ID              | 2K  |
+///| 0x50  | $SECURITY_DESCRIPTOR    |     |
+///| 0x60  | $VOLUME_NAME            |     |
+///| 0x70  | $VOLUME_INFORMATION     |     |
+///| 0x80  | $DATA                   |     |
+///| 0x90  | $INDEX_ROOT             |     |
+///| 0xA0  | $INDEX_ALLOCATION       |     |
+///| 0xB0  | $BITMAP                 |     |
+///| 0xC0  | $SYMBOLIC_LINK          | NT  |
+///| 0xC0  | $REPARSE_POINT          | 2K  |
+///| 0xD0  | $EA_INFORMATION         |     |
+///| 0xE0  | $EA                     |     |
+///| 0xF0  | $PROPERTY_SET           | NT  |
+///| 0x100 | $LOGGED_UTILITY_STREAM  | 2K  |
+///
+///Where Windows NT and Windows 2000 disagree, the Windows 2000 meaning is used.
+///Other type codes, like user-defined types from $AttrDef or types added by newer versions of Windows,
+///are kept as `Unknown` so the attributes after them can still be read.
+///Types are ordered by their type code, the order attributes have in a FILE record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AttributeType {
+    StandardInformation,
+    AttributeList,
+    FileName,
+    ObjectId,
+    SecurityDescriptor,
+    VolumeName,
+    VolumeInformation,
+    Data,
+    IndexRoot,
+    IndexAllocation,
+    Bitmap,
+    ReparsePoint,
+    EaInformation,
+    Ea,
+    PropertySet,
+    LoggedUtilityStream,
+    Unknown(u32),
+}
+
+impl AttributeType {
+    ///The name NTFS gives the attribute, e.g. "$DATA". Unknown types are named in $AttrDef,
+    ///see [`AttrDefTable`](crate::AttrDefTable), and are "$UNKNOWN" here.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AttributeType::StandardInformation => "$STANDARD_INFORMATION",
+            AttributeType::AttributeList => "$ATTRIBUTE_LIST",
+            AttributeType::FileName => "$FILE_NAME",
+            AttributeType::ObjectId => "$OBJECT_ID",
+            AttributeType::SecurityDescriptor => "$SECURITY_DESCRIPTOR",
+            AttributeType::VolumeName => "$VOLUME_NAME",
+            AttributeType::VolumeInformation => "$VOLUME_INFORMATION",
+            AttributeType::Data => "$DATA",
+            AttributeType::IndexRoot => "$INDEX_ROOT",
+            AttributeType::IndexAllocation => "$INDEX_ALLOCATION",
+            AttributeType::Bitmap => "$BITMAP",
+            AttributeType::ReparsePoint => "$REPARSE_POINT",
+            AttributeType::EaInformation => "$EA_INFORMATION",
+            AttributeType::Ea => "$EA",
+            AttributeType::PropertySet => "$PROPERTY_SET",
+            AttributeType::LoggedUtilityStream => "$LOGGED_UTILITY_STREAM",
+            AttributeType::Unknown(_) => "$UNKNOWN",
+        }
+    }
+}
+
+impl From<u32> for AttributeType {
+    fn from(type_code: u32) -> Self {
+        match type_code {
+            0x10 => AttributeType::StandardInformation,
+            0x20 => AttributeType::AttributeList,
+            0x30 => AttributeType::FileName,
+            0x40 => AttributeType::ObjectId,
+            0x50 => AttributeType::SecurityDescriptor,
+            0x60 => AttributeType::VolumeName,
+            0x70 => AttributeType::VolumeInformation,
+            0x80 => AttributeType::Data,
+            0x90 => AttributeType::IndexRoot,
+            0xA0 => AttributeType::IndexAllocation,
+            0xB0 => AttributeType::Bitmap,
+            0xC0 => AttributeType::ReparsePoint,
+            0xD0 => AttributeType::EaInformation,
+            0xE0 => AttributeType::Ea,
+            0xF0 => AttributeType::PropertySet,
+            0x100 => AttributeType::LoggedUtilityStream,
+            type_code => AttributeType::Unknown(type_code),
+        }
+    }
+}
+
+impl From<AttributeType> for u32 {
+    fn from(attribute_type: AttributeType) -> Self {
+        match attribute_type {
+            AttributeType::StandardInformation => 0x10,
+            AttributeType::AttributeList => 0x20,
+            AttributeType::FileName => 0x30,
+            AttributeType::ObjectId => 0x40,
+            AttributeType::SecurityDescriptor => 0x50,
+            AttributeType::VolumeName => 0x60,
+            AttributeType::VolumeInformation => 0x70,
+            AttributeType::Data => 0x80,
+            AttributeType::IndexRoot => 0x90,
+            AttributeType::IndexAllocation => 0xA0,
+            AttributeType::Bitmap => 0xB0,
+            AttributeType::ReparsePoint => 0xC0,
+            AttributeType::EaInformation => 0xD0,
+            AttributeType::Ea => 0xE0,
+            AttributeType::PropertySet => 0xF0,
+            AttributeType::LoggedUtilityStream => 0x100,
+            AttributeType::Unknown(type_code) => type_code,
+        }
+    }
+}
+
+impl PartialOrd for AttributeType {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for AttributeType {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        u32::from(*self).cmp(&u32::from(*other))
+    }
+}
+
+impl fmt::Display for AttributeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttributeType::Unknown(type_code) => write!(f, "attribute type {type_code:#x}"),
+            _ => f.write_str(self.name()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Flag {
     Compressed = 0x0001,
     Encrypted = 0x4000,
     Sparse = 0x8000,
 }
+
+/// Every attribute in every MFT record has a standard header.
+/// The header stores information about the attribute's type, size, name (optional) and whether it is resident, or not.
+/// <https://flatcap.github.io/linux-ntfs/ntfs/concepts/attribute_header.html>
+///
+///| Offset | Size | Description                      |
+///|--------|------|----------------------------------|
+///| 0x00   | 4    | Attribute type                   |
+///| 0x04   | 4    | Length (including this header)   |
+///| 0x08   | 1    | Non-resident flag                |
+///| 0x09   | 1    | Name length                      |
+///| 0x0A   | 2    | Offset to the name               |
+///| 0x0C   | 2    | Flags                            |
+///| 0x0E   | 2    | Attribute Id                     |
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttributeHeader {
+    pub type_code: AttributeType,
+    pub total_length: u32,
+    pub non_resident: bool,
+    ///Length of the name in UTF-16 code units.
+    pub name_length: u8,
+    pub name_offset: u16,
+    pub flags: u16,
+    pub attribute_id: u16,
+    pub residency: Residency,
+}
+
+///The part of the attribute header that depends on whether the value is stored in the FILE record.
+///
+///Resident
+///| Offset | Size | Description          |
+///|--------|------|----------------------|
+///| 0x10   | 4    | Length of the value  |
+///| 0x14   | 2    | Offset to the value  |
+///| 0x16   | 1    | Indexed flag         |
+///
+///Non-resident
+///| Offset | Size | Description                          |
+///|--------|------|--------------------------------------|
+///| 0x10   | 8    | Starting VCN                         |
+///| 0x18   | 8    | Last VCN                             |
+///| 0x20   | 2    | Offset to the data runs              |
+///| 0x22   | 2    | Compression unit size (2^x clusters) |
+///| 0x28   | 8    | Allocated size                       |
+///| 0x30   | 8    | Real size                            |
+///| 0x38   | 8    | Initialized size                     |
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Residency {
+    Resident {
+        value_length: u32,
+        value_offset: u16,
+        indexed: bool,
+    },
+    NonResident {
+        start_vcn: u64,
+        end_vcn: u64,
+        run_list_offset: u16,
+        ///The compression unit is 2^x clusters, 0 when the attribute is not compressed.
+        compression_unit: u16,
+        allocated_size: u64,
+        real_size: u64,
+        initialized_size: u64,
+    },
+}
+
+impl AttributeHeader {
+    pub fn parse(bytes: &[u8]) -> Result<AttributeHeader, NtfsError> {
+        if bytes.len() < 0x10 {
+            return Err(NtfsError::BufferTooSmall {
+                needed: 0x10,
+                available: bytes.len(),
+            });
+        }
+
+        let non_resident = bytes[0x08] != 0;
+        let needed = if non_resident { 0x40 } else { 0x18 };
+        if bytes.len() < needed {
+            return Err(NtfsError::BufferTooSmall {
+                needed,
+                available: bytes.len(),
+            });
+        }
+
+        let residency = if non_resident {
+            Residency::NonResident {
+                start_vcn: u64_at(bytes, 0x10),
+                end_vcn: u64_at(bytes, 0x18),
+                run_list_offset: u16_at(bytes, 0x20),
+                compression_unit: u16_at(bytes, 0x22),
+                allocated_size: u64_at(bytes, 0x28),
+                real_size: u64_at(bytes, 0x30),
+                initialized_size: u64_at(bytes, 0x38),
+            }
+        } else {
+            Residency::Resident {
+                value_length: u32_at(bytes, 0x10),
+                value_offset: u16_at(bytes, 0x14),
+                indexed: bytes[0x16] != 0,
+            }
+        };
+
+        Ok(AttributeHeader {
+            type_code: AttributeType::from(u32_at(bytes, 0x00)),
+            total_length: u32_at(bytes, 0x04),
+            non_resident,
+            name_length: bytes[0x09],
+            name_offset: u16_at(bytes, 0x0A),
+            flags: u16_at(bytes, 0x0C),
+            attribute_id: u16_at(bytes, 0x0E),
+            residency,
+        })
+    }
+
+    pub fn has_flag(&self, flag: Flag) -> bool {
+        self.flags & flag as u16 != 0
+    }
+}
+
+///An attribute inside a FILE record: the parsed header and the bytes it covers.
+///The lengths in the header are checked against the record,
+///so the accessors never slice past the end of the attribute.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Attribute<'a> {
+    header: AttributeHeader,
+    bytes: &'a [u8],
+    name: Option<String>,
+}
+
+impl<'a> Attribute<'a> {
+    ///Parses the attribute at the start of `bytes`, which may extend past the end of the attribute.
+    pub fn parse(bytes: &'a [u8]) -> Result<Attribute<'a>, NtfsError> {
+        let header = AttributeHeader::parse(bytes)?;
+        let length = header.total_length as usize;
+        if length > bytes.len() {
+            return Err(NtfsError::Corrupt(
+                "attribute length runs past the FILE record",
+            ));
+        }
+        let bytes = &bytes[..length];
+
+        let name_end = header.name_offset as usize + header.name_length as usize * 2;
+        if header.name_length > 0 && name_end > length {
+            return Err(NtfsError::Corrupt("attribute name runs past the attribute"));
+        }
+
+        match header.residency {
+            Residency::Resident {
+                value_length,
+                value_offset,
+                ..
+            } => {
+                if value_offset as usize + value_length as usize > length {
+                    return Err(NtfsError::Corrupt(
+                        "attribute value runs past the attribute",
+                    ));
+                }
+            }
+            Residency::NonResident {
+                run_list_offset, ..
+            } => {
+                if run_list_offset as usize > length {
+                    return Err(NtfsError::Corrupt("data runs start past the attribute"));
+                }
+            }
+        }
+
+        let mut attribute = Attribute {
+            header,
+            bytes,
+            name: None,
+        };
+        if attribute.header.name_length > 0 {
+            attribute.name = Some(String::from_utf16_lossy(&attribute.name_units()));
+        }
+        Ok(attribute)
+    }
+
+    pub fn header(&self) -> &AttributeHeader {
+        &self.header
+    }
+
+    pub fn type_code(&self) -> AttributeType {
+        self.header.type_code
+    }
+
+    pub fn is_resident(&self) -> bool {
+        !self.header.non_resident
+    }
+
+    ///The name of the attribute, e.g. "$I30" or "Zone.Identifier".
+    ///Unnamed attributes return `None`. Unpaired surrogates are replaced with U+FFFD.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    ///The raw UTF-16LE code units of the name.
+    pub fn name_units(&self) -> Vec<u16> {
+        if self.header.name_length == 0 {
+            return Vec::new();
+        }
+        let start = self.header.name_offset as usize;
+        let end = start + self.header.name_length as usize * 2;
+        self.bytes[start..end]
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+            .collect()
+    }
+
+    ///Returns true if the attribute is named `name`.
+    pub fn has_name(&self, name: &str) -> bool {
+        self.name() == Some(name)
+    }
+
+    ///The value of a resident attribute.
+    pub fn value(&self) -> Option<&'a [u8]> {
+        match self.header.residency {
+            Residency::Resident {
+                value_length,
+                value_offset,
+                ..
+            } => {
+                let start = value_offset as usize;
+                Some(&self.bytes[start..start + value_length as usize])
+            }
+            Residency::NonResident { .. } => None,
+        }
+    }
+
+    ///The encoded data runs of a non-resident attribute.
+    pub fn run_list(&self) -> Option<&'a [u8]> {
+        match self.header.residency {
+            Residency::Resident { .. } => None,
+            Residency::NonResident {
+                run_list_offset, ..
+            } => Some(&self.bytes[run_list_offset as usize..]),
+        }
+    }
+
+    ///Decodes the run list of a non-resident attribute.
+    ///The runs must not cover more clusters than the attribute's VCN range.
+    pub fn data_runs(&self) -> Result<Vec<DataRun>, NtfsError> {
+        let Residency::NonResident {
+            start_vcn,
+            end_vcn,
+            run_list_offset,
+            ..
+        } = self.header.residency
+        else {
+            return Err(NtfsError::Corrupt("resident attributes have no data runs"));
+        };
+        let runs = decode_runs(&self.bytes[run_list_offset as usize..])?;
+
+        let vcn_count = end_vcn.wrapping_sub(start_vcn).wrapping_add(1);
+        let mut total = 0u64;
+        for run in &runs {
+            total = total
+                .checked_add(run.length_clusters)
+                .filter(|total| *total <= vcn_count)
+                .ok_or(NtfsError::Corrupt("data runs extend past the last VCN"))?;
+        }
+        Ok(runs)
+    }
+
+    ///All the bytes of the attribute, including the header.
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+}
+
+///The system files in the first records of the $MFT, see the table at the top of this module.
+#[repr(u64)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SystemFile {
+    Mft = 0,
+    MftMirr = 1,
+    LogFile = 2,
+    Volume = 3,
+    AttrDef = 4,
+    RootDirectory = 5,
+    Bitmap = 6,
+    Boot = 7,
+    BadClus = 8,
+    ///$Quota on Windows NT.
+    Secure = 9,
+    UpCase = 10,
+    Extend = 11,
+}
+
+impl SystemFile {
+    pub fn record_number(&self) -> u64 {
+        *self as u64
+    }
+
+    ///The file name, e.g. "$MFT".
+    pub fn name(&self) -> &'static str {
+        match self {
+            SystemFile::Mft => "$MFT",
+            SystemFile::MftMirr => "$MFTMirr",
+            SystemFile::LogFile => "$LogFile",
+            SystemFile::Volume => "$Volume",
+            SystemFile::AttrDef => "$AttrDef",
+            SystemFile::RootDirectory => ".",
+            SystemFile::Bitmap => "$Bitmap",
+            SystemFile::Boot => "$Boot",
+            SystemFile::BadClus => "$BadClus",
+            SystemFile::Secure => "$Secure",
+            SystemFile::UpCase => "$UpCase",
+            SystemFile::Extend => "$Extend",
+        }
+    }
+}
+
+///Converts the runs of the $MFT's $DATA attribute to (LCN, length) pairs.
+fn mft_runs(runs: Vec<DataRun>) -> Result<Vec<(u64, u64)>, NtfsError> {
+    runs.into_iter()
+        .map(|run| match run.lcn {
+            Some(lcn) => Ok((lcn, run.length_clusters)),
+            None => Err(NtfsError::Corrupt("the $MFT cannot be sparse")),
+        })
+        .collect()
+}
+
+///The $MFT of a volume, located through its own data runs rather than assumed to be contiguous.
+///Created with [`Ntfs::master_file_table`].
+pub struct MasterFileTable<'a, R: Read + Seek> {
+    pub(crate) ntfs: &'a mut Ntfs<R>,
+}
+
+///Same as [`MasterFileTable`].
+pub type Mft<'a, R> = MasterFileTable<'a, R>;
+
+impl<R: Read + Seek> MasterFileTable<'_, R> {
+    ///Reads FILE record `n`, translating its position through the $MFT runs.
+    pub fn record(&mut self, n: u64) -> Result<FileRecord, NtfsError> {
+        self.ntfs.file_record(n)
+    }
+
+    ///The (LCN, length) runs of the $MFT.
+    pub fn runs(&self) -> &[(u64, u64)] {
+        self.ntfs.mft_runs.as_deref().unwrap_or_default()
+    }
+
+    ///The number of FILE records in the $MFT, from the real size of its $DATA attribute.
+    pub fn record_count(&self) -> u64 {
+        self.ntfs.mft_size.unwrap_or_default() / self.ntfs.geometry().file_record_size
+    }
+
+    ///Reads the FILE record of a system file.
+    pub fn system_file(&mut self, file: SystemFile) -> Result<FileRecord, NtfsError> {
+        self.record(file.record_number())
+    }
+
+    ///$Volume, which holds the volume label, version and dirty flag.
+    pub fn volume_record(&mut self) -> Result<FileRecord, NtfsError> {
+        self.system_file(SystemFile::Volume)
+    }
+
+    ///The root directory of the volume.
+    pub fn root_directory(&mut self) -> Result<FileRecord, NtfsError> {
+        self.system_file(SystemFile::RootDirectory)
+    }
+
+    ///$Bitmap, the cluster allocation bitmap of the volume.
+    pub fn bitmap(&mut self) -> Result<FileRecord, NtfsError> {
+        self.system_file(SystemFile::Bitmap)
+    }
+
+    ///$AttrDef, the attribute definition table.
+    pub fn attr_def(&mut self) -> Result<FileRecord, NtfsError> {
+        self.system_file(SystemFile::AttrDef)
+    }
+
+    ///$UpCase, the table used to compare file names case-insensitively.
+    pub fn upcase(&mut self) -> Result<FileRecord, NtfsError> {
+        self.system_file(SystemFile::UpCase)
+    }
+
+    ///$Secure, which holds the security descriptors shared by every file.
+    pub fn secure(&mut self) -> Result<FileRecord, NtfsError> {
+        self.system_file(SystemFile::Secure)
+    }
+
+    ///$Extend, the directory holding $ObjId, $Quota, $Reparse and $UsnJrnl.
+    pub fn extend(&mut self) -> Result<FileRecord, NtfsError> {
+        self.system_file(SystemFile::Extend)
+    }
+
+    ///Iterates over every record in the $MFT, see [`MasterFileTable::iter_from`].
+    pub fn iter(&mut self) -> MftIter<'_, R> {
+        self.iter_from(0)
+    }
+
+    ///Iterates over the records from `start` to the end of the $MFT, yielding each record number
+    ///with the result of parsing it. Records are read one at a time, and unlike [`FileRecords`],
+    ///BAAD and empty records are yielded as errors instead of being skipped, so long scans can checkpoint
+    ///on the record number and resume where they left off.
+    pub fn iter_from(&mut self, start: u64) -> MftIter<'_, R> {
+        let end = self.record_count();
+        MftIter {
+            ntfs: self.ntfs,
+            next: start.min(end),
+            end,
+        }
+    }
+
+    ///Finds the record number and FILE record of an absolute path, see [`Ntfs::resolve_path`].
+    pub fn resolve_path(&mut self, path: &str) -> Result<(u64, FileRecord), NtfsError> {
+        self.ntfs.resolve_path(path)
+    }
+
+    ///Reconstructs the path of a FILE record, see [`Ntfs::path_of`].
+    pub fn path_of(&mut self, record_number: u64) -> Result<Option<PathBuf>, NtfsError> {
+        self.ntfs.path_of(record_number)
+    }
+
+    ///The path of every hard link to a FILE record, see [`Ntfs::all_paths_of`].
+    pub fn all_paths_of(&mut self, record_number: u64) -> Result<Vec<PathBuf>, NtfsError> {
+        self.ntfs.all_paths_of(record_number)
+    }
+
+    ///Iterates over the FILE records in order, see [`Ntfs::file_records`].
+    pub fn records(&mut self) -> FileRecords<'_, R> {
+        self.ntfs.file_records()
+    }
+}
+
+///Iterates over a range of records in the $MFT. Created with [`MasterFileTable::iter`].
+pub struct MftIter<'a, R: Read + Seek> {
+    ntfs: &'a mut Ntfs<R>,
+    next: u64,
+    end: u64,
+}
+
+impl<R: Read + Seek> Iterator for MftIter<'_, R> {
+    type Item = (u64, Result<FileRecord, NtfsError>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next >= self.end {
+            return None;
+        }
+        let n = self.next;
+        self.next += 1;
+        Some((n, self.ntfs.file_record(n)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = usize::try_from(self.end - self.next).unwrap_or(usize::MAX);
+        (len, Some(len))
+    }
+}
+
+impl<R: Read + Seek> ExactSizeIterator for MftIter<'_, R> {}
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///Parses FILE record 0 and decodes the run list of its $DATA attribute.
+    ///The $MFT is usually fragmented, so later record reads translate through these runs.
+    ///On large volumes the $DATA attribute is split into extension records through an $ATTRIBUTE_LIST,
+    ///these are read through the runs in record 0 and merged with them.
+    pub fn load_mft_runs(&mut self) -> Result<Vec<(u64, u64)>, NtfsError> {
+        self.mft_runs = None;
+        self.mft_size = None;
+        self.mft_bitmap = None;
+        let mut buf = vec![0u8; self.geometry().file_record_size as usize];
+        let offset = self
+            .geometry()
+            .lcn_to_byte_offset(self.boot_sector().mft_cluster_number)?;
+        self.read_exact_at(offset, &mut buf)?;
+        let record = FileRecord::parse(&buf)?;
+
+        let data = record
+            .data_attribute()?
+            .ok_or(NtfsError::Corrupt("$MFT has no $DATA attribute"))?;
+        let mut runs = mft_runs(data.data_runs()?)?;
+        let Residency::NonResident { real_size, .. } = data.header().residency else {
+            return Err(NtfsError::Corrupt("$MFT $DATA attribute is resident"));
+        };
+
+        if record
+            .find_attribute(AttributeType::AttributeList)?
+            .is_some()
+        {
+            //The extension records are always in the part of the $MFT described by record 0.
+            self.mft_runs = Some(runs);
+            let merged = self.merged_attributes(0, &record);
+            self.mft_runs = None;
+            let data = merged?
+                .into_iter()
+                .find(|a| a.type_code == AttributeType::Data && a.name.is_none())
+                .ok_or(NtfsError::Corrupt("$MFT has no $DATA attribute"))?;
+            runs = mft_runs(data.runs)?;
+        }
+
+        self.mft_runs = Some(runs.clone());
+        self.mft_size = Some(real_size);
+        Ok(runs)
+    }
+
+    ///Bootstraps the $MFT from record 0, see [`Ntfs::load_mft_runs`].
+    pub fn master_file_table(&mut self) -> Result<MasterFileTable<'_, R>, NtfsError> {
+        if self.mft_runs.is_none() {
+            self.load_mft_runs()?;
+        }
+        Ok(MasterFileTable { ntfs: self })
+    }
+
+    ///Maps the bytes of FILE record `n` to (volume offset, length) pieces.
+    ///Returns `None` when the record lies past the end of the $MFT runs.
+    fn record_pieces(&self, n: u64) -> Option<Vec<(u64, usize)>> {
+        let geometry = self.geometry();
+        let record_size = geometry.file_record_size;
+        let start = n.checked_mul(record_size)?;
+        let Some(runs) = &self.mft_runs else {
+            //Until the runs are loaded, assume the $MFT is contiguous.
+            let offset = geometry
+                .lcn_to_byte_offset(self.boot_sector().mft_cluster_number)
+                .ok()?
+                .checked_add(start)?;
+            return Some(vec![(offset, record_size as usize)]);
+        };
+
+        let cluster_size = geometry.bytes_per_cluster;
+        let mut pieces = Vec::new();
+        let mut position = start;
+        let mut remaining = record_size;
+        let mut run_start = 0u64;
+        for &(lcn, length) in runs {
+            let run_end = run_start.saturating_add(length.saturating_mul(cluster_size));
+            if position < run_end {
+                let len = remaining.min(run_end - position);
+                let offset = lcn
+                    .saturating_mul(cluster_size)
+                    .saturating_add(position - run_start);
+                pieces.push((offset, len as usize));
+                position += len;
+                remaining -= len;
+                if remaining == 0 {
+                    return Some(pieces);
+                }
+            }
+            run_start = run_end;
+        }
+        None
+    }
+
+    ///Reads the raw bytes of FILE record `n`, or `None` past the end of the $MFT.
+    fn read_record_bytes(&mut self, n: u64) -> Result<Option<Vec<u8>>, NtfsError> {
+        let Some(pieces) = self.record_pieces(n) else {
+            return Ok(None);
+        };
+        let mut buf = vec![0u8; self.geometry().file_record_size as usize];
+        let mut filled = 0;
+        for (offset, len) in pieces {
+            self.read_exact_at(offset, &mut buf[filled..filled + len])?;
+            filled += len;
+        }
+        Ok(Some(buf))
+    }
+
+    ///Reads and parses FILE record `n`, loading the $MFT runs first if needed.
+    pub fn file_record(&mut self, n: u64) -> Result<FileRecord, NtfsError> {
+        if self.mft_runs.is_none() {
+            self.load_mft_runs()?;
+        }
+        let buf = self
+            .read_record_bytes(n)?
+            .ok_or(NtfsError::RecordOutOfRange(n))?;
+        FileRecord::parse(&buf)
+    }
+
+    ///Same as `file_record`, but goes through the record cache.
+    ///Used for lookups that revisit the same directories, not for scans of the whole $MFT.
+    pub(crate) fn cached_file_record(&mut self, n: u64) -> Result<FileRecord, NtfsError> {
+        if let Some(record) = self.record_cache.get(n) {
+            return Ok(record.clone());
+        }
+        let record = self.file_record(n)?;
+        self.record_cache.insert(n, record.clone());
+        Ok(record)
+    }
+}
+
+///Iterates over the FILE records of the $MFT in record number order.
+///Records marked 'BAAD' and empty records are skipped, their numbers are kept in
+///[`FileRecords::bad_records`] and [`FileRecords::empty_records`].
+///Created with [`Ntfs::file_records`].
+pub struct FileRecords<'a, R: Read + Seek> {
+    ntfs: &'a mut Ntfs<R>,
+    next: u64,
+    done: bool,
+    bad_records: Vec<u64>,
+    empty_records: Vec<u64>,
+}
+
+impl<'a, R: Read + Seek> FileRecords<'a, R> {
+    ///The record numbers skipped so far because they were marked 'BAAD'.
+    pub fn bad_records(&self) -> &[u64] {
+        &self.bad_records
+    }
+
+    ///The record numbers skipped so far because they were zeroed.
+    pub fn empty_records(&self) -> &[u64] {
+        &self.empty_records
+    }
+}
+
+impl<'a, R: Read + Seek> Iterator for FileRecords<'a, R> {
+    type Item = Result<FileRecord, NtfsError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let n = self.next;
+            let buf = match self.ntfs.read_record_bytes(n) {
+                Ok(Some(buf)) => buf,
+                Ok(None) => {
+                    self.done = true;
+                    return None;
+                }
+                Err(NtfsError::Io(err)) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                    self.done = true;
+                    return None;
+                }
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            };
+            self.next += 1;
+
+            match FileRecord::parse(&buf) {
+                Err(NtfsError::BadRecord) => self.bad_records.push(n),
+                //Without the $MFT runs the only sign of the end is the first zeroed record.
+                Err(NtfsError::EmptyRecord) if self.ntfs.mft_runs.is_none() => {
+                    self.done = true;
+                }
+                Err(NtfsError::EmptyRecord) => self.empty_records.push(n),
+                result => return Some(result),
+            }
+        }
+        None
+    }
+}
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///Reads FILE records in order from the start of the $MFT.
+    ///The records are read through the $MFT runs, loading them first if needed.
+    ///If record 0 can't be used the $MFT is assumed to be contiguous,
+    ///and iteration stops at the first zeroed record.
+    pub fn file_records(&mut self) -> FileRecords<'_, R> {
+        if self.mft_runs.is_none() {
+            //Falling back to a contiguous $MFT is the best that can be done.
+            let _ = self.load_mft_runs();
+        }
+        FileRecords {
+            ntfs: self,
+            next: 0,
+            done: false,
+            bad_records: Vec::new(),
+            empty_records: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! A disk image starts with a Master Boot Record, whose partition table locates the volumes on the disk.
+//! The table holds four 16 byte primary partition entries at offset 0x1BE and the sector ends with 0xAA55.
+//!
+//!| Offset | Size | Description                                 |
+//!|--------|------|---------------------------------------------|
+//!| 0x00   | 1    | Status, 0x80 if the partition is bootable   |
+//!| 0x01   | 3    | CHS address of the first sector, unused     |
+//!| 0x04   | 1    | Partition type                              |
+//!| 0x05   | 3    | CHS address of the last sector, unused      |
+//!| 0x08   | 4    | LBA of the first sector                     |
+//!| 0x0C   | 4    | Number of sectors                           |
+//!
+//! Disks partitioned with GPT have a protective MBR with a single partition of type 0xEE.
+//!
+//!<https://en.wikipedia.org/wiki/Master_boot_record>
+use crate::{bytes::u32_at, NtfsError};
+use std::io::{Read, Seek, SeekFrom};
+
+///The offset of the partition table in the MBR.
+pub const PARTITION_TABLE_OFFSET: usize = 0x1BE;
+
+///The size of a partition table entry.
+pub const PARTITION_ENTRY_SIZE: usize = 0x10;
+
+///MBR addresses are always in 512 byte sectors.
+pub const MBR_SECTOR_SIZE: u64 = 512;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PartitionEntry {
+    ///The position of the entry in the partition table, from 0 to 3.
+    pub index: usize,
+    pub bootable: bool,
+    pub partition_type: u8,
+    pub start_lba: u32,
+    pub sector_count: u32,
+}
+
+impl PartitionEntry {
+    ///The type used by NTFS, which it shares with exFAT and HPFS.
+    pub const NTFS: u8 = 0x07;
+
+    ///Returns true if the partition type is 0x07. The boot sector tells NTFS apart from exFAT and HPFS.
+    pub fn is_ntfs(&self) -> bool {
+        self.partition_type == Self::NTFS
+    }
+
+    ///The byte offset of the partition, to pass to [`Ntfs::new_at_offset`](crate::Ntfs::new_at_offset).
+    pub fn byte_offset(&self) -> u64 {
+        self.start_lba as u64 * MBR_SECTOR_SIZE
+    }
+
+    ///The size of the partition in bytes.
+    pub fn size(&self) -> u64 {
+        self.sector_count as u64 * MBR_SECTOR_SIZE
+    }
+}
+
+///Reads the MBR at the start of `reader` and returns its primary partitions.
+///Unused entries, which have a partition type of zero, are left out.
+pub fn scan_mbr<R: Read + Seek>(reader: &mut R) -> Result<Vec<PartitionEntry>, NtfsError> {
+    let mut buf = [0u8; 512];
+    reader.seek(SeekFrom::Start(0))?;
+    reader.read_exact(&mut buf)?;
+    if buf[510..512] != [0x55, 0xAA] {
+        return Err(NtfsError::BadEndMarker);
+    }
+
+    let mut partitions = Vec::new();
+    for index in 0..4 {
+        let entry = &buf[PARTITION_TABLE_OFFSET + index * PARTITION_ENTRY_SIZE..];
+        let partition_type = entry[0x04];
+        if partition_type == 0 {
+            continue;
+        }
+        partitions.push(PartitionEntry {
+            index,
+            bootable: entry[0x00] == 0x80,
+            partition_type,
+            start_lba: u32_at(entry, 0x08),
+            sector_count: u32_at(entry, 0x0C),
+        });
+    }
+    Ok(partitions)
+}
@@ -0,0 +1,98 @@
+//! The $MFT has a $BITMAP attribute (0xB0) of its own, with one bit per FILE record that is set while
+//! the record is in use. NTFS allocates records through it, so it answers "is this slot free?"
+//! without reading the record. A set bit for a record whose header isn't flagged in use is an
+//! inconsistency chkdsk repairs, and a good place for deleted file scanners to start.
+use crate::{AttributeType, MasterFileTable, Ntfs, NtfsError};
+use std::io::{Read, Seek};
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///Reads the $BITMAP attribute of the $MFT, following record 0's $ATTRIBUTE_LIST on large volumes.
+    ///The bitmap is padded to a multiple of 8 bytes, so it can have bits past the last record.
+    pub fn mft_bitmap(&mut self) -> Result<Vec<u8>, NtfsError> {
+        let record = self.file_record(0)?;
+        let bitmap = self
+            .merged_attributes(0, &record)?
+            .into_iter()
+            .find(|a| a.type_code == AttributeType::Bitmap && a.name.is_none())
+            .ok_or(NtfsError::Corrupt("$MFT has no $BITMAP attribute"))?;
+        self.read_merged_attribute(&bitmap)
+    }
+
+    ///The $MFT bitmap, read on first use and kept until the $MFT runs are reloaded.
+    fn cached_mft_bitmap(&mut self) -> Result<&[u8], NtfsError> {
+        if self.mft_bitmap.is_none() {
+            self.mft_bitmap = Some(self.mft_bitmap()?);
+        }
+        Ok(self.mft_bitmap.as_deref().unwrap_or_default())
+    }
+}
+
+impl<R: Read + Seek> MasterFileTable<'_, R> {
+    ///Returns true if the $MFT bitmap marks record `n` as in use. Records past the end of the $MFT aren't.
+    pub fn record_in_use(&mut self, n: u64) -> Result<bool, NtfsError> {
+        if n >= self.record_count() {
+            return Ok(false);
+        }
+        Ok(bit_is_set(self.ntfs.cached_mft_bitmap()?, n))
+    }
+
+    ///The number of records the $MFT bitmap marks as in use.
+    pub fn allocated_record_count(&mut self) -> Result<u64, NtfsError> {
+        let record_count = self.record_count();
+        let bitmap = self.ntfs.cached_mft_bitmap()?;
+        Ok((0..record_count).filter(|n| bit_is_set(bitmap, *n)).count() as u64)
+    }
+
+    ///Iterates over the records the $MFT bitmap marks as in use whose headers say they aren't,
+    ///usually files deleted without the bitmap being updated. Only the records with their bit set are read.
+    ///Records that fail to parse are skipped, and an I/O error ends the iteration.
+    pub fn allocated_unused_records(&mut self) -> Result<AllocatedUnusedRecords<'_, R>, NtfsError> {
+        let end = self.record_count();
+        let bitmap = self.ntfs.cached_mft_bitmap()?.to_vec();
+        Ok(AllocatedUnusedRecords {
+            ntfs: self.ntfs,
+            bitmap,
+            next: 0,
+            end,
+        })
+    }
+}
+
+fn bit_is_set(bitmap: &[u8], n: u64) -> bool {
+    usize::try_from(n / 8)
+        .ok()
+        .and_then(|i| bitmap.get(i))
+        .is_some_and(|byte| byte & (1 << (n % 8)) != 0)
+}
+
+///Created with [`MasterFileTable::allocated_unused_records`].
+pub struct AllocatedUnusedRecords<'a, R: Read + Seek> {
+    ntfs: &'a mut Ntfs<R>,
+    bitmap: Vec<u8>,
+    next: u64,
+    end: u64,
+}
+
+impl<R: Read + Seek> Iterator for AllocatedUnusedRecords<'_, R> {
+    type Item = Result<u64, NtfsError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.next < self.end {
+            let n = self.next;
+            self.next += 1;
+            if !bit_is_set(&self.bitmap, n) {
+                continue;
+            }
+            match self.ntfs.file_record(n) {
+                Ok(record) if !record.is_in_use() => return Some(Ok(n)),
+                Ok(_) => {}
+                Err(err @ NtfsError::Io(_)) => {
+                    self.next = self.end;
+                    return Some(Err(err));
+                }
+                Err(_) => {}
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,71 @@
+//! $MFTMirr (FILE record 1) is a copy of the first four records of the $MFT:
+//! $MFT, $MFTMirr, $LogFile and $Volume, kept in the middle of the volume.
+//! chkdsk uses it to repair a damaged start of the $MFT, and comparing the two
+//! is a quick way to tell whether the volume was modified behind NTFS's back.
+use crate::{FileRecord, Ntfs, NtfsError};
+use std::io::{Read, Seek};
+
+///The number of records copied into $MFTMirr.
+pub const MFT_MIRROR_RECORDS: u64 = 4;
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///Reads FILE record `n` from $MFTMirr, which only holds records 0 to 3.
+    pub fn mirror_record(&mut self, n: u64) -> Result<FileRecord, NtfsError> {
+        if n >= MFT_MIRROR_RECORDS {
+            return Err(NtfsError::RecordOutOfRange(n));
+        }
+        let offset = self
+            .geometry()
+            .lcn_to_byte_offset(self.boot_sector().mft_mirror_cluster_number)?;
+        self.read_contiguous_record(offset, n)
+    }
+
+    fn read_contiguous_record(&mut self, offset: u64, n: u64) -> Result<FileRecord, NtfsError> {
+        let record_size = self.geometry().file_record_size;
+        let mut buf = vec![0u8; record_size as usize];
+        self.read_exact_at(offset + n * record_size, &mut buf)?;
+        FileRecord::parse(&buf)
+    }
+
+    ///Compares the first four records of the $MFT with their copies in $MFTMirr.
+    ///The records are compared after their update sequences have been applied,
+    ///ignoring the update sequence arrays since the two copies are written separately.
+    ///Returns an error if either copy fails to parse, e.g. because a sector was torn.
+    pub fn verify_mft_mirror(&mut self) -> Result<bool, NtfsError> {
+        let mft_offset = self
+            .geometry()
+            .lcn_to_byte_offset(self.boot_sector().mft_cluster_number)?;
+        for n in 0..MFT_MIRROR_RECORDS {
+            let primary = self.read_contiguous_record(mft_offset, n)?;
+            let mirror = self.mirror_record(n)?;
+            if without_update_sequence(&primary) != without_update_sequence(&mirror) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    ///Reads FILE record `n`, falling back to its copy in $MFTMirr if the primary is
+    ///marked BAAD or has a torn sector. Records past the mirror are read as usual.
+    pub fn file_record_or_mirror(&mut self, n: u64) -> Result<FileRecord, NtfsError> {
+        match self.file_record(n) {
+            Err(NtfsError::BadRecord | NtfsError::FixupMismatch { .. })
+                if n < MFT_MIRROR_RECORDS =>
+            {
+                self.mirror_record(n)
+            }
+            result => result,
+        }
+    }
+}
+
+///The fixed up bytes of `record` with the update sequence array zeroed.
+fn without_update_sequence(record: &FileRecord) -> Vec<u8> {
+    let mut data = record.data().to_vec();
+    let start = record.update_sequence_offset as usize;
+    let end = start + record.update_sequence_size as usize * 2;
+    if let Some(array) = data.get_mut(start..end) {
+        array.fill(0);
+    }
+    data
+}
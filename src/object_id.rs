@@ -0,0 +1,161 @@
+//! Distributed link tracking gives a file a GUID in its $OBJECT_ID attribute (0x40), so shortcuts and
+//! OLE links can find it again after it's moved or renamed. The attribute is always resident.
+//!
+//!| Offset | Size | Description                                   |
+//!|--------|------|-----------------------------------------------|
+//!| 0x00   | 16   | Object id                                     |
+//!| 0x10   | 16   | Birth volume id, optional                     |
+//!| 0x20   | 16   | Birth object id, optional                     |
+//!| 0x30   | 16   | Domain id, optional                           |
+//!
+//! The "$O" view index of \$Extend\$ObjId maps each object id back to the file. Its entries are keyed
+//! on the object id, collated as four little-endian u32s, and their data follows the key.
+//!
+//!| Offset | Size | Description                                   |
+//!|--------|------|-----------------------------------------------|
+//!| 0x00   | 2    | Offset to the data                            |
+//!| 0x02   | 2    | Size of the data                              |
+//!| 0x04   | 4    | Reserved                                      |
+//!| 0x08   | 2    | Size of the index entry                       |
+//!| 0x0A   | 2    | Size of the key                               |
+//!| 0x0C   | 2    | Flags                                         |
+//!| 0x0E   | 2    | Reserved                                      |
+//!| 0x10   | 16   | Object id, the key                            |
+//!| 0x20   | 8    | File reference, the data                      |
+//!| 0x28   | 16   | Birth volume id                               |
+//!| 0x38   | 16   | Birth object id                               |
+//!| 0x48   | 16   | Domain id                                     |
+//!
+//!<https://flatcap.github.io/linux-ntfs/ntfs/attributes/object_id.html>
+//!
+//!<https://flatcap.github.io/linux-ntfs/ntfs/files/objid.html>
+use crate::{
+    bytes::{guid_at, u16_at, u32_at, u64_at},
+    FileRecord, FileReference, Guid, MasterFileTable, Ntfs, NtfsError,
+};
+use std::{
+    cmp::Ordering,
+    io::{Read, Seek},
+};
+
+///The size of an $OBJECT_ID value holding only the object id.
+pub const OBJECT_ID_SIZE: usize = 0x10;
+
+///The size of an $OBJECT_ID value that also has the birth volume, birth object and domain ids.
+pub const EXTENDED_OBJECT_ID_SIZE: usize = 0x40;
+
+///The size of the data of an entry in the $O index.
+pub const OBJECT_ID_ENTRY_DATA_SIZE: usize = 0x38;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjectId {
+    pub object_id: Guid,
+    ///The volume the file was created on.
+    pub birth_volume_id: Option<Guid>,
+    ///The object id the file was first given, kept when the file is copied or moved between volumes.
+    pub birth_object_id: Option<Guid>,
+    ///Reserved, always zero in practice.
+    pub domain_id: Option<Guid>,
+}
+
+impl ObjectId {
+    pub fn parse(value: &[u8]) -> Result<ObjectId, NtfsError> {
+        if value.len() < OBJECT_ID_SIZE {
+            return Err(NtfsError::BufferTooSmall {
+                needed: OBJECT_ID_SIZE,
+                available: value.len(),
+            });
+        }
+        let extended = value.len() >= EXTENDED_OBJECT_ID_SIZE;
+        let optional = |offset| extended.then(|| guid_at(value, offset));
+        Ok(ObjectId {
+            object_id: guid_at(value, 0x00),
+            birth_volume_id: optional(0x10),
+            birth_object_id: optional(0x20),
+            domain_id: optional(0x30),
+        })
+    }
+}
+
+///An entry of the $O index in \$Extend\$ObjId.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjectIdEntry {
+    pub object_id: Guid,
+    ///The file that has the object id.
+    pub file_reference: FileReference,
+    pub birth_volume_id: Guid,
+    pub birth_object_id: Guid,
+    pub domain_id: Guid,
+}
+
+impl ObjectIdEntry {
+    ///Parses an $O index entry, starting at its header.
+    pub fn parse(entry: &[u8]) -> Result<ObjectIdEntry, NtfsError> {
+        let needed = 0x10 + OBJECT_ID_SIZE;
+        if entry.len() < needed {
+            return Err(NtfsError::BufferTooSmall {
+                needed,
+                available: entry.len(),
+            });
+        }
+        let data_offset = u16_at(entry, 0x00) as usize;
+        let data_length = u16_at(entry, 0x02) as usize;
+        if u16_at(entry, 0x0A) as usize != OBJECT_ID_SIZE {
+            return Err(NtfsError::Corrupt("$O index key is not an object id"));
+        }
+        if data_length < OBJECT_ID_ENTRY_DATA_SIZE || data_offset + data_length > entry.len() {
+            return Err(NtfsError::Corrupt(
+                "$O index entry data runs past the entry",
+            ));
+        }
+        let data = &entry[data_offset..];
+        Ok(ObjectIdEntry {
+            object_id: guid_at(entry, 0x10),
+            file_reference: FileReference::from_raw(u64_at(data, 0x00)),
+            birth_volume_id: guid_at(data, 0x08),
+            birth_object_id: guid_at(data, 0x18),
+            domain_id: guid_at(data, 0x28),
+        })
+    }
+}
+
+///Compares object ids the way the $O index sorts them, as four little-endian u32s.
+pub fn collate_object_ids(a: &Guid, b: &Guid) -> Ordering {
+    let words = |guid: &Guid| [0, 4, 8, 12].map(|offset| u32_at(&guid.0, offset));
+    words(a).cmp(&words(b))
+}
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///Looks up an object id in the $O index of \$Extend\$ObjId, descending its B-tree.
+    ///Returns `None` if no file has the object id. Volumes without \$Extend\$ObjId are `NotFound`.
+    pub fn find_object_id(&mut self, object_id: &Guid) -> Result<Option<ObjectIdEntry>, NtfsError> {
+        let (_, record) = self.resolve_path("\\$Extend\\$ObjId")?;
+        let entry = self.search_view_index(&record, "$O", |entry| {
+            Ok(collate_object_ids(
+                object_id,
+                &ObjectIdEntry::parse(entry)?.object_id,
+            ))
+        })?;
+        entry.as_deref().map(ObjectIdEntry::parse).transpose()
+    }
+}
+
+impl<R: Read + Seek> MasterFileTable<'_, R> {
+    ///Finds the record number and FILE record of the file with `object_id`, e.g. the droid of a
+    ///Windows shortcut, through the $O index. Returns `None` if the object id isn't indexed,
+    ///or if the record has been reused since.
+    pub fn record_by_object_id(
+        &mut self,
+        object_id: &Guid,
+    ) -> Result<Option<(u64, FileRecord)>, NtfsError> {
+        let Some(entry) = self.ntfs.find_object_id(object_id)? else {
+            return Ok(None);
+        };
+        let reference = entry.file_reference;
+        let record = self.ntfs.file_record(reference.record_number)?;
+        if !record.is_in_use() || record.sequence_number != reference.sequence_number {
+            return Ok(None);
+        }
+        Ok(Some((reference.record_number, record)))
+    }
+}
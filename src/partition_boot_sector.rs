@@ -26,25 +26,52 @@
 //!| 0x54        | 426 bytes    |                    | Bootstrap Code                            | The code that loads the rest of the operating system.                       |
 //!| 0x01FE      | 2 bytes      | 0xAA55             | End-of-sector Marker                      | This flag indicates that this is a valid boot sector.                       |
 
-use std::{
-    fs::File,
-    io::{BufReader, Read},
-    str::from_utf8,
-};
+use crate::{bytes::u64_at, NtfsError};
+use std::io::Read;
 
 #[rustfmt::skip]
 pub const PARTITION_BOOT_SECTOR_SIZE: usize = 512;
 
-#[derive(Debug, PartialEq)]
+///The size of the bootstrap code at 0x54.
+pub const BOOTSTRAP_CODE_SIZE: usize = 426;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Size {
     Bytes(u32),
     Clusters(u8),
 }
 
-#[derive(Debug)]
+impl Size {
+    ///Decodes the signed "bytes or clusters" encoding used by the boot sector.
+    fn decode(field: &'static str, value: u8) -> Result<Size, NtfsError> {
+        match value as i8 {
+            clusters @ 1.. => Ok(Size::Clusters(clusters as u8)),
+            exponent => 1u32
+                .checked_shl(exponent.unsigned_abs() as u32)
+                .filter(|_| exponent != 0)
+                .map(Size::Bytes)
+                .ok_or(NtfsError::InvalidSizeEncoding { field, value }),
+        }
+    }
+
+    ///Resolves the size in bytes, given the size of a cluster.
+    pub fn bytes(&self, cluster_size: u64) -> u64 {
+        match *self {
+            Size::Bytes(bytes) => bytes as u64,
+            Size::Clusters(clusters) => cluster_size.saturating_mul(clusters as u64),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PartitionBootSector {
     pub bytes_per_sector: u16,
-    pub sectors_per_cluster: u8,
+    ///Already decoded, so this is always the number of sectors.
+    pub sectors_per_cluster: u32,
+    ///0xF8 for a hard disk. Only lenient parsing accepts anything else.
+    pub media_descriptor: u8,
     pub sectors_per_track: u16,
     pub number_of_heads: u16,
     pub hidden_sectors: u32,
@@ -57,149 +84,337 @@ pub struct PartitionBootSector {
     pub index_buffer: Size,
 
     pub volume_serial_number: u64,
+    ///Always zero on volumes formatted by Windows, which neither writes nor checks it.
+    pub checksum: u32,
+    ///The x86 code the jump instruction at 0x00 continues into, which loads NTLDR or BOOTMGR.
+    #[cfg_attr(feature = "serde", serde(with = "bootstrap_code"))]
+    pub bootstrap_code: [u8; BOOTSTRAP_CODE_SIZE],
 }
 
-pub fn pbs(reader: &mut BufReader<File>) -> PartitionBootSector {
-    let mut buf = [0u8; PARTITION_BOOT_SECTOR_SIZE];
-    reader.read_exact(&mut buf).unwrap();
-
-    //Causes execution to continue after the data structures in this boot sector.
-    assert_eq!(buf[0], 0xeb);
-    assert_eq!(buf[1], 0x52);
-    assert_eq!(buf[2], 0x90);
-
-    //This is the magic number that indicates this is an NTFS file system.
-    let oem_id = from_utf8(&buf[3..11]).unwrap();
-    assert_eq!(oem_id, "NTFS    ");
-
-    //The number of bytes in a disk sector.
-    let bytes_per_sector = u16::from_le_bytes([buf[11], buf[12]]);
-    assert_eq!(bytes_per_sector, 0x0200);
-
-    //The number of sectors in a cluster.
-    //If the value is greater than 0x80, the amount of sectors is 2 to the power of the absolute value of considering this field to be negative.
-    let sectors_per_cluster = buf[13];
-    assert_eq!(sectors_per_cluster, 0x08);
-
-    //Unused
-    assert_eq!(buf[14], 0);
-    assert_eq!(buf[15], 0);
-
-    //Unused
-    assert_eq!(buf[16], 0);
-    assert_eq!(buf[17], 0);
-    assert_eq!(buf[18], 0);
-
-    //Unused
-    assert_eq!(buf[19], 0);
-    assert_eq!(buf[20], 0);
-
-    //The type of drive. 0xF8 is used to denote a hard drive (in contrast to the several sizes of floppy).
-    let media_descriptor = buf[21];
-    assert_eq!(media_descriptor, 0xF8);
-
-    //Unused
-    assert_eq!(buf[22], 0);
-    assert_eq!(buf[23], 0);
-
-    //The number of disk sectors in a drive track.
-    let sectors_per_track = u16::from_le_bytes([buf[24], buf[25]]);
-    assert_eq!(sectors_per_track, 0x003F);
-
-    //The number of heads on the drive.
-    let number_of_heads = u16::from_le_bytes([buf[26], buf[27]]);
-    assert_eq!(number_of_heads, 0x0ff);
-
-    //The number of sectors preceding the partition.
-    assert_eq!(28, 0x1C);
-    let hidden_sectors = u32::from_le_bytes([buf[28], buf[29], buf[30], buf[31]]);
-
-    //Unused
-    assert_eq!(buf[32], 0);
-    assert_eq!(buf[33], 0);
-    assert_eq!(buf[34], 0);
-    assert_eq!(buf[35], 0);
-
-    let unused = u32::from_le_bytes([buf[36], buf[37], buf[38], buf[39]]);
-    assert_eq!(unused, 0x00800080);
-
-    //The partition size in sectors.
-    assert_eq!(40, 0x28);
-    let total_sectors = u64::from_le_bytes(buf[40..40 + 8].try_into().unwrap());
-
-    //The cluster that contains the Master File Table
-    assert_eq!(48, 0x30);
-    let mft_cluster_number = u64::from_le_bytes(buf[48..48 + 8].try_into().unwrap());
-
-    //The cluster that contains a backup of the Master File Table
-    assert_eq!(56, 0x38);
-    let mft_mirror_cluster_number = u64::from_le_bytes(buf[56..56 + 8].try_into().unwrap());
-
-    //A positive value denotes the number of clusters in a File Record Segment.
-    //A negative value denotes the amount of bytes in a File Record Segment, in which case the size is 2 to the power of the absolute value.
-    //(0xF6 = -10 → 210 = 1024).
-    assert_eq!(64, 0x40);
-    let file_record_segment = i8::from_le_bytes([buf[64]]);
-    let file_record_segment = if file_record_segment.is_positive() {
-        Size::Clusters(file_record_segment as u8)
-    } else {
-        Size::Bytes(2u32.pow(file_record_segment.abs() as u32))
-    };
-    assert_eq!(file_record_segment, Size::Bytes(1024));
-
-    //Unused
-    assert_eq!(buf[65], 0);
-    assert_eq!(buf[66], 0);
-    assert_eq!(buf[67], 0);
-
-    //A positive value denotes the number of clusters in an Index Buffer.
-    //A negative value denotes the amount of bytes and it uses the same algorithm for negative numbers as the "Bytes or Clusters Per File Record Segment."
-    assert_eq!(68, 0x44);
-    let index_buffer = i8::from_le_bytes([buf[68]]);
-    let index_buffer = if index_buffer.is_positive() {
-        Size::Clusters(index_buffer as u8)
+///Fails with `NtfsError::UnexpectedValue` when `found` is not `expected`.
+fn expect(field: &'static str, offset: usize, expected: u64, found: u64) -> Result<(), NtfsError> {
+    if expected == found {
+        Ok(())
     } else {
-        Size::Bytes(2u32.pow(index_buffer.abs() as u32))
-    };
-    assert_eq!(index_buffer, Size::Clusters(0x01));
-
-    //Unused
-    assert_eq!(buf[69], 0);
-    assert_eq!(buf[70], 0);
-    assert_eq!(buf[71], 0);
-
-    //A unique random number assigned to this partition, to keep things organized.
-    assert_eq!(72, 0x48);
-    let volume_serial_number = u64::from_le_bytes(buf[72..72 + 8].try_into().unwrap());
-
-    //Unused
-    assert_eq!(0x50, 80);
-    assert_eq!(buf[80], 0);
-    assert_eq!(buf[81], 0);
-    assert_eq!(buf[82], 0);
-    assert_eq!(buf[83], 0);
-
-    //The code that loads the rest of the operating system.
-    //This is pointed to by the first 3 bytes of this sector.
-    assert_eq!(84, 0x54);
-    let _bootstrap_code = &buf[84..84 + 426];
-
-    //This flag indicates that this is a valid boot sector.
-    assert_eq!(510, 0x01FE);
-    let end_of_sector = u16::from_le_bytes([buf[510], buf[511]]);
-    assert_eq!(end_of_sector, 0xAA55);
-
-    PartitionBootSector {
-        bytes_per_sector,
-        sectors_per_cluster,
-        sectors_per_track,
-        number_of_heads,
-        hidden_sectors,
-        total_sectors,
-        mft_cluster_number,
-        mft_mirror_cluster_number,
-        file_record_segment,
-        index_buffer,
-        volume_serial_number,
+        Err(NtfsError::UnexpectedValue {
+            field,
+            offset,
+            expected,
+            found,
+        })
+    }
+}
+
+///Checks that every byte in `buf[offset..offset + len]` is zero.
+fn expect_zero(buf: &[u8], offset: usize, len: usize) -> Result<(), NtfsError> {
+    for (i, byte) in buf[offset..offset + len].iter().enumerate() {
+        expect("unused byte", offset + i, 0, *byte as u64)?;
+    }
+    Ok(())
+}
+
+///Serde only implements its traits for arrays of up to 32 elements, so the bootstrap code goes through a slice.
+#[cfg(feature = "serde")]
+mod bootstrap_code {
+    use super::BOOTSTRAP_CODE_SIZE;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        code: &[u8; BOOTSTRAP_CODE_SIZE],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(code)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<[u8; BOOTSTRAP_CODE_SIZE], D::Error> {
+        let code = Vec::<u8>::deserialize(deserializer)?;
+        let len = code.len();
+        code.try_into()
+            .map_err(|_| D::Error::invalid_length(len, &"426 bytes of bootstrap code"))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParseOptions {
+    ///Fail on the first field that doesn't match the NTFS specification.
+    ///When `false`, only the OEM ID, the end marker and the fields needed to locate the MFT are required
+    ///and every other failed check is returned as a `ValidationWarning`.
+    pub strict: bool,
+    ///Fail with `TruncatedVolume` when the reader is shorter than the size the boot sector declares.
+    ///Turn off for devices that report their length oddly.
+    pub check_length: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            strict: true,
+            check_length: true,
+        }
+    }
+}
+
+///A check that failed while parsing in lenient mode.
+#[derive(Debug)]
+pub struct ValidationWarning(pub NtfsError);
+
+impl std::fmt::Display for ValidationWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+///Either fails or collects warnings depending on `ParseOptions::strict`.
+struct Validator {
+    strict: bool,
+    warnings: Vec<ValidationWarning>,
+}
+
+impl Validator {
+    fn check(&mut self, result: Result<(), NtfsError>) -> Result<(), NtfsError> {
+        match result {
+            Err(err) if !self.strict => {
+                self.warnings.push(ValidationWarning(err));
+                Ok(())
+            }
+            result => result,
+        }
+    }
+}
+
+pub fn pbs<R: Read>(reader: &mut R) -> Result<PartitionBootSector, NtfsError> {
+    let (pbs, _) = pbs_with_options(reader, ParseOptions::default())?;
+    Ok(pbs)
+}
+
+pub fn pbs_with_options<R: Read>(
+    reader: &mut R,
+    options: ParseOptions,
+) -> Result<(PartitionBootSector, Vec<ValidationWarning>), NtfsError> {
+    let mut buf = [0u8; PARTITION_BOOT_SECTOR_SIZE];
+    reader.read_exact(&mut buf)?;
+    PartitionBootSector::from_bytes_with_options(&buf, options)
+}
+
+impl PartitionBootSector {
+    ///The size of a cluster in bytes.
+    pub fn cluster_size(&self) -> u64 {
+        self.bytes_per_sector as u64 * self.sectors_per_cluster as u64
+    }
+
+    ///The size of a cluster in bytes. Same as `cluster_size`.
+    pub fn cluster_size_bytes(&self) -> u64 {
+        self.cluster_size()
+    }
+
+    ///The size of a cluster in bytes. Same as `cluster_size`.
+    pub fn bytes_per_cluster(&self) -> u64 {
+        self.cluster_size()
+    }
+
+    ///The byte offset of the logical cluster number `lcn` from the start of the volume.
+    pub fn cluster_to_byte_offset(&self, lcn: u64) -> u64 {
+        lcn.saturating_mul(self.cluster_size())
+    }
+
+    ///The byte offset of the $MFT from the start of the volume.
+    pub fn mft_offset(&self) -> u64 {
+        self.cluster_to_byte_offset(self.mft_cluster_number)
+    }
+
+    ///The byte offset of the $MFTMirr from the start of the volume.
+    pub fn mft_mirror_offset(&self) -> u64 {
+        self.cluster_to_byte_offset(self.mft_mirror_cluster_number)
+    }
+
+    ///The byte offset of the $MFT from the start of the volume. Same as `mft_offset`.
+    pub fn mft_byte_offset(&self) -> u64 {
+        self.mft_offset()
+    }
+
+    ///The byte offset of the $MFTMirr from the start of the volume. Same as `mft_mirror_offset`.
+    pub fn mft_mirror_byte_offset(&self) -> u64 {
+        self.mft_mirror_offset()
+    }
+
+    ///The size of the volume in bytes.
+    pub fn volume_size(&self) -> u64 {
+        self.total_sectors
+            .saturating_mul(self.bytes_per_sector as u64)
+    }
+
+    ///The size of the volume in bytes. Same as `volume_size`.
+    pub fn total_bytes(&self) -> u64 {
+        self.volume_size()
+    }
+
+    ///The size of a FILE record in bytes.
+    pub fn file_record_size(&self) -> u64 {
+        self.file_record_segment.bytes(self.cluster_size())
+    }
+
+    ///The size of an index buffer (INDX record) in bytes.
+    pub fn index_buffer_size(&self) -> u64 {
+        self.index_buffer.bytes(self.cluster_size())
+    }
+
+    ///Parses a boot sector that has already been read into memory.
+    pub fn from_bytes(
+        buf: &[u8; PARTITION_BOOT_SECTOR_SIZE],
+    ) -> Result<PartitionBootSector, NtfsError> {
+        let (pbs, _) = Self::from_bytes_with_options(buf, ParseOptions::default())?;
+        Ok(pbs)
+    }
+
+    pub fn from_bytes_with_options(
+        buf: &[u8; PARTITION_BOOT_SECTOR_SIZE],
+        options: ParseOptions,
+    ) -> Result<(PartitionBootSector, Vec<ValidationWarning>), NtfsError> {
+        let mut v = Validator {
+            strict: options.strict,
+            warnings: Vec::new(),
+        };
+
+        //Causes execution to continue after the data structures in this boot sector.
+        if buf[0..3] != [0xEB, 0x52, 0x90] {
+            v.check(Err(NtfsError::BadMagic))?;
+        }
+
+        //This is the magic number that indicates this is an NTFS file system.
+        //Required in both modes, since anything else isn't an NTFS boot sector at all.
+        if &buf[3..11] != b"NTFS    " {
+            return Err(NtfsError::BadOemId);
+        }
+
+        //The number of bytes in a disk sector.
+        //Usually 512, but Advanced Format (4Kn) drives use 4096.
+        let bytes_per_sector = u16::from_le_bytes([buf[11], buf[12]]);
+        if !bytes_per_sector.is_power_of_two() || !(512..=4096).contains(&bytes_per_sector) {
+            return Err(NtfsError::InvalidSectorSize(bytes_per_sector));
+        }
+
+        //The number of sectors in a cluster.
+        //If the value is greater than 0x80, the amount of sectors is 2 to the power of the absolute value of considering this field to be negative.
+        //(0xF4 = -12 → 2^12 = 4096).
+        let sectors_per_cluster = match buf[13] {
+            0 => None,
+            value @ 1..=0x80 => Some(value as u32),
+            value => 1u32.checked_shl(256 - value as u32),
+        }
+        .ok_or(NtfsError::InvalidClusterSize(buf[13]))?;
+
+        //Unused
+        v.check(expect_zero(buf, 14, 2))?;
+
+        //Unused
+        v.check(expect_zero(buf, 16, 3))?;
+
+        //Unused
+        v.check(expect_zero(buf, 19, 2))?;
+
+        //The type of drive. 0xF8 is used to denote a hard drive (in contrast to the several sizes of floppy).
+        let media_descriptor = buf[21];
+        v.check(expect(
+            "media descriptor",
+            21,
+            0xF8,
+            media_descriptor as u64,
+        ))?;
+
+        //Unused
+        v.check(expect_zero(buf, 22, 2))?;
+
+        //The legacy CHS geometry fields are ignored by NTFS and vary between drives,
+        //so they are stored as-is rather than validated.
+
+        //The number of disk sectors in a drive track.
+        let sectors_per_track = u16::from_le_bytes([buf[24], buf[25]]);
+
+        //The number of heads on the drive.
+        let number_of_heads = u16::from_le_bytes([buf[26], buf[27]]);
+
+        //The number of sectors preceding the partition.
+        //0x1C
+        let hidden_sectors = u32::from_le_bytes([buf[28], buf[29], buf[30], buf[31]]);
+
+        //Unused
+        v.check(expect_zero(buf, 32, 4))?;
+
+        //EBPB, not used by NTFS. Usually 0x00800080.
+        //0x24
+
+        //The partition size in sectors.
+        //0x28
+        let total_sectors = u64_at(buf, 40);
+
+        //The cluster that contains the Master File Table
+        //0x30
+        let mft_cluster_number = u64_at(buf, 48);
+
+        //The cluster that contains a backup of the Master File Table
+        //0x38
+        let mft_mirror_cluster_number = u64_at(buf, 56);
+
+        //A positive value denotes the number of clusters in a File Record Segment.
+        //A negative value denotes the amount of bytes in a File Record Segment, in which case the size is 2 to the power of the absolute value.
+        //(0xF6 = -10 → 210 = 1024).
+        //0x40
+        //Lenient mode falls back to the common 1024 byte record.
+        let file_record_segment = Size::decode("file record segment size", buf[64])
+            .or_else(|err| v.check(Err(err)).map(|_| Size::Bytes(1024)))?;
+
+        //Unused
+        v.check(expect_zero(buf, 65, 3))?;
+
+        //A positive value denotes the number of clusters in an Index Buffer.
+        //A negative value denotes the amount of bytes and it uses the same algorithm for negative numbers as the "Bytes or Clusters Per File Record Segment."
+        //0x44
+        //Lenient mode falls back to the common 4096 byte buffer.
+        let index_buffer = Size::decode("index buffer size", buf[68])
+            .or_else(|err| v.check(Err(err)).map(|_| Size::Bytes(4096)))?;
+
+        //Unused
+        v.check(expect_zero(buf, 69, 3))?;
+
+        //A unique random number assigned to this partition, to keep things organized.
+        //0x48
+        let volume_serial_number = u64_at(buf, 72);
+
+        //Supposedly a checksum, usually zero. Nothing checks it, so it's kept as it is.
+        //0x50
+        let checksum = u32::from_le_bytes([buf[80], buf[81], buf[82], buf[83]]);
+
+        //The code that loads the rest of the operating system.
+        //This is pointed to by the first 3 bytes of this sector.
+        //0x54
+        let mut bootstrap_code = [0u8; BOOTSTRAP_CODE_SIZE];
+        bootstrap_code.copy_from_slice(&buf[84..84 + BOOTSTRAP_CODE_SIZE]);
+
+        //This flag indicates that this is a valid boot sector.
+        //0x01FE
+        let end_of_sector = u16::from_le_bytes([buf[510], buf[511]]);
+        if end_of_sector != 0xAA55 {
+            return Err(NtfsError::BadEndMarker);
+        }
+
+        let pbs = PartitionBootSector {
+            bytes_per_sector,
+            sectors_per_cluster,
+            media_descriptor,
+            sectors_per_track,
+            number_of_heads,
+            hidden_sectors,
+            total_sectors,
+            mft_cluster_number,
+            mft_mirror_cluster_number,
+            file_record_segment,
+            index_buffer,
+            volume_serial_number,
+            checksum,
+            bootstrap_code,
+        };
+        Ok((pbs, v.warnings))
     }
 }
@@ -0,0 +1,224 @@
+//! \$Extend\$Quota tracks the disk space charged to each owner, whether or not quotas are enforced.
+//! Owners are numbered: the "$O" view index maps each owner's SID to its owner id and the "$Q"
+//! view index maps the owner id to its quota entry. Like $O of \$Extend\$ObjId, the data of their
+//! entries follows the key.
+//!
+//! $O entries are keyed on the SID, collated as raw bytes, and their data is the owner id as a u32.
+//! $Q entries are keyed on the owner id, collated as a u32, and their data is the quota entry.
+//!
+//!| Offset | Size | Description                                   |
+//!|--------|------|-----------------------------------------------|
+//!| 0x00   | 4    | Version, 2                                    |
+//!| 0x04   | 4    | Flags                                         |
+//!| 0x08   | 8    | Bytes used                                    |
+//!| 0x10   | 8    | Change time                                   |
+//!| 0x18   | 8    | Warning limit, -1 when unlimited              |
+//!| 0x20   | 8    | Hard limit, -1 when unlimited                 |
+//!| 0x28   | 8    | Time the warning limit was exceeded           |
+//!| 0x30   | V    | SID of the owner, missing for owner id 1      |
+//!
+//! Owner id 1 doesn't belong to an owner, it holds the defaults for new owners
+//! and the flags of the volume's quota tracking.
+//!
+//!<https://flatcap.github.io/linux-ntfs/ntfs/files/quota.html>
+use crate::{
+    bytes::{u16_at, u32_at, u64_at},
+    Ntfs, NtfsError, NtfsTime, Sid,
+};
+use std::{
+    collections::HashMap,
+    io::{Read, Seek},
+};
+
+///The size of the data of a $Q index entry without the SID.
+pub const QUOTA_ENTRY_DATA_SIZE: usize = 0x30;
+
+///The owner id of the $Q entry holding the defaults and flags of the volume.
+pub const QUOTA_DEFAULTS_OWNER_ID: u32 = 1;
+
+///Unknown bits are kept as they are, so the raw value can always be recovered with `bits`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct QuotaFlags(pub u32);
+
+impl QuotaFlags {
+    ///The owner uses the default limits.
+    pub const DEFAULT_LIMITS: QuotaFlags = QuotaFlags(0x0001);
+    pub const LIMIT_REACHED: QuotaFlags = QuotaFlags(0x0002);
+    pub const ID_DELETED: QuotaFlags = QuotaFlags(0x0004);
+    ///Only used by the defaults entry, like the flags below.
+    pub const TRACKING_ENABLED: QuotaFlags = QuotaFlags(0x0010);
+    pub const ENFORCEMENT_ENABLED: QuotaFlags = QuotaFlags(0x0020);
+    pub const TRACKING_REQUESTED: QuotaFlags = QuotaFlags(0x0040);
+    pub const LOG_THRESHOLD: QuotaFlags = QuotaFlags(0x0080);
+    pub const LOG_LIMIT: QuotaFlags = QuotaFlags(0x0100);
+    ///The bytes used need to be recounted.
+    pub const OUT_OF_DATE: QuotaFlags = QuotaFlags(0x0200);
+    pub const CORRUPT: QuotaFlags = QuotaFlags(0x0400);
+    pub const PENDING_DELETES: QuotaFlags = QuotaFlags(0x0800);
+
+    const NAMES: [(QuotaFlags, &'static str); 11] = [
+        (QuotaFlags::DEFAULT_LIMITS, "DEFAULT_LIMITS"),
+        (QuotaFlags::LIMIT_REACHED, "LIMIT_REACHED"),
+        (QuotaFlags::ID_DELETED, "ID_DELETED"),
+        (QuotaFlags::TRACKING_ENABLED, "TRACKING_ENABLED"),
+        (QuotaFlags::ENFORCEMENT_ENABLED, "ENFORCEMENT_ENABLED"),
+        (QuotaFlags::TRACKING_REQUESTED, "TRACKING_REQUESTED"),
+        (QuotaFlags::LOG_THRESHOLD, "LOG_THRESHOLD"),
+        (QuotaFlags::LOG_LIMIT, "LOG_LIMIT"),
+        (QuotaFlags::OUT_OF_DATE, "OUT_OF_DATE"),
+        (QuotaFlags::CORRUPT, "CORRUPT"),
+        (QuotaFlags::PENDING_DELETES, "PENDING_DELETES"),
+    ];
+
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    pub fn contains(&self, other: QuotaFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    ///The names of the flags that are set, e.g. ["DEFAULT_LIMITS"]. Unknown bits are left out.
+    pub fn names(&self) -> Vec<&'static str> {
+        Self::NAMES
+            .iter()
+            .filter(|(flag, _)| self.contains(*flag))
+            .map(|(_, name)| *name)
+            .collect()
+    }
+}
+
+///An entry of the $O index in \$Extend\$Quota.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QuotaOwner {
+    pub sid: Sid,
+    pub owner_id: u32,
+}
+
+impl QuotaOwner {
+    ///Parses an $O index entry, starting at its header.
+    pub fn parse(entry: &[u8]) -> Result<QuotaOwner, NtfsError> {
+        let (key, data) = key_and_data(entry, 4)?;
+        let (sid, _) = Sid::parse(key)?;
+        Ok(QuotaOwner {
+            sid,
+            owner_id: u32_at(data, 0x00),
+        })
+    }
+}
+
+///An entry of the $Q index in \$Extend\$Quota.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct QuotaEntry {
+    pub owner_id: u32,
+    pub version: u32,
+    pub flags: QuotaFlags,
+    ///The size of the files owned by the owner, charged whether or not quotas are enforced.
+    pub bytes_used: u64,
+    pub change_time: NtfsTime,
+    ///`u64::MAX` when there's no limit.
+    pub warning_limit: u64,
+    ///`u64::MAX` when there's no limit.
+    pub hard_limit: u64,
+    pub exceeded_time: NtfsTime,
+    ///`None` for the defaults entry.
+    pub sid: Option<Sid>,
+}
+
+impl QuotaEntry {
+    ///Parses a $Q index entry, starting at its header.
+    pub fn parse(entry: &[u8]) -> Result<QuotaEntry, NtfsError> {
+        let (key, data) = key_and_data(entry, QUOTA_ENTRY_DATA_SIZE)?;
+        if key.len() != 4 {
+            return Err(NtfsError::Corrupt("$Q index key is not an owner id"));
+        }
+        let sid = match &data[QUOTA_ENTRY_DATA_SIZE..] {
+            [] => None,
+            sid => Some(Sid::parse(sid)?.0),
+        };
+        Ok(QuotaEntry {
+            owner_id: u32_at(key, 0x00),
+            version: u32_at(data, 0x00),
+            flags: QuotaFlags(u32_at(data, 0x04)),
+            bytes_used: u64_at(data, 0x08),
+            change_time: NtfsTime(u64_at(data, 0x10)),
+            warning_limit: u64_at(data, 0x18),
+            hard_limit: u64_at(data, 0x20),
+            exceeded_time: NtfsTime(u64_at(data, 0x28)),
+            sid,
+        })
+    }
+
+    ///True for the entry holding the defaults and flags of the volume rather than an owner.
+    pub fn is_defaults(&self) -> bool {
+        self.owner_id == QUOTA_DEFAULTS_OWNER_ID
+    }
+}
+
+///The key and data of a view index entry, checking the data is at least `min_data` bytes.
+fn key_and_data(entry: &[u8], min_data: usize) -> Result<(&[u8], &[u8]), NtfsError> {
+    if entry.len() < 0x10 {
+        return Err(NtfsError::BufferTooSmall {
+            needed: 0x10,
+            available: entry.len(),
+        });
+    }
+    let data_offset = u16_at(entry, 0x00) as usize;
+    let data_length = u16_at(entry, 0x02) as usize;
+    let key_length = u16_at(entry, 0x0A) as usize;
+    let key = entry
+        .get(0x10..0x10 + key_length)
+        .ok_or(NtfsError::Corrupt("quota index key runs past the entry"))?;
+    let data = entry
+        .get(data_offset..data_offset + data_length)
+        .filter(|data| data.len() >= min_data)
+        .ok_or(NtfsError::Corrupt(
+            "quota index entry data runs past the entry",
+        ))?;
+    Ok((key, data))
+}
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///The owners in the $O index of \$Extend\$Quota, sorted by SID.
+    ///Volumes without \$Extend\$Quota are `NotFound`.
+    pub fn quota_owners(&mut self) -> Result<Vec<QuotaOwner>, NtfsError> {
+        let (_, record) = self.resolve_path("\\$Extend\\$Quota")?;
+        self.view_index_entries(&record, "$O")?
+            .iter()
+            .map(|entry| QuotaOwner::parse(entry))
+            .collect()
+    }
+
+    ///The entries in the $Q index of \$Extend\$Quota, sorted by owner id, including the defaults entry.
+    ///Volumes without \$Extend\$Quota are `NotFound`.
+    pub fn quota_entries(&mut self) -> Result<Vec<QuotaEntry>, NtfsError> {
+        let (_, record) = self.resolve_path("\\$Extend\\$Quota")?;
+        self.view_index_entries(&record, "$Q")?
+            .iter()
+            .map(|entry| QuotaEntry::parse(entry))
+            .collect()
+    }
+
+    ///The quota entry of every owner along with its SID, sorted by owner id.
+    ///Entries without a SID of their own take it from the $O index, and
+    ///the defaults entry and entries whose owner is unknown are left out.
+    pub fn quotas(&mut self) -> Result<Vec<(Sid, QuotaEntry)>, NtfsError> {
+        let owners: HashMap<u32, Sid> = self
+            .quota_owners()?
+            .into_iter()
+            .map(|owner| (owner.owner_id, owner.sid))
+            .collect();
+        Ok(self
+            .quota_entries()?
+            .into_iter()
+            .filter(|entry| !entry.is_defaults())
+            .filter_map(|entry| {
+                let sid = entry
+                    .sid
+                    .clone()
+                    .or_else(|| owners.get(&entry.owner_id).cloned())?;
+                Some((sid, entry))
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,110 @@
+//! A least recently used cache of parsed FILE records, so resolving paths that share directories
+//! doesn't read and parse the same records again. The entries form a doubly linked list through
+//! their indices, most recently used first, so lookups and evictions don't scan the cache.
+use crate::FileRecord;
+use std::collections::HashMap;
+
+///The number of records cached by a new `Ntfs` handle.
+pub const DEFAULT_CACHE_CAPACITY: usize = 1024;
+
+struct Entry {
+    record_number: u64,
+    ///The parsed record, which keeps the bytes it was parsed from.
+    record: FileRecord,
+    prev: Option<usize>,
+    next: Option<usize>,
+}
+
+pub(crate) struct RecordCache {
+    capacity: usize,
+    index: HashMap<u64, usize>,
+    entries: Vec<Entry>,
+    ///The most recently used entry.
+    head: Option<usize>,
+    ///The least recently used entry, evicted first.
+    tail: Option<usize>,
+    hits: u64,
+    misses: u64,
+}
+
+impl RecordCache {
+    pub(crate) fn new(capacity: usize) -> RecordCache {
+        RecordCache {
+            capacity,
+            index: HashMap::new(),
+            entries: Vec::new(),
+            head: None,
+            tail: None,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub(crate) fn stats(&self) -> (u64, u64) {
+        (self.hits, self.misses)
+    }
+
+    ///The cached record `record_number`, counting a hit or a miss.
+    pub(crate) fn get(&mut self, record_number: u64) -> Option<&FileRecord> {
+        let Some(&slot) = self.index.get(&record_number) else {
+            self.misses += 1;
+            return None;
+        };
+        self.hits += 1;
+        self.unlink(slot);
+        self.push_front(slot);
+        Some(&self.entries[slot].record)
+    }
+
+    ///Caches `record`, evicting the least recently used record if the cache is full.
+    pub(crate) fn insert(&mut self, record_number: u64, record: FileRecord) {
+        if self.capacity == 0 {
+            return;
+        }
+        let slot = if let Some(&slot) = self.index.get(&record_number) {
+            self.unlink(slot);
+            self.entries[slot].record = record;
+            slot
+        } else if self.entries.len() < self.capacity {
+            self.entries.push(Entry {
+                record_number,
+                record,
+                prev: None,
+                next: None,
+            });
+            self.entries.len() - 1
+        } else {
+            let slot = self.tail.expect("a full cache has a tail");
+            self.unlink(slot);
+            let entry = &mut self.entries[slot];
+            self.index.remove(&entry.record_number);
+            entry.record_number = record_number;
+            entry.record = record;
+            slot
+        };
+        self.index.insert(record_number, slot);
+        self.push_front(slot);
+    }
+
+    fn unlink(&mut self, slot: usize) {
+        let (prev, next) = (self.entries[slot].prev, self.entries[slot].next);
+        match prev {
+            Some(prev) => self.entries[prev].next = next,
+            None => self.head = next,
+        }
+        match next {
+            Some(next) => self.entries[next].prev = prev,
+            None => self.tail = prev,
+        }
+    }
+
+    fn push_front(&mut self, slot: usize) {
+        self.entries[slot].prev = None;
+        self.entries[slot].next = self.head;
+        match self.head {
+            Some(head) => self.entries[head].prev = Some(slot),
+            None => self.tail = Some(slot),
+        }
+        self.head = Some(slot);
+    }
+}
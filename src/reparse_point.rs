@@ -0,0 +1,398 @@
+//! $REPARSE_POINT (0xC0) marks a file or directory whose access is redirected, e.g. a symbolic link,
+//! a junction (mount point) or a file offloaded by a storage driver. The tag says who handles it.
+//! Tags with the high bit set are Microsoft's and have no GUID.
+//!
+//!| Offset | Size | Description                                   |
+//!|--------|------|-----------------------------------------------|
+//!| 0x00   | 4    | Reparse tag                                   |
+//!| 0x04   | 2    | Size of the reparse data                      |
+//!| 0x06   | 2    | Reserved                                      |
+//!| 0x08   | 16   | GUID, only for non-Microsoft tags             |
+//!| 0x08   | V    | Reparse data, at 0x18 for non-Microsoft tags  |
+//!
+//! Symbolic links and junctions start their reparse data with the location of two UTF-16 names in
+//! a path buffer: the substitute name the target is opened with, e.g. `\??\C:\Users`, and the
+//! print name shown to the user, e.g. `C:\Users`. Symbolic links have an extra 4 byte flags field.
+//!
+//!| Offset | Size | Description                                   |
+//!|--------|------|-----------------------------------------------|
+//!| 0x00   | 2    | Substitute name offset in the path buffer     |
+//!| 0x02   | 2    | Substitute name length in bytes               |
+//!| 0x04   | 2    | Print name offset in the path buffer          |
+//!| 0x06   | 2    | Print name length in bytes                    |
+//!| 0x08   | 4    | Flags, symbolic links only. 1 if relative     |
+//!| 0x08   | V    | Path buffer, at 0x0C for symbolic links       |
+//!
+//! The "$R" view index of \$Extend\$Reparse lists every reparse point on the volume.
+//! Its entries have no data, the key is the tag and the file, collated as u32s.
+//!
+//!| Offset | Size | Description                                   |
+//!|--------|------|-----------------------------------------------|
+//!| 0x00   | 2    | Offset to the data                            |
+//!| 0x02   | 2    | Size of the data, zero                        |
+//!| 0x04   | 4    | Reserved                                      |
+//!| 0x08   | 2    | Size of the index entry                       |
+//!| 0x0A   | 2    | Size of the key                               |
+//!| 0x0C   | 2    | Flags                                         |
+//!| 0x0E   | 2    | Reserved                                      |
+//!| 0x10   | 4    | Reparse tag                                   |
+//!| 0x14   | 8    | File reference                                |
+//!
+//!<https://flatcap.github.io/linux-ntfs/ntfs/attributes/reparse_point.html>
+//!
+//!<https://flatcap.github.io/linux-ntfs/ntfs/files/reparse.html>
+use crate::{
+    bytes::{u16_at, u32_at, u64_at},
+    FileReference, Ntfs, NtfsError,
+};
+use std::{
+    io::{Read, Seek},
+    path::PathBuf,
+};
+
+///The size of the header before the reparse data of a Microsoft reparse point.
+pub const REPARSE_POINT_HEADER_SIZE: usize = 0x08;
+
+///The size of the header of a non-Microsoft reparse point, which includes a GUID.
+pub const REPARSE_POINT_GUID_HEADER_SIZE: usize = 0x18;
+
+///The size of the key of an entry in the $R index.
+pub const REPARSE_INDEX_KEY_SIZE: usize = 0x0C;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReparsePoint {
+    pub tag: u32,
+    ///The target to open, only for symbolic links and junctions.
+    pub substitute_name: Option<String>,
+    ///The target to show to the user, only for symbolic links and junctions.
+    pub print_name: Option<String>,
+    ///Set for symbolic links whose target is relative to the link's directory.
+    pub relative: bool,
+    ///The reparse data after the header, which is opaque for other tags.
+    pub data: Vec<u8>,
+}
+
+impl ReparsePoint {
+    ///IO_REPARSE_TAG_MOUNT_POINT, used by junctions and volume mount points.
+    pub const MOUNT_POINT: u32 = 0xA000_0003;
+    ///IO_REPARSE_TAG_SYMLINK.
+    pub const SYMLINK: u32 = 0xA000_000C;
+    ///IO_REPARSE_TAG_WOF, a file compressed by the Windows Overlay Filter, e.g. with `compact /exe`.
+    pub const WOF: u32 = 0x8000_0017;
+    ///IO_REPARSE_TAG_APPEXECLINK, the app execution aliases of Store apps in `%LOCALAPPDATA%\Microsoft\WindowsApps`.
+    pub const APP_EXEC_LINK: u32 = 0x8000_001B;
+    ///IO_REPARSE_TAG_CLOUD, a Cloud Files placeholder like the ones OneDrive creates.
+    ///IO_REPARSE_TAG_CLOUD_1 to IO_REPARSE_TAG_CLOUD_F keep a provider number in bits 12 to 15.
+    pub const CLOUD: u32 = 0x9000_001A;
+    ///The bits of a tag that are the same for every Cloud Files tag.
+    pub const CLOUD_MASK: u32 = 0xFFFF_0FFF;
+    pub const MICROSOFT: u32 = 0x8000_0000;
+    pub const SYMLINK_FLAG_RELATIVE: u32 = 0x0000_0001;
+
+    pub fn parse(value: &[u8]) -> Result<ReparsePoint, NtfsError> {
+        if value.len() < REPARSE_POINT_HEADER_SIZE {
+            return Err(NtfsError::BufferTooSmall {
+                needed: REPARSE_POINT_HEADER_SIZE,
+                available: value.len(),
+            });
+        }
+        let tag = u32_at(value, 0x00);
+        let header_size = if tag & Self::MICROSOFT != 0 {
+            REPARSE_POINT_HEADER_SIZE
+        } else {
+            REPARSE_POINT_GUID_HEADER_SIZE
+        };
+        let needed = header_size + u16_at(value, 0x04) as usize;
+        if value.len() < needed {
+            return Err(NtfsError::BufferTooSmall {
+                needed,
+                available: value.len(),
+            });
+        }
+        let data = &value[header_size..needed];
+
+        let mut reparse_point = ReparsePoint {
+            tag,
+            substitute_name: None,
+            print_name: None,
+            relative: false,
+            data: data.to_vec(),
+        };
+        let path_buffer = match tag {
+            Self::MOUNT_POINT => 0x08,
+            Self::SYMLINK => 0x0C,
+            _ => return Ok(reparse_point),
+        };
+        if data.len() < path_buffer {
+            return Err(NtfsError::BufferTooSmall {
+                needed: header_size + path_buffer,
+                available: value.len(),
+            });
+        }
+        let buffer = &data[path_buffer..];
+        reparse_point.substitute_name =
+            Some(name_at(buffer, u16_at(data, 0x00), u16_at(data, 0x02))?);
+        reparse_point.print_name = Some(name_at(buffer, u16_at(data, 0x04), u16_at(data, 0x06))?);
+        reparse_point.relative =
+            tag == Self::SYMLINK && u32_at(data, 0x08) & Self::SYMLINK_FLAG_RELATIVE != 0;
+        Ok(reparse_point)
+    }
+
+    pub fn is_microsoft(&self) -> bool {
+        self.tag & Self::MICROSOFT != 0
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        self.tag == Self::SYMLINK
+    }
+
+    pub fn is_mount_point(&self) -> bool {
+        self.tag == Self::MOUNT_POINT
+    }
+
+    pub fn is_wof(&self) -> bool {
+        self.tag == Self::WOF
+    }
+
+    pub fn is_app_exec_link(&self) -> bool {
+        self.tag == Self::APP_EXEC_LINK
+    }
+
+    pub fn is_cloud_files(&self) -> bool {
+        self.tag & Self::CLOUD_MASK == Self::CLOUD
+    }
+
+    ///Where a symbolic link or junction points: the print name,
+    ///or the substitute name if the print name is empty, as older tools leave it.
+    pub fn target(&self) -> Option<&str> {
+        match self.print_name.as_deref() {
+            Some("") | None => self.substitute_name.as_deref(),
+            print_name => print_name,
+        }
+    }
+}
+
+///The payload of a reparse point, typed by its tag. Created from a [`ReparsePoint`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ReparseData {
+    SymbolicLink {
+        substitute_name: String,
+        print_name: String,
+        ///The target is relative to the link's directory.
+        relative: bool,
+    },
+    ///A junction, or a volume mounted on a directory.
+    MountPoint {
+        substitute_name: String,
+        print_name: String,
+    },
+    ///Compressed by the Windows Overlay Filter. The compressed data is in the `WofCompressedData` stream.
+    Wof,
+    AppExecLink,
+    ///A Cloud Files placeholder, whose content may only be in the cloud.
+    CloudFiles {
+        tag: u32,
+    },
+    Other {
+        tag: u32,
+        data: Vec<u8>,
+    },
+}
+
+impl ReparseData {
+    ///Where a symbolic link or junction points, see [`ReparsePoint::target`].
+    pub fn target(&self) -> Option<&str> {
+        match self {
+            ReparseData::SymbolicLink {
+                substitute_name,
+                print_name,
+                ..
+            }
+            | ReparseData::MountPoint {
+                substitute_name,
+                print_name,
+            } if print_name.is_empty() => Some(substitute_name),
+            ReparseData::SymbolicLink { print_name, .. }
+            | ReparseData::MountPoint { print_name, .. } => Some(print_name),
+            _ => None,
+        }
+    }
+
+    ///Returns true for symbolic links and junctions, which point somewhere else.
+    pub fn is_link(&self) -> bool {
+        matches!(
+            self,
+            ReparseData::SymbolicLink { .. } | ReparseData::MountPoint { .. }
+        )
+    }
+}
+
+impl From<ReparsePoint> for ReparseData {
+    fn from(reparse_point: ReparsePoint) -> ReparseData {
+        match reparse_point {
+            ReparsePoint {
+                tag: ReparsePoint::SYMLINK,
+                substitute_name: Some(substitute_name),
+                print_name: Some(print_name),
+                relative,
+                ..
+            } => ReparseData::SymbolicLink {
+                substitute_name,
+                print_name,
+                relative,
+            },
+            ReparsePoint {
+                tag: ReparsePoint::MOUNT_POINT,
+                substitute_name: Some(substitute_name),
+                print_name: Some(print_name),
+                ..
+            } => ReparseData::MountPoint {
+                substitute_name,
+                print_name,
+            },
+            reparse_point if reparse_point.is_wof() => ReparseData::Wof,
+            reparse_point if reparse_point.is_app_exec_link() => ReparseData::AppExecLink,
+            reparse_point if reparse_point.is_cloud_files() => ReparseData::CloudFiles {
+                tag: reparse_point.tag,
+            },
+            ReparsePoint { tag, data, .. } => ReparseData::Other { tag, data },
+        }
+    }
+}
+
+///A reparse tag, sorted into the same kinds as [`ReparseData`].
+///Used where only the tag is known, like the entries of the $R index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReparseTag {
+    SymbolicLink,
+    MountPoint,
+    Wof,
+    AppExecLink,
+    ///Any of the Cloud Files tags, which differ in their provider number.
+    CloudFiles(u32),
+    Other(u32),
+}
+
+impl ReparseTag {
+    ///The raw tag.
+    pub fn bits(&self) -> u32 {
+        match self {
+            ReparseTag::SymbolicLink => ReparsePoint::SYMLINK,
+            ReparseTag::MountPoint => ReparsePoint::MOUNT_POINT,
+            ReparseTag::Wof => ReparsePoint::WOF,
+            ReparseTag::AppExecLink => ReparsePoint::APP_EXEC_LINK,
+            ReparseTag::CloudFiles(tag) | ReparseTag::Other(tag) => *tag,
+        }
+    }
+
+    pub fn is_microsoft(&self) -> bool {
+        self.bits() & ReparsePoint::MICROSOFT != 0
+    }
+
+    ///Returns true for symbolic links and junctions, which point somewhere else.
+    pub fn is_link(&self) -> bool {
+        matches!(self, ReparseTag::SymbolicLink | ReparseTag::MountPoint)
+    }
+}
+
+impl From<u32> for ReparseTag {
+    fn from(tag: u32) -> ReparseTag {
+        match tag {
+            ReparsePoint::SYMLINK => ReparseTag::SymbolicLink,
+            ReparsePoint::MOUNT_POINT => ReparseTag::MountPoint,
+            ReparsePoint::WOF => ReparseTag::Wof,
+            ReparsePoint::APP_EXEC_LINK => ReparseTag::AppExecLink,
+            tag if tag & ReparsePoint::CLOUD_MASK == ReparsePoint::CLOUD => {
+                ReparseTag::CloudFiles(tag)
+            }
+            tag => ReparseTag::Other(tag),
+        }
+    }
+}
+
+///An entry of the $R index in \$Extend\$Reparse.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ReparseIndexEntry {
+    pub tag: ReparseTag,
+    ///The file with the reparse point.
+    pub file_reference: FileReference,
+    ///The path of the file relative to the root directory, only filled in by
+    ///[`Ntfs::reparse_points_with_options`] with `resolve_paths` set. `None` if the file
+    ///has no path, or if its record has been reused since it was indexed.
+    pub path: Option<PathBuf>,
+}
+
+impl ReparseIndexEntry {
+    ///Parses an $R index entry, starting at its header.
+    pub fn parse(entry: &[u8]) -> Result<ReparseIndexEntry, NtfsError> {
+        let needed = 0x10 + REPARSE_INDEX_KEY_SIZE;
+        if entry.len() < needed {
+            return Err(NtfsError::BufferTooSmall {
+                needed,
+                available: entry.len(),
+            });
+        }
+        if u16_at(entry, 0x0A) as usize != REPARSE_INDEX_KEY_SIZE {
+            return Err(NtfsError::Corrupt(
+                "$R index key is not a tag and file reference",
+            ));
+        }
+        Ok(ReparseIndexEntry {
+            tag: ReparseTag::from(u32_at(entry, 0x10)),
+            file_reference: FileReference::from_raw(u64_at(entry, 0x14)),
+            path: None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct ReparsePointOptions {
+    ///Look up the path of each file, which reads its FILE record and those of its parent directories.
+    pub resolve_paths: bool,
+}
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///Lists every reparse point on the volume from the $R index of \$Extend\$Reparse, sorted by tag,
+    ///without reading the FILE records. Volumes without \$Extend\$Reparse are `NotFound`.
+    pub fn reparse_points(&mut self) -> Result<Vec<ReparseIndexEntry>, NtfsError> {
+        self.reparse_points_with_options(ReparsePointOptions::default())
+    }
+
+    ///Same as [`Ntfs::reparse_points`], but can also look up the path of each file.
+    pub fn reparse_points_with_options(
+        &mut self,
+        options: ReparsePointOptions,
+    ) -> Result<Vec<ReparseIndexEntry>, NtfsError> {
+        let (_, record) = self.resolve_path("\\$Extend\\$Reparse")?;
+        let mut entries = self
+            .view_index_entries(&record, "$R")?
+            .iter()
+            .map(|entry| ReparseIndexEntry::parse(entry))
+            .collect::<Result<Vec<_>, _>>()?;
+        if options.resolve_paths {
+            for entry in &mut entries {
+                let reference = entry.file_reference;
+                let record = self.cached_file_record(reference.record_number)?;
+                if record.is_in_use() && record.sequence_number == reference.sequence_number {
+                    entry.path = self.path_of(reference.record_number)?;
+                }
+            }
+        }
+        Ok(entries)
+    }
+}
+
+///Reads the UTF-16 name at `offset` in the path buffer.
+fn name_at(buffer: &[u8], offset: u16, length: u16) -> Result<String, NtfsError> {
+    let (offset, length) = (offset as usize, length as usize);
+    let bytes = buffer
+        .get(offset..offset + length)
+        .ok_or(NtfsError::Corrupt(
+            "reparse point name runs past its path buffer",
+        ))?;
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+        .collect();
+    Ok(String::from_utf16_lossy(&units))
+}
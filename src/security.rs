@@ -0,0 +1,414 @@
+//! NTFS 3.x keeps every distinct security descriptor once, in the $SDS stream of $Secure (FILE record 9).
+//! $STANDARD_INFORMATION only holds the security id, which the $SII index maps to the descriptor's
+//! place in $SDS. $SDS is written in 256K blocks, each followed by a mirror copy of itself,
+//! and every entry starts on a 16 byte boundary with a header in front of the descriptor.
+//!
+//!| Offset | Size | Description                                   |
+//!|--------|------|-----------------------------------------------|
+//!| 0x00   | 4    | Hash of the security descriptor               |
+//!| 0x04   | 4    | Security id                                   |
+//!| 0x08   | 8    | Offset of this entry in $SDS                  |
+//!| 0x10   | 4    | Size of this entry, including the header      |
+//!| 0x14   | V    | Self-relative security descriptor             |
+//!
+//! The $SII entries are keyed on the security id, and their data is a copy of the $SDS entry header.
+//!
+//! The descriptor itself is the SECURITY_DESCRIPTOR_RELATIVE used throughout Windows, with offsets
+//! to the owner and group SIDs and to the system (audit) and discretionary (access) ACLs.
+//!
+//!<https://flatcap.github.io/linux-ntfs/ntfs/files/secure.html>
+//!
+//!<https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-dtyp/2918391b-75b9-4eeb-83f0-7fdc04a5c6c9>
+use crate::{
+    bytes::{guid_at, u16_at, u32_at, u64_at},
+    AttributeType, DataStream, FileRecord, Guid, Ntfs, NtfsError, Sid, SystemFile,
+};
+use std::io::{Read, Seek, SeekFrom};
+
+///The size of the header in front of each security descriptor in $SDS.
+pub const SDS_ENTRY_HEADER_SIZE: usize = 0x14;
+
+///The size of each block of $SDS. Every block is followed by a mirror copy of itself.
+pub const SDS_BLOCK_SIZE: u64 = 0x40000;
+
+///An access control entry, granting, denying or auditing access for a SID.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Ace {
+    pub ace_type: u8,
+    pub flags: u8,
+    pub access_mask: u32,
+    pub sid: Sid,
+    ///The kind of object or property the entry applies to, only for object ACEs.
+    pub object_type: Option<Guid>,
+    ///The kind of child object that inherits the entry, only for object ACEs.
+    pub inherited_object_type: Option<Guid>,
+}
+
+impl Ace {
+    pub const ACCESS_ALLOWED: u8 = 0x00;
+    pub const ACCESS_DENIED: u8 = 0x01;
+    pub const SYSTEM_AUDIT: u8 = 0x02;
+    pub const SYSTEM_ALARM: u8 = 0x03;
+    pub const ACCESS_ALLOWED_OBJECT: u8 = 0x05;
+    pub const ACCESS_DENIED_OBJECT: u8 = 0x06;
+    pub const SYSTEM_AUDIT_OBJECT: u8 = 0x07;
+    pub const SYSTEM_ALARM_OBJECT: u8 = 0x08;
+    pub const ACCESS_ALLOWED_CALLBACK: u8 = 0x09;
+    pub const ACCESS_DENIED_CALLBACK: u8 = 0x0A;
+    pub const ACCESS_ALLOWED_CALLBACK_OBJECT: u8 = 0x0B;
+    pub const ACCESS_DENIED_CALLBACK_OBJECT: u8 = 0x0C;
+    pub const SYSTEM_AUDIT_CALLBACK: u8 = 0x0D;
+    pub const SYSTEM_AUDIT_CALLBACK_OBJECT: u8 = 0x0F;
+    pub const SYSTEM_ALARM_CALLBACK_OBJECT: u8 = 0x10;
+    pub const SYSTEM_MANDATORY_LABEL: u8 = 0x11;
+
+    ///Inherited by files in the directory.
+    pub const OBJECT_INHERIT: u8 = 0x01;
+    ///Inherited by subdirectories.
+    pub const CONTAINER_INHERIT: u8 = 0x02;
+    pub const NO_PROPAGATE_INHERIT: u8 = 0x04;
+    ///Only applies to the children, not the directory itself.
+    pub const INHERIT_ONLY: u8 = 0x08;
+    ///Inherited from the parent directory rather than set on the file.
+    pub const INHERITED: u8 = 0x10;
+
+    pub const FILE_READ_DATA: u32 = 0x0000_0001;
+    pub const FILE_WRITE_DATA: u32 = 0x0000_0002;
+    pub const FILE_APPEND_DATA: u32 = 0x0000_0004;
+    pub const FILE_EXECUTE: u32 = 0x0000_0020;
+    pub const DELETE: u32 = 0x0001_0000;
+    pub const WRITE_DAC: u32 = 0x0004_0000;
+    pub const WRITE_OWNER: u32 = 0x0008_0000;
+    ///FILE_GENERIC_READ, what "Read" means on the Security tab.
+    pub const FILE_GENERIC_READ: u32 = 0x0012_0089;
+    ///FILE_ALL_ACCESS, "Full control".
+    pub const FILE_ALL_ACCESS: u32 = 0x001F_01FF;
+    pub const GENERIC_ALL: u32 = 0x1000_0000;
+    pub const GENERIC_EXECUTE: u32 = 0x2000_0000;
+    pub const GENERIC_WRITE: u32 = 0x4000_0000;
+    pub const GENERIC_READ: u32 = 0x8000_0000;
+
+    ///Parses an ACE, returning it along with its size.
+    pub fn parse(bytes: &[u8]) -> Result<(Ace, usize), NtfsError> {
+        if bytes.len() < 8 {
+            return Err(NtfsError::BufferTooSmall {
+                needed: 8,
+                available: bytes.len(),
+            });
+        }
+        let ace_type = bytes[0];
+        let size = u16_at(bytes, 0x02) as usize;
+        if size < 8 || size > bytes.len() {
+            return Err(NtfsError::Corrupt("ACE runs past its ACL"));
+        }
+        let ace = &bytes[..size];
+
+        let mut offset = 8;
+        let mut object_type = None;
+        let mut inherited_object_type = None;
+        if Self::is_object_type(ace_type) {
+            if ace.len() < 12 {
+                return Err(NtfsError::Corrupt("object ACE has no flags"));
+            }
+            let object_flags = u32_at(ace, 0x08);
+            offset = 12;
+            for (flag, guid) in [(1, &mut object_type), (2, &mut inherited_object_type)] {
+                if object_flags & flag != 0 {
+                    if ace.len() < offset + 16 {
+                        return Err(NtfsError::Corrupt("object ACE GUID runs past the ACE"));
+                    }
+                    *guid = Some(guid_at(ace, offset));
+                    offset += 16;
+                }
+            }
+        }
+        let (sid, _) = Sid::parse(&ace[offset..])?;
+        Ok((
+            Ace {
+                ace_type,
+                flags: bytes[1],
+                access_mask: u32_at(ace, 0x04),
+                sid,
+                object_type,
+                inherited_object_type,
+            },
+            size,
+        ))
+    }
+
+    fn is_object_type(ace_type: u8) -> bool {
+        matches!(
+            ace_type,
+            Self::ACCESS_ALLOWED_OBJECT
+                | Self::ACCESS_DENIED_OBJECT
+                | Self::SYSTEM_AUDIT_OBJECT
+                | Self::SYSTEM_ALARM_OBJECT
+                | Self::ACCESS_ALLOWED_CALLBACK_OBJECT
+                | Self::ACCESS_DENIED_CALLBACK_OBJECT
+                | Self::SYSTEM_AUDIT_CALLBACK_OBJECT
+                | Self::SYSTEM_ALARM_CALLBACK_OBJECT
+        )
+    }
+
+    pub fn is_access_allowed(&self) -> bool {
+        matches!(
+            self.ace_type,
+            Self::ACCESS_ALLOWED
+                | Self::ACCESS_ALLOWED_OBJECT
+                | Self::ACCESS_ALLOWED_CALLBACK
+                | Self::ACCESS_ALLOWED_CALLBACK_OBJECT
+        )
+    }
+
+    pub fn is_access_denied(&self) -> bool {
+        matches!(
+            self.ace_type,
+            Self::ACCESS_DENIED
+                | Self::ACCESS_DENIED_OBJECT
+                | Self::ACCESS_DENIED_CALLBACK
+                | Self::ACCESS_DENIED_CALLBACK_OBJECT
+        )
+    }
+
+    pub fn is_inherited(&self) -> bool {
+        self.flags & Self::INHERITED != 0
+    }
+
+    ///Returns true if the entry covers every right in `access_mask`, counting the generic rights
+    ///GENERIC_ALL and GENERIC_READ as the file rights they stand for.
+    pub fn covers(&self, access_mask: u32) -> bool {
+        let mut mask = self.access_mask;
+        if mask & Self::GENERIC_ALL != 0 {
+            mask |= Self::FILE_ALL_ACCESS;
+        }
+        if mask & Self::GENERIC_READ != 0 {
+            mask |= Self::FILE_GENERIC_READ;
+        }
+        mask & access_mask == access_mask
+    }
+}
+
+///An access control list, either the DACL that grants and denies access or the SACL that audits it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Acl {
+    pub revision: u8,
+    pub aces: Vec<Ace>,
+}
+
+impl Acl {
+    pub fn parse(bytes: &[u8]) -> Result<Acl, NtfsError> {
+        if bytes.len() < 8 {
+            return Err(NtfsError::BufferTooSmall {
+                needed: 8,
+                available: bytes.len(),
+            });
+        }
+        let size = u16_at(bytes, 0x02) as usize;
+        if size < 8 || size > bytes.len() {
+            return Err(NtfsError::Corrupt("ACL runs past the security descriptor"));
+        }
+        let mut aces = Vec::new();
+        let mut offset = 8;
+        for _ in 0..u16_at(bytes, 0x04) {
+            let (ace, len) = Ace::parse(&bytes[offset..size])?;
+            aces.push(ace);
+            offset += len;
+        }
+        Ok(Acl {
+            revision: bytes[0],
+            aces,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SecurityDescriptor {
+    pub revision: u8,
+    pub control: u16,
+    pub owner: Option<Sid>,
+    pub group: Option<Sid>,
+    ///The audit entries, `None` if the descriptor has no SACL.
+    pub sacl: Option<Acl>,
+    ///The access entries. `None` means no DACL, which grants everyone full access,
+    ///while an empty DACL grants nobody anything.
+    pub dacl: Option<Acl>,
+}
+
+impl SecurityDescriptor {
+    pub const DACL_PRESENT: u16 = 0x0004;
+    pub const SACL_PRESENT: u16 = 0x0010;
+    ///The DACL is protected from inheriting the parent's entries.
+    pub const DACL_PROTECTED: u16 = 0x1000;
+    pub const SELF_RELATIVE: u16 = 0x8000;
+
+    ///Parses a self-relative security descriptor, the only kind stored on disk.
+    pub fn parse(bytes: &[u8]) -> Result<SecurityDescriptor, NtfsError> {
+        if bytes.len() < 0x14 {
+            return Err(NtfsError::BufferTooSmall {
+                needed: 0x14,
+                available: bytes.len(),
+            });
+        }
+        let control = u16_at(bytes, 0x02);
+        if control & Self::SELF_RELATIVE == 0 {
+            return Err(NtfsError::Unsupported(
+                "security descriptor is not self-relative",
+            ));
+        }
+        let part = |offset: usize| -> Result<Option<&[u8]>, NtfsError> {
+            match u32_at(bytes, offset) as usize {
+                0 => Ok(None),
+                start => bytes.get(start..).map(Some).ok_or(NtfsError::Corrupt(
+                    "security descriptor offset is past its end",
+                )),
+            }
+        };
+        let sid = |offset| -> Result<Option<Sid>, NtfsError> {
+            part(offset)?
+                .map(|bytes| Sid::parse(bytes).map(|(sid, _)| sid))
+                .transpose()
+        };
+        let acl = |offset, present| -> Result<Option<Acl>, NtfsError> {
+            if control & present == 0 {
+                return Ok(None);
+            }
+            part(offset)?.map(Acl::parse).transpose()
+        };
+        Ok(SecurityDescriptor {
+            revision: bytes[0],
+            control,
+            owner: sid(0x04)?,
+            group: sid(0x08)?,
+            sacl: acl(0x0C, Self::SACL_PRESENT)?,
+            dacl: acl(0x10, Self::DACL_PRESENT)?,
+        })
+    }
+}
+
+///An entry of the $SDS stream.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SdsEntry {
+    pub hash: u32,
+    pub security_id: u32,
+    ///The offset of the entry in $SDS.
+    pub offset: u64,
+    ///The size of the entry, including the header.
+    pub length: u32,
+    pub descriptor: SecurityDescriptor,
+}
+
+impl SdsEntry {
+    ///Parses an $SDS entry, starting at its header.
+    pub fn parse(bytes: &[u8]) -> Result<SdsEntry, NtfsError> {
+        if bytes.len() < SDS_ENTRY_HEADER_SIZE {
+            return Err(NtfsError::BufferTooSmall {
+                needed: SDS_ENTRY_HEADER_SIZE,
+                available: bytes.len(),
+            });
+        }
+        let length = u32_at(bytes, 0x10);
+        let descriptor = bytes
+            .get(SDS_ENTRY_HEADER_SIZE..length as usize)
+            .ok_or(NtfsError::Corrupt("$SDS entry has an invalid length"))?;
+        Ok(SdsEntry {
+            hash: u32_at(bytes, 0x00),
+            security_id: u32_at(bytes, 0x04),
+            offset: u64_at(bytes, 0x08),
+            length,
+            descriptor: SecurityDescriptor::parse(descriptor)?,
+        })
+    }
+}
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///Looks up a security id from $STANDARD_INFORMATION in the $SII index of $Secure
+    ///and reads its descriptor from $SDS. Returns `None` if the id isn't indexed.
+    pub fn security_descriptor_by_id(
+        &mut self,
+        security_id: u32,
+    ) -> Result<Option<SecurityDescriptor>, NtfsError> {
+        let secure = self.file_record(SystemFile::Secure.record_number())?;
+        let entry = self.search_view_index(&secure, "$SII", |entry| {
+            if entry.len() < 0x14 || u16_at(entry, 0x0A) != 4 {
+                return Err(NtfsError::Corrupt("$SII index key is not a security id"));
+            }
+            Ok(security_id.cmp(&u32_at(entry, 0x10)))
+        })?;
+        let Some(entry) = entry else {
+            return Ok(None);
+        };
+        let data_offset = u16_at(&entry, 0x00) as usize;
+        let header = entry
+            .get(data_offset..data_offset + SDS_ENTRY_HEADER_SIZE)
+            .ok_or(NtfsError::Corrupt(
+                "$SII index entry data runs past the entry",
+            ))?;
+
+        let stream = self.sds_stream()?;
+        let mut reader = self.open_stream(&stream)?;
+        //Entries never span a 256K block, so a larger length is corrupt rather than a huge descriptor.
+        let length = u32_at(header, 0x10) as u64;
+        if length < SDS_ENTRY_HEADER_SIZE as u64 || length > SDS_BLOCK_SIZE.min(reader.len()) {
+            return Err(NtfsError::Corrupt(
+                "$SII entry has an invalid $SDS entry length",
+            ));
+        }
+        let mut bytes = vec![0u8; length as usize];
+        reader.seek(SeekFrom::Start(u64_at(header, 0x08)))?;
+        reader.read_exact(&mut bytes)?;
+        let sds_entry = SdsEntry::parse(&bytes)?;
+        if sds_entry.security_id != security_id {
+            return Err(NtfsError::Corrupt("$SII points at the wrong $SDS entry"));
+        }
+        Ok(Some(sds_entry.descriptor))
+    }
+
+    ///The security descriptor of a file. Files with a $SECURITY_DESCRIPTOR attribute, like those
+    ///written by NTFS 1.x, use it whether it's resident or not. Other files are looked up through
+    ///the security id in their $STANDARD_INFORMATION, and `None` if they have neither.
+    pub fn security_descriptor(
+        &mut self,
+        record: &FileRecord,
+    ) -> Result<Option<SecurityDescriptor>, NtfsError> {
+        if let Some(attribute) = record.find_attribute(AttributeType::SecurityDescriptor)? {
+            return SecurityDescriptor::parse(&self.read_attribute(&attribute)?).map(Some);
+        }
+        let Some(security_id) = record
+            .standard_information()?
+            .and_then(|standard_information| standard_information.security_id)
+        else {
+            return Ok(None);
+        };
+        self.security_descriptor_by_id(security_id)
+    }
+
+    ///Reads every entry of $SDS, skipping the mirror copy of each block.
+    pub fn sds_entries(&mut self) -> Result<Vec<SdsEntry>, NtfsError> {
+        let stream = self.sds_stream()?;
+        let sds = self.read_stream(&stream)?;
+        let mut entries = Vec::new();
+        let mut offset = 0u64;
+        while offset + SDS_ENTRY_HEADER_SIZE as u64 <= sds.len() as u64 {
+            let header = &sds[offset as usize..];
+            let length = u32_at(header, 0x10) as u64;
+            //The rest of a block after its last entry is left empty.
+            if length < SDS_ENTRY_HEADER_SIZE as u64 || u64_at(header, 0x08) != offset {
+                offset = (offset / (2 * SDS_BLOCK_SIZE) + 1) * (2 * SDS_BLOCK_SIZE);
+                continue;
+            }
+            entries.push(SdsEntry::parse(header)?);
+            offset = (offset + length).next_multiple_of(16);
+            if offset % (2 * SDS_BLOCK_SIZE) >= SDS_BLOCK_SIZE {
+                offset = offset.next_multiple_of(2 * SDS_BLOCK_SIZE);
+            }
+        }
+        Ok(entries)
+    }
+
+    fn sds_stream(&mut self) -> Result<DataStream, NtfsError> {
+        self.data_streams(SystemFile::Secure.record_number())?
+            .into_iter()
+            .find(|stream| stream.name.as_deref() == Some("$SDS"))
+            .ok_or(NtfsError::Corrupt("$Secure has no $SDS stream"))
+    }
+}
@@ -0,0 +1,186 @@
+//! A security identifier names a user, group or other principal in security descriptors and $Quota.
+//!
+//!| Offset | Size | Description                                   |
+//!|--------|------|-----------------------------------------------|
+//!| 0x00   | 1    | Revision, always 1                            |
+//!| 0x01   | 1    | Number of sub-authorities (N), at most 15     |
+//!| 0x02   | 6    | Identifier authority, big-endian              |
+//!| 0x08   | 4N   | Sub-authorities, little-endian                |
+//!
+//!<https://learn.microsoft.com/en-us/openspecs/windows_protocols/ms-dtyp/f992ad60-0fe4-4b87-9fed-beb478836861>
+//!
+//!<https://learn.microsoft.com/en-us/windows/win32/secauthz/well-known-sids>
+use crate::{bytes::u32_at, NtfsError};
+use std::{fmt, str::FromStr};
+
+///The most sub-authorities a SID can have.
+pub const MAX_SUB_AUTHORITIES: usize = 15;
+
+///A security identifier, e.g. S-1-5-18 for the local system account.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Sid {
+    pub revision: u8,
+    ///Stored as a 48-bit big-endian number, 5 (NT Authority) for most SIDs.
+    pub identifier_authority: u64,
+    pub sub_authorities: Vec<u32>,
+}
+
+impl Sid {
+    ///Parses a SID, returning it along with the number of bytes it takes.
+    pub fn parse(bytes: &[u8]) -> Result<(Sid, usize), NtfsError> {
+        if bytes.len() < 8 {
+            return Err(NtfsError::BufferTooSmall {
+                needed: 8,
+                available: bytes.len(),
+            });
+        }
+        if bytes[1] as usize > MAX_SUB_AUTHORITIES {
+            return Err(NtfsError::Corrupt("SID has more than 15 sub-authorities"));
+        }
+        let len = 8 + bytes[1] as usize * 4;
+        if bytes.len() < len {
+            return Err(NtfsError::BufferTooSmall {
+                needed: len,
+                available: bytes.len(),
+            });
+        }
+        let identifier_authority = bytes[2..8]
+            .iter()
+            .fold(0u64, |authority, byte| authority << 8 | *byte as u64);
+        let sub_authorities = bytes[8..len]
+            .chunks_exact(4)
+            .map(|sub_authority| u32_at(sub_authority, 0))
+            .collect();
+        Ok((
+            Sid {
+                revision: bytes[0],
+                identifier_authority,
+                sub_authorities,
+            },
+            len,
+        ))
+    }
+
+    ///The binary form of the SID, as [`Sid::parse`] reads it.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.revision, self.sub_authorities.len() as u8];
+        bytes.extend_from_slice(&self.identifier_authority.to_be_bytes()[2..]);
+        for sub_authority in &self.sub_authorities {
+            bytes.extend_from_slice(&sub_authority.to_le_bytes());
+        }
+        bytes
+    }
+
+    ///The relative identifier, the last sub-authority, e.g. 500 for the built-in administrator.
+    pub fn rid(&self) -> Option<u32> {
+        self.sub_authorities.last().copied()
+    }
+
+    ///The name Windows shows for a well-known SID, e.g. "Administrators" for S-1-5-32-544.
+    ///Domain accounts like S-1-5-21-...-500 are named after their relative identifier.
+    pub fn well_known_name(&self) -> Option<&'static str> {
+        if self.revision != 1 {
+            return None;
+        }
+        Some(
+            match (self.identifier_authority, self.sub_authorities.as_slice()) {
+                (0, [0]) => "NULL SID",
+                (1, [0]) => "Everyone",
+                (2, [0]) => "LOCAL",
+                (2, [1]) => "CONSOLE LOGON",
+                (3, [0]) => "CREATOR OWNER",
+                (3, [1]) => "CREATOR GROUP",
+                (3, [4]) => "OWNER RIGHTS",
+                (5, [1]) => "DIALUP",
+                (5, [2]) => "NETWORK",
+                (5, [3]) => "BATCH",
+                (5, [4]) => "INTERACTIVE",
+                (5, [6]) => "SERVICE",
+                (5, [7]) => "ANONYMOUS LOGON",
+                (5, [9]) => "ENTERPRISE DOMAIN CONTROLLERS",
+                (5, [10]) => "SELF",
+                (5, [11]) => "Authenticated Users",
+                (5, [12]) => "RESTRICTED",
+                (5, [13]) => "TERMINAL SERVER USER",
+                (5, [14]) => "REMOTE INTERACTIVE LOGON",
+                (5, [18]) => "SYSTEM",
+                (5, [19]) => "LOCAL SERVICE",
+                (5, [20]) => "NETWORK SERVICE",
+                (5, [32, 544]) => "Administrators",
+                (5, [32, 545]) => "Users",
+                (5, [32, 546]) => "Guests",
+                (5, [32, 547]) => "Power Users",
+                (5, [32, 551]) => "Backup Operators",
+                (5, [32, 555]) => "Remote Desktop Users",
+                (5, [80, 0]) => "ALL SERVICES",
+                (5, [21, _, _, _, 500]) => "Administrator",
+                (5, [21, _, _, _, 501]) => "Guest",
+                (5, [21, _, _, _, 512]) => "Domain Admins",
+                (5, [21, _, _, _, 513]) => "Domain Users",
+                (5, [21, _, _, _, 514]) => "Domain Guests",
+                (15, [2, 1]) => "ALL APPLICATION PACKAGES",
+                (16, [4096]) => "Low Mandatory Level",
+                (16, [8192]) => "Medium Mandatory Level",
+                (16, [12288]) => "High Mandatory Level",
+                (16, [16384]) => "System Mandatory Level",
+                _ => return None,
+            },
+        )
+    }
+}
+
+impl fmt::Display for Sid {
+    ///Formats the SID the way Windows does, e.g. S-1-5-21-1004336348-1177238915-682003330-512.
+    ///Authorities that don't fit in 32 bits are written in hex.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "S-{}-", self.revision)?;
+        if self.identifier_authority >> 32 == 0 {
+            write!(f, "{}", self.identifier_authority)?;
+        } else {
+            write!(f, "0x{:012X}", self.identifier_authority)?;
+        }
+        for sub_authority in &self.sub_authorities {
+            write!(f, "-{sub_authority}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Sid {
+    type Err = NtfsError;
+
+    ///Parses the string form written by `Display`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || NtfsError::InvalidSid(s.to_string());
+        let mut parts = s.split('-');
+        if !parts
+            .next()
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case("S"))
+        {
+            return Err(invalid());
+        }
+        let revision = parts
+            .next()
+            .and_then(|revision| revision.parse().ok())
+            .ok_or_else(invalid)?;
+        let identifier_authority = parts
+            .next()
+            .and_then(|authority| match authority.strip_prefix("0x") {
+                Some(hex) => u64::from_str_radix(hex, 16).ok(),
+                None => authority.parse().ok(),
+            })
+            .filter(|authority| authority >> 48 == 0)
+            .ok_or_else(invalid)?;
+        let sub_authorities = parts
+            .map(|sub_authority| sub_authority.parse().map_err(|_| invalid()))
+            .collect::<Result<Vec<u32>, _>>()?;
+        if sub_authorities.len() > MAX_SUB_AUTHORITIES {
+            return Err(invalid());
+        }
+        Ok(Sid {
+            revision,
+            identifier_authority,
+            sub_authorities,
+        })
+    }
+}
@@ -0,0 +1,128 @@
+//! $STANDARD_INFORMATION (0x10) is in every FILE record and holds the timestamps and DOS file permissions.
+//! The timestamps are Windows FILETIMEs: 100 nanosecond intervals since 1601-01-01 UTC.
+//! NTFS 3.0 (Windows 2000) extended the attribute from 48 to 72 bytes.
+//!
+//!| Offset | Size | OS | Description                       |
+//!|--------|------|----|-----------------------------------|
+//!| 0x00   | 8    |    | File creation time                |
+//!| 0x08   | 8    |    | File altered time                 |
+//!| 0x10   | 8    |    | MFT changed time                  |
+//!| 0x18   | 8    |    | File read time                    |
+//!| 0x20   | 4    |    | DOS file permissions              |
+//!| 0x24   | 4    |    | Maximum number of versions        |
+//!| 0x28   | 4    |    | Version number                    |
+//!| 0x2C   | 4    |    | Class Id                          |
+//!| 0x30   | 4    | 2K | Owner Id                          |
+//!| 0x34   | 4    | 2K | Security Id                       |
+//!| 0x38   | 8    | 2K | Quota Charged                     |
+//!| 0x40   | 8    | 2K | Update Sequence Number (USN)      |
+//!
+//!<https://flatcap.github.io/linux-ntfs/ntfs/attributes/standard_information.html>
+use crate::{
+    bytes::{u32_at, u64_at},
+    NtfsError, NtfsTime,
+};
+
+///The size of the attribute value on NTFS 1.x volumes.
+pub const STANDARD_INFORMATION_SIZE: usize = 0x30;
+
+///The size of the attribute value on NTFS 3.x volumes.
+pub const STANDARD_INFORMATION_SIZE_V3: usize = 0x48;
+
+///The DOS file permissions, shared by $STANDARD_INFORMATION and $FILE_NAME.
+///Unknown bits are kept as they are, so the raw value can always be recovered with `bits`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileAttributes(pub u32);
+
+impl FileAttributes {
+    pub const READ_ONLY: FileAttributes = FileAttributes(0x0001);
+    pub const HIDDEN: FileAttributes = FileAttributes(0x0002);
+    pub const SYSTEM: FileAttributes = FileAttributes(0x0004);
+    pub const ARCHIVE: FileAttributes = FileAttributes(0x0020);
+    pub const DEVICE: FileAttributes = FileAttributes(0x0040);
+    pub const NORMAL: FileAttributes = FileAttributes(0x0080);
+    pub const TEMPORARY: FileAttributes = FileAttributes(0x0100);
+    pub const SPARSE_FILE: FileAttributes = FileAttributes(0x0200);
+    pub const REPARSE_POINT: FileAttributes = FileAttributes(0x0400);
+    pub const COMPRESSED: FileAttributes = FileAttributes(0x0800);
+    pub const OFFLINE: FileAttributes = FileAttributes(0x1000);
+    pub const NOT_CONTENT_INDEXED: FileAttributes = FileAttributes(0x2000);
+    pub const ENCRYPTED: FileAttributes = FileAttributes(0x4000);
+    ///Only set in $FILE_NAME.
+    pub const DIRECTORY: FileAttributes = FileAttributes(0x1000_0000);
+    ///Only set in $FILE_NAME.
+    pub const INDEX_VIEW: FileAttributes = FileAttributes(0x2000_0000);
+
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    pub fn contains(&self, other: FileAttributes) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StandardInformation {
+    pub creation_time: NtfsTime,
+    pub modified_time: NtfsTime,
+    pub mft_changed_time: NtfsTime,
+    pub accessed_time: NtfsTime,
+    pub dos_flags: FileAttributes,
+    pub max_versions: u32,
+    pub version_number: u32,
+    pub class_id: u32,
+    ///The fields below are only present on NTFS 3.x volumes.
+    pub owner_id: Option<u32>,
+    ///Index into the $Secure file.
+    pub security_id: Option<u32>,
+    pub quota_charged: Option<u64>,
+    ///The offset of the last change to this file in the $UsnJrnl.
+    pub usn: Option<u64>,
+}
+
+impl StandardInformation {
+    ///Parses either the 48 byte NTFS 1.x layout or the 72 byte NTFS 3.x layout.
+    pub fn parse(value: &[u8]) -> Result<StandardInformation, NtfsError> {
+        if value.len() < STANDARD_INFORMATION_SIZE {
+            return Err(NtfsError::BufferTooSmall {
+                needed: STANDARD_INFORMATION_SIZE,
+                available: value.len(),
+            });
+        }
+        let v3 = value.len() >= STANDARD_INFORMATION_SIZE_V3;
+
+        Ok(StandardInformation {
+            creation_time: NtfsTime(u64_at(value, 0x00)),
+            modified_time: NtfsTime(u64_at(value, 0x08)),
+            mft_changed_time: NtfsTime(u64_at(value, 0x10)),
+            accessed_time: NtfsTime(u64_at(value, 0x18)),
+            dos_flags: FileAttributes(u32_at(value, 0x20)),
+            max_versions: u32_at(value, 0x24),
+            version_number: u32_at(value, 0x28),
+            class_id: u32_at(value, 0x2C),
+            owner_id: v3.then(|| u32_at(value, 0x30)),
+            security_id: v3.then(|| u32_at(value, 0x34)),
+            quota_charged: v3.then(|| u64_at(value, 0x38)),
+            usn: v3.then(|| u64_at(value, 0x40)),
+        })
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.dos_flags.contains(FileAttributes::READ_ONLY)
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.dos_flags.contains(FileAttributes::HIDDEN)
+    }
+
+    pub fn is_system(&self) -> bool {
+        self.dos_flags.contains(FileAttributes::SYSTEM)
+    }
+
+    pub fn is_archive(&self) -> bool {
+        self.dos_flags.contains(FileAttributes::ARCHIVE)
+    }
+}
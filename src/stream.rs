@@ -0,0 +1,169 @@
+//! Reading a large attribute into memory isn't always possible,
+//! so [`NtfsStreamReader`] reads it through `std::io::Read` and `Seek` instead,
+//! translating each stream position to a cluster on the fly.
+use crate::{
+    data_run::RunTable, Attribute, AttributeHeader, DataRun, Flag, Ntfs, NtfsError, Residency,
+};
+use std::io::{self, Read, Seek, SeekFrom};
+
+///Reads the value of a non-resident attribute with constant memory.
+///Sparse runs and bytes past the initialized size read as zeros and reads stop at the real size.
+///Created with [`Ntfs::stream_reader`].
+pub struct NtfsStreamReader<'a, R: Read + Seek> {
+    ntfs: &'a mut Ntfs<R>,
+    ///The value of a resident stream, which is read from memory instead of the runs.
+    value: Option<Vec<u8>>,
+    runs: RunTable,
+    real_size: u64,
+    initialized_size: u64,
+    position: u64,
+}
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///Creates a reader over the value of a non-resident attribute.
+    ///Compressed attributes have to be read with [`Ntfs::read_attribute`].
+    pub fn stream_reader(
+        &mut self,
+        attribute: &Attribute,
+    ) -> Result<NtfsStreamReader<'_, R>, NtfsError> {
+        if attribute.is_resident() {
+            return Err(NtfsError::Unsupported(
+                "resident attributes can't be streamed",
+            ));
+        }
+        self.runs_reader(attribute.header(), attribute.data_runs()?)
+    }
+
+    ///Creates a reader over the runs of a non-resident attribute, using the sizes from its `header`.
+    pub(crate) fn runs_reader(
+        &mut self,
+        header: &AttributeHeader,
+        data_runs: Vec<DataRun>,
+    ) -> Result<NtfsStreamReader<'_, R>, NtfsError> {
+        let Residency::NonResident {
+            start_vcn,
+            real_size,
+            initialized_size,
+            ..
+        } = header.residency
+        else {
+            return Err(NtfsError::Corrupt("resident attributes have no data runs"));
+        };
+        if header.has_flag(Flag::Compressed) {
+            return Err(NtfsError::Unsupported(
+                "compressed attributes can't be streamed",
+            ));
+        }
+
+        let runs = RunTable::new(start_vcn, data_runs)?;
+
+        Ok(NtfsStreamReader {
+            ntfs: self,
+            value: None,
+            runs,
+            real_size,
+            initialized_size: initialized_size.min(real_size),
+            position: 0,
+        })
+    }
+
+    ///Creates a reader over the value of a resident attribute.
+    pub(crate) fn value_reader(&mut self, value: Vec<u8>) -> NtfsStreamReader<'_, R> {
+        let len = value.len() as u64;
+        NtfsStreamReader {
+            ntfs: self,
+            value: Some(value),
+            runs: RunTable::default(),
+            real_size: len,
+            initialized_size: len,
+            position: 0,
+        }
+    }
+}
+
+impl<R: Read + Seek> NtfsStreamReader<'_, R> {
+    ///The size of the stream.
+    pub fn len(&self) -> u64 {
+        self.real_size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.real_size == 0
+    }
+
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+}
+
+impl<R: Read + Seek> Read for NtfsStreamReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.real_size || buf.is_empty() {
+            return Ok(0);
+        }
+        let remaining = self.real_size - self.position;
+        let mut len = (buf.len() as u64).min(remaining);
+
+        if let Some(value) = &self.value {
+            let start = self.position as usize;
+            buf[..len as usize].copy_from_slice(&value[start..start + len as usize]);
+            self.position += len;
+            return Ok(len as usize);
+        }
+
+        if self.position >= self.initialized_size {
+            buf[..len as usize].fill(0);
+            self.position += len;
+            return Ok(len as usize);
+        }
+        len = len.min(self.initialized_size - self.position);
+
+        let geometry = *self.ntfs.geometry();
+        let cluster_size = geometry.bytes_per_cluster;
+        let vcn = self.position / cluster_size;
+        let Some((start_vcn, run)) = self.runs.find(vcn) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "stream position is not covered by the data runs",
+            ));
+        };
+
+        let run_offset = self.position - start_vcn * cluster_size;
+        let run_bytes = run.length_clusters.saturating_mul(cluster_size);
+        len = len.min(run_bytes - run_offset);
+        let buf = &mut buf[..len as usize];
+
+        match run.lcn {
+            Some(lcn) => {
+                let offset = geometry.lcn_to_byte_offset(lcn)? + run_offset;
+                self.ntfs.read_exact_at(offset, buf)?;
+            }
+            None => buf.fill(0),
+        }
+        self.position += len;
+        Ok(len as usize)
+    }
+}
+
+impl<R: Read + Seek> Seek for NtfsStreamReader<'_, R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.position = seek_position(pos, self.position, self.real_size)?;
+        Ok(self.position)
+    }
+}
+
+///The position `pos` moves a reader at `position` in a stream of `len` bytes to.
+///Seeking past the end is allowed, like it is for files.
+pub(crate) fn seek_position(pos: SeekFrom, position: u64, len: u64) -> io::Result<u64> {
+    let position = match pos {
+        SeekFrom::Start(offset) => Some(offset),
+        SeekFrom::End(delta) => len.checked_add_signed(delta),
+        SeekFrom::Current(delta) => position.checked_add_signed(delta),
+    };
+    position.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "invalid seek to a negative or overflowing position",
+        )
+    })
+}
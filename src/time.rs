@@ -0,0 +1,103 @@
+//! NTFS timestamps are Windows FILETIMEs: the number of 100 nanosecond intervals since 1601-01-01 UTC.
+use crate::NtfsError;
+use std::{
+    fmt,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+///The number of seconds between 1601-01-01 and 1970-01-01.
+pub const FILETIME_UNIX_EPOCH_DIFFERENCE: i64 = 11_644_473_600;
+
+///The number of FILETIME intervals in a second.
+pub const FILETIME_TICKS_PER_SECOND: u64 = 10_000_000;
+
+///Converts a FILETIME to seconds since the Unix epoch, negative for dates before 1970.
+pub fn filetime_to_unix_seconds(ft: u64) -> i64 {
+    (ft / FILETIME_TICKS_PER_SECOND) as i64 - FILETIME_UNIX_EPOCH_DIFFERENCE
+}
+
+///Converts a FILETIME to a `SystemTime`. Fails for dates before the Unix epoch.
+pub fn filetime_to_system_time(ft: u64) -> Result<SystemTime, NtfsError> {
+    let epoch = FILETIME_UNIX_EPOCH_DIFFERENCE as u64 * FILETIME_TICKS_PER_SECOND;
+    let ticks = ft
+        .checked_sub(epoch)
+        .ok_or(NtfsError::TimeBeforeUnixEpoch(ft))?;
+    let duration = Duration::new(
+        ticks / FILETIME_TICKS_PER_SECOND,
+        (ticks % FILETIME_TICKS_PER_SECOND) as u32 * 100,
+    );
+    Ok(UNIX_EPOCH + duration)
+}
+
+///A Windows FILETIME as stored on disk.
+///Every value is representable, including 0 (1601-01-01) and dates far in the future.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NtfsTime(pub u64);
+
+impl NtfsTime {
+    ///The exact tick count, in 100 nanosecond intervals since 1601-01-01 UTC.
+    pub fn raw(&self) -> u64 {
+        self.0
+    }
+
+    ///Seconds since the Unix epoch, negative for dates before 1970.
+    pub fn to_unix_seconds(&self) -> i64 {
+        filetime_to_unix_seconds(self.0)
+    }
+
+    ///Returns `None` if the platform's `SystemTime` can't represent the date.
+    pub fn to_system_time(&self) -> Option<SystemTime> {
+        let epoch = FILETIME_UNIX_EPOCH_DIFFERENCE as u64 * FILETIME_TICKS_PER_SECOND;
+        let ticks = |ticks: u64| {
+            Duration::new(
+                ticks / FILETIME_TICKS_PER_SECOND,
+                (ticks % FILETIME_TICKS_PER_SECOND) as u32 * 100,
+            )
+        };
+        if self.0 >= epoch {
+            UNIX_EPOCH.checked_add(ticks(self.0 - epoch))
+        } else {
+            UNIX_EPOCH.checked_sub(ticks(epoch - self.0))
+        }
+    }
+}
+
+impl From<u64> for NtfsTime {
+    fn from(ft: u64) -> Self {
+        NtfsTime(ft)
+    }
+}
+
+impl fmt::Display for NtfsTime {
+    ///Formats the time in ISO 8601 with the full 100 nanosecond precision, e.g. `2024-01-01T00:00:00.0000000Z`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seconds = self.to_unix_seconds();
+        let (days, time) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+        let (year, month, day) = civil_from_days(days);
+        write!(
+            f,
+            "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:07}Z",
+            time / 3600,
+            time / 60 % 60,
+            time % 60,
+            self.0 % FILETIME_TICKS_PER_SECOND
+        )
+    }
+}
+
+///Converts days since 1970-01-01 to a (year, month, day) in the proleptic Gregorian calendar.
+///
+///<https://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
@@ -0,0 +1,251 @@
+//! Deleting a file only clears the in-use flag of its FILE record and the bits of its clusters in $Bitmap.
+//! Until the record and the clusters are reused, the file can be put back together from its
+//! $FILE_NAME and the data runs of its $DATA attribute. Clusters that $Bitmap marks as allocated
+//! again belong to another file by now.
+use crate::{
+    ClusterBitmap, DataRun, FileName, FileRecord, Flag, MasterFileTable, Ntfs, NtfsError,
+    Residency, StandardInformation, ROOT_RECORD_NUMBER,
+};
+use std::{
+    collections::HashSet,
+    io::{self, Read, Seek, Write},
+    path::PathBuf,
+};
+
+///The number of bytes copied at a time by [`Ntfs::extract_deleted`].
+const EXTRACT_BUFFER_SIZE: u64 = 0x10_0000;
+
+///A deleted file found by [`MasterFileTable::deleted_files`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DeletedFile {
+    pub record_number: u64,
+    pub record: FileRecord,
+    ///The best of the file's names.
+    pub file_name: FileName,
+    ///The path relative to the root directory, rebuilt from the parent references.
+    ///`None` if a parent can't be read or the parents form a cycle.
+    pub path: Option<PathBuf>,
+    ///Set when a directory on the way to the root doesn't have the sequence number the reference
+    ///expects. The directory was deleted too or its record reused, so the path may be wrong.
+    pub parent_reused: bool,
+    ///The real size of the unnamed $DATA attribute.
+    pub size: u64,
+    ///The timestamps of the file. The MFT changed time is usually when the file was deleted.
+    pub standard_information: Option<StandardInformation>,
+    ///The data runs of a non-resident file, empty for a resident one.
+    pub runs: Vec<DataRun>,
+    ///For each of `runs`, whether any of its clusters are allocated again.
+    pub reallocated: Vec<bool>,
+}
+
+impl DeletedFile {
+    pub fn is_resident(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    ///The percentage of the runs whose clusters are all still unallocated. Sparse runs aren't counted.
+    ///Resident files are kept in the FILE record, so they're always fully recoverable.
+    pub fn recoverable_percent(&self) -> f64 {
+        let runs: Vec<bool> = self
+            .runs
+            .iter()
+            .zip(&self.reallocated)
+            .filter(|(run, _)| run.lcn.is_some())
+            .map(|(_, reallocated)| *reallocated)
+            .collect();
+        if runs.is_empty() {
+            return 100.0;
+        }
+        let unallocated = runs.iter().filter(|reallocated| !**reallocated).count();
+        unallocated as f64 * 100.0 / runs.len() as f64
+    }
+}
+
+impl<R: Read + Seek> MasterFileTable<'_, R> {
+    ///Scans the $MFT for deleted files that still have a $FILE_NAME and an unnamed $DATA attribute,
+    ///checking each of their runs against $Bitmap. The bitmap is read into memory first.
+    ///Records that fail to parse are skipped, and an I/O error ends the scan.
+    pub fn deleted_files(&mut self) -> Result<DeletedFiles<'_, R>, NtfsError> {
+        let end = self.record_count();
+        let bitmap = self.ntfs.cluster_bitmap()?;
+        Ok(DeletedFiles {
+            ntfs: self.ntfs,
+            bitmap,
+            next: 0,
+            end,
+        })
+    }
+}
+
+///Iterates over the deleted files in the $MFT. Created with [`MasterFileTable::deleted_files`].
+pub struct DeletedFiles<'a, R: Read + Seek> {
+    ntfs: &'a mut Ntfs<R>,
+    bitmap: ClusterBitmap,
+    next: u64,
+    end: u64,
+}
+
+impl<R: Read + Seek> DeletedFiles<'_, R> {
+    ///Returns the deleted file in record `n`, or `None` if there's nothing to recover.
+    fn deleted_file(&mut self, n: u64) -> Result<Option<DeletedFile>, NtfsError> {
+        let record = match self.ntfs.file_record(n) {
+            Ok(record) => record,
+            Err(err @ NtfsError::Io(_)) => return Err(err),
+            Err(_) => return Ok(None),
+        };
+        if record.is_in_use() || record.base_record_reference.record_number != 0 {
+            return Ok(None);
+        }
+        let Some(file_name) = record.file_names().ok().and_then(|names| {
+            names
+                .into_iter()
+                .max_by_key(|name| name.namespace.priority())
+        }) else {
+            return Ok(None);
+        };
+        let Ok(Some(data)) = record.data_attribute() else {
+            return Ok(None);
+        };
+        let (size, runs) = match data.header().residency {
+            Residency::Resident { value_length, .. } => (value_length as u64, Vec::new()),
+            Residency::NonResident { real_size, .. } => match data.data_runs() {
+                Ok(runs) => (real_size, runs),
+                Err(_) => return Ok(None),
+            },
+        };
+        let reallocated = runs
+            .iter()
+            .map(|run| match run.lcn {
+                Some(lcn) => (lcn..lcn.saturating_add(run.length_clusters))
+                    .any(|lcn| self.bitmap.is_allocated(lcn)),
+                None => false,
+            })
+            .collect();
+        let (path, parent_reused) = self.ntfs.deleted_path(&file_name)?;
+        Ok(Some(DeletedFile {
+            record_number: n,
+            standard_information: record.standard_information().ok().flatten(),
+            record,
+            file_name,
+            path,
+            parent_reused,
+            size,
+            runs,
+            reallocated,
+        }))
+    }
+}
+
+impl<R: Read + Seek> Iterator for DeletedFiles<'_, R> {
+    type Item = Result<DeletedFile, NtfsError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.next < self.end {
+            let n = self.next;
+            self.next += 1;
+            match self.deleted_file(n) {
+                Ok(Some(file)) => return Some(Ok(file)),
+                Ok(None) => {}
+                Err(err) => {
+                    self.next = self.end;
+                    return Some(Err(err));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///Follows the parent references of a deleted file up to the root, whether or not the directories
+    ///are still in use. Returns the path and whether a sequence number didn't match on the way.
+    fn deleted_path(&mut self, name: &FileName) -> Result<(Option<PathBuf>, bool), NtfsError> {
+        let mut components = vec![name.name.clone()];
+        let mut visited = HashSet::new();
+        let mut reference = name.parent_directory;
+        let mut parent_reused = false;
+        while reference.record_number != ROOT_RECORD_NUMBER {
+            if !visited.insert(reference.record_number) {
+                return Ok((None, parent_reused));
+            }
+            let record = match self.file_record(reference.record_number) {
+                Ok(record) => record,
+                Err(err @ NtfsError::Io(_)) => return Err(err),
+                Err(_) => return Ok((None, true)),
+            };
+            if record.sequence_number != reference.sequence_number {
+                parent_reused = true;
+            }
+            let Some(parent) = record.file_names().ok().and_then(|names| {
+                names
+                    .into_iter()
+                    .max_by_key(|name| name.namespace.priority())
+            }) else {
+                return Ok((None, parent_reused));
+            };
+            reference = parent.parent_directory;
+            components.push(parent.name);
+        }
+        Ok((Some(components.iter().rev().collect()), parent_reused))
+    }
+
+    ///Copies what's left of a deleted file to `writer`. Runs whose clusters are allocated again,
+    ///sparse runs and bytes past the initialized size are written as zeros, so the output keeps
+    ///the size and layout of the file. Returns the number of bytes copied from the volume.
+    pub fn extract_deleted<W: Write>(
+        &mut self,
+        file: &DeletedFile,
+        writer: &mut W,
+    ) -> Result<u64, NtfsError> {
+        let data = file
+            .record
+            .data_attribute()?
+            .ok_or(NtfsError::Corrupt("FILE record has no $DATA attribute"))?;
+        let initialized_size = match data.header().residency {
+            Residency::Resident { .. } => {
+                let value = data.value().unwrap_or_default();
+                writer.write_all(value)?;
+                return Ok(value.len() as u64);
+            }
+            Residency::NonResident {
+                initialized_size, ..
+            } => initialized_size.min(file.size),
+        };
+        if data.header().has_flag(Flag::Compressed) {
+            return Err(NtfsError::Unsupported(
+                "compressed files can't be extracted",
+            ));
+        }
+
+        let cluster_size = self.geometry().bytes_per_cluster;
+        //The offset in the file of the run being copied.
+        let mut position = 0u64;
+        let mut copied = 0u64;
+        for (run, reallocated) in file.runs.iter().zip(&file.reallocated) {
+            if position >= file.size {
+                break;
+            }
+            let run_len = run
+                .length_clusters
+                .saturating_mul(cluster_size)
+                .min(file.size - position);
+            let mut readable = 0;
+            if let (Some(lcn), false) = (run.lcn, *reallocated) {
+                readable = run_len.min(initialized_size.saturating_sub(position));
+                let start = self.geometry().lcn_to_byte_offset(lcn)?;
+                let mut done = 0;
+                while done < readable {
+                    let mut buf = vec![0u8; (readable - done).min(EXTRACT_BUFFER_SIZE) as usize];
+                    self.read_exact_at(start.saturating_add(done), &mut buf)?;
+                    writer.write_all(&buf)?;
+                    done += buf.len() as u64;
+                }
+            }
+            io::copy(&mut io::repeat(0).take(run_len - readable), writer)?;
+            copied += readable;
+            position += run_len;
+        }
+        io::copy(&mut io::repeat(0).take(file.size - position), writer)?;
+        Ok(copied)
+    }
+}
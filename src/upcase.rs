@@ -0,0 +1,89 @@
+//! NTFS compares file names by mapping every UTF-16 code unit through $UpCase (FILE record 10),
+//! a table of 65536 little-endian code units written when the volume was formatted.
+//! Using the volume's own table keeps lookups consistent with Windows, even for characters
+//! whose case mapping changed in later versions of Unicode.
+use crate::{Ntfs, NtfsError, SystemFile};
+use std::{
+    cmp::Ordering,
+    io::{Read, Seek},
+};
+
+///The number of entries in a complete $UpCase table.
+pub const UPCASE_TABLE_LENGTH: usize = 0x10000;
+
+///Maps UTF-16 code units to upper case. Code units past the end of the table,
+///including every one when the table is empty, fall back to Rust's simple case mapping.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct UpcaseTable(pub Vec<u16>);
+
+///Same as [`UpcaseTable`].
+pub type UpCaseTable = UpcaseTable;
+
+impl UpcaseTable {
+    pub fn parse(bytes: &[u8]) -> UpcaseTable {
+        UpcaseTable(
+            bytes
+                .chunks_exact(2)
+                .take(UPCASE_TABLE_LENGTH)
+                .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                .collect(),
+        )
+    }
+
+    ///A complete table built from Rust's simple case mapping, for volumes whose $UpCase can't be read.
+    ///It agrees with the tables written by Windows for almost every character.
+    pub fn builtin() -> UpcaseTable {
+        let empty = UpcaseTable::default();
+        UpcaseTable((0..=u16::MAX).map(|unit| empty.upcase(unit)).collect())
+    }
+
+    pub fn upcase(&self, unit: u16) -> u16 {
+        if let Some(upper) = self.0.get(unit as usize) {
+            return *upper;
+        }
+        let Some(c) = char::from_u32(unit as u32) else {
+            return unit;
+        };
+        let mut upper = c.to_uppercase();
+        match (upper.next(), upper.next()) {
+            (Some(upper), None) if (upper as u32) <= 0xFFFF => upper as u16,
+            _ => unit,
+        }
+    }
+
+    ///Same as `upcase`.
+    pub fn to_upper(&self, unit: u16) -> u16 {
+        self.upcase(unit)
+    }
+
+    ///Compares two UTF-16 names code unit by code unit after mapping them to upper case,
+    ///which is the order of the entries in a directory index.
+    pub fn compare(&self, a: &[u16], b: &[u16]) -> Ordering {
+        a.iter()
+            .map(|unit| self.upcase(*unit))
+            .cmp(b.iter().map(|unit| self.upcase(*unit)))
+    }
+
+    ///Returns true if `a` and `b` are the same name, ignoring case.
+    pub fn eq_ignore_case(&self, a: &str, b: &str) -> bool {
+        a.encode_utf16()
+            .map(|unit| self.upcase(unit))
+            .eq(b.encode_utf16().map(|unit| self.upcase(unit)))
+    }
+}
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///Reads the $UpCase table of the volume.
+    pub fn upcase_table(&mut self) -> Result<UpcaseTable, NtfsError> {
+        let record = self.file_record(SystemFile::UpCase.record_number())?;
+        Ok(UpcaseTable::parse(&self.read_data(&record, None)?))
+    }
+
+    ///Reads the $UpCase table and keeps it for [`Ntfs::resolve_path`], which otherwise loads it
+    ///on first use and quietly falls back to [`UpcaseTable::builtin`] if it can't be read.
+    pub fn load_upcase(&mut self) -> Result<UpcaseTable, NtfsError> {
+        let upcase = self.upcase_table()?;
+        self.upcase = Some(upcase.clone());
+        Ok(upcase)
+    }
+}
@@ -0,0 +1,392 @@
+//! The USN change journal, \$Extend\$UsnJrnl, logs every change to the files of the volume.
+//! The "$Max" stream describes the journal and the sparse "$J" stream holds the records.
+//! A record's update sequence number (USN) is its offset in $J. As the journal grows, Windows
+//! deallocates the oldest records, leaving a sparse hole in front of the lowest valid USN.
+//!
+//! $Max
+//!| Offset | Size | Description                                   |
+//!|--------|------|-----------------------------------------------|
+//!| 0x00   | 8    | Maximum size                                  |
+//!| 0x08   | 8    | Allocation delta                              |
+//!| 0x10   | 8    | Journal id, the FILETIME it was created       |
+//!| 0x18   | 8    | Lowest valid USN                              |
+//!
+//! Records are 8 byte aligned and never cross a 4K page, the rest of which is left zeroed.
+//! Version 3 widens the file references to 128 bits, of which NTFS only uses the low 64.
+//!
+//!| V2     | V3     | Size   | Description                          |
+//!|--------|--------|--------|--------------------------------------|
+//!| 0x00   | 0x00   | 4      | Record length                        |
+//!| 0x04   | 0x04   | 2      | Major version                        |
+//!| 0x06   | 0x06   | 2      | Minor version                        |
+//!| 0x08   | 0x08   | 8 / 16 | File reference                       |
+//!| 0x10   | 0x18   | 8 / 16 | Parent directory reference           |
+//!| 0x18   | 0x28   | 8      | USN                                  |
+//!| 0x20   | 0x30   | 8      | Timestamp                            |
+//!| 0x28   | 0x38   | 4      | Reason flags                         |
+//!| 0x2C   | 0x3C   | 4      | Source info                          |
+//!| 0x30   | 0x40   | 4      | Security id                          |
+//!| 0x34   | 0x44   | 4      | File attributes                      |
+//!| 0x38   | 0x48   | 2      | Name length in bytes                 |
+//!| 0x3A   | 0x4A   | 2      | Offset to the name                   |
+//!| 0x3C   | 0x4C   | V      | Name in UTF-16                       |
+//!
+//!<https://learn.microsoft.com/en-us/windows/win32/api/winioctl/ns-winioctl-usn_record_v2>
+//!
+//!<https://learn.microsoft.com/en-us/windows/win32/api/winioctl/ns-winioctl-usn_record_v3>
+use crate::{
+    bytes::{u16_at, u32_at, u64_at},
+    DataStream, FileAttributes, FileReference, Ntfs, NtfsError, NtfsStreamReader, NtfsTime,
+};
+use std::{
+    io::{Read, Seek, SeekFrom},
+    ops::BitOr,
+};
+
+///The size of the $Max stream.
+pub const USN_JOURNAL_MAX_SIZE: usize = 0x20;
+
+///Records are written in pages of this size and never cross from one into the next.
+pub const USN_PAGE_SIZE: u64 = 0x1000;
+
+///The size of a version 2 record without its name.
+pub const USN_RECORD_V2_SIZE: usize = 0x3C;
+
+///The size of a version 3 record without its name.
+pub const USN_RECORD_V3_SIZE: usize = 0x4C;
+
+///The contents of the $Max stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UsnJournalInfo {
+    ///The size the journal is trimmed back to once it grows past it by the allocation delta.
+    pub maximum_size: u64,
+    pub allocation_delta: u64,
+    ///Changes whenever the journal is deleted and recreated, which invalidates older USNs.
+    pub journal_id: u64,
+    ///The USN of the oldest record still in the journal.
+    pub lowest_valid_usn: u64,
+}
+
+impl UsnJournalInfo {
+    pub fn parse(value: &[u8]) -> Result<UsnJournalInfo, NtfsError> {
+        if value.len() < USN_JOURNAL_MAX_SIZE {
+            return Err(NtfsError::BufferTooSmall {
+                needed: USN_JOURNAL_MAX_SIZE,
+                available: value.len(),
+            });
+        }
+        Ok(UsnJournalInfo {
+            maximum_size: u64_at(value, 0x00),
+            allocation_delta: u64_at(value, 0x08),
+            journal_id: u64_at(value, 0x10),
+            lowest_valid_usn: u64_at(value, 0x18),
+        })
+    }
+}
+
+///Why a record was written. A file that stays open collects reasons in each record until it's closed,
+///when a last record with `CLOSE` set sums them up.
+///Unknown bits are kept as they are, so the raw value can always be recovered with `bits`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UsnReason(pub u32);
+
+impl UsnReason {
+    pub const DATA_OVERWRITE: UsnReason = UsnReason(0x0000_0001);
+    pub const DATA_EXTEND: UsnReason = UsnReason(0x0000_0002);
+    pub const DATA_TRUNCATION: UsnReason = UsnReason(0x0000_0004);
+    pub const NAMED_DATA_OVERWRITE: UsnReason = UsnReason(0x0000_0010);
+    pub const NAMED_DATA_EXTEND: UsnReason = UsnReason(0x0000_0020);
+    pub const NAMED_DATA_TRUNCATION: UsnReason = UsnReason(0x0000_0040);
+    pub const FILE_CREATE: UsnReason = UsnReason(0x0000_0100);
+    pub const FILE_DELETE: UsnReason = UsnReason(0x0000_0200);
+    pub const EA_CHANGE: UsnReason = UsnReason(0x0000_0400);
+    pub const SECURITY_CHANGE: UsnReason = UsnReason(0x0000_0800);
+    pub const RENAME_OLD_NAME: UsnReason = UsnReason(0x0000_1000);
+    pub const RENAME_NEW_NAME: UsnReason = UsnReason(0x0000_2000);
+    pub const INDEXABLE_CHANGE: UsnReason = UsnReason(0x0000_4000);
+    pub const BASIC_INFO_CHANGE: UsnReason = UsnReason(0x0000_8000);
+    pub const HARD_LINK_CHANGE: UsnReason = UsnReason(0x0001_0000);
+    pub const COMPRESSION_CHANGE: UsnReason = UsnReason(0x0002_0000);
+    pub const ENCRYPTION_CHANGE: UsnReason = UsnReason(0x0004_0000);
+    pub const OBJECT_ID_CHANGE: UsnReason = UsnReason(0x0008_0000);
+    pub const REPARSE_POINT_CHANGE: UsnReason = UsnReason(0x0010_0000);
+    pub const STREAM_CHANGE: UsnReason = UsnReason(0x0020_0000);
+    pub const TRANSACTED_CHANGE: UsnReason = UsnReason(0x0040_0000);
+    pub const INTEGRITY_CHANGE: UsnReason = UsnReason(0x0080_0000);
+    pub const CLOSE: UsnReason = UsnReason(0x8000_0000);
+
+    const NAMES: [(UsnReason, &'static str); 23] = [
+        (UsnReason::DATA_OVERWRITE, "DATA_OVERWRITE"),
+        (UsnReason::DATA_EXTEND, "DATA_EXTEND"),
+        (UsnReason::DATA_TRUNCATION, "DATA_TRUNCATION"),
+        (UsnReason::NAMED_DATA_OVERWRITE, "NAMED_DATA_OVERWRITE"),
+        (UsnReason::NAMED_DATA_EXTEND, "NAMED_DATA_EXTEND"),
+        (UsnReason::NAMED_DATA_TRUNCATION, "NAMED_DATA_TRUNCATION"),
+        (UsnReason::FILE_CREATE, "FILE_CREATE"),
+        (UsnReason::FILE_DELETE, "FILE_DELETE"),
+        (UsnReason::EA_CHANGE, "EA_CHANGE"),
+        (UsnReason::SECURITY_CHANGE, "SECURITY_CHANGE"),
+        (UsnReason::RENAME_OLD_NAME, "RENAME_OLD_NAME"),
+        (UsnReason::RENAME_NEW_NAME, "RENAME_NEW_NAME"),
+        (UsnReason::INDEXABLE_CHANGE, "INDEXABLE_CHANGE"),
+        (UsnReason::BASIC_INFO_CHANGE, "BASIC_INFO_CHANGE"),
+        (UsnReason::HARD_LINK_CHANGE, "HARD_LINK_CHANGE"),
+        (UsnReason::COMPRESSION_CHANGE, "COMPRESSION_CHANGE"),
+        (UsnReason::ENCRYPTION_CHANGE, "ENCRYPTION_CHANGE"),
+        (UsnReason::OBJECT_ID_CHANGE, "OBJECT_ID_CHANGE"),
+        (UsnReason::REPARSE_POINT_CHANGE, "REPARSE_POINT_CHANGE"),
+        (UsnReason::STREAM_CHANGE, "STREAM_CHANGE"),
+        (UsnReason::TRANSACTED_CHANGE, "TRANSACTED_CHANGE"),
+        (UsnReason::INTEGRITY_CHANGE, "INTEGRITY_CHANGE"),
+        (UsnReason::CLOSE, "CLOSE"),
+    ];
+
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    pub fn contains(&self, other: UsnReason) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    ///Returns true if any of the reasons in `other` are set.
+    pub fn intersects(&self, other: UsnReason) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    ///The names of the reasons that are set, e.g. ["FILE_CREATE", "CLOSE"]. Unknown bits are left out.
+    pub fn names(&self) -> Vec<&'static str> {
+        Self::NAMES
+            .iter()
+            .filter(|(reason, _)| self.contains(*reason))
+            .map(|(_, name)| *name)
+            .collect()
+    }
+}
+
+impl BitOr for UsnReason {
+    type Output = UsnReason;
+
+    fn bitor(self, other: UsnReason) -> UsnReason {
+        UsnReason(self.0 | other.0)
+    }
+}
+
+///A record of the change journal, either version 2 or 3.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UsnRecord {
+    pub record_length: u32,
+    pub major_version: u16,
+    pub minor_version: u16,
+    pub file_reference: FileReference,
+    pub parent_reference: FileReference,
+    pub usn: u64,
+    pub timestamp: NtfsTime,
+    pub reason: UsnReason,
+    ///Set when the change was made by the system rather than the user, e.g. by replication.
+    pub source_info: u32,
+    pub security_id: u32,
+    pub file_attributes: FileAttributes,
+    pub file_name: String,
+}
+
+impl UsnRecord {
+    ///Parses a version 2 or 3 record. Other versions are `Unsupported`.
+    pub fn parse(bytes: &[u8]) -> Result<UsnRecord, NtfsError> {
+        if bytes.len() < 8 {
+            return Err(NtfsError::BufferTooSmall {
+                needed: 8,
+                available: bytes.len(),
+            });
+        }
+        let record_length = u32_at(bytes, 0x00);
+        let major_version = u16_at(bytes, 0x04);
+        //V3 moves every field after the references 0x10 bytes further on.
+        let (size, parent_offset, shift) = match major_version {
+            2 => (USN_RECORD_V2_SIZE, 0x10, 0),
+            3 => (USN_RECORD_V3_SIZE, 0x18, 0x10),
+            _ => return Err(NtfsError::Unsupported("USN record version")),
+        };
+        if (record_length as usize) < size || record_length as usize > bytes.len() {
+            return Err(NtfsError::Corrupt("USN record has an invalid length"));
+        }
+        let record = &bytes[..record_length as usize];
+        let name_length = u16_at(record, 0x38 + shift) as usize;
+        let name_offset = u16_at(record, 0x3A + shift) as usize;
+        let name = record
+            .get(name_offset..name_offset + name_length)
+            .ok_or(NtfsError::Corrupt("USN record name runs past the record"))?;
+        let units: Vec<u16> = name
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+            .collect();
+
+        Ok(UsnRecord {
+            record_length,
+            major_version,
+            minor_version: u16_at(record, 0x06),
+            file_reference: FileReference::from_raw(u64_at(record, 0x08)),
+            parent_reference: FileReference::from_raw(u64_at(record, parent_offset)),
+            usn: u64_at(record, 0x18 + shift),
+            timestamp: NtfsTime(u64_at(record, 0x20 + shift)),
+            reason: UsnReason(u32_at(record, 0x28 + shift)),
+            source_info: u32_at(record, 0x2C + shift),
+            security_id: u32_at(record, 0x30 + shift),
+            file_attributes: FileAttributes(u32_at(record, 0x34 + shift)),
+            file_name: String::from_utf16_lossy(&units),
+        })
+    }
+}
+
+///The change journal of a volume. Created with [`Ntfs::usn_journal`].
+pub struct UsnJournal<'a, R: Read + Seek> {
+    ntfs: &'a mut Ntfs<R>,
+    info: UsnJournalInfo,
+    j_stream: DataStream,
+}
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///Opens \$Extend\$UsnJrnl. Volumes without a change journal are `NotFound`.
+    pub fn usn_journal(&mut self) -> Result<UsnJournal<'_, R>, NtfsError> {
+        let (record_number, _) = self.resolve_path("\\$Extend\\$UsnJrnl")?;
+        let streams = self.data_streams(record_number)?;
+        let stream = |name: &str| {
+            streams
+                .iter()
+                .find(|stream| stream.name.as_deref() == Some(name))
+                .cloned()
+        };
+        let max = stream("$Max").ok_or(NtfsError::Corrupt("$UsnJrnl has no $Max stream"))?;
+        let j_stream = stream("$J").ok_or(NtfsError::Corrupt("$UsnJrnl has no $J stream"))?;
+        let info = UsnJournalInfo::parse(&self.read_stream(&max)?)?;
+        Ok(UsnJournal {
+            ntfs: self,
+            info,
+            j_stream,
+        })
+    }
+}
+
+impl<R: Read + Seek> UsnJournal<'_, R> {
+    pub fn info(&self) -> &UsnJournalInfo {
+        &self.info
+    }
+
+    ///The USN the next record will be written at, which is the size of $J.
+    pub fn next_usn(&self) -> u64 {
+        self.j_stream.real_size
+    }
+
+    ///Iterates over the records from `usn` on. A USN older than the lowest valid one starts
+    ///at the oldest record instead, and the sparse ranges of $J are skipped without reading them.
+    pub fn records_since(&mut self, usn: u64) -> Result<UsnRecords<'_, R>, NtfsError> {
+        let cluster_size = self.ntfs.geometry().bytes_per_cluster;
+        let mut sparse = Vec::new();
+        let mut offset = 0u64;
+        for run in self.j_stream.runs() {
+            let len = run.length_clusters.saturating_mul(cluster_size);
+            if run.lcn.is_none() {
+                sparse.push((offset, offset.saturating_add(len)));
+            }
+            offset = offset.saturating_add(len);
+        }
+        let reader = self.ntfs.open_stream(&self.j_stream)?;
+        Ok(UsnRecords {
+            reader,
+            sparse,
+            position: usn.max(self.info.lowest_valid_usn),
+            page: Vec::new(),
+            page_start: 0,
+            reasons: None,
+        })
+    }
+
+    ///Iterates over every record still in the journal.
+    pub fn records(&mut self) -> Result<UsnRecords<'_, R>, NtfsError> {
+        let lowest_valid_usn = self.info.lowest_valid_usn;
+        self.records_since(lowest_valid_usn)
+    }
+}
+
+///Iterates over the records of the change journal. Created with [`UsnJournal::records_since`].
+///Records of versions other than 2 and 3 are skipped, and an error ends the iteration.
+pub struct UsnRecords<'a, R: Read + Seek> {
+    reader: NtfsStreamReader<'a, R>,
+    ///The (start, end) byte ranges of the sparse runs of $J.
+    sparse: Vec<(u64, u64)>,
+    position: u64,
+    ///The page of $J holding `position`.
+    page: Vec<u8>,
+    page_start: u64,
+    reasons: Option<UsnReason>,
+}
+
+impl<R: Read + Seek> UsnRecords<'_, R> {
+    ///Only yields records with any of `reasons` set, e.g. `UsnReason::FILE_CREATE | UsnReason::FILE_DELETE`.
+    pub fn with_reasons(mut self, reasons: UsnReason) -> Self {
+        self.reasons = Some(reasons);
+        self
+    }
+
+    ///Reads the record at `position` and moves past it. Returns `None` for a record of another
+    ///version, or if the rest of the page is empty, moving on to the next page.
+    fn record(&mut self) -> Result<Option<UsnRecord>, NtfsError> {
+        let page_start = self.position - self.position % USN_PAGE_SIZE;
+        if self.page.is_empty() || self.page_start != page_start {
+            let len = USN_PAGE_SIZE.min(self.reader.len() - page_start);
+            self.page = vec![0; len as usize];
+            self.reader.seek(SeekFrom::Start(page_start))?;
+            self.reader.read_exact(&mut self.page)?;
+            self.page_start = page_start;
+        }
+        let bytes = &self.page[(self.position - page_start) as usize..];
+        if bytes.len() < 8 || u32_at(bytes, 0x00) == 0 {
+            self.position = page_start + USN_PAGE_SIZE;
+            return Ok(None);
+        }
+        let record_length = u32_at(bytes, 0x00);
+        if record_length < 8 || record_length as usize > bytes.len() {
+            return Err(NtfsError::Corrupt("USN record crosses a page"));
+        }
+        self.position += record_length.next_multiple_of(8) as u64;
+        match UsnRecord::parse(bytes) {
+            Ok(record) => Ok(Some(record)),
+            Err(NtfsError::Unsupported(_)) => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl<R: Read + Seek> Iterator for UsnRecords<'_, R> {
+    type Item = Result<UsnRecord, NtfsError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((_, end)) = self
+                .sparse
+                .iter()
+                .find(|(start, end)| (*start..*end).contains(&self.position))
+            {
+                self.position = *end;
+            }
+            if self.position >= self.reader.len() {
+                return None;
+            }
+            match self.record() {
+                Ok(Some(record)) => {
+                    if self
+                        .reasons
+                        .is_none_or(|reasons| record.reason.intersects(reasons))
+                    {
+                        return Some(Ok(record));
+                    }
+                }
+                Ok(None) => {}
+                Err(err) => {
+                    self.position = self.reader.len();
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,211 @@
+//! An NTFS volume is read through a handle that owns the reader and the parsed boot sector.
+//! Everything past the boot sector is located using the geometry it describes.
+#[cfg(unix)]
+use crate::PARTITION_BOOT_SECTOR_SIZE;
+use crate::{
+    pbs_with_options, Geometry, NtfsError, ParseOptions, PartitionBootSector, RecordCache,
+    UpcaseTable, DEFAULT_CACHE_CAPACITY,
+};
+#[cfg(any(unix, windows))]
+use std::fs::File;
+#[cfg(windows)]
+use std::io::BufReader;
+use std::io::{self, Read, Seek, SeekFrom};
+#[cfg(unix)]
+use std::path::Path;
+
+pub struct Ntfs<R: Read + Seek> {
+    reader: R,
+    ///The byte offset of the volume in `reader`, non-zero when reading a whole disk image.
+    offset: u64,
+    boot_sector: PartitionBootSector,
+    geometry: Geometry,
+    ///The (LCN, length) runs of the $MFT, once loaded from record 0.
+    pub(crate) mft_runs: Option<Vec<(u64, u64)>>,
+    ///The real size in bytes of the $MFT's $DATA attribute, loaded with the runs.
+    pub(crate) mft_size: Option<u64>,
+    ///The $BITMAP of the $MFT, loaded on first use.
+    pub(crate) mft_bitmap: Option<Vec<u8>>,
+    ///The $UpCase table used to compare names, loaded on the first path lookup.
+    pub(crate) upcase: Option<UpcaseTable>,
+    ///Recently parsed FILE records, consulted when resolving paths and walking directories.
+    pub(crate) record_cache: RecordCache,
+}
+
+///The low-level view of the same handle, used when reading sectors and clusters.
+pub type Volume<R> = Ntfs<R>;
+
+///Opens the NTFS volume mounted at `drive_letter`, e.g. 'C', through its `\\.\C:` device path.
+///Reading a raw volume needs administrator rights, otherwise this fails with `NotElevated`.
+///
+///```ignore
+///let mut ntfs = ntfs::open_volume('C')?;
+///let (record_number, record) = ntfs.resolve_path("\\Windows\\notepad.exe")?;
+///println!("{record_number}: {:?}", record.file_names()?);
+///# Ok::<(), ntfs::NtfsError>(())
+///```
+#[cfg(windows)]
+pub fn open_volume(drive_letter: char) -> Result<Ntfs<BufReader<File>>, NtfsError> {
+    let path = format!("\\\\.\\{}:", drive_letter.to_ascii_uppercase());
+    let file = File::open(&path).map_err(|err| match err.kind() {
+        io::ErrorKind::PermissionDenied => NtfsError::NotElevated(path.clone()),
+        _ => NtfsError::Io(err),
+    })?;
+    //Volume handles don't report the size of the volume when seeking to the end.
+    let options = ParseOptions {
+        check_length: false,
+        ..ParseOptions::default()
+    };
+    Ntfs::new_with_options(BufReader::new(file), options)
+}
+
+///Opens the NTFS volume on a block device such as `/dev/sdb1`, read-only.
+///Devices without read permission, usually for anyone but root and the disk group,
+///fail with `NotElevated`. Disk images work too.
+///
+///Block devices are read in whole sectors, so the boot sector is read with a buffer the size of
+///the largest sector NTFS allows, and the file isn't wrapped in a `BufReader`.
+#[cfg(unix)]
+pub fn open_device(path: &Path) -> Result<Ntfs<File>, NtfsError> {
+    let mut file = File::open(path).map_err(|err| match err.kind() {
+        io::ErrorKind::PermissionDenied => NtfsError::NotElevated(path.display().to_string()),
+        _ => NtfsError::Io(err),
+    })?;
+    let mut sector = [0u8; 4096];
+    file.read_exact(&mut sector)?;
+    let mut buf = [0u8; PARTITION_BOOT_SECTOR_SIZE];
+    buf.copy_from_slice(&sector[..PARTITION_BOOT_SECTOR_SIZE]);
+    let boot_sector = PartitionBootSector::from_bytes(&buf)?;
+    Ntfs::with_boot_sector(file, 0, boot_sector, ParseOptions::default())
+}
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///Reads the boot sector from the start of `reader`.
+    ///Fails with `TruncatedVolume` if `reader` is shorter than the volume.
+    pub fn new(reader: R) -> Result<Ntfs<R>, NtfsError> {
+        Self::new_with_options(reader, ParseOptions::default())
+    }
+
+    ///Same as `new`, but parses the boot sector with `options`.
+    ///Warnings from a lenient parse are dropped, use `pbs_with_options` to see them.
+    pub fn new_with_options(reader: R, options: ParseOptions) -> Result<Ntfs<R>, NtfsError> {
+        Self::open(reader, 0, options)
+    }
+
+    ///Opens the volume starting at the byte `offset` of `reader`, e.g. a partition of a disk image.
+    ///Every read is relative to the start of the volume.
+    ///The hidden sectors field of the boot sector isn't checked against `offset`,
+    ///as it is often stale on volumes that were moved or imaged.
+    pub fn new_at_offset(reader: R, offset: u64) -> Result<Ntfs<R>, NtfsError> {
+        Self::open(reader, offset, ParseOptions::default())
+    }
+
+    fn open(mut reader: R, offset: u64, options: ParseOptions) -> Result<Ntfs<R>, NtfsError> {
+        reader.seek(SeekFrom::Start(offset))?;
+        let (boot_sector, _) = pbs_with_options(&mut reader, options)?;
+        Self::with_boot_sector(reader, offset, boot_sector, options)
+    }
+
+    fn with_boot_sector(
+        mut reader: R,
+        offset: u64,
+        boot_sector: PartitionBootSector,
+        options: ParseOptions,
+    ) -> Result<Ntfs<R>, NtfsError> {
+        let geometry = boot_sector.geometry()?;
+        if options.check_length {
+            //The backup boot sector sits just past the last sector, so it isn't required.
+            let declared = boot_sector.total_bytes();
+            let available = reader.seek(SeekFrom::End(0))?.saturating_sub(offset);
+            if declared > available {
+                return Err(NtfsError::TruncatedVolume {
+                    declared,
+                    available,
+                });
+            }
+        }
+        Ok(Ntfs {
+            reader,
+            offset,
+            boot_sector,
+            geometry,
+            mft_runs: None,
+            mft_size: None,
+            mft_bitmap: None,
+            upcase: None,
+            record_cache: RecordCache::new(DEFAULT_CACHE_CAPACITY),
+        })
+    }
+
+    ///Replaces the FILE record cache with an empty one holding up to `capacity` records,
+    ///[`DEFAULT_CACHE_CAPACITY`] unless changed. A capacity of zero turns caching off.
+    ///Records are cached as they were first read, so a volume that is being written to should
+    ///be opened without a cache.
+    pub fn with_cache_capacity(mut self, capacity: usize) -> Self {
+        self.record_cache = RecordCache::new(capacity);
+        self
+    }
+
+    ///The number of FILE record cache (hits, misses) since the cache was created.
+    pub fn cache_stats(&self) -> (u64, u64) {
+        self.record_cache.stats()
+    }
+
+    pub fn boot_sector(&self) -> &PartitionBootSector {
+        &self.boot_sector
+    }
+
+    pub fn geometry(&self) -> &Geometry {
+        &self.geometry
+    }
+
+    ///The byte offset of the volume in the underlying reader.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    ///Fills `buf` starting at the byte `offset` from the start of the volume.
+    pub(crate) fn read_exact_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<(), NtfsError> {
+        let offset = self
+            .offset
+            .checked_add(offset)
+            .ok_or(NtfsError::Corrupt("read past the end of the volume"))?;
+        self.reader.seek(SeekFrom::Start(offset))?;
+        self.reader.read_exact(buf)?;
+        Ok(())
+    }
+
+    fn read_vec_at(&mut self, offset: u64, len: u64) -> Result<Vec<u8>, NtfsError> {
+        let len = usize::try_from(len)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "read is too large"))?;
+        let mut buf = vec![0u8; len];
+        self.read_exact_at(offset, &mut buf)?;
+        Ok(buf)
+    }
+
+    ///Reads `count` sectors starting at the logical block address `lba`.
+    pub fn read_sectors(&mut self, lba: u64, count: u64) -> Result<Vec<u8>, NtfsError> {
+        let bytes_per_sector = self.geometry.bytes_per_sector;
+        self.read_vec_at(
+            lba.saturating_mul(bytes_per_sector),
+            count.saturating_mul(bytes_per_sector),
+        )
+    }
+
+    ///Reads the cluster at the logical cluster number `lcn`.
+    pub fn read_cluster(&mut self, lcn: u64) -> Result<Vec<u8>, NtfsError> {
+        self.read_clusters(lcn, 1)
+    }
+
+    ///Reads `count` clusters starting at the logical cluster number `lcn`.
+    pub fn read_clusters(&mut self, lcn: u64, count: u64) -> Result<Vec<u8>, NtfsError> {
+        let offset = self.geometry.lcn_to_byte_offset(lcn)?;
+        let len = self.geometry.clusters_to_bytes(count)?;
+        self.read_vec_at(offset, len)
+    }
+
+    ///Gives back the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
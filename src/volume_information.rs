@@ -0,0 +1,124 @@
+//! The $Volume file (FILE record 3) holds the volume label in $VOLUME_NAME (0x60)
+//! and the NTFS version and state flags in $VOLUME_INFORMATION (0x70).
+//!
+//! $VOLUME_NAME is the label in Unicode, without a terminator. It's empty if the volume has no label.
+//!
+//! $VOLUME_INFORMATION
+//!| Offset | Size | Description           |
+//!|--------|------|-----------------------|
+//!| 0x00   | 8    | Always zero?          |
+//!| 0x08   | 1    | Major version number  |
+//!| 0x09   | 1    | Minor version number  |
+//!| 0x0A   | 2    | Flags                 |
+//!
+//!<https://flatcap.github.io/linux-ntfs/ntfs/attributes/volume_information.html>
+use crate::{bytes::u16_at, AttributeType, Ntfs, NtfsError};
+use std::io::{Read, Seek};
+
+///The size of the $VOLUME_INFORMATION value.
+pub const VOLUME_INFORMATION_SIZE: usize = 0x0C;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VolumeName {
+    pub label: String,
+}
+
+impl VolumeName {
+    pub fn parse(value: &[u8]) -> Result<VolumeName, NtfsError> {
+        if !value.len().is_multiple_of(2) {
+            return Err(NtfsError::Corrupt("volume name has an odd length"));
+        }
+        let units: Vec<u16> = value
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+            .collect();
+        Ok(VolumeName {
+            label: String::from_utf16_lossy(&units),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VolumeInformation {
+    pub major_version: u8,
+    pub minor_version: u8,
+    pub flags: u16,
+}
+
+impl VolumeInformation {
+    ///The volume wasn't unmounted cleanly and chkdsk will run on the next boot.
+    pub const DIRTY: u16 = 0x0001;
+    pub const RESIZE_LOG_FILE: u16 = 0x0002;
+    pub const UPGRADE_ON_MOUNT: u16 = 0x0004;
+    pub const MOUNTED_ON_NT4: u16 = 0x0008;
+    pub const DELETE_USN_UNDERWAY: u16 = 0x0010;
+    pub const REPAIR_OBJECT_IDS: u16 = 0x0020;
+    pub const MODIFIED_BY_CHKDSK: u16 = 0x8000;
+
+    pub fn parse(value: &[u8]) -> Result<VolumeInformation, NtfsError> {
+        if value.len() < VOLUME_INFORMATION_SIZE {
+            return Err(NtfsError::BufferTooSmall {
+                needed: VOLUME_INFORMATION_SIZE,
+                available: value.len(),
+            });
+        }
+
+        Ok(VolumeInformation {
+            major_version: value[0x08],
+            minor_version: value[0x09],
+            flags: u16_at(value, 0x0A),
+        })
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.flags & Self::DIRTY != 0
+    }
+}
+
+///The label, version and state of a volume. Returned by [`Ntfs::info`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct VolumeInfo {
+    ///Empty if the volume has no label.
+    pub label: String,
+    ///The (major, minor) NTFS version, e.g. (3, 1) for Windows XP and later.
+    pub version: (u8, u8),
+    pub dirty: bool,
+    pub flags: u16,
+}
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///Reads the label, NTFS version and flags from the $Volume file.
+    pub fn info(&mut self) -> Result<VolumeInfo, NtfsError> {
+        let (label, information) = self.volume_info()?;
+        Ok(VolumeInfo {
+            label: label.unwrap_or_default(),
+            version: (information.major_version, information.minor_version),
+            dirty: information.is_dirty(),
+            flags: information.flags,
+        })
+    }
+
+    ///Reads the label and $VOLUME_INFORMATION from the $Volume file.
+    ///The label is `None` if the volume doesn't have one.
+    pub fn volume_info(&mut self) -> Result<(Option<String>, VolumeInformation), NtfsError> {
+        let record = self.master_file_table()?.volume_record()?;
+
+        let mut label = None;
+        if let Some(name) = record.find_attribute(AttributeType::VolumeName)? {
+            let value = name
+                .value()
+                .ok_or(NtfsError::Corrupt("$VOLUME_NAME is not resident"))?;
+            label = Some(VolumeName::parse(value)?.label).filter(|label| !label.is_empty());
+        }
+
+        let information = record
+            .find_attribute(AttributeType::VolumeInformation)?
+            .ok_or(NtfsError::Corrupt("$Volume has no $VOLUME_INFORMATION"))?;
+        let information = VolumeInformation::parse(
+            information
+                .value()
+                .ok_or(NtfsError::Corrupt("$VOLUME_INFORMATION is not resident"))?,
+        )?;
+        Ok((label, information))
+    }
+}
@@ -0,0 +1,531 @@
+//! Directories are looked up and walked from the root directory, FILE record 5.
+use crate::{
+    AttributeType, FileAttributes, FileName, FileRecord, FileReference, IndexEntry, Namespace,
+    Ntfs, NtfsError, ReparseData, SystemFile, UpcaseTable,
+};
+use std::{
+    collections::HashSet,
+    io::{Read, Seek},
+    path::{Path, PathBuf},
+};
+
+///The FILE record of the root directory.
+pub const ROOT_RECORD_NUMBER: u64 = SystemFile::RootDirectory as u64;
+
+///The most symbolic links and junctions followed while resolving one path, the same as Windows.
+pub const MAX_LINK_HOPS: usize = 31;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ResolveOptions {
+    ///Follow directory junctions and absolute or relative symbolic links that point into this volume.
+    ///When `false`, a link is returned as the record of the link itself.
+    pub follow_links: bool,
+    ///The drive letter the volume was mounted as, e.g. 'C'. Absolute links to any other drive
+    ///are `LinkOutsideVolume`, since the image doesn't record which letter it had.
+    pub drive_letter: char,
+}
+
+impl Default for ResolveOptions {
+    fn default() -> Self {
+        ResolveOptions {
+            follow_links: false,
+            drive_letter: 'C',
+        }
+    }
+}
+
+impl<R: Read + Seek> Ntfs<R> {
+    ///The entries of a directory, from both $INDEX_ROOT and $INDEX_ALLOCATION, sorted by name.
+    ///DOS short names are left out, since the same file is also indexed under its long name.
+    pub fn read_directory(&mut self, record: &FileRecord) -> Result<Vec<IndexEntry>, NtfsError> {
+        let mut entries = self.directory_entries(record)?;
+        entries.retain(|entry| entry.file_name.namespace != Namespace::Dos);
+        Ok(entries)
+    }
+
+    ///Every entry of a directory in index order, including the DOS short names.
+    fn directory_entries(&mut self, record: &FileRecord) -> Result<Vec<IndexEntry>, NtfsError> {
+        self.directory_iter(record)?.collect()
+    }
+
+    ///Finds the FILE record of an absolute path like `\Windows\System32`, see [`Ntfs::resolve_path`].
+    pub fn open_path(&mut self, path: &str) -> Result<FileRecord, NtfsError> {
+        Ok(self.resolve_path(path)?.1)
+    }
+
+    ///Finds the record number and FILE record of an absolute path like `\Windows\System32`.
+    ///Both `\` and `/` separate components, which are matched against both long and DOS short names
+    ///ignoring case, using the volume's $UpCase table like Windows does, or a builtin table if it can't be read.
+    ///A missing component is `NotFound` with the path up to and including it,
+    ///and going through a file is `NotADirectory` with the path of the file.
+    pub fn resolve_path(&mut self, path: &str) -> Result<(u64, FileRecord), NtfsError> {
+        self.resolve_path_with_options(path, ResolveOptions::default())
+    }
+
+    ///Same as [`Ntfs::resolve_path`], but can follow the symbolic links and junctions on the way,
+    ///including the last component. Links are followed at most [`MAX_LINK_HOPS`] times.
+    pub fn resolve_path_with_options(
+        &mut self,
+        path: &str,
+        options: ResolveOptions,
+    ) -> Result<(u64, FileRecord), NtfsError> {
+        let components = path
+            .split(['\\', '/'])
+            .filter(|c| !c.is_empty() && *c != ".")
+            .map(str::to_string)
+            .collect();
+        self.resolve_components(components, options)
+    }
+
+    fn resolve_components(
+        &mut self,
+        components: Vec<String>,
+        options: ResolveOptions,
+    ) -> Result<(u64, FileRecord), NtfsError> {
+        //Volumes without a readable $UpCase still get a sensible case-insensitive match.
+        let upcase = match self.upcase.take() {
+            Some(upcase) => upcase,
+            None => self
+                .upcase_table()
+                .unwrap_or_else(|_| UpcaseTable::builtin()),
+        };
+        let result = self.resolve_components_with(components, &upcase, options);
+        self.upcase = Some(upcase);
+        result
+    }
+
+    fn resolve_components_with(
+        &mut self,
+        mut components: Vec<String>,
+        upcase: &UpcaseTable,
+        options: ResolveOptions,
+    ) -> Result<(u64, FileRecord), NtfsError> {
+        let mut hops = 0;
+        'restart: loop {
+            let mut record_number = ROOT_RECORD_NUMBER;
+            let mut record = self.cached_file_record(record_number)?;
+            let mut walked = String::new();
+            for (i, component) in components.iter().enumerate() {
+                if !record.is_directory() {
+                    return Err(NtfsError::NotADirectory(walked));
+                }
+                walked.push('\\');
+                walked.push_str(component);
+
+                let reference = self
+                    .find_in_directory(&record, component, upcase)?
+                    .map(|entry| entry.file_reference)
+                    .ok_or_else(|| NtfsError::NotFound(walked.clone()))?;
+
+                record_number = reference.record_number;
+                record = self.cached_file_record(record_number)?;
+                if record.sequence_number != reference.sequence_number {
+                    return Err(NtfsError::NotFound(walked));
+                }
+
+                if !options.follow_links {
+                    continue;
+                }
+                let Some(link) = record.reparse().filter(ReparseData::is_link) else {
+                    continue;
+                };
+                hops += 1;
+                if hops > MAX_LINK_HOPS {
+                    return Err(NtfsError::TooManyLinks(walked));
+                }
+                let mut target = link_target(&components[..i], &link, options.drive_letter)
+                    .ok_or_else(|| NtfsError::LinkOutsideVolume {
+                        link: walked.clone(),
+                        target: link.target().unwrap_or_default().to_string(),
+                    })?;
+                target.extend_from_slice(&components[i + 1..]);
+                components = target;
+                continue 'restart;
+            }
+            return Ok((record_number, record));
+        }
+    }
+
+    ///The names of FILE record `record_number`, read from every $FILE_NAME including the ones in extension records.
+    ///Returns `None` if the record is not in use.
+    fn link_names(&mut self, record_number: u64) -> Result<Option<Vec<FileName>>, NtfsError> {
+        let record = self.cached_file_record(record_number)?;
+        if !record.is_in_use() {
+            return Ok(None);
+        }
+        let mut names = Vec::new();
+        for attribute in self.merged_attributes(record_number, &record)? {
+            if attribute.type_code == AttributeType::FileName {
+                let value = attribute
+                    .value
+                    .ok_or(NtfsError::Corrupt("$FILE_NAME is not resident"))?;
+                names.push(FileName::parse(&value)?);
+            }
+        }
+        Ok(Some(names))
+    }
+
+    ///The path of directory `reference`, following the parent of each directory up to the root.
+    ///Returns `None` if a directory on the way was deleted or its record reused.
+    fn directory_path(&mut self, reference: FileReference) -> Result<Option<PathBuf>, NtfsError> {
+        let mut components = Vec::new();
+        let mut visited = HashSet::new();
+        let mut reference = reference;
+        while reference.record_number != ROOT_RECORD_NUMBER {
+            if !visited.insert(reference.record_number) {
+                return Err(NtfsError::Corrupt("parent directories form a cycle"));
+            }
+            let record = self.cached_file_record(reference.record_number)?;
+            if !record.is_in_use() || record.sequence_number != reference.sequence_number {
+                return Ok(None);
+            }
+            let Some(name) = record
+                .file_names()?
+                .into_iter()
+                .max_by_key(|name| name.namespace.priority())
+            else {
+                return Ok(None);
+            };
+            reference = name.parent_directory;
+            components.push(name.name);
+        }
+        Ok(Some(components.iter().rev().collect()))
+    }
+
+    ///Reconstructs the path of FILE record `record_number` from the parent references in its $FILE_NAME,
+    ///relative to the root directory like the paths from [`Ntfs::walk`].
+    ///Win32 names are preferred over DOS short names. A file with several hard links gets the path
+    ///of its best name, see [`Ntfs::all_paths_of`] for the others.
+    ///Returns `None` for records that are not in use and for orphans, whose parent directory
+    ///was deleted or its record reused for another file.
+    pub fn path_of(&mut self, record_number: u64) -> Result<Option<PathBuf>, NtfsError> {
+        if record_number == ROOT_RECORD_NUMBER {
+            return Ok(Some(PathBuf::new()));
+        }
+        let Some(names) = self.link_names(record_number)? else {
+            return Ok(None);
+        };
+        let Some(name) = names
+            .into_iter()
+            .max_by_key(|name| name.namespace.priority())
+        else {
+            return Ok(None);
+        };
+        Ok(self
+            .directory_path(name.parent_directory)?
+            .map(|parent| parent.join(name.name)))
+    }
+
+    ///The path of every hard link to FILE record `record_number`, see [`Ntfs::path_of`].
+    ///DOS short names are left out, since they are another name for a long name in the same directory.
+    ///Links in orphaned directories are skipped.
+    pub fn all_paths_of(&mut self, record_number: u64) -> Result<Vec<PathBuf>, NtfsError> {
+        if record_number == ROOT_RECORD_NUMBER {
+            return Ok(vec![PathBuf::new()]);
+        }
+        let names = self.link_names(record_number)?.unwrap_or_default();
+        let mut paths = Vec::new();
+        for name in &names {
+            let has_long_name = names.iter().any(|other| {
+                other.namespace != Namespace::Dos && other.parent_directory == name.parent_directory
+            });
+            if name.namespace == Namespace::Dos && has_long_name {
+                continue;
+            }
+            if let Some(parent) = self.directory_path(name.parent_directory)? {
+                paths.push(parent.join(&name.name));
+            }
+        }
+        Ok(paths)
+    }
+
+    ///Recursively enumerates every file and directory below the root, yielding each with its path.
+    ///Reparse points are not followed, so junctions and symbolic links can't cause a loop.
+    pub fn walk(&mut self) -> Walk<'_, R> {
+        self.walk_with_options(ResolveOptions::default())
+    }
+
+    ///Same as [`Ntfs::walk`], but can follow the junctions and symbolic links to directories,
+    ///listing the target's entries under the link's path. A link back to a directory the walk is
+    ///inside of isn't followed, so links can't cause a loop. Links that can't be followed are
+    ///yielded as errors after the link itself, and the walk carries on.
+    pub fn walk_with_options(&mut self, options: ResolveOptions) -> Walk<'_, R> {
+        Walk {
+            ntfs: self,
+            start: Some(ROOT_RECORD_NUMBER),
+            base: Vec::new(),
+            stack: Vec::new(),
+            visited: HashSet::new(),
+            ancestors: Vec::new(),
+            options,
+            max_depth: usize::MAX,
+            filter: None,
+            pending_error: None,
+        }
+    }
+
+    ///Same as [`Ntfs::walk`], but starts from directory `start_record` instead of the root.
+    ///Paths are relative to the start directory.
+    pub fn walk_from(&mut self, start_record: u64) -> Result<Walk<'_, R>, NtfsError> {
+        self.walk_from_with_options(start_record, ResolveOptions::default())
+    }
+
+    ///Same as [`Ntfs::walk_from`], but can follow links like [`Ntfs::walk_with_options`].
+    pub fn walk_from_with_options(
+        &mut self,
+        start_record: u64,
+        options: ResolveOptions,
+    ) -> Result<Walk<'_, R>, NtfsError> {
+        let record = self.cached_file_record(start_record)?;
+        if !record.is_directory() {
+            return Err(NtfsError::NotADirectory(format!(
+                "FILE record {start_record}"
+            )));
+        }
+        //Relative links are resolved against the path of the start directory.
+        let base = if options.follow_links {
+            self.path_of(start_record)?
+                .ok_or(NtfsError::NotFound(format!("FILE record {start_record}")))?
+                .iter()
+                .map(|component| component.to_string_lossy().into_owned())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let mut walk = self.walk_with_options(options);
+        walk.start = Some(start_record);
+        walk.base = base;
+        Ok(walk)
+    }
+}
+
+///The components of the path a symbolic link or junction in directory `parent` points at,
+///or `None` if it points at another volume or climbs above the root.
+fn link_target(parent: &[String], link: &ReparseData, drive_letter: char) -> Option<Vec<String>> {
+    let (substitute_name, relative) = match link {
+        ReparseData::SymbolicLink {
+            substitute_name,
+            relative,
+            ..
+        } => (substitute_name.as_str(), *relative),
+        ReparseData::MountPoint {
+            substitute_name, ..
+        } => (substitute_name.as_str(), false),
+        _ => return None,
+    };
+
+    let (mut components, path) = if relative {
+        //A relative link starting with a separator is relative to the root of the link's volume.
+        match substitute_name.strip_prefix(['\\', '/']) {
+            Some(path) => (Vec::new(), path),
+            None => (parent.to_vec(), substitute_name),
+        }
+    } else {
+        //Absolute targets are NT paths like \??\C:\Users. Volume GUID and UNC targets aren't on a drive letter.
+        let path = substitute_name
+            .strip_prefix("\\??\\")
+            .unwrap_or(substitute_name);
+        let mut chars = path.chars();
+        let (Some(letter), Some(':')) = (chars.next(), chars.next()) else {
+            return None;
+        };
+        if !letter.eq_ignore_ascii_case(&drive_letter) {
+            return None;
+        }
+        (Vec::new(), chars.as_str())
+    };
+
+    for component in path.split(['\\', '/']) {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop()?;
+            }
+            component => components.push(component.to_string()),
+        }
+    }
+    Some(components)
+}
+
+///An entry yielded by [`Walk`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WalkEntry {
+    ///The path relative to the directory the walk started from.
+    pub path: PathBuf,
+    pub record_number: u64,
+    ///The $FILE_NAME stored in the parent's index. Its sizes and times can lag behind the file's own.
+    pub file_name: FileName,
+    ///1 for the entries of the start directory, 2 for their children, and so on.
+    pub depth: usize,
+    pub record: FileRecord,
+}
+
+type EntryFilter<'a> = Box<dyn FnMut(&WalkEntry) -> bool + 'a>;
+
+///A depth-first iterator over the directory tree. Created with [`Ntfs::walk`] and [`Ntfs::walk_from`].
+pub struct Walk<'a, R: Read + Seek> {
+    ntfs: &'a mut Ntfs<R>,
+    ///The directory to list first, taken when the walk starts.
+    start: Option<u64>,
+    ///The components of the path of the start directory, when following links.
+    base: Vec<String>,
+    ///The entries still to visit, as (path, index entry, whether to descend into it, depth).
+    stack: Vec<(PathBuf, IndexEntry, bool, usize)>,
+    ///The directories already listed.
+    visited: HashSet<u64>,
+    ///The directories the entry being visited is in, from the start directory down, when following links.
+    ///The same directory can be listed more than once through links, but never inside itself.
+    ancestors: Vec<u64>,
+    options: ResolveOptions,
+    max_depth: usize,
+    filter: Option<EntryFilter<'a>>,
+    ///An error listing the directory that was just yielded.
+    pending_error: Option<NtfsError>,
+}
+
+impl<'a, R: Read + Seek> Walk<'a, R> {
+    ///Only yields entries up to `depth` levels below the start directory. A depth of 1 lists the start directory.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    ///Skips the entries `predicate` returns false for. Directories that are skipped aren't descended into.
+    pub fn filter_entry(mut self, predicate: impl FnMut(&WalkEntry) -> bool + 'a) -> Self {
+        self.filter = Some(Box::new(predicate));
+        self
+    }
+
+    ///Pushes the entries of `record` so the first one is visited next.
+    fn push_children(
+        &mut self,
+        path: &Path,
+        record_number: u64,
+        record: &FileRecord,
+        depth: usize,
+    ) {
+        if depth >= self.max_depth {
+            return;
+        }
+        if self.options.follow_links {
+            if self.ancestors.contains(&record_number) {
+                return;
+            }
+            self.ancestors.push(record_number);
+        } else if !self.visited.insert(record_number) {
+            return;
+        }
+        let entries = match self.ntfs.read_directory(record) {
+            Ok(entries) => entries,
+            Err(err) => {
+                self.pending_error = Some(err);
+                return;
+            }
+        };
+        for entry in entries.into_iter().rev() {
+            //Skip '.' in the root and any other entry pointing back at its own directory.
+            if entry.file_reference.record_number == record_number {
+                continue;
+            }
+            let flags = entry.file_name.flags;
+            let descend = flags.contains(FileAttributes::DIRECTORY)
+                && (self.options.follow_links || !flags.contains(FileAttributes::REPARSE_POINT));
+            self.stack
+                .push((path.join(&entry.file_name.name), entry, descend, depth + 1));
+        }
+    }
+
+    ///Lists the directory a link at `path` points at, under the link's path.
+    fn follow(&mut self, path: &Path, link: &ReparseData, depth: usize) -> Result<(), NtfsError> {
+        let components: Vec<String> = self
+            .base
+            .iter()
+            .cloned()
+            .chain(
+                path.iter()
+                    .map(|component| component.to_string_lossy().into_owned()),
+            )
+            .collect();
+        let link_path = || format!("\\{}", components.join("\\"));
+        let target = link_target(
+            &components[..components.len().saturating_sub(1)],
+            link,
+            self.options.drive_letter,
+        )
+        .ok_or_else(|| NtfsError::LinkOutsideVolume {
+            link: link_path(),
+            target: link.target().unwrap_or_default().to_string(),
+        })?;
+        let (record_number, record) = self.ntfs.resolve_components(target, self.options)?;
+        if record.is_directory() {
+            self.push_children(path, record_number, &record, depth);
+        }
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek> Iterator for Walk<'_, R> {
+    type Item = Result<WalkEntry, NtfsError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(err) = self.pending_error.take() {
+            return Some(Err(err));
+        }
+        if let Some(start) = self.start.take() {
+            let record = match self.ntfs.cached_file_record(start) {
+                Ok(record) => record,
+                Err(err) => return Some(Err(err)),
+            };
+            self.push_children(Path::new(""), start, &record, 0);
+            if let Some(err) = self.pending_error.take() {
+                return Some(Err(err));
+            }
+        }
+
+        loop {
+            let (path, entry, descend, depth) = self.stack.pop()?;
+            let reference = entry.file_reference;
+            self.ancestors.truncate(depth);
+            let record = match self.ntfs.cached_file_record(reference.record_number) {
+                Ok(record) => record,
+                Err(err) => return Some(Err(err)),
+            };
+            if record.sequence_number != reference.sequence_number {
+                return Some(Err(NtfsError::Corrupt(
+                    "directory entry points at a reused FILE record",
+                )));
+            }
+            let entry = WalkEntry {
+                path,
+                record_number: reference.record_number,
+                file_name: entry.file_name,
+                depth,
+                record,
+            };
+            if let Some(filter) = &mut self.filter {
+                if !filter(&entry) {
+                    continue;
+                }
+            }
+            if descend && entry.record.is_directory() {
+                let link = self
+                    .options
+                    .follow_links
+                    .then(|| entry.record.reparse())
+                    .flatten()
+                    .filter(ReparseData::is_link);
+                match link {
+                    Some(link) => {
+                        if let Err(err) = self.follow(&entry.path, &link, depth) {
+                            self.pending_error = Some(err);
+                        }
+                    }
+                    None => {
+                        self.push_children(&entry.path, entry.record_number, &entry.record, depth)
+                    }
+                }
+            }
+            return Some(Ok(entry));
+        }
+    }
+}
@@ -0,0 +1,153 @@
+mod common;
+
+use ntfs::*;
+use std::io::Cursor;
+
+fn entry(name: &str, type_code: u32, flags: AttrDefFlags, minimum: u64, maximum: u64) -> Vec<u8> {
+    let mut entry = vec![0u8; ATTR_DEF_ENTRY_SIZE];
+    for (i, unit) in name.encode_utf16().enumerate() {
+        entry[i * 2..i * 2 + 2].copy_from_slice(&unit.to_le_bytes());
+    }
+    entry[0x80..0x84].copy_from_slice(&type_code.to_le_bytes());
+    entry[0x88..0x8C].copy_from_slice(&1u32.to_le_bytes());
+    entry[0x8C..0x90].copy_from_slice(&flags.bits().to_le_bytes());
+    entry[0x90..0x98].copy_from_slice(&minimum.to_le_bytes());
+    entry[0x98..0xA0].copy_from_slice(&maximum.to_le_bytes());
+    entry
+}
+
+fn attr_def() -> Vec<u8> {
+    let resident = AttrDefFlags::RESIDENT;
+    [
+        entry("$STANDARD_INFORMATION", 0x10, resident, 0x30, 0x48),
+        entry(
+            "$FILE_NAME",
+            0x30,
+            AttrDefFlags(AttrDefFlags::INDEXABLE.bits() | resident.bits()),
+            0x44,
+            0x242,
+        ),
+        entry("$VOLUME_NAME", 0x60, resident, 0x02, 0x100),
+        entry("$DATA", 0x80, AttrDefFlags(0), 0, u64::MAX),
+        vec![0u8; ATTR_DEF_ENTRY_SIZE],
+    ]
+    .concat()
+}
+
+///A volume whose $AttrDef (record 4) is stored in the cluster at 0x70.
+fn attr_def_volume() -> Vec<u8> {
+    let table = attr_def();
+    let mut records = vec![Vec::new(); 5];
+    records[0] = common::mft_record_0();
+    records[4] = common::protected(common::record_with(
+        4,
+        0x0001,
+        &[
+            common::resident(0x30, "", &common::file_name(5, "$AttrDef", 3, 0x06)),
+            common::non_resident(0x80, "", &[0x11, 0x01, 0x70], 1, table.len() as u64),
+        ],
+    ));
+    let mut image = common::volume_with_mft(&records);
+    image[0x70 * 4096..0x70 * 4096 + table.len()].copy_from_slice(&table);
+    image
+}
+
+#[test]
+fn reads_the_table() {
+    let mut ntfs = Ntfs::new(Cursor::new(attr_def_volume())).unwrap();
+    let table = ntfs.attr_def_table().unwrap();
+    let names: Vec<&str> = table.iter().map(|def| def.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "$STANDARD_INFORMATION",
+            "$FILE_NAME",
+            "$VOLUME_NAME",
+            "$DATA"
+        ]
+    );
+
+    let file_name = table.get(AttributeType::FileName).unwrap();
+    assert_eq!(file_name.collation_rule, 1);
+    assert_eq!(file_name.flags.names(), ["INDEXABLE", "RESIDENT"]);
+    assert_eq!(file_name.minimum_size, 0x44);
+    assert_eq!(file_name.maximum_size, 0x242);
+    assert_eq!(
+        table.get(AttributeType::Data).unwrap().maximum_size,
+        u64::MAX
+    );
+    assert_eq!(table.get(AttributeType::ObjectId), None);
+}
+
+#[test]
+fn keeps_user_defined_types() {
+    let mut table = attr_def();
+    table[0x80..0x84].copy_from_slice(&0x1000u32.to_le_bytes());
+    let table = AttrDefTable::parse(&table).unwrap();
+    assert_eq!(table.0.len(), 4);
+    assert_eq!(
+        table.get(AttributeType::Unknown(0x1000)).unwrap().name,
+        "$STANDARD_INFORMATION"
+    );
+}
+
+#[test]
+fn checks_attributes_against_the_table() {
+    let table = AttrDefTable::parse(&attr_def()).unwrap();
+    let valid = FileRecord::parse(&common::protected(common::record_with(
+        40,
+        0x0001,
+        &[
+            common::resident(0x10, "", &common::standard_information(0, 0x20)),
+            common::resident(0x30, "", &common::file_name(5, "a.txt", 1, 0x20)),
+            common::non_resident(0x80, "", &[0x11, 0x02, 0x70], 2, 0x1800),
+        ],
+    )))
+    .unwrap();
+    table.check_record(&valid).unwrap();
+
+    let short_standard_information = common::resident(0x10, "", &[0u8; 0x20]);
+    let non_resident_file_name = common::non_resident(0x30, "", &[0x11, 0x01, 0x70], 1, 0x44);
+    let undefined = common::resident(0x40, "", &[0u8; 0x10]);
+    for (attribute, type_code, reason) in [
+        (
+            short_standard_information,
+            AttributeType::StandardInformation,
+            "value is below the minimum size",
+        ),
+        (
+            non_resident_file_name,
+            AttributeType::FileName,
+            "attribute has to be resident",
+        ),
+        (
+            undefined,
+            AttributeType::ObjectId,
+            "type isn't defined in $AttrDef",
+        ),
+    ] {
+        let record = FileRecord::parse(&common::protected(common::record_with(
+            40,
+            0x0001,
+            &[attribute],
+        )))
+        .unwrap();
+        let err = table.check_record(&record).unwrap_err();
+        assert!(
+            matches!(err, NtfsError::AttrDefViolation { type_code: t, reason: r } if t == type_code && r == reason),
+            "{err}"
+        );
+    }
+
+    let volume_name = common::resident(0x60, "", &[0u8; 0x102]);
+    let record = FileRecord::parse(&common::protected(common::record_with(
+        3,
+        0x0001,
+        &[volume_name],
+    )))
+    .unwrap();
+    assert_eq!(
+        table.check_record(&record).unwrap_err().to_string(),
+        "$VOLUME_NAME violates $AttrDef: value is above the maximum size"
+    );
+}
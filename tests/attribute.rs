@@ -0,0 +1,205 @@
+mod common;
+
+use ntfs::*;
+
+#[test]
+fn parses_resident_header() {
+    let attr = common::resident(0x30, "", &[0u8; 0x4A]);
+    let header = AttributeHeader::parse(&attr).unwrap();
+    assert_eq!(header.type_code, AttributeType::FileName);
+    assert_eq!(header.total_length as usize, attr.len());
+    assert!(!header.non_resident);
+    assert_eq!(header.name_length, 0);
+    match header.residency {
+        Residency::Resident {
+            value_length,
+            value_offset,
+            indexed,
+        } => {
+            assert!(!indexed);
+            assert_eq!(value_length, 0x4A);
+            assert_eq!(value_offset, 0x18);
+        }
+        _ => panic!("expected a resident attribute"),
+    }
+}
+
+#[test]
+fn parses_non_resident_header() {
+    let mut attr = common::non_resident(0x80, "$Bad", &[0x11, 0x10, 0x04], 16, 60000);
+    attr[0x0C..0x0E].copy_from_slice(&0x8000u16.to_le_bytes());
+    let header = AttributeHeader::parse(&attr).unwrap();
+    assert_eq!(header.type_code, AttributeType::Data);
+    assert!(header.non_resident);
+    assert_eq!(header.name_length, 4);
+    assert_eq!(header.name_offset, 0x40);
+    assert!(header.has_flag(Flag::Sparse));
+    assert!(!header.has_flag(Flag::Compressed));
+    match header.residency {
+        Residency::NonResident {
+            start_vcn,
+            end_vcn,
+            run_list_offset,
+            compression_unit,
+            allocated_size,
+            real_size,
+            initialized_size,
+        } => {
+            assert_eq!(start_vcn, 0);
+            assert_eq!(end_vcn, 15);
+            assert_eq!(run_list_offset, 0x48);
+            assert_eq!(compression_unit, 0);
+            assert_eq!(allocated_size, 16 * 4096);
+            assert_eq!(real_size, 60000);
+            assert_eq!(initialized_size, 60000);
+        }
+        _ => panic!("expected a non-resident attribute"),
+    }
+}
+
+#[test]
+fn rejects_short_non_resident_header() {
+    let attr = common::non_resident(0x80, "", &[0x11, 0x10, 0x04], 16, 60000);
+    assert!(matches!(
+        AttributeHeader::parse(&attr[..0x20]),
+        Err(NtfsError::BufferTooSmall { needed: 0x40, .. })
+    ));
+}
+
+#[test]
+fn resident_value() {
+    let attr = common::resident(0x80, "", b"Hello, NTFS");
+    let attribute = Attribute::parse(&attr).unwrap();
+    assert!(attribute.is_resident());
+    assert_eq!(attribute.value(), Some(&b"Hello, NTFS"[..]));
+    assert_eq!(attribute.run_list(), None);
+}
+
+#[test]
+fn non_resident_run_list() {
+    let attr = common::non_resident(0x80, "", &[0x11, 0x10, 0x04], 16, 60000);
+    let attribute = Attribute::parse(&attr).unwrap();
+    assert!(!attribute.is_resident());
+    assert_eq!(attribute.value(), None);
+    assert_eq!(
+        &attribute.run_list().unwrap()[..4],
+        &[0x11, 0x10, 0x04, 0x00]
+    );
+}
+
+#[test]
+fn rejects_value_past_attribute() {
+    let mut attr = common::resident(0x80, "", b"Hello, NTFS");
+    attr[0x10..0x14].copy_from_slice(&0x1000u32.to_le_bytes());
+    assert!(matches!(
+        Attribute::parse(&attr),
+        Err(NtfsError::Corrupt(_))
+    ));
+}
+
+#[test]
+fn rejects_length_past_record() {
+    let mut attr = common::resident(0x80, "", b"Hello, NTFS");
+    attr[0x04..0x08].copy_from_slice(&0x1000u32.to_le_bytes());
+    assert!(matches!(
+        Attribute::parse(&attr),
+        Err(NtfsError::Corrupt(_))
+    ));
+}
+
+#[test]
+fn unnamed_attribute() {
+    let attr = common::resident(0x80, "", b"data");
+    let attr = Attribute::parse(&attr).unwrap();
+    assert_eq!(attr.name(), None);
+    assert!(attr.name_units().is_empty());
+}
+
+#[test]
+fn decodes_attribute_names() {
+    let ads = common::resident(0x80, "Zone.Identifier", b"[ZoneTransfer]");
+    let ads = Attribute::parse(&ads).unwrap();
+    assert_eq!(ads.name(), Some("Zone.Identifier"));
+    assert_eq!(ads.value(), Some(&b"[ZoneTransfer]"[..]));
+
+    let index = common::non_resident(0xA0, "$I30", &[0x11, 0x01, 0x20], 1, 4096);
+    let index = Attribute::parse(&index).unwrap();
+    assert!(index.has_name("$I30"));
+    assert!(!index.has_name("$J"));
+    assert_eq!(
+        index.name_units(),
+        "$I30".encode_utf16().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn unpaired_surrogates_are_replaced() {
+    let mut attr = common::resident(0x80, "ab", b"");
+    //Replace 'b' with a lone high surrogate.
+    attr[0x1A..0x1C].copy_from_slice(&0xD800u16.to_le_bytes());
+    let attr = Attribute::parse(&attr).unwrap();
+    assert_eq!(attr.name(), Some("a\u{FFFD}"));
+    assert_eq!(attr.name_units(), [0x61, 0xD800]);
+}
+
+#[test]
+fn attribute_type_round_trip() {
+    for attribute_type in [
+        AttributeType::StandardInformation,
+        AttributeType::FileName,
+        AttributeType::Data,
+        AttributeType::IndexAllocation,
+        AttributeType::LoggedUtilityStream,
+    ] {
+        let type_code = u32::from(attribute_type);
+        assert_eq!(AttributeType::from(type_code), attribute_type);
+    }
+    assert_eq!(u32::from(AttributeType::ObjectId), 0x40);
+    assert_eq!(AttributeType::Data.to_string(), "$DATA");
+    assert_eq!(AttributeType::from(0x18), AttributeType::Unknown(0x18));
+    assert_eq!(u32::from(AttributeType::Unknown(0x18)), 0x18);
+    assert_eq!(
+        AttributeType::Unknown(0x18).to_string(),
+        "attribute type 0x18"
+    );
+    assert!(AttributeType::Unknown(0x18) < AttributeType::FileName);
+    assert!(AttributeType::Unknown(0x1000) > AttributeType::LoggedUtilityStream);
+}
+
+#[test]
+fn attribute_yields_its_type() {
+    let attr = common::resident(0x30, "", &[0u8; 0x42]);
+    let attr = Attribute::parse(&attr).unwrap();
+    assert_eq!(attr.type_code(), AttributeType::FileName);
+
+    let attr = common::resident(0x1234, "", &[]);
+    let attr = Attribute::parse(&attr).unwrap();
+    assert_eq!(attr.type_code(), AttributeType::Unknown(0x1234));
+}
+
+#[test]
+fn attributes_after_an_unknown_type_are_read() {
+    let record = FileRecord::parse(&common::protected(common::record_with(
+        40,
+        0x0001,
+        &[
+            common::resident(0x30, "", &common::file_name(5, "a.txt", 1, 0x20)),
+            common::resident(0x1000, "", &[1, 2, 3, 4]),
+            common::resident(0x80, "", b"content"),
+        ],
+    )))
+    .unwrap();
+    let types: Vec<AttributeType> = record
+        .attributes()
+        .map(|attribute| attribute.unwrap().type_code())
+        .collect();
+    assert_eq!(
+        types,
+        [
+            AttributeType::FileName,
+            AttributeType::Unknown(0x1000),
+            AttributeType::Data
+        ]
+    );
+    assert_eq!(record.read_resident_data(), Some(b"content".as_slice()));
+}
@@ -0,0 +1,155 @@
+mod common;
+
+use ntfs::*;
+use std::io::Cursor;
+
+fn list() -> Vec<u8> {
+    [
+        common::attribute_list_entry(0x10, "", 0, 0x0001_0000_0000_001E, 0),
+        common::attribute_list_entry(0x30, "", 0, 0x0001_0000_0000_001E, 2),
+        common::attribute_list_entry(0x80, "", 0, 0x0001_0000_0000_001F, 0),
+        common::attribute_list_entry(0x80, "", 2, 0x0001_0000_0000_0020, 0),
+    ]
+    .concat()
+}
+
+///Record 30 is a file whose $DATA is split between extension records 31 and 32.
+///`attribute_list` is the $ATTRIBUTE_LIST attribute of record 30.
+fn volume(attribute_list: Vec<u8>) -> Vec<u8> {
+    let base = common::record_with(
+        30,
+        0x0001,
+        &[
+            common::resident(0x10, "", &common::standard_information(0, 0)),
+            attribute_list,
+            common::resident(0x30, "", &common::file_name(5, "big.bin", 1, 0)),
+        ],
+    );
+    let mut data = common::non_resident(0x80, "", &[0x11, 0x02, 0x40], 2, 8292);
+    common::set_vcns(&mut data, 0, 1);
+    //The first piece holds the sizes of the whole attribute.
+    data[0x28..0x30].copy_from_slice(&(3 * 4096u64).to_le_bytes());
+    let first = common::extension_record(31, 30, &[data]);
+    let mut data = common::non_resident(0x80, "", &[0x11, 0x01, 0x50], 1, 0);
+    common::set_vcns(&mut data, 2, 2);
+    let second = common::extension_record(32, 30, &[data]);
+
+    let mut records = vec![Vec::new(); 33];
+    records[0] = common::mft_record_0();
+    records[30] = common::protected(base);
+    records[31] = common::protected(first);
+    records[32] = common::protected(second);
+    let mut image = common::volume_with_mft(&records);
+    image[0x40 * 4096..0x42 * 4096].fill(0xA1);
+    image[0x50 * 4096..0x51 * 4096].fill(0xB2);
+    image
+}
+
+fn check_merged(ntfs: &mut Ntfs<Cursor<Vec<u8>>>) {
+    let attributes = ntfs.attributes_of(30).unwrap();
+    let types: Vec<u32> = attributes.iter().map(|a| u32::from(a.type_code)).collect();
+    assert_eq!(types, [0x10, 0x30, 0x80]);
+
+    let data = &attributes[2];
+    assert_eq!(
+        data.runs,
+        [
+            DataRun {
+                lcn: Some(0x40),
+                length_clusters: 2
+            },
+            DataRun {
+                lcn: Some(0x50),
+                length_clusters: 1
+            },
+        ]
+    );
+    let content = ntfs.read_merged_attribute(data).unwrap();
+    assert_eq!(content.len(), 8292);
+    assert!(content[..8192].iter().all(|b| *b == 0xA1));
+    assert!(content[8192..].iter().all(|b| *b == 0xB2));
+}
+
+#[test]
+fn parses_entries() {
+    let list = [
+        common::attribute_list_entry(0x10, "", 0, 0x0001_0000_0000_001E, 0),
+        common::attribute_list_entry(0xA0, "$I30", 4, 0x0003_0000_0000_0021, 5),
+    ]
+    .concat();
+    let entries = parse_attribute_list(&list).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].type_code, AttributeType::StandardInformation);
+    assert_eq!(entries[0].name, None);
+    assert_eq!(entries[1].type_code, AttributeType::IndexAllocation);
+    assert_eq!(entries[1].name.as_deref(), Some("$I30"));
+    assert_eq!(entries[1].name_length, 4);
+    assert_eq!(entries[1].name_offset, 0x1A);
+    assert_eq!(entries[1].starting_vcn, 4);
+    assert_eq!(entries[1].file_reference.record_number, 0x21);
+    assert_eq!(entries[1].file_reference.sequence_number, 3);
+    assert_eq!(entries[1].attribute_id, 5);
+}
+
+#[test]
+fn rejects_truncated_entry() {
+    let mut list = common::attribute_list_entry(0x80, "", 0, 0x1E, 0);
+    list[0x04] = 0xFF;
+    assert!(matches!(
+        parse_attribute_list(&list),
+        Err(NtfsError::Corrupt(_))
+    ));
+}
+
+#[test]
+fn merges_split_data_attribute() {
+    let image = volume(common::resident(0x20, "", &list()));
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    check_merged(&mut ntfs);
+}
+
+#[test]
+fn non_resident_attribute_list() {
+    let list = list();
+    let attribute_list = common::non_resident(0x20, "", &[0x11, 0x01, 0x60], 1, list.len() as u64);
+    let mut image = volume(attribute_list);
+    image[0x60 * 4096..0x60 * 4096 + list.len()].copy_from_slice(&list);
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+
+    let base = ntfs.file_record(30).unwrap();
+    assert_eq!(ntfs.attribute_list(&base).unwrap().unwrap().len(), 4);
+    check_merged(&mut ntfs);
+}
+
+#[test]
+fn record_without_attribute_list() {
+    let image = common::volume_with_mft(&[common::mft_record_0()]);
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    let record = ntfs.file_record(0).unwrap();
+    assert!(ntfs.attribute_list(&record).unwrap().is_none());
+    let types: Vec<u32> = ntfs
+        .attributes_of(0)
+        .unwrap()
+        .iter()
+        .map(|a| u32::from(a.type_code))
+        .collect();
+    assert_eq!(types, [0x10, 0x30, 0x80, 0xB0]);
+}
+
+#[test]
+fn read_data_follows_two_entry_attribute_list() {
+    let list = [
+        common::attribute_list_entry(0x80, "", 0, 0x0001_0000_0000_001F, 0),
+        common::attribute_list_entry(0x80, "", 2, 0x0001_0000_0000_0020, 0),
+    ]
+    .concat();
+    assert_eq!(parse_attribute_list(&list).unwrap().len(), 2);
+
+    let image = volume(common::resident(0x20, "", &list));
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    let base = ntfs.file_record(30).unwrap();
+    let content = ntfs.read_data(&base, None).unwrap();
+    assert_eq!(content.len(), 8292);
+    assert!(content[..8192].iter().all(|b| *b == 0xA1));
+    assert!(content[8192..].iter().all(|b| *b == 0xB2));
+}
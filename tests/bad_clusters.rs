@@ -0,0 +1,111 @@
+//Single bad cluster ranges are compared as one element lists.
+#![allow(clippy::single_range_in_vec_init)]
+mod common;
+
+use ntfs::*;
+use std::io::Cursor;
+
+fn volume(bad: Vec<u8>) -> Vec<u8> {
+    volume_with_files(bad, Vec::new())
+}
+
+///A volume with $BadClus and `files` from record 40 on.
+fn volume_with_files(bad: Vec<u8>, files: Vec<Vec<u8>>) -> Vec<u8> {
+    let mut records = vec![Vec::new(); 40];
+    records.extend(files.into_iter().map(common::protected));
+    records[0] = common::mft_record_0();
+    records[8] = common::protected(common::record_with(
+        8,
+        0x0001,
+        &[
+            common::resident(0x30, "", &common::file_name(5, "$BadClus", 3, 0x06)),
+            common::resident(0x80, "", &[]),
+            bad,
+        ],
+    ));
+    common::volume_with_mft(&records)
+}
+
+#[test]
+fn lists_bad_region() {
+    //0x50 good clusters, 3 bad ones at LCN 0x50 and 0xAD good ones up to the end of the 256 cluster volume.
+    let runs = [0x01, 0x50, 0x11, 0x03, 0x50, 0x01, 0xAD];
+    let image = volume(common::non_resident(0x80, "$Bad", &runs, 256, 256 * 4096));
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    assert_eq!(ntfs.bad_clusters().unwrap(), [0x50..0x53]);
+    assert!(ntfs.has_bad_clusters().unwrap());
+}
+
+#[test]
+fn no_bad_clusters() {
+    let image = volume(common::non_resident(
+        0x80,
+        "$Bad",
+        &[0x02, 0x00, 0x01],
+        256,
+        256 * 4096,
+    ));
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    assert!(ntfs.bad_clusters().unwrap().is_empty());
+    assert!(!ntfs.has_bad_clusters().unwrap());
+    assert!(ntfs
+        .master_file_table()
+        .unwrap()
+        .files_on_bad_clusters()
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn missing_bad_stream() {
+    let image = volume(common::resident(0x80, "$Other", &[]));
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    assert!(matches!(ntfs.bad_clusters(), Err(NtfsError::NotFound(_))));
+}
+
+#[test]
+fn finds_files_on_bad_clusters() {
+    let runs = [0x01, 0x50, 0x11, 0x03, 0x50, 0x01, 0xAD];
+    let data = |name: &str, runs: &[u8], clusters: u64| {
+        common::non_resident(0x80, name, runs, clusters, clusters * 4096)
+    };
+    let files = vec![
+        //Clusters 0x4F to 0x51, the last two of them bad.
+        common::record_with(40, 0x0001, &[data("", &[0x11, 0x03, 0x4F], 3)]),
+        common::record_with(41, 0x0001, &[data("", &[0x11, 0x02, 0x60], 2)]),
+        //Record 43's alternate stream in its extension record 42 covers 0x52 and 0x30.
+        common::extension_record(
+            42,
+            43,
+            &[data("ads", &[0x11, 0x01, 0x52, 0x11, 0x01, 0xDE], 2)],
+        ),
+        common::record_with(43, 0x0001, &[common::resident(0x80, "", b"hi")]),
+    ];
+    let image = volume_with_files(
+        common::non_resident(0x80, "$Bad", &runs, 256, 256 * 4096),
+        files,
+    );
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    let files = ntfs
+        .master_file_table()
+        .unwrap()
+        .files_on_bad_clusters()
+        .unwrap();
+    assert_eq!(
+        files,
+        [
+            BadClusterFile {
+                record_number: 40,
+                type_code: AttributeType::Data,
+                name: None,
+                bad_clusters: vec![0x50..0x52],
+            },
+            BadClusterFile {
+                record_number: 43,
+                type_code: AttributeType::Data,
+                name: Some(String::from("ads")),
+                bad_clusters: vec![0x52..0x53],
+            },
+        ]
+    );
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use ntfs::*;
+use std::io::Cursor;
+
+///A 1 MB volume of 256 clusters whose $Bitmap holds `bitmap`.
+fn volume(bitmap: &[u8]) -> Vec<u8> {
+    let record = common::protected(common::record_with(
+        6,
+        0x0001,
+        &[
+            common::resident(0x30, "", &common::file_name(5, "$Bitmap", 3, 0x06)),
+            common::resident(0x80, "", bitmap),
+        ],
+    ));
+    let mut image = common::volume_with_mft(&[common::mft_record_0()]);
+    image[40..48].copy_from_slice(&2048u64.to_le_bytes());
+    let offset = 4 * 4096 + 6 * 1024;
+    image[offset..offset + 1024].copy_from_slice(&record);
+    image
+}
+
+#[test]
+fn alternating_clusters() {
+    let mut ntfs = Ntfs::new(Cursor::new(volume(&[0x55; 32]))).unwrap();
+    let bitmap = ntfs.cluster_bitmap().unwrap();
+    assert_eq!(bitmap.cluster_count(), 256);
+    assert!(bitmap.is_allocated(0));
+    assert!(!bitmap.is_allocated(1));
+    assert!(bitmap.is_allocated(254));
+    assert!(!bitmap.is_allocated(255));
+    assert_eq!(bitmap.free_cluster_count(), 128);
+    assert_eq!(bitmap.allocated_cluster_count(), 128);
+
+    let runs: Vec<(u64, u64)> = bitmap.free_runs().collect();
+    assert_eq!(runs.len(), 128);
+    assert!(runs
+        .iter()
+        .enumerate()
+        .all(|(i, run)| *run == (2 * i as u64 + 1, 1)));
+}
+
+#[test]
+fn free_runs_span_bytes() {
+    let mut bytes = vec![0xFF; 32];
+    bytes[1] = 0xF0;
+    bytes[2] = 0x00;
+    bytes[3] = 0x01;
+    bytes[31] = 0x7F;
+    let bitmap = ClusterBitmap::new(bytes, 256);
+    assert_eq!(
+        bitmap.free_runs().collect::<Vec<_>>(),
+        vec![(8, 4), (16, 8), (25, 7), (255, 1)]
+    );
+    assert_eq!(bitmap.free_cluster_count(), 20);
+}
+
+#[test]
+fn bits_past_the_volume() {
+    //12 clusters, with the padding bits of the second byte left clear.
+    let bitmap = ClusterBitmap::new(vec![0xFF, 0x07], 12);
+    assert_eq!(bitmap.free_cluster_count(), 1);
+    assert_eq!(bitmap.free_runs().collect::<Vec<_>>(), vec![(11, 1)]);
+    assert!(bitmap.is_allocated(12));
+    assert!(bitmap.is_allocated(1000));
+
+    //A bitmap shorter than the volume only describes its own bits.
+    let bitmap = ClusterBitmap::new(vec![0x00], 100);
+    assert_eq!(bitmap.cluster_count(), 8);
+    assert_eq!(bitmap.free_runs().collect::<Vec<_>>(), vec![(0, 8)]);
+}
+
+#[test]
+fn aliases() {
+    let bitmap = ClusterBitmap::new(vec![0x0F], 8);
+    assert_eq!(bitmap.free_clusters(), 4);
+    assert_eq!(bitmap.used_clusters(), 4);
+}
+
+///A volume whose $Bitmap is 17 clusters at LCN 0x40, one cluster more than a chunk.
+///Clusters 3 and 0x7FFF0 to 0x8000F are free, the last one crossing into the second chunk,
+///and the volume ends 5 clusters before the bitmap does.
+fn large_bitmap_volume() -> (Vec<u8>, u64) {
+    let size = 17 * 4096;
+    assert!(size > BITMAP_CHUNK_SIZE);
+    let mut bitmap = vec![0xFF; size];
+    bitmap[0] = 0xF7;
+    bitmap[0xFFFE..0x10002].fill(0);
+    bitmap[size - 1] = 0x07;
+    let total_clusters = size as u64 * 8 - 5;
+
+    let record = common::protected(common::record_with(
+        6,
+        0x0001,
+        &[
+            common::resident(0x30, "", &common::file_name(5, "$Bitmap", 3, 0x06)),
+            common::non_resident(0x80, "", &[0x11, 17, 0x40], 17, size as u64),
+        ],
+    ));
+    let mut image = common::volume_with_mft(&[common::mft_record_0()]);
+    image[40..48].copy_from_slice(&(total_clusters * 8).to_le_bytes());
+    let offset = 4 * 4096 + 6 * 1024;
+    image[offset..offset + 1024].copy_from_slice(&record);
+    image[0x40 * 4096..0x40 * 4096 + size].copy_from_slice(&bitmap);
+    (image, total_clusters)
+}
+
+///The bitmap covers far more clusters than the image holds, so the length check is skipped.
+fn open_large_bitmap_volume(image: Vec<u8>) -> Ntfs<Cursor<Vec<u8>>> {
+    let options = ParseOptions {
+        check_length: false,
+        ..ParseOptions::default()
+    };
+    Ntfs::new_with_options(Cursor::new(image), options).unwrap()
+}
+
+#[test]
+fn free_extents_across_chunks() {
+    let (image, _) = large_bitmap_volume();
+    let mut ntfs = open_large_bitmap_volume(image);
+    let extents: Vec<(u64, u64)> = ntfs.free_extents().unwrap().map(Result::unwrap).collect();
+    assert_eq!(extents, vec![(3, 1), (0xFFFE * 8, 32)]);
+    assert_eq!(
+        ntfs.cluster_bitmap()
+            .unwrap()
+            .free_runs()
+            .collect::<Vec<_>>(),
+        extents
+    );
+}
+
+#[test]
+fn usage() {
+    let (image, total_clusters) = large_bitmap_volume();
+    let mut ntfs = open_large_bitmap_volume(image);
+    let usage = ntfs.usage().unwrap();
+    assert_eq!(usage.total_clusters, total_clusters);
+    assert_eq!(usage.free_clusters, 33);
+    assert_eq!(usage.used_clusters(), total_clusters - 33);
+    assert_eq!(usage.free_bytes(), 33 * 4096);
+    assert_eq!(usage.used_bytes() + usage.free_bytes(), usage.total_bytes());
+}
@@ -0,0 +1,544 @@
+//! Builders for synthetic NTFS structures used by the integration tests.
+#![allow(dead_code)]
+
+///A boot sector using the typical values from the `partition_boot_sector` docs.
+pub fn boot_sector() -> [u8; 512] {
+    let mut buf = [0u8; 512];
+    buf[0..3].copy_from_slice(&[0xEB, 0x52, 0x90]);
+    buf[3..11].copy_from_slice(b"NTFS    ");
+    buf[11..13].copy_from_slice(&0x0200u16.to_le_bytes());
+    buf[13] = 0x08;
+    buf[21] = 0xF8;
+    buf[24..26].copy_from_slice(&0x003Fu16.to_le_bytes());
+    buf[26..28].copy_from_slice(&0x00FFu16.to_le_bytes());
+    buf[28..32].copy_from_slice(&0x0000003Fu32.to_le_bytes());
+    buf[36..40].copy_from_slice(&0x00800080u32.to_le_bytes());
+    buf[40..48].copy_from_slice(&0x00000000007FF54Au64.to_le_bytes());
+    buf[48..56].copy_from_slice(&0x0000000000000004u64.to_le_bytes());
+    buf[56..64].copy_from_slice(&0x000000000007FF54u64.to_le_bytes());
+    buf[64] = 0xF6;
+    buf[68] = 0x01;
+    buf[72..80].copy_from_slice(&0x1C741BC9741BA514u64.to_le_bytes());
+    buf[510..512].copy_from_slice(&0xAA55u16.to_le_bytes());
+    buf
+}
+
+///A boot sector for a native 4K sector (4Kn) drive with 4K clusters and 4K FILE records.
+pub fn boot_sector_4kn() -> [u8; 512] {
+    let mut buf = boot_sector();
+    buf[11..13].copy_from_slice(&4096u16.to_le_bytes());
+    buf[13] = 0x01;
+    buf[40..48].copy_from_slice(&0x000FFEA9u64.to_le_bytes());
+    buf[64] = 0xF4;
+    buf
+}
+
+///A 1024 byte FILE record header with the attributes starting at 0x38.
+pub fn file_record(record_number: u32, flags: u16) -> Vec<u8> {
+    let mut buf = vec![0u8; 1024];
+    buf[0..4].copy_from_slice(b"FILE");
+    buf[0x04..0x06].copy_from_slice(&0x30u16.to_le_bytes());
+    buf[0x06..0x08].copy_from_slice(&3u16.to_le_bytes());
+    buf[0x08..0x10].copy_from_slice(&0x1234_5678u64.to_le_bytes());
+    buf[0x10..0x12].copy_from_slice(&1u16.to_le_bytes());
+    buf[0x12..0x14].copy_from_slice(&1u16.to_le_bytes());
+    buf[0x14..0x16].copy_from_slice(&0x38u16.to_le_bytes());
+    buf[0x16..0x18].copy_from_slice(&flags.to_le_bytes());
+    buf[0x18..0x1C].copy_from_slice(&0x40u32.to_le_bytes());
+    buf[0x1C..0x20].copy_from_slice(&1024u32.to_le_bytes());
+    buf[0x28..0x2A].copy_from_slice(&1u16.to_le_bytes());
+    buf[0x2C..0x30].copy_from_slice(&record_number.to_le_bytes());
+    buf[0x38..0x3C].copy_from_slice(&0xFFFF_FFFFu32.to_le_bytes());
+    buf
+}
+
+///Applies the update sequence protection NTFS adds when writing a FILE or INDX record.
+pub fn protect(buf: &mut [u8]) {
+    let offset = u16::from_le_bytes([buf[4], buf[5]]) as usize;
+    let count = u16::from_le_bytes([buf[6], buf[7]]) as usize;
+    let usn = [0x07, 0x00];
+    buf[offset..offset + 2].copy_from_slice(&usn);
+    for sector in 0..count - 1 {
+        let end = (sector + 1) * 512 - 2;
+        let saved = offset + 2 + sector * 2;
+        buf.copy_within(end..end + 2, saved);
+        buf[end..end + 2].copy_from_slice(&usn);
+    }
+}
+
+///A protected FILE record, ready to be parsed.
+pub fn protected(mut buf: Vec<u8>) -> Vec<u8> {
+    protect(&mut buf);
+    buf
+}
+
+///Shrinks the boot sector's total sectors to fit `image`, less the backup boot sector at the end.
+pub fn fit_total_sectors(image: &mut [u8]) {
+    let total_sectors = image.len() as u64 / 512 - 1;
+    image[40..48].copy_from_slice(&total_sectors.to_le_bytes());
+}
+
+///A 1 MB volume with the typical boot sector, where every cluster after the first
+///is filled with its own cluster number.
+pub fn numbered_volume() -> Vec<u8> {
+    let mut image = boot_sector().to_vec();
+    image.resize(1024 * 1024, 0);
+    fit_total_sectors(&mut image);
+    for (lcn, cluster) in image.chunks_mut(4096).enumerate().skip(1) {
+        cluster.fill(lcn as u8);
+    }
+    image
+}
+
+fn utf16(name: &str) -> Vec<u8> {
+    name.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+fn align8(buf: &mut Vec<u8>) {
+    buf.resize(buf.len().next_multiple_of(8), 0);
+}
+
+///A resident attribute with an optional name.
+pub fn resident(type_code: u32, name: &str, value: &[u8]) -> Vec<u8> {
+    let name = utf16(name);
+    let mut attr = vec![0u8; 0x18];
+    attr.extend_from_slice(&name);
+    align8(&mut attr);
+    let value_offset = attr.len();
+    attr.extend_from_slice(value);
+    align8(&mut attr);
+
+    attr[0x00..0x04].copy_from_slice(&type_code.to_le_bytes());
+    let len = attr.len() as u32;
+    attr[0x04..0x08].copy_from_slice(&len.to_le_bytes());
+    attr[0x09] = (name.len() / 2) as u8;
+    attr[0x0A..0x0C].copy_from_slice(&0x18u16.to_le_bytes());
+    attr[0x10..0x14].copy_from_slice(&(value.len() as u32).to_le_bytes());
+    attr[0x14..0x16].copy_from_slice(&(value_offset as u16).to_le_bytes());
+    attr
+}
+
+///A non-resident attribute. `runs` is the encoded run list, without the terminating zero.
+pub fn non_resident(
+    type_code: u32,
+    name: &str,
+    runs: &[u8],
+    clusters: u64,
+    real_size: u64,
+) -> Vec<u8> {
+    let name = utf16(name);
+    let mut attr = vec![0u8; 0x40];
+    attr.extend_from_slice(&name);
+    align8(&mut attr);
+    let runs_offset = attr.len();
+    attr.extend_from_slice(runs);
+    attr.push(0);
+    align8(&mut attr);
+
+    attr[0x00..0x04].copy_from_slice(&type_code.to_le_bytes());
+    let len = attr.len() as u32;
+    attr[0x04..0x08].copy_from_slice(&len.to_le_bytes());
+    attr[0x08] = 1;
+    attr[0x09] = (name.len() / 2) as u8;
+    attr[0x0A..0x0C].copy_from_slice(&0x40u16.to_le_bytes());
+    attr[0x18..0x20].copy_from_slice(&clusters.saturating_sub(1).to_le_bytes());
+    attr[0x20..0x22].copy_from_slice(&(runs_offset as u16).to_le_bytes());
+    attr[0x28..0x30].copy_from_slice(&(clusters * 4096).to_le_bytes());
+    attr[0x30..0x38].copy_from_slice(&real_size.to_le_bytes());
+    attr[0x38..0x40].copy_from_slice(&real_size.to_le_bytes());
+    attr
+}
+
+///An unprotected FILE record holding `attributes`, numbering their attribute ids.
+pub fn record_with(record_number: u32, flags: u16, attributes: &[Vec<u8>]) -> Vec<u8> {
+    let mut buf = file_record(record_number, flags);
+    let mut offset = 0x38;
+    for (id, attr) in attributes.iter().enumerate() {
+        buf[offset..offset + attr.len()].copy_from_slice(attr);
+        buf[offset + 0x0E..offset + 0x10].copy_from_slice(&(id as u16).to_le_bytes());
+        offset += attr.len();
+    }
+    buf[offset..offset + 4].copy_from_slice(&0xFFFF_FFFFu32.to_le_bytes());
+    buf[offset + 4..offset + 8].fill(0);
+    buf[0x18..0x1C].copy_from_slice(&(offset as u32 + 8).to_le_bytes());
+    buf[0x28..0x2A].copy_from_slice(&(attributes.len() as u16).to_le_bytes());
+    buf
+}
+
+///A $STANDARD_INFORMATION value in the NTFS 3.x layout.
+pub fn standard_information(time: u64, dos_flags: u32) -> Vec<u8> {
+    let mut value = vec![0u8; 72];
+    for field in 0..4 {
+        value[field * 8..field * 8 + 8].copy_from_slice(&time.to_le_bytes());
+    }
+    value[0x20..0x24].copy_from_slice(&dos_flags.to_le_bytes());
+    value
+}
+
+///A $FILE_NAME value. `parent` is the raw file reference of the parent directory.
+pub fn file_name(parent: u64, name: &str, namespace: u8, flags: u32) -> Vec<u8> {
+    let units = utf16(name);
+    let mut value = vec![0u8; 0x42];
+    value[0x00..0x08].copy_from_slice(&parent.to_le_bytes());
+    for field in 0..4 {
+        let time = 0x01D0_0000_0000_0000u64;
+        value[0x08 + field * 8..0x10 + field * 8].copy_from_slice(&time.to_le_bytes());
+    }
+    value[0x38..0x3C].copy_from_slice(&flags.to_le_bytes());
+    value[0x40] = (units.len() / 2) as u8;
+    value[0x41] = namespace;
+    value.extend_from_slice(&units);
+    value
+}
+
+///A $MFT record 0 laid out the way Windows writes it: $STANDARD_INFORMATION, $FILE_NAME,
+///a non-resident $DATA of 64 records at cluster 4 and a non-resident $BITMAP.
+pub fn mft_record_0() -> Vec<u8> {
+    mft_record_0_with_runs(&[0x11, 0x10, 0x04], 16)
+}
+
+///A $MFT record 0 whose $DATA attribute covers `clusters` clusters through `runs`.
+pub fn mft_record_0_with_runs(runs: &[u8], clusters: u64) -> Vec<u8> {
+    protected(record_with(
+        0,
+        0x0001,
+        &[
+            resident(0x10, "", &standard_information(0x01D0_0000_0000_0000, 0x06)),
+            resident(0x30, "", &file_name(0x0005_0000_0000_0005, "$MFT", 3, 0x06)),
+            non_resident(0x80, "", runs, clusters, clusters * 4096),
+            non_resident(0xB0, "", &[0x11, 0x01, 0x40], 1, 8),
+        ],
+    ))
+}
+
+///A 1 MB volume with the typical boot sector and `records` written from the start of the $MFT at cluster 4.
+pub fn volume_with_mft(records: &[Vec<u8>]) -> Vec<u8> {
+    let mut image = boot_sector().to_vec();
+    image.resize(1024 * 1024, 0);
+    fit_total_sectors(&mut image);
+    for (i, record) in records.iter().enumerate() {
+        let offset = 4 * 4096 + i * 1024;
+        image[offset..offset + record.len()].copy_from_slice(record);
+    }
+    image
+}
+
+///An index entry pointing at `file_reference` with a $FILE_NAME stream.
+pub fn index_entry(file_reference: u64, file_name: &[u8]) -> Vec<u8> {
+    let mut entry = vec![0u8; 0x10];
+    entry.extend_from_slice(file_name);
+    align8(&mut entry);
+    entry[0x00..0x08].copy_from_slice(&file_reference.to_le_bytes());
+    let len = entry.len() as u16;
+    entry[0x08..0x0A].copy_from_slice(&len.to_le_bytes());
+    entry[0x0A..0x0C].copy_from_slice(&(file_name.len() as u16).to_le_bytes());
+    entry
+}
+
+///The entry that ends every index node.
+pub fn last_index_entry() -> Vec<u8> {
+    let mut entry = vec![0u8; 0x10];
+    entry[0x08] = 0x10;
+    entry[0x0C] = 0x02;
+    entry
+}
+
+///An $INDEX_ROOT value for a $FILE_NAME index holding `entries`, followed by the last entry.
+pub fn index_root(entries: &[Vec<u8>]) -> Vec<u8> {
+    let mut value = vec![0u8; 0x20];
+    for entry in entries {
+        value.extend_from_slice(entry);
+    }
+    value.extend_from_slice(&last_index_entry());
+    let entries_size = (value.len() - 0x10) as u32;
+
+    value[0x00..0x04].copy_from_slice(&0x30u32.to_le_bytes());
+    value[0x04..0x08].copy_from_slice(&1u32.to_le_bytes());
+    value[0x08..0x0C].copy_from_slice(&4096u32.to_le_bytes());
+    value[0x0C] = 1;
+    value[0x10..0x14].copy_from_slice(&0x10u32.to_le_bytes());
+    value[0x14..0x18].copy_from_slice(&entries_size.to_le_bytes());
+    value[0x18..0x1C].copy_from_slice(&entries_size.to_le_bytes());
+    value
+}
+
+///An $ATTRIBUTE_LIST entry for the attribute `attribute_id` held in `file_reference`.
+pub fn attribute_list_entry(
+    type_code: u32,
+    name: &str,
+    starting_vcn: u64,
+    file_reference: u64,
+    attribute_id: u16,
+) -> Vec<u8> {
+    let name = utf16(name);
+    let mut entry = vec![0u8; 0x1A];
+    entry.extend_from_slice(&name);
+    align8(&mut entry);
+    entry[0x00..0x04].copy_from_slice(&type_code.to_le_bytes());
+    let len = entry.len() as u16;
+    entry[0x04..0x06].copy_from_slice(&len.to_le_bytes());
+    entry[0x06] = (name.len() / 2) as u8;
+    entry[0x07] = 0x1A;
+    entry[0x08..0x10].copy_from_slice(&starting_vcn.to_le_bytes());
+    entry[0x10..0x18].copy_from_slice(&file_reference.to_le_bytes());
+    entry[0x18..0x1A].copy_from_slice(&attribute_id.to_le_bytes());
+    entry
+}
+
+///Sets the VCN range of a non-resident attribute built with [`non_resident`].
+pub fn set_vcns(attr: &mut [u8], start_vcn: u64, end_vcn: u64) {
+    attr[0x10..0x18].copy_from_slice(&start_vcn.to_le_bytes());
+    attr[0x18..0x20].copy_from_slice(&end_vcn.to_le_bytes());
+}
+
+///An unprotected extension record of `base`, holding `attributes`.
+pub fn extension_record(record_number: u32, base: u64, attributes: &[Vec<u8>]) -> Vec<u8> {
+    let mut record = record_with(record_number, 0x0001, attributes);
+    record[0x20..0x28].copy_from_slice(&base.to_le_bytes());
+    record
+}
+
+///A protected 4096 byte INDX block at `vcn` holding `entries`, followed by the last entry.
+pub fn index_block(vcn: u64, entries: &[Vec<u8>]) -> Vec<u8> {
+    let mut block = vec![0u8; 0x40];
+    for entry in entries {
+        block.extend_from_slice(entry);
+    }
+    block.extend_from_slice(&last_index_entry());
+    let entries_size = (block.len() - 0x18) as u32;
+    block.resize(4096, 0);
+
+    block[0..4].copy_from_slice(b"INDX");
+    block[0x04..0x06].copy_from_slice(&0x28u16.to_le_bytes());
+    block[0x06..0x08].copy_from_slice(&9u16.to_le_bytes());
+    block[0x10..0x18].copy_from_slice(&vcn.to_le_bytes());
+    block[0x18..0x1C].copy_from_slice(&0x28u32.to_le_bytes());
+    block[0x1C..0x20].copy_from_slice(&entries_size.to_le_bytes());
+    block[0x20..0x24].copy_from_slice(&(4096u32 - 0x18).to_le_bytes());
+    protected(block)
+}
+
+///An index entry for `name` in directory `parent`. Both are record numbers with sequence number 1.
+pub fn child(record_number: u64, parent: u64, name: &str, namespace: u8, flags: u32) -> Vec<u8> {
+    let sequence = 0x0001_0000_0000_0000;
+    index_entry(
+        sequence | record_number,
+        &file_name(sequence | parent, name, namespace, flags),
+    )
+}
+
+///A protected directory record with a small $I30 index holding `entries`.
+pub fn directory_record(
+    record_number: u32,
+    parent: u64,
+    name: &str,
+    entries: &[Vec<u8>],
+) -> Vec<u8> {
+    protected(record_with(
+        record_number,
+        0x0003,
+        &[
+            resident(0x30, "", &file_name(parent, name, 1, 0x1000_0000)),
+            resident(0x90, "$I30", &index_root(entries)),
+        ],
+    ))
+}
+
+///A protected file record named `name` in `parent`.
+pub fn file_in(record_number: u32, parent: u64, name: &str) -> Vec<u8> {
+    protected(record_with(
+        record_number,
+        0x0001,
+        &[resident(0x30, "", &file_name(parent, name, 1, 0x20))],
+    ))
+}
+
+///A volume with a small directory tree:
+///
+///```text
+///\ (5)
+///├── readme.txt (31), also indexed under its DOS name README~1.TXT
+///└── Windows (30)
+///    ├── System32 (32)
+///    │   ├── kernel32.dll (34)
+///    │   └── loop (35), a junction back to the root
+///    └── notepad.exe (33)
+///```
+pub fn tree_volume() -> Vec<u8> {
+    const DIR: u32 = 0x1000_0000;
+    const SEQ: u64 = 0x0001_0000_0000_0000;
+    let mut records = vec![Vec::new(); 36];
+    records[0] = mft_record_0();
+    records[5] = directory_record(
+        5,
+        SEQ | 5,
+        ".",
+        &[
+            child(5, 5, ".", 3, DIR),
+            child(31, 5, "readme.txt", 1, 0x20),
+            child(31, 5, "README~1.TXT", 2, 0x20),
+            child(30, 5, "Windows", 3, DIR),
+        ],
+    );
+    records[30] = directory_record(
+        30,
+        SEQ | 5,
+        "Windows",
+        &[
+            child(33, 30, "notepad.exe", 3, 0x20),
+            child(32, 30, "System32", 1, DIR),
+        ],
+    );
+    records[31] = file_in(31, SEQ | 5, "readme.txt");
+    records[32] = directory_record(
+        32,
+        SEQ | 30,
+        "System32",
+        &[
+            child(34, 32, "kernel32.dll", 3, 0x20),
+            child(35, 32, "loop", 3, DIR | 0x400),
+        ],
+    );
+    records[33] = file_in(33, SEQ | 30, "notepad.exe");
+    records[34] = file_in(34, SEQ | 32, "kernel32.dll");
+    records[35] = directory_record(35, SEQ | 32, "loop", &[child(5, 35, "root", 3, DIR)]);
+    volume_with_mft(&records)
+}
+
+///A $VOLUME_INFORMATION value.
+pub fn volume_information(major: u8, minor: u8, flags: u16) -> Vec<u8> {
+    let mut value = vec![0u8; 0x0C];
+    value[0x08] = major;
+    value[0x09] = minor;
+    value[0x0A..0x0C].copy_from_slice(&flags.to_le_bytes());
+    value
+}
+
+///A volume whose $Volume record has the label `label` and the given $VOLUME_INFORMATION flags.
+pub fn labelled_volume(label: &str, flags: u16) -> Vec<u8> {
+    let volume = protected(record_with(
+        3,
+        0x0001,
+        &[
+            resident(0x30, "", &file_name(5, "$Volume", 3, 0x06)),
+            resident(0x60, "", &utf16(label)),
+            resident(0x70, "", &volume_information(3, 1, flags)),
+        ],
+    ));
+    volume_with_mft(&[mft_record_0(), Vec::new(), Vec::new(), volume])
+}
+
+///A symbolic link or junction $REPARSE_POINT value with the substitute name before the print name.
+///`flags` is only written for symbolic links.
+pub fn reparse_link(
+    tag: u32,
+    substitute_name: &str,
+    print_name: &str,
+    flags: Option<u32>,
+) -> Vec<u8> {
+    let substitute_name = utf16(substitute_name);
+    let print_name = utf16(print_name);
+    let mut data = Vec::new();
+    data.extend_from_slice(&0u16.to_le_bytes());
+    data.extend_from_slice(&(substitute_name.len() as u16).to_le_bytes());
+    data.extend_from_slice(&(substitute_name.len() as u16).to_le_bytes());
+    data.extend_from_slice(&(print_name.len() as u16).to_le_bytes());
+    if let Some(flags) = flags {
+        data.extend_from_slice(&flags.to_le_bytes());
+    }
+    data.extend_from_slice(&substitute_name);
+    data.extend_from_slice(&print_name);
+
+    let mut value = Vec::new();
+    value.extend_from_slice(&tag.to_le_bytes());
+    value.extend_from_slice(&(data.len() as u16).to_le_bytes());
+    value.extend_from_slice(&0u16.to_le_bytes());
+    value.extend_from_slice(&data);
+    value
+}
+
+///A protected directory link named `name` in `parent`, pointing at `substitute_name`.
+///Symbolic links get `Some(flags)`, junctions `None`.
+pub fn directory_link(
+    record_number: u32,
+    parent: u64,
+    name: &str,
+    substitute_name: &str,
+    flags: Option<u32>,
+) -> Vec<u8> {
+    let tag = if flags.is_some() {
+        0xA000_000C
+    } else {
+        0xA000_0003
+    };
+    protected(record_with(
+        record_number,
+        0x0003,
+        &[
+            resident(0x30, "", &file_name(parent, name, 1, 0x1000_0400)),
+            resident(0x90, "$I30", &index_root(&[])),
+            resident(0xC0, "", &reparse_link(tag, substitute_name, "", flags)),
+        ],
+    ))
+}
+
+///A volume with links between its directories:
+///
+///```text
+///\ (5)
+///├── a (47), a symbolic link to \??\C:\b
+///├── b (48), a symbolic link to \??\C:\a
+///├── Documents and Settings (43), a junction to \??\C:\Users
+///└── Users (40)
+///    ├── escape (46), a relative symbolic link to ..\..\..
+///    ├── link (45), a symbolic link to \??\D:\Data
+///    └── Public (41)
+///        ├── notes.txt (42)
+///        └── Up (44), a relative symbolic link to ..
+///```
+pub fn linked_volume() -> Vec<u8> {
+    const DIR: u32 = 0x1000_0000;
+    const LINK: u32 = 0x1000_0400;
+    const SEQ: u64 = 0x0001_0000_0000_0000;
+    let mut records = vec![Vec::new(); 49];
+    records[0] = mft_record_0();
+    records[5] = directory_record(
+        5,
+        SEQ | 5,
+        ".",
+        &[
+            child(5, 5, ".", 3, DIR),
+            child(47, 5, "a", 1, LINK),
+            child(48, 5, "b", 1, LINK),
+            child(43, 5, "Documents and Settings", 1, LINK),
+            child(40, 5, "Users", 1, DIR),
+        ],
+    );
+    records[40] = directory_record(
+        40,
+        SEQ | 5,
+        "Users",
+        &[
+            child(46, 40, "escape", 1, LINK),
+            child(45, 40, "link", 1, LINK),
+            child(41, 40, "Public", 1, DIR),
+        ],
+    );
+    records[41] = directory_record(
+        41,
+        SEQ | 40,
+        "Public",
+        &[
+            child(42, 41, "notes.txt", 1, 0x20),
+            child(44, 41, "Up", 1, LINK),
+        ],
+    );
+    records[42] = file_in(42, SEQ | 41, "notes.txt");
+    records[43] = directory_link(43, SEQ | 5, "Documents and Settings", r"\??\C:\Users", None);
+    records[44] = directory_link(44, SEQ | 41, "Up", "..", Some(1));
+    records[45] = directory_link(45, SEQ | 40, "link", r"\??\D:\Data", Some(0));
+    records[46] = directory_link(46, SEQ | 40, "escape", r"..\..\..", Some(1));
+    records[47] = directory_link(47, SEQ | 5, "a", r"\??\C:\b", Some(0));
+    records[48] = directory_link(48, SEQ | 5, "b", r"\??\C:\a", Some(0));
+    volume_with_mft(&records)
+}
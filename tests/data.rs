@@ -0,0 +1,154 @@
+mod common;
+
+use ntfs::*;
+use std::io::Cursor;
+
+fn record(attributes: &[Vec<u8>]) -> FileRecord {
+    FileRecord::parse(&common::protected(common::record_with(64, 1, attributes))).unwrap()
+}
+
+#[test]
+fn reads_multi_run_file() {
+    //Two clusters at 0x20, then one cluster backwards at 0x10.
+    let data = common::non_resident(0x80, "", &[0x11, 0x02, 0x20, 0x11, 0x01, 0xF0], 3, 8292);
+    let record = record(&[data]);
+    let mut ntfs = Ntfs::new(Cursor::new(common::numbered_volume())).unwrap();
+
+    let content = ntfs.read_data(&record, None).unwrap();
+    assert_eq!(content.len(), 8292);
+    assert!(content[..4096].iter().all(|b| *b == 0x20));
+    assert!(content[4096..8192].iter().all(|b| *b == 0x21));
+    assert!(content[8192..].iter().all(|b| *b == 0x10));
+}
+
+#[test]
+fn reads_resident_file() {
+    let record = record(&[common::resident(0x80, "", b"Hello, NTFS")]);
+    let mut ntfs = Ntfs::new(Cursor::new(common::numbered_volume())).unwrap();
+    assert_eq!(ntfs.read_data(&record, None).unwrap(), b"Hello, NTFS");
+}
+
+#[test]
+fn missing_data_attribute() {
+    let record = record(&[common::resident(0x80, "stream", b"ads")]);
+    let mut ntfs = Ntfs::new(Cursor::new(common::numbered_volume())).unwrap();
+    assert!(matches!(
+        ntfs.read_data(&record, None),
+        Err(NtfsError::Corrupt(_))
+    ));
+}
+
+#[test]
+fn reads_compressed_file() {
+    //The first compression unit is one cluster of LZNT1 data at 0x30 followed by 15 sparse clusters.
+    //The second unit is 16 uncompressed clusters at 0x40.
+    let mut data = common::non_resident(
+        0x80,
+        "",
+        &[0x11, 0x01, 0x30, 0x01, 0x0F, 0x11, 0x10, 0x10],
+        32,
+        65536 + 100,
+    );
+    data[0x0C..0x0E].copy_from_slice(&0x0001u16.to_le_bytes());
+    data[0x22..0x24].copy_from_slice(&4u16.to_le_bytes());
+    let record = record(&[data]);
+
+    let mut image = common::numbered_volume();
+    let chunk = [0x05, 0xB0, 0x08, b'a', b'b', b'c', 0x06, 0x20, 0x00, 0x00];
+    image[0x30 * 4096..0x30 * 4096 + 4096].fill(0);
+    image[0x30 * 4096..0x30 * 4096 + chunk.len()].copy_from_slice(&chunk);
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+
+    let content = ntfs.read_data(&record, None).unwrap();
+    assert_eq!(content.len(), 65536 + 100);
+    assert_eq!(&content[..12], b"abcabcabcabc");
+    assert!(content[12..65536].iter().all(|b| *b == 0));
+    assert!(content[65536..].iter().all(|b| *b == 0x40));
+}
+
+#[test]
+fn reads_attribute() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::numbered_volume())).unwrap();
+
+    let bitmap = common::non_resident(0xB0, "", &[0x11, 0x01, 0x40], 1, 8);
+    let bitmap = Attribute::parse(&bitmap).unwrap();
+    assert_eq!(ntfs.read_attribute(&bitmap).unwrap(), [0x40; 8]);
+
+    let value = common::resident(0x10, "", &[1, 2, 3]);
+    let value = Attribute::parse(&value).unwrap();
+    assert_eq!(ntfs.read_attribute(&value).unwrap(), [1, 2, 3]);
+}
+
+#[test]
+fn bytes_past_initialized_size_are_zero() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::numbered_volume())).unwrap();
+    //Two clusters allocated at 0x20, but only the first 5000 bytes were ever written.
+    let mut data = common::non_resident(0x80, "", &[0x11, 0x02, 0x20], 2, 6000);
+    data[0x38..0x40].copy_from_slice(&5000u64.to_le_bytes());
+    let data = Attribute::parse(&data).unwrap();
+
+    let content = ntfs.read_attribute(&data).unwrap();
+    assert_eq!(content.len(), 6000);
+    assert!(content[..4096].iter().all(|b| *b == 0x20));
+    assert!(content[4096..5000].iter().all(|b| *b == 0x21));
+    assert!(content[5000..].iter().all(|b| *b == 0));
+}
+
+#[test]
+fn sparse_hole_reads_as_zeros() {
+    //One cluster at 0x20, a two cluster hole, then one cluster at 0x21.
+    let mut data = common::non_resident(
+        0x80,
+        "",
+        &[0x11, 0x01, 0x20, 0x01, 0x02, 0x11, 0x01, 0x01],
+        4,
+        3 * 4096 + 10,
+    );
+    data[0x0C..0x0E].copy_from_slice(&0x8000u16.to_le_bytes());
+    let record = record(&[data]);
+    let mut ntfs = Ntfs::new(Cursor::new(common::numbered_volume())).unwrap();
+
+    let content = ntfs.read_data(&record, None).unwrap();
+    assert_eq!(content.len(), 3 * 4096 + 10);
+    assert!(content[..4096].iter().all(|b| *b == 0x20));
+    assert!(content[4096..3 * 4096].iter().all(|b| *b == 0));
+    assert!(content[3 * 4096..].iter().all(|b| *b == 0x21));
+}
+
+#[test]
+fn rejects_real_size_past_the_clusters() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::numbered_volume())).unwrap();
+    //The header claims an exabyte in a single cluster.
+    let mut data = common::non_resident(0x80, "", &[0x11, 0x01, 0x20], 1, 1 << 60);
+    let attribute = Attribute::parse(&data).unwrap();
+    assert!(matches!(
+        ntfs.read_attribute(&attribute),
+        Err(NtfsError::Corrupt(_))
+    ));
+
+    //Real size within the allocated size, but past the clusters the runs cover.
+    data[0x28..0x30].copy_from_slice(&(1u64 << 60).to_le_bytes());
+    let attribute = Attribute::parse(&data).unwrap();
+    assert!(matches!(
+        ntfs.read_attribute(&attribute),
+        Err(NtfsError::Corrupt(_))
+    ));
+}
+
+#[test]
+fn rejects_oversized_compression_units() {
+    //2^16 clusters per unit would need 256 MB for a single unit.
+    let mut data = common::non_resident(0x80, "", &[0x11, 0x01, 0x30], 1, 100);
+    data[0x0C..0x0E].copy_from_slice(&0x0001u16.to_le_bytes());
+    data[0x22..0x24].copy_from_slice(&16u16.to_le_bytes());
+    let record = record(&[data]);
+    let mut ntfs = Ntfs::new(Cursor::new(common::numbered_volume())).unwrap();
+    assert!(matches!(
+        ntfs.read_data(&record, None),
+        Err(NtfsError::Unsupported(_))
+    ));
+    assert!(matches!(
+        ntfs.open_reader(&record, None),
+        Err(NtfsError::Unsupported(_))
+    ));
+}
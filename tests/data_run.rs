@@ -0,0 +1,98 @@
+mod common;
+
+use ntfs::*;
+
+fn run(lcn: u64, length: u64) -> DataRun {
+    DataRun {
+        lcn: Some(lcn),
+        length_clusters: length,
+    }
+}
+
+#[test]
+fn single_run() {
+    let runs = decode_runs(&[0x21, 0x18, 0x34, 0x56, 0x00]).unwrap();
+    assert_eq!(runs, [run(0x5634, 0x18)]);
+}
+
+#[test]
+fn negative_delta() {
+    //0x5634 forwards, then -0x10 (0xF0) backwards.
+    let runs = decode_runs(&[0x21, 0x18, 0x34, 0x56, 0x11, 0x08, 0xF0, 0x00]).unwrap();
+    assert_eq!(runs, [run(0x5634, 0x18), run(0x5624, 0x08)]);
+}
+
+#[test]
+fn sparse_run() {
+    let runs = decode_runs(&[0x11, 0x04, 0x20, 0x01, 0x10, 0x11, 0x04, 0x10, 0x00]).unwrap();
+    assert_eq!(
+        runs,
+        [
+            run(0x20, 4),
+            DataRun {
+                lcn: None,
+                length_clusters: 0x10
+            },
+            run(0x30, 4),
+        ]
+    );
+}
+
+#[test]
+fn truncated_run() {
+    assert!(matches!(
+        decode_runs(&[0x21, 0x18, 0x34]),
+        Err(NtfsError::Corrupt(_))
+    ));
+}
+
+#[test]
+fn forward_backward_and_sparse_runs() {
+    //A typical fragmented file: forwards to 0x1000, a sparse hole, backwards to 0x0800,
+    //then forwards again by a two-byte delta.
+    let runs = decode_runs(&[
+        0x21, 0x10, 0x00, 0x10, //16 clusters at 0x1000
+        0x01, 0x20, //32 sparse clusters
+        0x21, 0x08, 0x00, 0xF8, //8 clusters at 0x1000 - 0x800
+        0x21, 0x04, 0x34, 0x12, //4 clusters at 0x800 + 0x1234
+        0x00,
+    ])
+    .unwrap();
+    assert_eq!(
+        runs,
+        [
+            run(0x1000, 0x10),
+            DataRun {
+                lcn: None,
+                length_clusters: 0x20
+            },
+            run(0x0800, 0x08),
+            run(0x1A34, 0x04),
+        ]
+    );
+}
+
+#[test]
+fn stops_at_terminator() {
+    let runs = decode_runs(&[0x11, 0x01, 0x20, 0x00, 0x11, 0x01, 0x30]).unwrap();
+    assert_eq!(runs, [run(0x20, 1)]);
+}
+
+#[test]
+fn attribute_runs_within_vcn_range() {
+    let attr = common::non_resident(0x80, "", &[0x11, 0x04, 0x20, 0x11, 0x04, 0x10], 8, 8 * 4096);
+    let runs = Attribute::parse(&attr).unwrap().data_runs().unwrap();
+    assert_eq!(runs, [run(0x20, 4), run(0x30, 4)]);
+}
+
+#[test]
+fn attribute_runs_past_last_vcn() {
+    let attr = common::non_resident(0x80, "", &[0x11, 0x04, 0x20, 0x11, 0x04, 0x10], 6, 6 * 4096);
+    assert!(matches!(
+        Attribute::parse(&attr).unwrap().data_runs(),
+        Err(NtfsError::Corrupt(_))
+    ));
+
+    let attr = common::resident(0x80, "", b"data");
+    assert!(Attribute::parse(&attr).unwrap().data_runs().is_err());
+}
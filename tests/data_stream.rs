@@ -0,0 +1,170 @@
+mod common;
+
+use ntfs::*;
+use std::io::{Cursor, Read};
+
+const ZONE_IDENTIFIER: &[u8] = b"[ZoneTransfer]\r\nZoneId=3\r\n";
+
+///Record 30 is a downloaded file with a resident Zone.Identifier stream.
+///Record 31 has a "payload" stream split between extension records 32 and 33.
+///Record 34 is a plain file.
+fn volume() -> Vec<u8> {
+    let downloaded = common::record_with(
+        30,
+        0x0001,
+        &[
+            common::resident(0x30, "", &common::file_name(5, "setup.exe", 1, 0)),
+            common::resident(0x30, "", &common::file_name(5, "SETUP.EXE", 2, 0)),
+            common::resident(0x80, "", b"MZ"),
+            common::resident(0x80, "Zone.Identifier", ZONE_IDENTIFIER),
+        ],
+    );
+
+    let list = [
+        common::attribute_list_entry(0x30, "", 0, 0x0001_0000_0000_001F, 0),
+        common::attribute_list_entry(0x80, "", 0, 0x0001_0000_0000_001F, 1),
+        common::attribute_list_entry(0x80, "payload", 0, 0x0001_0000_0000_0020, 0),
+        common::attribute_list_entry(0x80, "payload", 1, 0x0001_0000_0000_0021, 0),
+    ]
+    .concat();
+    let dropper = common::record_with(
+        31,
+        0x0001,
+        &[
+            common::resident(0x20, "", &list),
+            common::resident(0x30, "", &common::file_name(5, "notes.txt", 3, 0)),
+            common::resident(0x80, "", b"nothing to see"),
+        ],
+    );
+    let mut first = common::non_resident(0x80, "payload", &[0x11, 0x01, 0x70], 2, 6000);
+    common::set_vcns(&mut first, 0, 0);
+    let mut second = common::non_resident(0x80, "payload", &[0x11, 0x01, 0x50], 1, 0);
+    common::set_vcns(&mut second, 1, 1);
+
+    let mut records = vec![Vec::new(); 35];
+    records[0] = common::mft_record_0();
+    records[30] = common::protected(downloaded);
+    records[31] = common::protected(dropper);
+    records[32] = common::protected(common::extension_record(32, 31, &[first]));
+    records[33] = common::protected(common::extension_record(33, 31, &[second]));
+    records[34] = common::protected(common::file_in(34, 5, "plain.txt"));
+    let mut image = common::volume_with_mft(&records);
+    image[0x70 * 4096..0x71 * 4096].fill(0xC3);
+    image[0x50 * 4096..0x51 * 4096].fill(0xD4);
+    image
+}
+
+#[test]
+fn lists_streams_of_a_record() {
+    let mut ntfs = Ntfs::new(Cursor::new(volume())).unwrap();
+    let record = ntfs.file_record(30).unwrap();
+    let streams = record.data_streams().unwrap();
+    assert_eq!(streams.len(), 2);
+
+    assert_eq!(streams[0].name, None);
+    assert!(!streams[0].is_alternate());
+    assert_eq!(streams[0].real_size, 2);
+    assert!(streams[0].resident);
+
+    assert_eq!(streams[1].name.as_deref(), Some("Zone.Identifier"));
+    assert_eq!(streams[1].real_size, ZONE_IDENTIFIER.len() as u64);
+    assert_eq!(streams[1].allocated_size, ZONE_IDENTIFIER.len() as u64);
+    assert!(streams[1].resident);
+
+    let mut content = Vec::new();
+    ntfs.open_stream(&streams[1])
+        .unwrap()
+        .read_to_end(&mut content)
+        .unwrap();
+    assert_eq!(content, ZONE_IDENTIFIER);
+}
+
+#[test]
+fn merges_stream_split_across_extension_records() {
+    let mut ntfs = Ntfs::new(Cursor::new(volume())).unwrap();
+    let streams = ntfs.data_streams(31).unwrap();
+    assert_eq!(streams.len(), 2);
+    assert_eq!(streams[0].name, None);
+
+    let payload = &streams[1];
+    assert_eq!(payload.name.as_deref(), Some("payload"));
+    assert!(!payload.resident);
+    assert_eq!(payload.real_size, 6000);
+    assert_eq!(payload.allocated_size, 8192);
+
+    let mut content = Vec::new();
+    ntfs.open_stream(payload)
+        .unwrap()
+        .read_to_end(&mut content)
+        .unwrap();
+    assert_eq!(content.len(), 6000);
+    assert!(content[..4096].iter().all(|b| *b == 0xC3));
+    assert!(content[4096..].iter().all(|b| *b == 0xD4));
+    assert_eq!(ntfs.read_stream(payload).unwrap(), content);
+}
+
+#[test]
+fn finds_files_with_alternate_streams() {
+    let mut ntfs = Ntfs::new(Cursor::new(volume())).unwrap();
+    let files = ntfs
+        .master_file_table()
+        .unwrap()
+        .files_with_alternate_streams()
+        .unwrap();
+    let numbers: Vec<u64> = files.iter().map(|(n, _)| *n).collect();
+    assert_eq!(numbers, [30, 31]);
+    assert_eq!(files[1].1[1].name.as_deref(), Some("payload"));
+}
+
+#[test]
+fn alternate_stream_scan_skips_damaged_records() {
+    let mut image = volume();
+    //Record 30 has a torn sector and record 34 has an $ATTRIBUTE_LIST pointing past the $MFT.
+    let offset = 4 * 4096 + 30 * 1024;
+    image[offset + 1022] ^= 0xFF;
+    let list = common::attribute_list_entry(0x80, "", 0, 0x0001_0000_0000_0063, 0);
+    let broken = common::record_with(
+        34,
+        0x0001,
+        &[
+            common::resident(0x20, "", &list),
+            common::resident(0x30, "", &common::file_name(5, "plain.txt", 3, 0)),
+        ],
+    );
+    let offset = 4 * 4096 + 34 * 1024;
+    image[offset..offset + 1024].copy_from_slice(&common::protected(broken));
+
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    assert!(ntfs.file_record(30).is_err());
+    assert!(ntfs.attributes_of(34).is_err());
+    let files = ntfs
+        .master_file_table()
+        .unwrap()
+        .files_with_alternate_streams()
+        .unwrap();
+    let numbers: Vec<u64> = files.iter().map(|(n, _)| *n).collect();
+    assert_eq!(numbers, [31]);
+}
+
+#[test]
+fn reads_named_stream() {
+    let mut ntfs = Ntfs::new(Cursor::new(volume())).unwrap();
+    let record = ntfs.file_record(30).unwrap();
+    assert_eq!(record.stream_names().unwrap(), ["", "Zone.Identifier"]);
+
+    assert_eq!(ntfs.read_data(&record, None).unwrap(), b"MZ");
+    assert_eq!(
+        ntfs.read_data(&record, Some("Zone.Identifier")).unwrap(),
+        ZONE_IDENTIFIER
+    );
+    assert!(matches!(
+        ntfs.read_data(&record, Some("missing")),
+        Err(NtfsError::NotFound(_))
+    ));
+
+    let dropper = ntfs.file_record(31).unwrap();
+    assert_eq!(
+        ntfs.read_data(&dropper, Some("payload")).unwrap().len(),
+        6000
+    );
+}
@@ -0,0 +1,178 @@
+mod common;
+
+use ntfs::*;
+use std::io::Cursor;
+
+///An $EA entry, padded to a 4 byte boundary, whose offset to the next entry is its own size.
+fn ea_entry(name: &str, flags: u8, value: &[u8]) -> Vec<u8> {
+    let mut entry = vec![0u8; 0x08];
+    entry[0x04] = flags;
+    entry[0x05] = name.len() as u8;
+    entry[0x06..0x08].copy_from_slice(&(value.len() as u16).to_le_bytes());
+    entry.extend_from_slice(name.as_bytes());
+    entry.push(0);
+    entry.extend_from_slice(value);
+    entry.resize(entry.len().next_multiple_of(4), 0);
+    let len = entry.len() as u32;
+    entry[0x00..0x04].copy_from_slice(&len.to_le_bytes());
+    entry
+}
+
+fn ea_information(ea: &[u8]) -> Vec<u8> {
+    let mut value = vec![0u8; 0x08];
+    value[0x00..0x02].copy_from_slice(&(ea.len() as u16).to_le_bytes());
+    value[0x04..0x08].copy_from_slice(&(ea.len() as u32).to_le_bytes());
+    value
+}
+
+fn wsl_ea() -> Vec<u8> {
+    let mut device = 8u32.to_le_bytes().to_vec();
+    device.extend_from_slice(&1u32.to_le_bytes());
+    [
+        ea_entry("$LXUID", 0, &1000u32.to_le_bytes()),
+        ea_entry("$LXGID", 0, &100u32.to_le_bytes()),
+        ea_entry("$LXMOD", 0, &0o060660u32.to_le_bytes()),
+        ea_entry("$LXDEV", 0, &device),
+    ]
+    .concat()
+}
+
+fn lxattrb() -> Vec<u8> {
+    let mut value = vec![0u8; LXATTRB_SIZE];
+    value[0x02..0x04].copy_from_slice(&1u16.to_le_bytes());
+    value[0x04..0x08].copy_from_slice(&0o100755u32.to_le_bytes());
+    value[0x08..0x0C].copy_from_slice(&0u32.to_le_bytes());
+    value[0x0C..0x10].copy_from_slice(&0u32.to_le_bytes());
+    value[0x28..0x30].copy_from_slice(&1_500_000_000u64.to_le_bytes());
+    value
+}
+
+#[test]
+fn parses_extended_attributes() {
+    let mut value = [
+        ea_entry("FIRST", 0, b"one"),
+        ea_entry(".LONGNAME", ExtendedAttribute::NEED_EA, &[1, 2, 3, 4, 5]),
+    ]
+    .concat();
+    let attributes = ExtendedAttribute::parse_all(&value).unwrap();
+    let pairs: Vec<(&str, &[u8])> = attributes
+        .iter()
+        .map(|attribute| (attribute.name.as_str(), attribute.value.as_slice()))
+        .collect();
+    assert_eq!(
+        pairs,
+        [
+            ("FIRST", b"one".as_slice()),
+            (".LONGNAME", [1, 2, 3, 4, 5].as_slice())
+        ]
+    );
+    assert!(!attributes[0].needs_ea());
+    assert!(attributes[1].needs_ea());
+
+    //The last entry can also end the list with a zero offset.
+    let last = ea_entry("FIRST", 0, b"one").len();
+    value[last..last + 4].fill(0);
+    value.extend_from_slice(&[0xFF; 12]);
+    assert_eq!(ExtendedAttribute::parse_all(&value).unwrap().len(), 2);
+
+    let mut overflowing = ea_entry("FIRST", 0, b"one");
+    overflowing[0x06] = 0x40;
+    assert!(matches!(
+        ExtendedAttribute::parse_all(&overflowing),
+        Err(NtfsError::Corrupt(_))
+    ));
+}
+
+#[test]
+fn reads_wsl_metadata() {
+    let ea = wsl_ea();
+    let record = FileRecord::parse(&common::protected(common::record_with(
+        40,
+        0x0001,
+        &[
+            common::resident(0x30, "", &common::file_name(5, "sda1", 1, 0x20)),
+            common::resident(0xD0, "", &ea_information(&ea)),
+            common::resident(0xE0, "", &ea),
+        ],
+    )))
+    .unwrap();
+    let information = record.ea_information().unwrap().unwrap();
+    assert_eq!(information.unpacked_size as usize, ea.len());
+    assert_eq!(information.need_ea_count, 0);
+
+    let mut ntfs = Ntfs::new(Cursor::new(common::numbered_volume())).unwrap();
+    let metadata = ntfs.wsl_metadata(&record).unwrap().unwrap();
+    assert_eq!(metadata.uid, Some(1000));
+    assert_eq!(metadata.gid, Some(100));
+    assert_eq!(metadata.mode, Some(0o060660));
+    assert_eq!(metadata.device, Some((8, 1)));
+    assert_eq!(metadata.lxattrb, None);
+
+    let plain = FileRecord::parse(&common::file_in(31, 5, "readme.txt")).unwrap();
+    assert_eq!(plain.ea_information().unwrap(), None);
+    assert!(ntfs.extended_attributes(&plain).unwrap().is_empty());
+    assert_eq!(ntfs.wsl_metadata(&plain).unwrap(), None);
+}
+
+#[test]
+fn falls_back_to_lxattrb() {
+    let attributes = [
+        ExtendedAttribute {
+            flags: 0,
+            name: "LXATTRB".to_string(),
+            value: lxattrb(),
+        },
+        ExtendedAttribute {
+            flags: 0,
+            name: "$LXUID".to_string(),
+            value: 1000u32.to_le_bytes().to_vec(),
+        },
+    ];
+    let metadata = WslMetadata::from_extended_attributes(&attributes)
+        .unwrap()
+        .unwrap();
+    assert_eq!(metadata.uid, Some(1000));
+    assert_eq!(metadata.gid, Some(0));
+    assert_eq!(metadata.mode, Some(0o100755));
+    assert_eq!(metadata.device, None);
+    let lxattrb = metadata.lxattrb.unwrap();
+    assert_eq!(lxattrb.version, 1);
+    assert_eq!(lxattrb.modified_time, 1_500_000_000);
+
+    let unrelated = [ExtendedAttribute {
+        flags: 0,
+        name: "FIRST".to_string(),
+        value: Vec::new(),
+    }];
+    assert_eq!(
+        WslMetadata::from_extended_attributes(&unrelated).unwrap(),
+        None
+    );
+}
+
+#[test]
+fn reads_non_resident_extended_attributes() {
+    let mut ea = wsl_ea();
+    //Enough padding attributes to need a cluster of their own.
+    for i in 0..40 {
+        ea.extend_from_slice(&ea_entry(&format!("PAD{i}"), 0, &[i as u8; 64]));
+    }
+    let record = FileRecord::parse(&common::protected(common::record_with(
+        40,
+        0x0001,
+        &[
+            common::resident(0xD0, "", &ea_information(&ea)),
+            common::non_resident(0xE0, "", &[0x11, 0x01, 0x30], 1, ea.len() as u64),
+        ],
+    )))
+    .unwrap();
+
+    let mut image = common::numbered_volume();
+    image[0x30 * 4096..0x30 * 4096 + ea.len()].copy_from_slice(&ea);
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    let attributes = ntfs.extended_attributes(&record).unwrap();
+    assert_eq!(attributes.len(), 44);
+    assert_eq!(attributes[43].name, "PAD39");
+    assert_eq!(attributes[43].value, [39; 64]);
+    assert_eq!(ntfs.wsl_metadata(&record).unwrap().unwrap().uid, Some(1000));
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use ntfs::*;
+
+fn record_with_names(names: &[(&str, Namespace)]) -> FileRecord {
+    let attributes: Vec<Vec<u8>> = names
+        .iter()
+        .map(|(name, namespace)| {
+            common::resident(0x30, "", &common::file_name(5, name, *namespace as u8, 0))
+        })
+        .collect();
+    FileRecord::parse(&common::protected(common::record_with(64, 1, &attributes))).unwrap()
+}
+
+#[test]
+fn parses_file_name() {
+    let value = common::file_name(0x0005_0000_0000_0005, "$MFT", 3, 6);
+    let file_name = FileName::parse(&value).unwrap();
+    assert_eq!(file_name.name, "$MFT");
+    assert_eq!(file_name.namespace, Namespace::Win32AndDos);
+    assert_eq!(file_name.parent_directory.record_number(), 5);
+    assert_eq!(file_name.parent_directory.sequence_number(), 5);
+}
+
+#[test]
+fn parses_timestamps_sizes_and_flags() {
+    let mut value = common::file_name(0x0001_0000_0000_0005, "dir", 1, 0x1000_0000);
+    value[0x28..0x30].copy_from_slice(&8192u64.to_le_bytes());
+    value[0x30..0x38].copy_from_slice(&5000u64.to_le_bytes());
+    let file_name = FileName::parse(&value).unwrap();
+    assert_eq!(file_name.creation_time.raw(), 0x01D0_0000_0000_0000);
+    assert_eq!(file_name.accessed_time.raw(), 0x01D0_0000_0000_0000);
+    assert_eq!(file_name.allocated_size, 8192);
+    assert_eq!(file_name.real_size, 5000);
+    assert!(file_name.is_directory());
+    assert_eq!(file_name.flags.bits(), 0x1000_0000);
+    assert_eq!(file_name.parent_directory.sequence_number, 1);
+}
+
+#[test]
+fn non_ascii_names() {
+    let value = common::file_name(5, "résumé.txt", 1, 0);
+    //'é' is a single code unit.
+    assert_eq!(value[0x40], 10);
+    assert_eq!(FileName::parse(&value).unwrap().name, "résumé.txt");
+
+    let value = common::file_name(5, "\u{1F600}.txt", 1, 0);
+    //The emoji is a surrogate pair.
+    assert_eq!(value[0x40], 6);
+    assert_eq!(value.len(), 0x42 + 12);
+    assert_eq!(FileName::parse(&value).unwrap().name, "\u{1F600}.txt");
+}
+
+#[test]
+fn hard_links() {
+    let attributes = [
+        common::resident(0x30, "", &common::file_name(5, "a.txt", 1, 0)),
+        common::resident(0x30, "", &common::file_name(40, "b.txt", 3, 0)),
+    ];
+    let record = common::protected(common::record_with(64, 1, &attributes));
+    let record = FileRecord::parse(&record).unwrap();
+    let names = record.file_names().unwrap();
+    assert_eq!(names.len(), 2);
+    assert_eq!(names[0].parent_directory.record_number, 5);
+    assert_eq!(names[1].parent_directory.record_number, 40);
+    assert_eq!(record.best_name().as_deref(), Some("b.txt"));
+}
+
+#[test]
+fn rejects_truncated_name() {
+    let mut value = common::file_name(5, "name", 1, 0);
+    value[0x40] = 10;
+    assert!(matches!(
+        FileName::parse(&value),
+        Err(NtfsError::Corrupt(_))
+    ));
+    assert!(matches!(
+        FileName::parse(&value[..0x40]),
+        Err(NtfsError::BufferTooSmall { needed: 0x42, .. })
+    ));
+}
+
+#[test]
+fn namespace_priority() {
+    assert!(Namespace::Win32AndDos.priority() > Namespace::Win32.priority());
+    assert!(Namespace::Win32.priority() > Namespace::Posix.priority());
+    assert!(Namespace::Posix.priority() > Namespace::Dos.priority());
+    assert!(Namespace::try_from(4).is_err());
+}
+
+#[test]
+fn best_name_prefers_long_name() {
+    let record = record_with_names(&[
+        ("LONGFI~1", Namespace::Dos),
+        ("LongFilename.txt", Namespace::Win32),
+    ]);
+    assert_eq!(record.best_name().as_deref(), Some("LongFilename.txt"));
+    assert_eq!(record.names(false).unwrap(), ["LongFilename.txt"]);
+    assert_eq!(
+        record.names(true).unwrap(),
+        ["LONGFI~1", "LongFilename.txt"]
+    );
+}
+
+#[test]
+fn best_name_without_file_name() {
+    let record = FileRecord::parse(&common::protected(common::record_with(64, 1, &[]))).unwrap();
+    assert_eq!(record.best_name(), None);
+    assert!(record.file_names().unwrap().is_empty());
+}
@@ -0,0 +1,179 @@
+mod common;
+
+use ntfs::*;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+fn record(attributes: &[Vec<u8>]) -> FileRecord {
+    FileRecord::parse(&common::protected(common::record_with(64, 1, attributes))).unwrap()
+}
+
+///An 8 MB volume where every byte after the boot sector is part of a pattern that doesn't line up with clusters.
+fn patterned_volume() -> Vec<u8> {
+    let mut image = common::boot_sector().to_vec();
+    image.resize(8 * 1024 * 1024, 0);
+    common::fit_total_sectors(&mut image);
+    for (i, byte) in image.iter_mut().enumerate().skip(4096) {
+        *byte = (i / 7 % 251) as u8;
+    }
+    image
+}
+
+///Encodes runs of `(clusters, lcn)` with two byte lengths and three byte offsets, `None` being sparse.
+fn encode_runs(runs: &[(u64, Option<u64>)]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut previous = 0i64;
+    for (clusters, lcn) in runs {
+        let length = (*clusters as u16).to_le_bytes();
+        match lcn {
+            Some(lcn) => {
+                let delta = *lcn as i64 - previous;
+                previous = *lcn as i64;
+                bytes.push(0x32);
+                bytes.extend_from_slice(&length);
+                bytes.extend_from_slice(&delta.to_le_bytes()[..3]);
+            }
+            None => {
+                bytes.push(0x02);
+                bytes.extend_from_slice(&length);
+            }
+        }
+    }
+    bytes
+}
+
+fn read_in_chunks<R: Read>(mut reader: R) -> Vec<u8> {
+    let mut content = Vec::new();
+    let mut chunk = vec![0u8; 64 * 1024];
+    loop {
+        let n = reader.read(&mut chunk).unwrap();
+        if n == 0 {
+            return content;
+        }
+        content.extend_from_slice(&chunk[..n]);
+    }
+}
+
+#[test]
+fn reads_a_large_file_in_chunks() {
+    //256 clusters at 0x100, a 128 cluster hole and 385 clusters at 0x300, about 3 MB.
+    let runs = encode_runs(&[(256, Some(0x100)), (128, None), (385, Some(0x300))]);
+    let real_size = 769 * 4096 - 1000;
+    let initialized_size = real_size - 5000;
+    let mut data = common::non_resident(0x80, "", &runs, 769, real_size);
+    data[0x38..0x40].copy_from_slice(&initialized_size.to_le_bytes());
+    let record = record(&[data]);
+
+    let image = patterned_volume();
+    let mut ntfs = Ntfs::new(Cursor::new(image.clone())).unwrap();
+    let expected = ntfs.read_data(&record, None).unwrap();
+
+    let reader = ntfs.open_reader(&record, None).unwrap();
+    assert_eq!(reader.len(), real_size);
+    let content = read_in_chunks(reader);
+    assert_eq!(content.len() as u64, real_size);
+    assert!(content == expected);
+
+    assert_eq!(content[..256 * 4096], image[0x100 * 4096..0x200 * 4096]);
+    assert!(content[256 * 4096..384 * 4096].iter().all(|b| *b == 0));
+    assert_eq!(
+        content[384 * 4096..384 * 4096 + 4096],
+        image[0x300 * 4096..0x301 * 4096]
+    );
+    assert!(content[initialized_size as usize..].iter().all(|b| *b == 0));
+}
+
+#[test]
+fn reads_a_compressed_file_in_chunks() {
+    //32 compression units of 16 clusters, cycling through LZNT1 data, uncompressed clusters and sparse units.
+    let chunk = [0x05, 0xB0, 0x08, b'a', b'b', b'c', 0x06, 0x20, 0x00, 0x00];
+    let mut image = patterned_volume();
+    let mut runs = Vec::new();
+    let mut lcn = 0x500;
+    for unit in 0..32 {
+        match unit % 3 {
+            0 => {
+                let offset = lcn as usize * 4096;
+                image[offset..offset + 4096].fill(0);
+                image[offset..offset + chunk.len()].copy_from_slice(&chunk);
+                runs.extend([(1, Some(lcn)), (15, None)]);
+                lcn += 1;
+            }
+            1 => {
+                runs.push((16, Some(lcn)));
+                lcn += 16;
+            }
+            _ => runs.push((16, None)),
+        }
+    }
+    let real_size = 32 * 65536 - 300;
+    let mut data = common::non_resident(0x80, "", &encode_runs(&runs), 512, real_size);
+    data[0x0C..0x0E].copy_from_slice(&0x0001u16.to_le_bytes());
+    data[0x22..0x24].copy_from_slice(&4u16.to_le_bytes());
+    let record = record(&[data]);
+
+    let mut ntfs = Ntfs::new(Cursor::new(image.clone())).unwrap();
+    let expected = ntfs.read_data(&record, None).unwrap();
+    let content = read_in_chunks(ntfs.open_reader(&record, None).unwrap());
+    assert_eq!(content.len() as u64, real_size);
+    assert!(content == expected);
+
+    assert_eq!(&content[..12], b"abcabcabcabc");
+    assert!(content[12..65536].iter().all(|b| *b == 0));
+    //The first uncompressed unit comes right after the cluster of LZNT1 data.
+    assert_eq!(content[65536..131072], image[0x501 * 4096..0x511 * 4096]);
+    assert!(content[131072..196608].iter().all(|b| *b == 0));
+}
+
+#[test]
+fn seeks_within_a_compressed_file() {
+    //One unit of LZNT1 data at 0x30 followed by an uncompressed unit at 0x40.
+    let runs = [0x11, 0x01, 0x30, 0x01, 0x0F, 0x11, 0x10, 0x10];
+    let mut data = common::non_resident(0x80, "", &runs, 32, 65536 + 100);
+    data[0x0C..0x0E].copy_from_slice(&0x0001u16.to_le_bytes());
+    data[0x22..0x24].copy_from_slice(&4u16.to_le_bytes());
+    let record = record(&[data]);
+
+    let mut image = common::numbered_volume();
+    let chunk = [0x05, 0xB0, 0x08, b'a', b'b', b'c', 0x06, 0x20, 0x00, 0x00];
+    image[0x30 * 4096..0x30 * 4096 + 4096].fill(0);
+    image[0x30 * 4096..0x30 * 4096 + chunk.len()].copy_from_slice(&chunk);
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    let mut reader = ntfs.open_reader(&record, None).unwrap();
+
+    let mut buf = [0u8; 4];
+    reader.seek(SeekFrom::Start(4)).unwrap();
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"bcab");
+
+    reader.seek(SeekFrom::End(-4)).unwrap();
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [0x40; 4]);
+    assert_eq!(reader.position(), 65536 + 100);
+    assert_eq!(reader.read(&mut buf).unwrap(), 0);
+
+    reader.seek(SeekFrom::Current(-65536)).unwrap();
+    reader.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [0; 4]);
+    assert!(reader.seek(SeekFrom::Current(-65536)).is_err());
+}
+
+#[test]
+fn reads_resident_files_and_named_streams() {
+    let record = record(&[
+        common::resident(0x80, "", b"Hello, NTFS"),
+        common::resident(0x80, "Zone.Identifier", b"[ZoneTransfer]"),
+    ]);
+    let mut ntfs = Ntfs::new(Cursor::new(common::numbered_volume())).unwrap();
+    assert_eq!(
+        read_in_chunks(ntfs.open_reader(&record, None).unwrap()),
+        b"Hello, NTFS"
+    );
+    assert_eq!(
+        read_in_chunks(ntfs.open_reader(&record, Some("Zone.Identifier")).unwrap()),
+        b"[ZoneTransfer]"
+    );
+    assert!(matches!(
+        ntfs.open_reader(&record, Some("missing")),
+        Err(NtfsError::NotFound(_))
+    ));
+}
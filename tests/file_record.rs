@@ -0,0 +1,270 @@
+mod common;
+
+use ntfs::*;
+
+#[test]
+fn parses_header() {
+    let record = FileRecord::parse(&common::protected(common::file_record(42, 0x0001))).unwrap();
+    assert_eq!(&record.magic, b"FILE");
+    assert_eq!(record.update_sequence_offset, 0x30);
+    assert_eq!(record.update_sequence_size, 3);
+    assert_eq!(record.lsn, 0x1234_5678);
+    assert_eq!(record.sequence_number, 1);
+    assert_eq!(record.hard_link_count, 1);
+    assert_eq!(record.first_attribute_offset, 0x38);
+    assert_eq!(record.flags, 0x0001);
+    assert_eq!(record.real_size, 0x40);
+    assert_eq!(record.allocated_size, 1024);
+    assert_eq!(record.base_record_reference, FileReference::from(0));
+    assert_eq!(record.next_attribute_id, 1);
+    assert_eq!(record.mft_record_number, 42);
+}
+
+#[test]
+fn rejects_bad_magic() {
+    let mut buf = common::protected(common::file_record(0, 0x0001));
+    buf[0..4].copy_from_slice(b"INDX");
+    assert!(matches!(FileRecord::parse(&buf), Err(NtfsError::BadMagic)));
+}
+
+#[test]
+fn applies_fixup() {
+    let mut buf = common::file_record(0, 0x0001);
+    buf[510..512].copy_from_slice(&[0xAB, 0xCD]);
+    buf[1022..1024].copy_from_slice(&[0x12, 0x34]);
+    let buf = common::protected(buf);
+    assert_eq!(&buf[510..512], &[0x07, 0x00]);
+
+    let record = FileRecord::parse(&buf).unwrap();
+    assert_eq!(&record.data()[510..512], &[0xAB, 0xCD]);
+    assert_eq!(&record.data()[1022..1024], &[0x12, 0x34]);
+}
+
+#[test]
+fn rejects_torn_sector() {
+    let mut buf = common::protected(common::file_record(0, 0x0001));
+    buf[1022] = 0xFF;
+    assert!(matches!(
+        FileRecord::parse(&buf),
+        Err(NtfsError::FixupMismatch { sector_index: 1 })
+    ));
+}
+
+#[test]
+fn parses_torn_record_unverified() {
+    let mut buf = common::file_record(0, 0x0001);
+    buf[1022..1024].copy_from_slice(&[0x12, 0x34]);
+    let mut buf = common::protected(buf);
+    buf[1022] = 0xFF;
+
+    let record = FileRecord::parse_unverified(&buf).unwrap();
+    assert_eq!(&record.data()[1022..1024], &[0x12, 0x34]);
+}
+
+#[test]
+fn rejects_bad_update_sequence() {
+    let buf = common::protected(common::file_record(0, 0x0001));
+    for (usn_offset, usn_count, bytes_per_sector) in [
+        (0x30, 3, 0),
+        (0x30, 3, 2),
+        (0x30, 3, 1000),
+        (0x30, 0, 512),
+        (0x1F0, 8, 512),
+    ] {
+        let mut data = buf.clone();
+        assert!(matches!(
+            apply_fixup(&mut data, usn_offset, usn_count, bytes_per_sector),
+            Err(NtfsError::Corrupt(_))
+        ));
+        assert!(data == buf);
+    }
+}
+
+#[test]
+fn update_sequence_must_cover_the_record() {
+    //An update sequence for one sector leaves the second sector of the record unchecked.
+    let mut buf = common::file_record(0, 0x0001);
+    buf[0x06..0x08].copy_from_slice(&2u16.to_le_bytes());
+    let buf = common::protected(buf);
+    assert!(matches!(
+        FileRecord::parse(&buf),
+        Err(NtfsError::Corrupt(_))
+    ));
+
+    //Carving still restores what it can.
+    let mut data = buf.clone();
+    apply_fixup_unverified(&mut data, 0x30, 2, 512).unwrap();
+}
+
+#[test]
+fn fixup_covers_4k_records() {
+    //4Kn drives use 4K records, still protected in 512 byte strides.
+    let mut buf = common::file_record(0, 0x0001);
+    buf.resize(4096, 0);
+    buf[0x06..0x08].copy_from_slice(&9u16.to_le_bytes());
+    buf[0x1C..0x20].copy_from_slice(&4096u32.to_le_bytes());
+    buf[0x38..0x3C].fill(0);
+    buf[0x48..0x4C].copy_from_slice(&0xFFFF_FFFFu32.to_le_bytes());
+    buf[0x14..0x16].copy_from_slice(&0x48u16.to_le_bytes());
+    buf[4094..4096].copy_from_slice(&[0x56, 0x78]);
+    let buf = common::protected(buf);
+
+    let record = FileRecord::parse(&buf).unwrap();
+    assert_eq!(&record.data()[4094..4096], &[0x56, 0x78]);
+}
+
+#[test]
+fn flags() {
+    for (flags, in_use, directory) in [
+        (0x0000, false, false),
+        (0x0001, true, false),
+        (0x0002, false, true),
+        (0x0003, true, true),
+    ] {
+        let record = FileRecord::parse(&common::protected(common::file_record(0, flags))).unwrap();
+        assert_eq!(record.flags(), FileRecordFlags(flags));
+        assert_eq!(record.is_in_use(), in_use);
+        assert_eq!(record.is_directory(), directory);
+    }
+}
+
+#[test]
+fn parses_mft_record_0() {
+    let buf = common::mft_record_0();
+    assert_eq!(buf.len(), 1024);
+    let record = FileRecord::parse(&buf).unwrap();
+    assert_eq!(record.mft_record_number, 0);
+    assert!(record.is_in_use());
+    assert!(!record.is_directory());
+    assert_eq!(record.next_attribute_id, 4);
+    assert!(record.real_size > 0x38 && record.real_size <= record.allocated_size);
+}
+
+#[test]
+fn rejects_real_size_past_allocated_size() {
+    let mut buf = common::file_record(0, 0x0001);
+    buf[0x18..0x1C].copy_from_slice(&2048u32.to_le_bytes());
+    let buf = common::protected(buf);
+    assert!(matches!(
+        FileRecord::parse(&buf),
+        Err(NtfsError::Corrupt(_))
+    ));
+}
+
+#[test]
+fn rejects_attribute_offset_outside_record() {
+    let mut buf = common::file_record(0, 0x0001);
+    buf[0x14..0x16].copy_from_slice(&1022u16.to_le_bytes());
+    let buf = common::protected(buf);
+    assert!(matches!(
+        FileRecord::parse(&buf),
+        Err(NtfsError::Corrupt(_))
+    ));
+}
+
+#[test]
+fn record_flags_preserve_unknown_bits() {
+    let record = FileRecord::parse(&common::protected(common::file_record(0, 0x800D))).unwrap();
+    let flags = record.flags();
+    assert_eq!(flags.bits(), 0x800D);
+    assert!(flags.contains(FileRecordFlags::IN_USE));
+    assert!(!flags.contains(FileRecordFlags::DIRECTORY));
+    assert!(flags.contains(FileRecordFlags::EXTENSION));
+    assert!(flags.contains(FileRecordFlags::VIEW_INDEX));
+}
+
+#[test]
+fn iterates_attributes() {
+    let buf = common::protected(common::record_with(
+        30,
+        0x0001,
+        &[
+            common::resident(0x10, "", &common::standard_information(0, 0)),
+            common::resident(0x30, "", &common::file_name(5, "a.txt", 3, 0)),
+            common::resident(0x80, "", b"main"),
+            common::resident(0x80, "Zone.Identifier", b"[ZoneTransfer]"),
+            common::non_resident(0x80, "big", &[0x11, 0x10, 0x20], 16, 60000),
+        ],
+    ));
+    let record = FileRecord::parse(&buf).unwrap();
+    let attributes: Vec<_> = record.attributes().collect::<Result<_, _>>().unwrap();
+    let types: Vec<_> = attributes
+        .iter()
+        .map(|a| u32::from(a.type_code()))
+        .collect();
+    assert_eq!(types, [0x10, 0x30, 0x80, 0x80, 0x80]);
+
+    let streams: Vec<_> = record
+        .attributes()
+        .flatten()
+        .filter(|a| a.type_code() == AttributeType::Data)
+        .map(|a| a.header().name_length)
+        .collect();
+    assert_eq!(streams, [0, 15, 3]);
+}
+
+#[test]
+fn attributes_stop_without_end_marker() {
+    let mut buf = common::record_with(0, 0x0001, &[common::resident(0x80, "", b"data")]);
+    //Drop the end marker by shrinking the real size to the end of the attribute.
+    let end = 0x38 + 0x20;
+    buf[end..end + 8].fill(0xAA);
+    buf[0x18..0x1C].copy_from_slice(&(end as u32).to_le_bytes());
+    let record = FileRecord::parse(&common::protected(buf)).unwrap();
+    assert_eq!(record.attributes().count(), 1);
+}
+
+#[test]
+fn attributes_refuse_zero_length() {
+    let mut buf = common::record_with(0, 0x0001, &[common::resident(0x80, "", b"data")]);
+    buf[0x38 + 4..0x38 + 8].fill(0);
+    let record = FileRecord::parse(&common::protected(buf)).unwrap();
+    let attributes: Vec<_> = record.attributes().collect();
+    assert_eq!(attributes.len(), 1);
+    assert!(attributes[0].is_err());
+}
+
+#[test]
+fn base_record_reference() {
+    let mut record = common::file_record(70, 1);
+    record[0x20..0x28].copy_from_slice(&0x0002_0000_0000_0040u64.to_le_bytes());
+    let record = FileRecord::parse(&common::protected(record)).unwrap();
+    assert_eq!(record.base_record_reference.record_number, 64);
+    assert_eq!(record.base_record_reference.sequence_number, 2);
+}
+
+#[test]
+fn reads_resident_data() {
+    let attributes = [
+        common::resident(0x10, "", &common::standard_information(0, 0)),
+        common::resident(0x80, "Zone.Identifier", b"[ZoneTransfer]"),
+        common::resident(0x80, "", b"Hello, NTFS"),
+    ];
+    let record = common::protected(common::record_with(64, 1, &attributes));
+    let record = FileRecord::parse(&record).unwrap();
+    assert_eq!(record.read_resident_data(), Some(&b"Hello, NTFS"[..]));
+}
+
+#[test]
+fn non_resident_data_is_not_read() {
+    let data = common::non_resident(0x80, "", &[0x11, 0x01, 0x20], 1, 4096);
+    let record = common::protected(common::record_with(64, 1, &[data]));
+    let record = FileRecord::parse(&record).unwrap();
+    assert_eq!(record.read_resident_data(), None);
+
+    let record = common::protected(common::record_with(65, 1, &[]));
+    let record = FileRecord::parse(&record).unwrap();
+    assert_eq!(record.read_resident_data(), None);
+}
+
+#[test]
+fn clones_compare_equal() {
+    let record = FileRecord::parse(&common::file_in(31, 5, "readme.txt")).unwrap();
+    let copy = record.clone();
+    assert_eq!(copy, record);
+    assert_eq!(copy.file_names().unwrap(), record.file_names().unwrap());
+    assert_ne!(
+        FileRecord::parse(&common::file_in(32, 5, "readme.txt")).unwrap(),
+        record
+    );
+}
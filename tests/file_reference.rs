@@ -0,0 +1,59 @@
+use ntfs::*;
+use std::collections::{BTreeMap, HashSet};
+
+#[test]
+fn splits_record_and_sequence_number() {
+    let reference = FileReference::from(0x0005_0000_0000_0005);
+    assert_eq!(reference.record_number(), 5);
+    assert_eq!(reference.sequence_number(), 5);
+}
+
+#[test]
+fn high_sequence_numbers() {
+    let reference = FileReference::from(0xFFFF_0000_0000_04D2);
+    assert_eq!(reference.record_number(), 1234);
+    assert_eq!(reference.sequence_number(), 0xFFFF);
+    assert_eq!(reference.to_string(), "1234-65535");
+
+    let reference = FileReference::from(0x8001_FFFF_FFFF_FFFF);
+    assert_eq!(reference.record_number(), 0xFFFF_FFFF_FFFF);
+    assert_eq!(reference.sequence_number(), 0x8001);
+}
+
+#[test]
+fn orders_by_record_number_first() {
+    let mut map = BTreeMap::new();
+    map.insert(FileReference::from(0xFFFF_0000_0000_0002), "b");
+    map.insert(FileReference::from(0x0001_0000_0000_0003), "c");
+    map.insert(FileReference::from(0x0002_0000_0000_0001), "a");
+    assert_eq!(map.values().copied().collect::<String>(), "abc");
+}
+
+#[test]
+fn from_raw_known_values() {
+    //The root directory as referenced from $MFT's $FILE_NAME.
+    let root = FileReference::from_raw(0x0005_0000_0000_0005);
+    assert_eq!(
+        root,
+        FileReference {
+            record_number: 5,
+            sequence_number: 5
+        }
+    );
+
+    let reference = FileReference::from_raw(0x0003_0000_0001_E240);
+    assert_eq!(reference.record_number, 123456);
+    assert_eq!(reference.sequence_number, 3);
+    assert_eq!(reference.to_raw(), 0x0003_0000_0001_E240);
+    assert_eq!(FileReference::from(0x0003_0000_0001_E240), reference);
+}
+
+#[test]
+fn hashes_by_record_and_sequence_number() {
+    let mut seen = HashSet::new();
+    assert!(seen.insert(FileReference::from(0x0005_0000_0000_0005)));
+    assert!(!seen.insert(FileReference::from(0x0005_0000_0000_0005)));
+    //The same record after it has been reused is a different file.
+    assert!(seen.insert(FileReference::from(0x0006_0000_0000_0005)));
+    assert_eq!(seen.len(), 2);
+}
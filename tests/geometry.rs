@@ -0,0 +1,84 @@
+mod common;
+
+use ntfs::*;
+
+fn parse(buf: &[u8; 512]) -> Geometry {
+    PartitionBootSector::from_bytes(buf)
+        .unwrap()
+        .geometry()
+        .unwrap()
+}
+
+#[test]
+fn typical_512_byte_sectors_with_4k_clusters() {
+    let geometry = parse(&common::boot_sector());
+    assert_eq!(
+        geometry,
+        Geometry {
+            bytes_per_sector: 512,
+            bytes_per_cluster: 4096,
+            file_record_size: 1024,
+            index_record_size: 4096,
+        }
+    );
+    assert_eq!(geometry.lcn_to_byte_offset(4).unwrap(), 0x4000);
+    assert_eq!(geometry.vcn_count_for_bytes(0), 0);
+    assert_eq!(geometry.vcn_count_for_bytes(1), 1);
+    assert_eq!(geometry.vcn_count_for_bytes(4096), 1);
+    assert_eq!(geometry.vcn_count_for_bytes(4097), 2);
+}
+
+#[test]
+fn native_4k_sectors() {
+    let geometry = parse(&common::boot_sector_4kn());
+    assert_eq!(geometry.bytes_per_sector, 4096);
+    assert_eq!(geometry.bytes_per_cluster, 4096);
+    assert_eq!(geometry.file_record_size, 4096);
+    assert_eq!(geometry.index_record_size, 4096);
+}
+
+#[test]
+fn clusters_of_64k() {
+    let mut buf = common::boot_sector();
+    buf[13] = 0x80;
+    let geometry = parse(&buf);
+    assert_eq!(geometry.bytes_per_cluster, 65536);
+    assert_eq!(geometry.file_record_size, 1024);
+    assert_eq!(geometry.index_record_size, 65536);
+    assert_eq!(geometry.lcn_to_byte_offset(3).unwrap(), 3 * 65536);
+    assert_eq!(geometry.vcn_count_for_bytes(65537), 2);
+
+    let mut buf = common::boot_sector_4kn();
+    buf[13] = 0x10;
+    assert_eq!(parse(&buf).bytes_per_cluster, 65536);
+}
+
+#[test]
+fn offsets_are_overflow_checked() {
+    let geometry = parse(&common::boot_sector());
+    assert!(matches!(
+        geometry.lcn_to_byte_offset(u64::MAX / 2),
+        Err(NtfsError::Corrupt(_))
+    ));
+    assert!(geometry.clusters_to_bytes(u64::MAX).is_err());
+    assert_eq!(geometry.vcn_count_for_bytes(u64::MAX), u64::MAX / 4096 + 1);
+}
+
+#[test]
+fn rejects_out_of_range_record_sizes() {
+    //0xE1 is 2^31 bytes, 0xF9 is 128 bytes and 3 clusters isn't a power of two.
+    for (offset, value) in [(64, 0xE1), (64, 0xF9), (68, 0xE1), (68, 0x03)] {
+        let mut buf = common::boot_sector();
+        buf[offset] = value;
+        let pbs = PartitionBootSector::from_bytes_with_options(
+            &buf,
+            ParseOptions {
+                strict: false,
+                ..ParseOptions::default()
+            },
+        )
+        .unwrap()
+        .0;
+        assert!(matches!(pbs.geometry(), Err(NtfsError::Corrupt(_))));
+    }
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use ntfs::*;
+use std::io::Cursor;
+
+const MICROSOFT_RESERVED: Guid = Guid([
+    0x16, 0xE3, 0xC9, 0xE3, 0x5C, 0x0B, 0xB8, 0x4D, 0x81, 0x7D, 0xF9, 0x2D, 0xF0, 0x02, 0x15, 0xAE,
+]);
+
+fn gpt_entry(type_guid: Guid, start_lba: u64, end_lba: u64, name: &str) -> [u8; 128] {
+    let mut entry = [0u8; 128];
+    entry[0x00..0x10].copy_from_slice(&type_guid.0);
+    entry[0x10..0x20].copy_from_slice(&[0x11; 16]);
+    entry[0x20..0x28].copy_from_slice(&start_lba.to_le_bytes());
+    entry[0x28..0x30].copy_from_slice(&end_lba.to_le_bytes());
+    let name: Vec<u8> = name.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    entry[0x38..0x38 + name.len()].copy_from_slice(&name);
+    entry
+}
+
+///A GPT disk with a Microsoft Reserved partition and the tree volume in a Basic Data Partition at LBA 2048.
+fn disk() -> Vec<u8> {
+    let mut volume = common::tree_volume();
+    volume[0x1C..0x20].copy_from_slice(&2048u32.to_le_bytes());
+    let end_lba = 2048 + (volume.len() / 512) as u64 - 1;
+
+    let mut image = vec![0u8; 2048 * 512];
+    image[0x1C2] = 0xEE;
+    image[510..512].copy_from_slice(&[0x55, 0xAA]);
+
+    let header = &mut image[512..1024];
+    header[0x00..0x08].copy_from_slice(b"EFI PART");
+    header[0x08..0x0C].copy_from_slice(&0x0001_0000u32.to_le_bytes());
+    header[0x0C..0x10].copy_from_slice(&92u32.to_le_bytes());
+    header[0x18..0x20].copy_from_slice(&1u64.to_le_bytes());
+    header[0x48..0x50].copy_from_slice(&2u64.to_le_bytes());
+    header[0x50..0x54].copy_from_slice(&128u32.to_le_bytes());
+    header[0x54..0x58].copy_from_slice(&128u32.to_le_bytes());
+
+    let reserved = gpt_entry(MICROSOFT_RESERVED, 34, 2047, "Microsoft reserved partition");
+    let data = gpt_entry(Guid::BASIC_DATA, 2048, end_lba, "Basic data partition");
+    image[1024..1152].copy_from_slice(&reserved);
+    image[1152..1280].copy_from_slice(&data);
+    image.extend(volume);
+    image
+}
+
+#[test]
+fn finds_basic_data_partition() {
+    let mut reader = Cursor::new(disk());
+    let partitions = scan_gpt(&mut reader).unwrap();
+    assert_eq!(partitions.len(), 2);
+
+    assert_eq!(partitions[0].name, "Microsoft reserved partition");
+    assert!(!partitions[0].is_basic_data());
+
+    let data = &partitions[1];
+    assert_eq!(data.index, 1);
+    assert!(data.is_basic_data());
+    assert_eq!(
+        data.type_guid.to_string(),
+        "EBD0A0A2-B9E5-4433-87C0-68B6B72699C7"
+    );
+    assert_eq!(data.name, "Basic data partition");
+    assert_eq!(data.start_lba, 2048);
+    assert_eq!(data.size(), 1024 * 1024);
+
+    let mut ntfs = Ntfs::new_at_offset(reader, data.byte_offset()).unwrap();
+    assert!(ntfs.open_path(r"\Windows\notepad.exe").is_ok());
+}
+
+#[test]
+fn rejects_missing_signature() {
+    let mut image = disk();
+    image[512..520].copy_from_slice(b"NOT GPT!");
+    assert!(matches!(
+        scan_gpt(&mut Cursor::new(image)),
+        Err(NtfsError::BadMagic)
+    ));
+}
+
+#[test]
+fn rejects_oversized_entries() {
+    let mut image = disk();
+    image[512 + 0x54..512 + 0x58].copy_from_slice(&0x8000_0000u32.to_le_bytes());
+    assert!(matches!(
+        scan_gpt(&mut Cursor::new(image)),
+        Err(NtfsError::Corrupt(_))
+    ));
+}
@@ -0,0 +1,324 @@
+mod common;
+
+use ntfs::*;
+
+fn directory() -> FileRecord {
+    let root = common::index_root(&[
+        common::index_entry(
+            0x0001_0000_0000_0040,
+            &common::file_name(0x0005_0000_0000_0005, "a.txt", 3, 0x20),
+        ),
+        common::index_entry(
+            0x0002_0000_0000_0041,
+            &common::file_name(0x0005_0000_0000_0005, "Documents", 1, 0x1000_0000),
+        ),
+    ]);
+    let attributes = [
+        common::resident(0x30, "", &common::file_name(5, "dir", 1, 0x1000_0000)),
+        common::resident(0x90, "$I30", &root),
+    ];
+    FileRecord::parse(&common::protected(common::record_with(40, 3, &attributes))).unwrap()
+}
+
+#[test]
+fn parses_index_root() {
+    let root = common::index_root(&[common::index_entry(
+        0x0001_0000_0000_0040,
+        &common::file_name(5, "a.txt", 3, 0),
+    )]);
+    let root = IndexRoot::parse(&root).unwrap();
+    assert_eq!(root.attribute_type, 0x30);
+    assert_eq!(root.collation_rule, 1);
+    assert_eq!(root.index_record_size, 4096);
+    assert_eq!(root.clusters_per_index_record, 1);
+    assert!(!root.is_large_index());
+    assert_eq!(root.entries.len(), 1);
+}
+
+#[test]
+fn lists_small_directory() {
+    let entries = directory().index_entries().unwrap();
+    assert_eq!(entries.len(), 2);
+
+    assert_eq!(entries[0].file_reference.record_number, 0x40);
+    assert_eq!(entries[0].file_reference.sequence_number, 1);
+    assert_eq!(entries[0].file_name.name, "a.txt");
+    assert_eq!(entries[0].file_name.parent_directory.record_number, 5);
+    assert!(!entries[0].has_sub_node());
+
+    assert_eq!(entries[1].file_reference.record_number, 0x41);
+    assert_eq!(entries[1].file_name.name, "Documents");
+    assert!(entries[1].file_name.is_directory());
+}
+
+#[test]
+fn empty_directory() {
+    let attributes = [common::resident(0x90, "$I30", &common::index_root(&[]))];
+    let record = common::protected(common::record_with(41, 3, &attributes));
+    let record = FileRecord::parse(&record).unwrap();
+    assert!(record.index_entries().unwrap().is_empty());
+}
+
+#[test]
+fn missing_last_entry() {
+    let mut root = common::index_root(&[]);
+    root.truncate(0x20);
+    root[0x14..0x18].copy_from_slice(&0x10u32.to_le_bytes());
+    assert!(matches!(
+        IndexRoot::parse(&root),
+        Err(NtfsError::Corrupt(_))
+    ));
+}
+
+fn file_entry(record_number: u64, name: &str) -> Vec<u8> {
+    common::index_entry(
+        0x0001_0000_0000_0000 | record_number,
+        &common::file_name(0x0001_0000_0000_0028, name, 1, 0),
+    )
+}
+
+///A large directory whose entries are in two INDX blocks at clusters 0x40 and 0x41.
+fn large_directory(bitmap: u8) -> (FileRecord, Vec<u8>) {
+    let mut root = common::index_root(&[]);
+    root[0x1C] = 0x01;
+    let attributes = [
+        common::resident(0x90, "$I30", &root),
+        common::non_resident(0xA0, "$I30", &[0x11, 0x02, 0x40], 2, 2 * 4096),
+        common::resident(0xB0, "$I30", &[bitmap, 0, 0, 0, 0, 0, 0, 0]),
+    ];
+    let record = common::protected(common::record_with(40, 3, &attributes));
+
+    let mut image = common::numbered_volume();
+    let first = common::index_block(0, &[file_entry(64, "alpha"), file_entry(65, "beta")]);
+    let second = common::index_block(1, &[file_entry(66, "gamma")]);
+    image[0x40 * 4096..0x41 * 4096].copy_from_slice(&first);
+    image[0x41 * 4096..0x42 * 4096].copy_from_slice(&second);
+    (FileRecord::parse(&record).unwrap(), image)
+}
+
+#[test]
+fn parses_index_block() {
+    let block = common::index_block(3, &[file_entry(64, "alpha")]);
+    let block = IndexBlock::parse(&block).unwrap();
+    assert_eq!(block.vcn, 3);
+    assert_eq!(block.entries.len(), 1);
+    assert_eq!(block.entries[0].file_name.name, "alpha");
+
+    let mut bad = common::index_block(0, &[]);
+    bad[0..4].copy_from_slice(b"FILE");
+    assert!(matches!(IndexBlock::parse(&bad), Err(NtfsError::BadMagic)));
+
+    let mut torn = common::index_block(0, &[]);
+    torn[1022] ^= 0xFF;
+    assert!(matches!(
+        IndexBlock::parse(&torn),
+        Err(NtfsError::FixupMismatch { sector_index: 1 })
+    ));
+}
+
+#[test]
+fn reads_index_allocation() {
+    let (record, image) = large_directory(0x03);
+    assert!(record.index_root().unwrap().is_large_index());
+    let mut ntfs = Ntfs::new(std::io::Cursor::new(image)).unwrap();
+    let names: Vec<String> = ntfs
+        .read_index_allocation(&record)
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.file_name.name)
+        .collect();
+    assert_eq!(names, ["alpha", "beta", "gamma"]);
+}
+
+#[test]
+fn skips_unused_index_blocks() {
+    let (record, image) = large_directory(0x01);
+    let mut ntfs = Ntfs::new(std::io::Cursor::new(image)).unwrap();
+    let entries = ntfs.read_index_allocation(&record).unwrap();
+    assert_eq!(entries.len(), 2);
+}
+
+#[test]
+fn small_directory_has_no_index_allocation() {
+    let mut ntfs = Ntfs::new(std::io::Cursor::new(common::numbered_volume())).unwrap();
+    assert!(ntfs.read_index_allocation(&directory()).unwrap().is_empty());
+}
+
+///Adds a sub-node VCN to the end of an index entry.
+fn with_sub_node(mut entry: Vec<u8>, vcn: u64) -> Vec<u8> {
+    entry.extend_from_slice(&vcn.to_le_bytes());
+    let len = entry.len() as u16;
+    entry[0x08..0x0A].copy_from_slice(&len.to_le_bytes());
+    entry[0x0C] |= IndexEntry::SUB_NODE;
+    entry
+}
+
+#[test]
+fn keeps_sub_node_pointers() {
+    let entry = common::index_entry(0x0001_0000_0000_0040, &common::file_name(5, "m.txt", 3, 0));
+    let mut root = common::index_root(&[with_sub_node(entry, 1)]);
+    //Swap the plain last entry for one pointing at the node after "m.txt".
+    root.truncate(root.len() - 0x10);
+    root.extend_from_slice(&with_sub_node(common::last_index_entry(), 2));
+    let entries_size = (root.len() - 0x10) as u32;
+    root[0x14..0x18].copy_from_slice(&entries_size.to_le_bytes());
+    root[0x18..0x1C].copy_from_slice(&entries_size.to_le_bytes());
+    root[0x1C] = IndexRoot::LARGE_INDEX;
+
+    let attributes = [common::resident(0x90, "$I30", &root)];
+    let record = common::protected(common::record_with(42, 3, &attributes));
+    let record = FileRecord::parse(&record).unwrap();
+
+    let entries = record.index_entries().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert!(entries[0].has_sub_node());
+    assert_eq!(entries[0].sub_node_vcn, Some(1));
+    let root = record.index_root().unwrap();
+    assert!(root.is_large_index());
+    assert_eq!(root.last_sub_node_vcn, Some(2));
+}
+
+///A directory whose B-tree is three levels deep:
+///
+///```text
+///root:    [m] -> 1
+///          |
+///vcn 0:   [d, h]        vcn 1: [q, z]
+///          |
+///vcn 2:   [a, b]
+///```
+fn btree_directory(bitmap: u8) -> (FileRecord, Vec<u8>) {
+    let mut root = common::index_root(&[with_sub_node(file_entry(77, "m"), 0)]);
+    root.truncate(root.len() - 0x10);
+    root.extend_from_slice(&with_sub_node(common::last_index_entry(), 1));
+    let entries_size = (root.len() - 0x10) as u32;
+    root[0x14..0x18].copy_from_slice(&entries_size.to_le_bytes());
+    root[0x18..0x1C].copy_from_slice(&entries_size.to_le_bytes());
+    root[0x1C] = IndexRoot::LARGE_INDEX;
+    let attributes = [
+        common::resident(0x90, "$I30", &root),
+        common::non_resident(0xA0, "$I30", &[0x11, 0x03, 0x40], 3, 3 * 4096),
+        common::resident(0xB0, "$I30", &[bitmap, 0, 0, 0, 0, 0, 0, 0]),
+    ];
+    let record = common::protected(common::record_with(40, 3, &attributes));
+
+    let mut image = common::numbered_volume();
+    let blocks = [
+        common::index_block(
+            0,
+            &[with_sub_node(file_entry(67, "d"), 2), file_entry(68, "h")],
+        ),
+        common::index_block(1, &[file_entry(81, "q"), file_entry(90, "z")]),
+        common::index_block(2, &[file_entry(64, "a"), file_entry(65, "b")]),
+    ];
+    for (i, block) in blocks.iter().enumerate() {
+        let offset = (0x40 + i) * 4096;
+        image[offset..offset + 4096].copy_from_slice(block);
+    }
+    (FileRecord::parse(&record).unwrap(), image)
+}
+
+#[test]
+fn walks_btree_in_order() {
+    let (record, image) = btree_directory(0x07);
+    let mut ntfs = Ntfs::new(std::io::Cursor::new(image)).unwrap();
+    let names: Vec<String> = ntfs
+        .directory_iter(&record)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name.name)
+        .collect();
+    assert_eq!(names, ["a", "b", "d", "h", "m", "q", "z"]);
+
+    let listed: Vec<String> = ntfs
+        .read_directory(&record)
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.file_name.name)
+        .collect();
+    assert_eq!(listed, names);
+}
+
+#[test]
+fn sub_node_must_be_in_use() {
+    let (record, image) = btree_directory(0x03);
+    let mut ntfs = Ntfs::new(std::io::Cursor::new(image)).unwrap();
+    let entries: Vec<_> = ntfs.directory_iter(&record).unwrap().collect();
+    assert_eq!(entries.len(), 1);
+    assert!(matches!(entries[0], Err(NtfsError::Corrupt(_))));
+}
+
+#[test]
+fn sub_node_cycle_is_an_error() {
+    let (record, mut image) = btree_directory(0x07);
+    //Point "d" at its own block.
+    let cycle = common::index_block(
+        0,
+        &[with_sub_node(file_entry(67, "d"), 0), file_entry(68, "h")],
+    );
+    image[0x40 * 4096..0x41 * 4096].copy_from_slice(&cycle);
+    let mut ntfs = Ntfs::new(std::io::Cursor::new(image)).unwrap();
+    let result: Result<Vec<_>, _> = ntfs.directory_iter(&record).unwrap().collect();
+    assert!(matches!(result, Err(NtfsError::Corrupt(_))));
+}
+
+#[test]
+fn finds_entries_by_descending_btree() {
+    let (record, image) = btree_directory(0x07);
+    let mut ntfs = Ntfs::new(std::io::Cursor::new(image)).unwrap();
+    let upcase = UpcaseTable::builtin();
+    for (name, n) in [("A", 64), ("d", 67), ("M", 77), ("q", 81), ("Z", 90)] {
+        let entry = ntfs.find_in_directory(&record, name, &upcase).unwrap();
+        assert_eq!(entry.unwrap().file_reference.record_number, n, "{name}");
+    }
+    for name in ["c", "n", "zz", ""] {
+        assert!(ntfs
+            .find_in_directory(&record, name, &upcase)
+            .unwrap()
+            .is_none());
+    }
+}
+
+#[test]
+fn collates_greek_names_with_upcase() {
+    //"αβγ" sorts before "Σίσυφος" once upper cased, but after it by code unit,
+    //so folding only ASCII descends the wrong way.
+    let mut root = common::index_root(&[
+        with_sub_node(file_entry(70, "Σίσυφος"), 0),
+        file_entry(72, "Привет"),
+    ]);
+    let entries_size = (root.len() - 0x10) as u32;
+    root[0x14..0x18].copy_from_slice(&entries_size.to_le_bytes());
+    root[0x18..0x1C].copy_from_slice(&entries_size.to_le_bytes());
+    root[0x1C] = IndexRoot::LARGE_INDEX;
+    let attributes = [
+        common::resident(0x90, "$I30", &root),
+        common::non_resident(0xA0, "$I30", &[0x11, 0x01, 0x40], 1, 4096),
+        common::resident(0xB0, "$I30", &[0x01, 0, 0, 0, 0, 0, 0, 0]),
+    ];
+    let record =
+        FileRecord::parse(&common::protected(common::record_with(40, 3, &attributes))).unwrap();
+    let mut image = common::numbered_volume();
+    let block = common::index_block(0, &[file_entry(71, "αβγ")]);
+    image[0x40 * 4096..0x41 * 4096].copy_from_slice(&block);
+    let mut ntfs = Ntfs::new(std::io::Cursor::new(image)).unwrap();
+
+    let upcase = UpcaseTable::builtin();
+    let found = |ntfs: &mut Ntfs<_>, name, upcase| {
+        ntfs.find_in_directory(&record, name, upcase)
+            .unwrap()
+            .map(|entry| entry.file_reference.record_number)
+    };
+    assert_eq!(found(&mut ntfs, "ΑΒΓ", &upcase), Some(71));
+    assert_eq!(found(&mut ntfs, "σίσυφος", &upcase), Some(70));
+    assert_eq!(found(&mut ntfs, "ПРИВЕТ", &upcase), Some(72));
+
+    let ascii = UpcaseTable(
+        (0..=u16::MAX)
+            .map(|unit| match unit {
+                0x61..=0x7A => unit - 0x20,
+                _ => unit,
+            })
+            .collect(),
+    );
+    assert_eq!(found(&mut ntfs, "αβγ", &ascii), None);
+}
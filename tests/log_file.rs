@@ -0,0 +1,316 @@
+mod common;
+
+use ntfs::*;
+use std::io::Cursor;
+
+const PAGE: usize = 0x1000;
+const DATA_OFFSET: usize = 0x40;
+
+///With 44 sequence number bits, an LSN holds the offset in 8 byte units in its low 20 bits.
+fn lsn(offset: usize) -> u64 {
+    1 << 20 | (offset as u64 >> 3)
+}
+
+fn restart_page(major: u16, minor: u16, current_lsn: u64, client_restart_lsn: u64) -> Vec<u8> {
+    let mut page = vec![0u8; PAGE];
+    page[0x00..0x04].copy_from_slice(b"RSTR");
+    page[0x04..0x06].copy_from_slice(&0x1Eu16.to_le_bytes());
+    page[0x06..0x08].copy_from_slice(&9u16.to_le_bytes());
+    page[0x10..0x14].copy_from_slice(&(PAGE as u32).to_le_bytes());
+    page[0x14..0x18].copy_from_slice(&(PAGE as u32).to_le_bytes());
+    page[0x18..0x1A].copy_from_slice(&0x30u16.to_le_bytes());
+    page[0x1A..0x1C].copy_from_slice(&minor.to_le_bytes());
+    page[0x1C..0x1E].copy_from_slice(&major.to_le_bytes());
+
+    let area = &mut page[0x30..];
+    area[0x00..0x08].copy_from_slice(&current_lsn.to_le_bytes());
+    area[0x08..0x0A].copy_from_slice(&1u16.to_le_bytes());
+    area[0x0A..0x0C].copy_from_slice(&0xFFFFu16.to_le_bytes());
+    area[0x0E..0x10].copy_from_slice(&RestartArea::VOLUME_IS_CLEAN.to_le_bytes());
+    area[0x10..0x14].copy_from_slice(&44u32.to_le_bytes());
+    area[0x14..0x16].copy_from_slice(&0x30u16.to_le_bytes());
+    area[0x16..0x18].copy_from_slice(&0x30u16.to_le_bytes());
+    area[0x18..0x20].copy_from_slice(&0x8000u64.to_le_bytes());
+    area[0x24..0x26].copy_from_slice(&0x30u16.to_le_bytes());
+    area[0x26..0x28].copy_from_slice(&(DATA_OFFSET as u16).to_le_bytes());
+    area[0x28..0x2C].copy_from_slice(&3u32.to_le_bytes());
+
+    let client = &mut area[0x30..];
+    client[0x00..0x08].copy_from_slice(&client_restart_lsn.to_le_bytes());
+    client[0x08..0x10].copy_from_slice(&client_restart_lsn.to_le_bytes());
+    client[0x10..0x14].copy_from_slice(&[0xFF; 4]);
+    client[0x14..0x16].copy_from_slice(&1u16.to_le_bytes());
+    client[0x1C..0x20].copy_from_slice(&8u32.to_le_bytes());
+    for (i, unit) in "NTFS".encode_utf16().enumerate() {
+        client[0x20 + i * 2..0x22 + i * 2].copy_from_slice(&unit.to_le_bytes());
+    }
+    common::protected(page)
+}
+
+fn record_page(last_lsn: u64, next_record_offset: u16, last_end_lsn: u64) -> Vec<u8> {
+    let mut page = vec![0u8; PAGE];
+    page[0x00..0x04].copy_from_slice(b"RCRD");
+    page[0x04..0x06].copy_from_slice(&0x28u16.to_le_bytes());
+    page[0x06..0x08].copy_from_slice(&9u16.to_le_bytes());
+    page[0x08..0x10].copy_from_slice(&last_lsn.to_le_bytes());
+    page[0x10..0x14].copy_from_slice(&RecordPageHeader::RECORD_END.to_le_bytes());
+    page[0x14..0x16].copy_from_slice(&1u16.to_le_bytes());
+    page[0x16..0x18].copy_from_slice(&1u16.to_le_bytes());
+    page[0x18..0x1A].copy_from_slice(&next_record_offset.to_le_bytes());
+    page[0x20..0x28].copy_from_slice(&last_end_lsn.to_le_bytes());
+    page
+}
+
+fn operation(redo: LogOperation, undo: LogOperation, target_vcn: u64, lcns: &[u64]) -> Vec<u8> {
+    let mut data = vec![0u8; 0x20];
+    data[0x00..0x02].copy_from_slice(&redo.0.to_le_bytes());
+    data[0x02..0x04].copy_from_slice(&undo.0.to_le_bytes());
+    data[0x04..0x06].copy_from_slice(&0x28u16.to_le_bytes());
+    data[0x06..0x08].copy_from_slice(&0x10u16.to_le_bytes());
+    data[0x08..0x0A].copy_from_slice(&0x38u16.to_le_bytes());
+    data[0x0C..0x0E].copy_from_slice(&0x18u16.to_le_bytes());
+    data[0x0E..0x10].copy_from_slice(&(lcns.len() as u16).to_le_bytes());
+    data[0x10..0x12].copy_from_slice(&0x400u16.to_le_bytes());
+    data[0x12..0x14].copy_from_slice(&0x98u16.to_le_bytes());
+    data[0x14..0x16].copy_from_slice(&2u16.to_le_bytes());
+    data[0x18..0x20].copy_from_slice(&target_vcn.to_le_bytes());
+    for lcn in lcns {
+        data.extend_from_slice(&lcn.to_le_bytes());
+    }
+    data
+}
+
+fn log_record(this_lsn: u64, previous_lsn: u64, record_type: u32, client_data: &[u8]) -> Vec<u8> {
+    let mut record = vec![0u8; 0x30];
+    record[0x00..0x08].copy_from_slice(&this_lsn.to_le_bytes());
+    record[0x08..0x10].copy_from_slice(&previous_lsn.to_le_bytes());
+    record[0x18..0x1C].copy_from_slice(&(client_data.len() as u32).to_le_bytes());
+    record[0x1C..0x1E].copy_from_slice(&1u16.to_le_bytes());
+    record[0x20..0x24].copy_from_slice(&record_type.to_le_bytes());
+    record[0x24..0x28].copy_from_slice(&0x18u32.to_le_bytes());
+    if client_data.len() > PAGE - DATA_OFFSET {
+        record[0x28..0x2A].copy_from_slice(&LogRecord::MULTI_PAGE.to_le_bytes());
+    }
+    record.extend_from_slice(client_data);
+    record
+}
+
+///Writes `bytes` to the log from `offset` on, continuing after the header of the following pages.
+fn write_log(log: &mut [u8], mut offset: usize, mut bytes: &[u8]) {
+    while !bytes.is_empty() {
+        let page_end = offset - offset % PAGE + PAGE;
+        let len = bytes.len().min(page_end - offset);
+        log[offset..offset + len].copy_from_slice(&bytes[..len]);
+        bytes = &bytes[len..];
+        offset = page_end + DATA_OFFSET;
+    }
+}
+
+///The offsets of the records in [`log_volume`].
+const A: usize = 0x4040;
+const B: usize = 0x4098;
+const C: usize = 0x5108;
+const D: usize = 0x6040;
+
+///A volume whose $LogFile (record 2) fills the eight clusters at 0x70. Page 0 is a 2.0 restart page
+///and page 1 an older 1.1 one, pages 2 and 3 hold a tail copy of page 4. Page 4 holds record A and
+///the start of B, which ends in page 5 before the checkpoint C and a stale record. Page 6 holds D
+///but is torn.
+fn log_volume() -> Vec<u8> {
+    let mut log = vec![0u8; 8 * PAGE];
+    log[0..PAGE].copy_from_slice(&restart_page(2, 0, lsn(C), lsn(A)));
+    log[PAGE..2 * PAGE].copy_from_slice(&restart_page(1, 1, lsn(B), lsn(A)));
+
+    log[4 * PAGE..5 * PAGE].copy_from_slice(&record_page(lsn(B), 0x98, lsn(A)));
+    log[5 * PAGE..6 * PAGE].copy_from_slice(&record_page(lsn(C), 0x148, lsn(C)));
+    log[6 * PAGE..7 * PAGE].copy_from_slice(&record_page(lsn(D), 0x98, lsn(D)));
+    let a = operation(
+        LogOperation::INITIALIZE_FILE_RECORD_SEGMENT,
+        LogOperation::NOOP,
+        0x10,
+        &[0x1234],
+    );
+    write_log(&mut log, A, &log_record(lsn(A), 0, LogRecord::STANDARD, &a));
+    let mut b = operation(
+        LogOperation::UPDATE_NONRESIDENT_VALUE,
+        LogOperation::NOOP,
+        0x20,
+        &[0x5678, 0x5679],
+    );
+    b.resize(0x1000, 0xAA);
+    write_log(
+        &mut log,
+        B,
+        &log_record(lsn(B), lsn(A), LogRecord::STANDARD, &b),
+    );
+    write_log(
+        &mut log,
+        C,
+        &log_record(lsn(C), lsn(B), LogRecord::CHECKPOINT, &[0x11; 0x10]),
+    );
+    let stale = 0x5148;
+    write_log(
+        &mut log,
+        stale,
+        &log_record(stale as u64 >> 3, 0, LogRecord::STANDARD, &a),
+    );
+    let d = operation(LogOperation::COMMIT_TRANSACTION, LogOperation::NOOP, 0, &[]);
+    write_log(
+        &mut log,
+        D,
+        &log_record(lsn(D), lsn(C), LogRecord::STANDARD, &d),
+    );
+
+    let tail = log[4 * PAGE..5 * PAGE].to_vec();
+    log[2 * PAGE..3 * PAGE].copy_from_slice(&tail);
+    log[3 * PAGE..4 * PAGE].copy_from_slice(&tail);
+    for page in log.chunks_mut(PAGE).skip(2).take(5) {
+        common::protect(page);
+    }
+    log[6 * PAGE + 0x3FE] ^= 0xFF;
+
+    let mut log_record_2 = common::record_with(
+        2,
+        0x0001,
+        &[common::non_resident(
+            0x80,
+            "",
+            &[0x11, 0x08, 0x70],
+            8,
+            0x8000,
+        )],
+    );
+    log_record_2[0x08..0x10].copy_from_slice(&lsn(A).to_le_bytes());
+    let records = vec![
+        common::mft_record_0(),
+        Vec::new(),
+        common::protected(log_record_2),
+    ];
+    let mut image = common::volume_with_mft(&records);
+    image[0x70 * 4096..0x78 * 4096].copy_from_slice(&log);
+    image
+}
+
+#[test]
+fn parses_restart_pages() {
+    let mut ntfs = Ntfs::new(Cursor::new(log_volume())).unwrap();
+    let log_file = ntfs.log_file().unwrap();
+    let versions: Vec<(u16, u16)> = log_file
+        .restart_pages()
+        .iter()
+        .map(|page| (page.major_version, page.minor_version))
+        .collect();
+    assert_eq!(versions, [(2, 0), (1, 1)]);
+
+    let page = log_file.restart_page();
+    assert!(!page.is_chkdsk);
+    assert_eq!(page.system_page_size, 0x1000);
+    assert_eq!(page.log_page_size, 0x1000);
+    let area = log_file.restart_area();
+    assert_eq!(area.current_lsn, lsn(C));
+    assert!(area.is_clean());
+    assert_eq!(area.file_size, 0x8000);
+    assert_eq!(area.log_page_data_offset, 0x40);
+    assert_eq!(area.restart_log_open_count, Some(3));
+    assert_eq!(area.lsn_to_offset(lsn(C)), C as u64);
+    assert_eq!(area.lsn_sequence_number(lsn(C)), 1);
+    assert_eq!(area.clients.len(), 1);
+    assert_eq!(area.clients[0].name, "NTFS");
+    assert_eq!(area.clients[0].client_restart_lsn, lsn(A));
+}
+
+#[test]
+fn falls_back_to_the_second_restart_page() {
+    let mut image = log_volume();
+    image[0x70 * 4096 + 0x200 - 2] ^= 0xFF;
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    let log_file = ntfs.log_file().unwrap();
+    assert_eq!(log_file.restart_pages().len(), 1);
+    assert_eq!(log_file.restart_page().minor_version, 1);
+    assert_eq!(log_file.restart_area().current_lsn, lsn(B));
+}
+
+#[test]
+fn rejects_unknown_versions() {
+    let page = restart_page(1, 0, lsn(C), lsn(A));
+    assert!(matches!(
+        RestartPage::parse(&page),
+        Err(NtfsError::Unsupported(_))
+    ));
+    let mut page = restart_page(2, 0, lsn(C), lsn(A));
+    page[0..4].copy_from_slice(b"CHKD");
+    assert!(RestartPage::parse(&page).unwrap().is_chkdsk);
+    page[0..4].copy_from_slice(b"RCRD");
+    assert!(matches!(
+        RestartPage::parse(&page),
+        Err(NtfsError::BadMagic)
+    ));
+}
+
+#[test]
+fn iterates_log_records() {
+    let mut ntfs = Ntfs::new(Cursor::new(log_volume())).unwrap();
+    let mut log_file = ntfs.log_file().unwrap();
+    let records: Vec<LogRecord> = log_file
+        .records()
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let lsns: Vec<u64> = records.iter().map(|record| record.this_lsn).collect();
+    assert_eq!(lsns, [lsn(A), lsn(B), lsn(C)]);
+
+    let a = records[0].operation.as_ref().unwrap();
+    assert_eq!(
+        a.redo_operation,
+        LogOperation::INITIALIZE_FILE_RECORD_SEGMENT
+    );
+    assert_eq!(a.undo_operation, LogOperation::NOOP);
+    assert_eq!(a.target_attribute, 0x18);
+    assert_eq!(a.record_offset, 0x400);
+    assert_eq!(a.attribute_offset, 0x98);
+    assert_eq!(a.cluster_index, 2);
+    assert_eq!(a.target_vcn, 0x10);
+    assert_eq!(a.lcns, [0x1234]);
+    assert_eq!(records[0].transaction_id, 0x18);
+
+    assert!(records[1].is_multi_page());
+    assert_eq!(records[1].client_previous_lsn, lsn(A));
+    let b = records[1].operation.as_ref().unwrap();
+    assert_eq!(b.redo_operation.to_string(), "UpdateNonresidentValue");
+    assert_eq!(b.lcns, [0x5678, 0x5679]);
+
+    assert_eq!(records[2].record_type, LogRecord::CHECKPOINT);
+    assert_eq!(records[2].operation, None);
+}
+
+#[test]
+fn finds_the_last_change_of_a_file_record() {
+    let mut ntfs = Ntfs::new(Cursor::new(log_volume())).unwrap();
+    let lsn_of_record = ntfs.file_record(2).unwrap().lsn;
+    let mut log_file = ntfs.log_file().unwrap();
+    let record = log_file.record_at(lsn_of_record).unwrap().unwrap();
+    assert_eq!(record.this_lsn, lsn(A));
+    assert_eq!(
+        log_file
+            .record_at(lsn(B))
+            .unwrap()
+            .unwrap()
+            .operation
+            .unwrap()
+            .target_vcn,
+        0x20
+    );
+    assert_eq!(log_file.record_at(lsn(D)).unwrap(), None);
+    assert_eq!(log_file.record_at(lsn(A) + 1).unwrap(), None);
+}
+
+#[test]
+fn names_operations() {
+    assert_eq!(
+        LogOperation::COMMIT_TRANSACTION.name(),
+        Some("CommitTransaction")
+    );
+    assert_eq!(LogOperation(0x25).to_string(), "ZeroEndOfFileRecord");
+    assert_eq!(LogOperation(0x26).name(), None);
+    assert_eq!(LogOperation(0x26).to_string(), "0x26");
+}
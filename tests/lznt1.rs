@@ -0,0 +1,78 @@
+use ntfs::*;
+
+///"abc" as literals, then a back-reference 3 bytes back for 9 bytes.
+const ABC: [u8; 8] = [0x05, 0xB0, 0x08, b'a', b'b', b'c', 0x06, 0x20];
+
+#[test]
+fn overlapping_back_reference() {
+    let mut output = Vec::new();
+    decompress_lznt1(&ABC, &mut output).unwrap();
+    assert_eq!(output, b"abcabcabcabc");
+}
+
+#[test]
+fn offset_length_split_shifts() {
+    //20 literals, then a back-reference 20 bytes back for 20 bytes.
+    //Past position 16, the offset takes 5 bits and the length 11.
+    let mut compressed = vec![0x18, 0xB0, 0x00];
+    compressed.extend_from_slice(b"01234567");
+    compressed.push(0x00);
+    compressed.extend_from_slice(b"89ABCDEF");
+    compressed.push(0x10);
+    compressed.extend_from_slice(b"GHIJ");
+    compressed.extend_from_slice(&[0x11, 0x98]);
+
+    let mut output = Vec::new();
+    decompress_lznt1(&compressed, &mut output).unwrap();
+    assert_eq!(output, b"0123456789ABCDEFGHIJ0123456789ABCDEFGHIJ");
+}
+
+#[test]
+fn uncompressed_chunk_and_end_marker() {
+    let mut compressed = vec![0xFF, 0x3F];
+    compressed.extend(std::iter::repeat_n(0x5A, 4096));
+    compressed.extend_from_slice(&ABC);
+    compressed.extend_from_slice(&[0x00, 0x00, 0xFF, 0xFF]);
+
+    let mut output = Vec::new();
+    decompress_lznt1(&compressed, &mut output).unwrap();
+    assert_eq!(output.len(), 4096 + 12);
+    assert!(output[..4096].iter().all(|b| *b == 0x5A));
+    assert_eq!(&output[4096..], b"abcabcabcabc");
+}
+
+#[test]
+fn short_chunks_are_padded() {
+    let compressed = [ABC, ABC].concat();
+    let mut output = Vec::new();
+    decompress_lznt1(&compressed, &mut output).unwrap();
+    assert_eq!(output.len(), 4096 + 12);
+    assert_eq!(&output[..12], b"abcabcabcabc");
+    assert!(output[12..4096].iter().all(|b| *b == 0));
+}
+
+#[test]
+fn pads_relative_to_existing_output() {
+    let compressed = [ABC, ABC].concat();
+    let mut output = b"header".to_vec();
+    decompress_lznt1(&compressed, &mut output).unwrap();
+    assert_eq!(output.len(), 6 + 4096 + 12);
+    assert_eq!(&output[..6], b"header");
+    assert_eq!(&output[6..18], b"abcabcabcabc");
+    assert!(output[18..6 + 4096].iter().all(|b| *b == 0));
+    assert_eq!(&output[6 + 4096..], b"abcabcabcabc");
+}
+
+#[test]
+fn rejects_bad_back_reference() {
+    //A back-reference as the first token has nothing to point at.
+    let mut output = Vec::new();
+    assert!(matches!(
+        decompress_lznt1(&[0x02, 0xB0, 0x01, 0x00, 0x00], &mut output),
+        Err(NtfsError::Corrupt(_))
+    ));
+    assert!(matches!(
+        decompress_lznt1(&[0x10, 0xB0, 0x00, b'a'], &mut output),
+        Err(NtfsError::Corrupt(_))
+    ));
+}
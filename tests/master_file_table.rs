@@ -0,0 +1,220 @@
+mod common;
+
+use ntfs::*;
+use std::io::Cursor;
+
+#[test]
+fn iterates_file_records() {
+    let image = common::volume_with_mft(&[
+        common::mft_record_0(),
+        common::protected(common::file_record(1, 0x0001)),
+    ]);
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+
+    let records: Vec<_> = ntfs.file_records().collect::<Result<_, _>>().unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].mft_record_number, 0);
+    assert_eq!(records[1].mft_record_number, 1);
+}
+
+#[test]
+fn file_records_stops_at_end_of_volume() {
+    let mut image = common::volume_with_mft(&[common::mft_record_0()]);
+    image.truncate(4 * 4096 + 1024 + 512);
+    let options = ParseOptions {
+        check_length: false,
+        ..ParseOptions::default()
+    };
+    let mut ntfs = Ntfs::new_with_options(Cursor::new(image), options).unwrap();
+    assert_eq!(ntfs.file_records().count(), 1);
+}
+
+///An $MFT split into one cluster at LCN 4 and 15 clusters at LCN 100.
+fn fragmented_volume() -> Vec<u8> {
+    let record_0 = common::mft_record_0_with_runs(&[0x11, 0x01, 0x04, 0x11, 0x0F, 0x60], 16);
+    let mut image = common::volume_with_mft(&[record_0]);
+    for n in 1..6u32 {
+        let record = common::protected(common::file_record(n, 0x0001));
+        let offset = if n < 4 {
+            4 * 4096 + n as usize * 1024
+        } else {
+            100 * 4096 + (n as usize - 4) * 1024
+        };
+        image[offset..offset + 1024].copy_from_slice(&record);
+    }
+    image
+}
+
+#[test]
+fn loads_mft_runs() {
+    let mut ntfs = Ntfs::new(Cursor::new(fragmented_volume())).unwrap();
+    assert_eq!(ntfs.load_mft_runs().unwrap(), vec![(4, 1), (100, 15)]);
+}
+
+#[test]
+fn reads_records_through_mft_runs() {
+    let mut ntfs = Ntfs::new(Cursor::new(fragmented_volume())).unwrap();
+    assert_eq!(ntfs.file_record(3).unwrap().mft_record_number, 3);
+    assert_eq!(ntfs.file_record(4).unwrap().mft_record_number, 4);
+    assert_eq!(ntfs.file_record(5).unwrap().mft_record_number, 5);
+    assert!(matches!(
+        ntfs.file_record(64),
+        Err(NtfsError::RecordOutOfRange(64))
+    ));
+
+    let numbers: Vec<_> = ntfs
+        .file_records()
+        .map(|record| record.unwrap().mft_record_number)
+        .collect();
+    assert_eq!(numbers, [0, 1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn skips_bad_and_empty_records() {
+    let mut bad = common::protected(common::file_record(1, 0x0001));
+    bad[0..4].copy_from_slice(b"BAAD");
+    let image = common::volume_with_mft(&[
+        common::mft_record_0(),
+        bad,
+        vec![0; 1024],
+        common::protected(common::file_record(3, 0x0001)),
+    ]);
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+
+    let mut records = ntfs.file_records();
+    let numbers: Vec<_> = records
+        .by_ref()
+        .map(|record| record.unwrap().mft_record_number)
+        .collect();
+    assert_eq!(numbers, [0, 3]);
+    assert_eq!(records.bad_records(), [1]);
+    assert_eq!(records.empty_records().len(), 64 - 3);
+    assert_eq!(records.empty_records()[0], 2);
+}
+
+#[test]
+fn parse_distinguishes_bad_and_empty_records() {
+    let mut bad = common::protected(common::file_record(1, 0x0001));
+    bad[0..4].copy_from_slice(b"BAAD");
+    assert!(matches!(FileRecord::parse(&bad), Err(NtfsError::BadRecord)));
+    assert!(matches!(
+        FileRecord::parse(&[0; 1024]),
+        Err(NtfsError::EmptyRecord)
+    ));
+}
+
+///A $MFT of 128 records whose $DATA is split between record 0 (clusters 4-19)
+///and extension record 1 (clusters 0x80-0x8F), with record 70 in the second half.
+fn mft_with_attribute_list() -> Vec<u8> {
+    let list = [
+        common::attribute_list_entry(0x10, "", 0, 0x0001_0000_0000_0000, 0),
+        common::attribute_list_entry(0x30, "", 0, 0x0001_0000_0000_0000, 2),
+        common::attribute_list_entry(0x80, "", 0, 0x0001_0000_0000_0000, 3),
+        common::attribute_list_entry(0x80, "", 16, 0x0001_0000_0000_0001, 0),
+    ]
+    .concat();
+    let mut data = common::non_resident(0x80, "", &[0x11, 0x10, 0x04], 16, 128 * 1024);
+    common::set_vcns(&mut data, 0, 15);
+    let record_0 = common::record_with(
+        0,
+        0x0001,
+        &[
+            common::resident(0x10, "", &common::standard_information(0, 0x06)),
+            common::resident(0x20, "", &list),
+            common::resident(0x30, "", &common::file_name(5, "$MFT", 3, 0x06)),
+            data,
+        ],
+    );
+    let mut data = common::non_resident(0x80, "", &[0x21, 0x10, 0x80, 0x00], 16, 0);
+    common::set_vcns(&mut data, 16, 31);
+    let record_1 = common::extension_record(1, 0, &[data]);
+
+    let mut image =
+        common::volume_with_mft(&[common::protected(record_0), common::protected(record_1)]);
+    let record_70 = common::protected(common::file_record(70, 0x0001));
+    let offset = 0x80 * 4096 + 6 * 1024;
+    image[offset..offset + 1024].copy_from_slice(&record_70);
+    image
+}
+
+#[test]
+fn follows_mft_attribute_list() {
+    let mut ntfs = Ntfs::new(Cursor::new(mft_with_attribute_list())).unwrap();
+    let mut mft = ntfs.master_file_table().unwrap();
+    assert_eq!(mft.runs(), [(4, 16), (0x80, 16)]);
+    assert_eq!(mft.record_count(), 128);
+    assert_eq!(mft.record(70).unwrap().mft_record_number, 70);
+    assert!(matches!(
+        mft.record(128),
+        Err(NtfsError::RecordOutOfRange(128))
+    ));
+}
+
+#[test]
+fn master_file_table_of_fragmented_volume() {
+    let mut ntfs = Ntfs::new(Cursor::new(fragmented_volume())).unwrap();
+    let mut mft = ntfs.master_file_table().unwrap();
+    assert_eq!(mft.record_count(), 64);
+    assert_eq!(mft.record(5).unwrap().mft_record_number, 5);
+    let numbers: Vec<_> = mft
+        .records()
+        .map(|record| record.unwrap().mft_record_number)
+        .collect();
+    assert_eq!(numbers, [0, 1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn iterates_whole_mft() {
+    let mut image = fragmented_volume();
+    //Mark record 2 as BAAD.
+    image[4 * 4096 + 2 * 1024..4 * 4096 + 2 * 1024 + 4].copy_from_slice(b"BAAD");
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    let mut mft = ntfs.master_file_table().unwrap();
+
+    let iter = mft.iter();
+    assert_eq!(iter.len(), 64);
+    let results: Vec<_> = iter.collect();
+    assert_eq!(results.len(), 64);
+    assert_eq!(results[1].0, 1);
+    assert_eq!(results[1].1.as_ref().unwrap().mft_record_number, 1);
+    assert!(matches!(results[2].1, Err(NtfsError::BadRecord)));
+    assert_eq!(results[5].1.as_ref().unwrap().mft_record_number, 5);
+    assert!(matches!(results[6].1, Err(NtfsError::EmptyRecord)));
+    assert!(matches!(results[63].1, Err(NtfsError::EmptyRecord)));
+}
+
+#[test]
+fn iterates_from_checkpoint() {
+    let mut ntfs = Ntfs::new(Cursor::new(fragmented_volume())).unwrap();
+    let mut mft = ntfs.master_file_table().unwrap();
+
+    let mut iter = mft.iter_from(4);
+    assert_eq!(iter.len(), 60);
+    let (n, record) = iter.next().unwrap();
+    assert_eq!(n, 4);
+    assert_eq!(record.unwrap().mft_record_number, 4);
+    assert_eq!(iter.len(), 59);
+
+    assert_eq!(mft.iter_from(1000).count(), 0);
+}
+
+#[test]
+fn system_files() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::tree_volume())).unwrap();
+    let mut mft = ntfs.master_file_table().unwrap();
+    assert_eq!(
+        mft.system_file(SystemFile::Mft).unwrap().mft_record_number,
+        0
+    );
+    let root = mft.root_directory().unwrap();
+    assert_eq!(root.mft_record_number, 5);
+    assert!(root.is_directory());
+    assert!(matches!(mft.volume_record(), Err(NtfsError::EmptyRecord)));
+
+    assert_eq!(SystemFile::UpCase.record_number(), 10);
+    assert_eq!(SystemFile::Extend.name(), "$Extend");
+    assert_eq!(
+        ROOT_RECORD_NUMBER,
+        SystemFile::RootDirectory.record_number()
+    );
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use ntfs::*;
+use std::io::Cursor;
+
+fn partition_entry(status: u8, partition_type: u8, start_lba: u32, sectors: u32) -> [u8; 16] {
+    let mut entry = [0u8; 16];
+    entry[0x00] = status;
+    entry[0x04] = partition_type;
+    entry[0x08..0x0C].copy_from_slice(&start_lba.to_le_bytes());
+    entry[0x0C..0x10].copy_from_slice(&sectors.to_le_bytes());
+    entry
+}
+
+///A disk with a small FAT partition and the tree volume in an NTFS partition at sector 2048.
+fn disk() -> Vec<u8> {
+    let mut volume = common::tree_volume();
+    volume[0x1C..0x20].copy_from_slice(&2048u32.to_le_bytes());
+    let sectors = (volume.len() / 512) as u32;
+
+    let mut image = vec![0u8; 2048 * 512];
+    image[0x1BE..0x1CE].copy_from_slice(&partition_entry(0x00, 0x0C, 63, 1985));
+    image[0x1CE..0x1DE].copy_from_slice(&partition_entry(0x80, 0x07, 2048, sectors));
+    image[510..512].copy_from_slice(&[0x55, 0xAA]);
+    image.extend(volume);
+    image
+}
+
+#[test]
+fn finds_ntfs_partition() {
+    let mut reader = Cursor::new(disk());
+    let partitions = scan_mbr(&mut reader).unwrap();
+    assert_eq!(partitions.len(), 2);
+
+    assert_eq!(partitions[0].partition_type, 0x0C);
+    assert!(!partitions[0].is_ntfs());
+    assert!(!partitions[0].bootable);
+
+    let ntfs = partitions[1];
+    assert_eq!(ntfs.index, 1);
+    assert!(ntfs.is_ntfs());
+    assert!(ntfs.bootable);
+    assert_eq!(ntfs.start_lba, 2048);
+    assert_eq!(ntfs.size(), 1024 * 1024);
+
+    let mut volume = Ntfs::new_at_offset(reader, ntfs.byte_offset()).unwrap();
+    assert!(volume.open_path(r"\readme.txt").is_ok());
+}
+
+#[test]
+fn rejects_missing_signature() {
+    let mut image = disk();
+    image[510] = 0;
+    assert!(matches!(
+        scan_mbr(&mut Cursor::new(image)),
+        Err(NtfsError::BadEndMarker)
+    ));
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use ntfs::*;
+use std::io::Cursor;
+
+///A volume whose $MFT bitmap at cluster 0x40 marks records 0 to 3, 5 and 9 as in use.
+///Record 5 was deleted without clearing its bit, record 9 is zeroed and record 6 is in use
+///without its bit set.
+fn volume() -> Vec<u8> {
+    let mut records = vec![Vec::new(); 10];
+    records[0] = common::mft_record_0();
+    records[1] = common::file_in(1, 5, "$MFTMirr");
+    records[2] = common::file_in(2, 5, "$LogFile");
+    records[3] = common::file_in(3, 5, "$Volume");
+    records[5] = common::protected(common::record_with(
+        5,
+        0x0000,
+        &[common::resident(
+            0x30,
+            "",
+            &common::file_name(5, "deleted.txt", 1, 0x20),
+        )],
+    ));
+    records[6] = common::file_in(6, 5, "unmarked.txt");
+    let mut image = common::volume_with_mft(&records);
+    image[0x40 * 4096..0x40 * 4096 + 8].copy_from_slice(&[0x2F, 0x02, 0, 0, 0, 0, 0, 0]);
+    image
+}
+
+#[test]
+fn reads_record_bitmap() {
+    let mut ntfs = Ntfs::new(Cursor::new(volume())).unwrap();
+    assert_eq!(ntfs.mft_bitmap().unwrap(), [0x2F, 0x02, 0, 0, 0, 0, 0, 0]);
+
+    let mut mft = ntfs.master_file_table().unwrap();
+    assert!(mft.record_in_use(0).unwrap());
+    assert!(mft.record_in_use(5).unwrap());
+    assert!(!mft.record_in_use(4).unwrap());
+    assert!(!mft.record_in_use(6).unwrap());
+    assert!(!mft.record_in_use(1000).unwrap());
+    assert_eq!(mft.allocated_record_count().unwrap(), 6);
+}
+
+#[test]
+fn finds_allocated_unused_records() {
+    let mut ntfs = Ntfs::new(Cursor::new(volume())).unwrap();
+    let mut mft = ntfs.master_file_table().unwrap();
+    let records: Vec<u64> = mft
+        .allocated_unused_records()
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(records, [5]);
+}
+
+#[test]
+fn ignores_padding_past_last_record() {
+    //A $MFT of 15 clusters holds 60 records, so the last 4 bits of the 8 byte bitmap are padding.
+    let mut image = volume();
+    let mft = common::mft_record_0_with_runs(&[0x11, 0x0F, 0x04], 15);
+    image[4 * 4096..4 * 4096 + 1024].copy_from_slice(&mft);
+    image[0x40 * 4096 + 7] = 0xF0;
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    let mut mft = ntfs.master_file_table().unwrap();
+    assert_eq!(mft.record_count(), 60);
+    assert_eq!(mft.allocated_record_count().unwrap(), 6);
+    assert!(!mft.record_in_use(60).unwrap());
+}
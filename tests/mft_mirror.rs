@@ -0,0 +1,89 @@
+mod common;
+
+use ntfs::*;
+use std::io::Cursor;
+
+const MIRROR_LCN: usize = 0x30;
+
+fn system_record(record_number: u32, name: &str) -> Vec<u8> {
+    common::protected(common::record_with(
+        record_number,
+        0x0001,
+        &[common::resident(
+            0x30,
+            "",
+            &common::file_name(0x0005_0000_0000_0005, name, 3, 0x06),
+        )],
+    ))
+}
+
+///Changes the update sequence number of a protected record, as if it was written again.
+fn rewrite_usn(record: &mut [u8], usn: u16) {
+    record[0x30..0x32].copy_from_slice(&usn.to_le_bytes());
+    record[510..512].copy_from_slice(&usn.to_le_bytes());
+    record[1022..1024].copy_from_slice(&usn.to_le_bytes());
+}
+
+///A volume whose $MFTMirr at cluster 0x30 is an exact copy of the first four records.
+fn volume() -> Vec<u8> {
+    let records = vec![
+        common::mft_record_0(),
+        system_record(1, "$MFTMirr"),
+        system_record(2, "$LogFile"),
+        system_record(3, "$Volume"),
+    ];
+    let mut image = common::volume_with_mft(&records);
+    image[56..64].copy_from_slice(&(MIRROR_LCN as u64).to_le_bytes());
+    for (n, record) in records.iter().enumerate() {
+        let offset = MIRROR_LCN * 4096 + n * 1024;
+        image[offset..offset + 1024].copy_from_slice(record);
+    }
+    image
+}
+
+fn mirror_offset(n: usize) -> usize {
+    MIRROR_LCN * 4096 + n * 1024
+}
+
+#[test]
+fn matching_mirror() {
+    let mut image = volume();
+    //A different update sequence number doesn't count as a difference.
+    rewrite_usn(&mut image[mirror_offset(2)..mirror_offset(3)], 0x0042);
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    assert!(ntfs.verify_mft_mirror().unwrap());
+    assert_eq!(
+        ntfs.mirror_record(3).unwrap().best_name().as_deref(),
+        Some("$Volume")
+    );
+    assert!(matches!(
+        ntfs.mirror_record(4),
+        Err(NtfsError::RecordOutOfRange(4))
+    ));
+}
+
+#[test]
+fn mismatching_mirror() {
+    let mut image = volume();
+    //Bump the sequence number of the $Volume copy.
+    image[mirror_offset(3) + 0x10] = 2;
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    assert!(!ntfs.verify_mft_mirror().unwrap());
+}
+
+#[test]
+fn falls_back_to_mirror() {
+    let mut image = volume();
+    //Tear the second sector of $LogFile in the $MFT.
+    let offset = 4 * 4096 + 2 * 1024;
+    image[offset + 1022] ^= 0xFF;
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+
+    assert!(matches!(
+        ntfs.file_record(2),
+        Err(NtfsError::FixupMismatch { sector_index: 1 })
+    ));
+    assert!(ntfs.verify_mft_mirror().is_err());
+    let record = ntfs.file_record_or_mirror(2).unwrap();
+    assert_eq!(record.best_name().as_deref(), Some("$LogFile"));
+}
@@ -0,0 +1,201 @@
+mod common;
+
+use ntfs::*;
+use std::{cmp::Ordering, io::Cursor};
+
+const SEQ: u64 = 0x0001_0000_0000_0000;
+
+///A GUID whose first little-endian u32 is `first`, so it sorts by `first` in the $O index.
+fn guid(first: u32) -> Guid {
+    let mut bytes = [0xEE; 16];
+    bytes[0..4].copy_from_slice(&first.to_le_bytes());
+    Guid(bytes)
+}
+
+fn object_id_value(object_id: Guid, birth_volume_id: Guid) -> Vec<u8> {
+    let mut value = object_id.0.to_vec();
+    value.extend_from_slice(&birth_volume_id.0);
+    value.extend_from_slice(&object_id.0);
+    value.extend_from_slice(&[0; 16]);
+    value
+}
+
+///An $O index entry mapping `object_id` to `file_reference`.
+fn o_entry(object_id: Guid, file_reference: u64) -> Vec<u8> {
+    let mut entry = vec![0u8; 0x20];
+    entry[0x00..0x02].copy_from_slice(&0x20u16.to_le_bytes());
+    entry[0x02..0x04].copy_from_slice(&0x38u16.to_le_bytes());
+    entry[0x08..0x0A].copy_from_slice(&0x58u16.to_le_bytes());
+    entry[0x0A..0x0C].copy_from_slice(&0x10u16.to_le_bytes());
+    entry[0x10..0x20].copy_from_slice(&object_id.0);
+    entry.extend_from_slice(&file_reference.to_le_bytes());
+    entry.extend_from_slice(&[0x11; 16]);
+    entry.extend_from_slice(&object_id.0);
+    entry.extend_from_slice(&[0; 16]);
+    entry
+}
+
+fn with_sub_node(mut entry: Vec<u8>, vcn: u64) -> Vec<u8> {
+    entry.extend_from_slice(&vcn.to_le_bytes());
+    let len = entry.len() as u16;
+    entry[0x08..0x0A].copy_from_slice(&len.to_le_bytes());
+    entry[0x0C] |= IndexEntry::SUB_NODE;
+    entry
+}
+
+///A large $O $INDEX_ROOT value holding `entries`, whose last entry points at `last_sub_node`.
+fn o_root(entries: &[Vec<u8>], last_sub_node: u64) -> Vec<u8> {
+    let mut value = common::index_root(entries);
+    let last = value.len() - 0x10;
+    value.truncate(last);
+    value.extend_from_slice(&with_sub_node(common::last_index_entry(), last_sub_node));
+    let entries_size = (value.len() - 0x10) as u32;
+    value[0x00..0x04].copy_from_slice(&0u32.to_le_bytes());
+    value[0x04..0x08].copy_from_slice(&0x13u32.to_le_bytes());
+    value[0x14..0x18].copy_from_slice(&entries_size.to_le_bytes());
+    value[0x18..0x1C].copy_from_slice(&entries_size.to_le_bytes());
+    value[0x1C] = IndexRoot::LARGE_INDEX;
+    value
+}
+
+///A volume with \$Extend\$ObjId (25), whose $O index holds guid(20) in its root
+///and guid(10) and guid(30) in INDX blocks at clusters 0x50 and 0x51.
+///guid(10) is the object id of record 40, guid(30) points at record 41 with a stale sequence number.
+fn object_id_volume() -> Vec<u8> {
+    const DIR: u32 = 0x1000_0000;
+    let mut records = vec![Vec::new(); 42];
+    records[0] = common::mft_record_0();
+    records[5] = common::directory_record(
+        5,
+        SEQ | 5,
+        ".",
+        &[common::child(11, 5, "$Extend", 3, DIR | 0x06)],
+    );
+    records[11] = common::directory_record(
+        11,
+        SEQ | 5,
+        "$Extend",
+        &[common::child(25, 11, "$ObjId", 3, 0x2000_0006)],
+    );
+    records[25] = common::protected(common::record_with(
+        25,
+        0x000D,
+        &[
+            common::resident(0x30, "", &common::file_name(SEQ | 11, "$ObjId", 3, 0x06)),
+            common::resident(
+                0x90,
+                "$O",
+                &o_root(&[with_sub_node(o_entry(guid(20), SEQ | 42), 0)], 1),
+            ),
+            common::non_resident(0xA0, "$O", &[0x11, 0x02, 0x50], 2, 2 * 4096),
+            common::resident(0xB0, "$O", &[0x03, 0, 0, 0, 0, 0, 0, 0]),
+        ],
+    ));
+    records[40] = common::protected(common::record_with(
+        40,
+        0x0001,
+        &[
+            common::resident(
+                0x30,
+                "",
+                &common::file_name(SEQ | 5, "report.docx", 1, 0x20),
+            ),
+            common::resident(0x40, "", &object_id_value(guid(10), guid(1))),
+        ],
+    ));
+    records[41] = common::file_in(41, SEQ | 5, "moved.txt");
+
+    let mut image = common::volume_with_mft(&records);
+    let first = common::index_block(0, &[o_entry(guid(10), SEQ | 40)]);
+    let second = common::index_block(1, &[o_entry(guid(30), 0x0002_0000_0000_0000 | 41)]);
+    image[0x50 * 4096..0x51 * 4096].copy_from_slice(&first);
+    image[0x51 * 4096..0x52 * 4096].copy_from_slice(&second);
+    image
+}
+
+#[test]
+fn parses_object_id() {
+    let object_id = ObjectId::parse(&object_id_value(guid(10), guid(1))).unwrap();
+    assert_eq!(object_id.object_id, guid(10));
+    assert_eq!(object_id.birth_volume_id, Some(guid(1)));
+    assert_eq!(object_id.birth_object_id, Some(guid(10)));
+    assert_eq!(object_id.domain_id, Some(Guid([0; 16])));
+
+    let short = ObjectId::parse(&guid(10).0).unwrap();
+    assert_eq!(short.object_id, guid(10));
+    assert_eq!(short.birth_volume_id, None);
+    assert_eq!(short.domain_id, None);
+
+    assert!(matches!(
+        ObjectId::parse(&[0; 8]),
+        Err(NtfsError::BufferTooSmall { needed: 16, .. })
+    ));
+}
+
+#[test]
+fn formats_mixed_endian() {
+    let guid = Guid([
+        0x33, 0x22, 0x11, 0x00, 0x55, 0x44, 0x77, 0x66, 0x88, 0x99, 0xAA, 0xBB, 0xCC, 0xDD, 0xEE,
+        0xFF,
+    ]);
+    assert_eq!(guid.to_string(), "00112233-4455-6677-8899-AABBCCDDEEFF");
+}
+
+#[test]
+fn collates_as_u32s() {
+    //Byte by byte the first sorts before the second, but its first u32 is larger.
+    let mut a = Guid([0; 16]);
+    a.0[3] = 0x01;
+    let mut b = Guid([0; 16]);
+    b.0[0] = 0x02;
+    assert_eq!(collate_object_ids(&a, &b), Ordering::Greater);
+    assert_eq!(collate_object_ids(&a, &a), Ordering::Equal);
+}
+
+#[test]
+fn reads_object_id_attribute() {
+    let mut ntfs = Ntfs::new(Cursor::new(object_id_volume())).unwrap();
+    let record = ntfs.file_record(40).unwrap();
+    assert_eq!(record.object_id().unwrap().unwrap().object_id, guid(10));
+    assert!(ntfs.file_record(41).unwrap().object_id().unwrap().is_none());
+}
+
+#[test]
+fn finds_object_ids_through_the_btree() {
+    let mut ntfs = Ntfs::new(Cursor::new(object_id_volume())).unwrap();
+    let entry = ntfs.find_object_id(&guid(20)).unwrap().unwrap();
+    assert_eq!(entry.file_reference.record_number, 42);
+    assert_eq!(entry.birth_volume_id, Guid([0x11; 16]));
+    assert_eq!(entry.birth_object_id, guid(20));
+
+    let entry = ntfs.find_object_id(&guid(10)).unwrap().unwrap();
+    assert_eq!(entry.file_reference.record_number, 40);
+    let entry = ntfs.find_object_id(&guid(30)).unwrap().unwrap();
+    assert_eq!(entry.file_reference.record_number, 41);
+
+    for missing in [5, 15, 25, 35] {
+        assert!(ntfs.find_object_id(&guid(missing)).unwrap().is_none());
+    }
+}
+
+#[test]
+fn record_by_object_id() {
+    let mut ntfs = Ntfs::new(Cursor::new(object_id_volume())).unwrap();
+    let mut mft = ntfs.master_file_table().unwrap();
+    let (record_number, record) = mft.record_by_object_id(&guid(10)).unwrap().unwrap();
+    assert_eq!(record_number, 40);
+    assert_eq!(record.object_id().unwrap().unwrap().object_id, guid(10));
+
+    //Record 41 has been reused since its object id was indexed.
+    assert!(mft.record_by_object_id(&guid(30)).unwrap().is_none());
+    assert!(mft.record_by_object_id(&guid(15)).unwrap().is_none());
+}
+
+#[test]
+fn volume_without_obj_id() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::tree_volume())).unwrap();
+    assert!(matches!(
+        ntfs.find_object_id(&guid(10)),
+        Err(NtfsError::NotFound(_))
+    ));
+}
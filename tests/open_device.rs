@@ -0,0 +1,43 @@
+#![cfg(unix)]
+mod common;
+
+use ntfs::*;
+use std::{fs, path::Path};
+
+#[test]
+fn opens_image_files() {
+    let path = std::env::temp_dir().join(format!("ntfs-open-device-{}.img", std::process::id()));
+    fs::write(&path, common::tree_volume()).unwrap();
+    let result = open_device(&path).and_then(|mut ntfs| {
+        assert_eq!(ntfs.boot_sector().bytes_per_sector, 512);
+        ntfs.resolve_path("\\Windows\\notepad.exe")
+    });
+    fs::remove_file(&path).unwrap();
+    assert_eq!(result.unwrap().0, 33);
+}
+
+#[test]
+fn missing_device() {
+    assert!(matches!(
+        open_device(Path::new("/dev/ntfs-does-not-exist")),
+        Err(NtfsError::Io(_))
+    ));
+}
+
+///Reads a real volume through a loop device. Create and attach an image with
+///
+///```text
+///truncate -s 64M ntfs.img && mkfs.ntfs -F -Q ntfs.img
+///sudo losetup --find --show ntfs.img
+///```
+///
+///then run `NTFS_DEVICE=/dev/loop0 cargo test --test open_device -- --ignored`
+///as root or a member of the disk group.
+#[test]
+#[ignore = "needs a loop device in NTFS_DEVICE"]
+fn opens_loop_device() {
+    let device = std::env::var("NTFS_DEVICE").expect("NTFS_DEVICE is not set");
+    let mut ntfs = open_device(Path::new(&device)).unwrap();
+    let (record_number, _) = ntfs.resolve_path("\\$MFT").unwrap();
+    assert_eq!(record_number, 0);
+}
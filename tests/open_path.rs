@@ -0,0 +1,290 @@
+mod common;
+
+use ntfs::*;
+use std::io::Cursor;
+
+#[test]
+fn resolves_paths() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::tree_volume())).unwrap();
+    assert_eq!(ntfs.open_path("\\").unwrap().mft_record_number, 5);
+    assert_eq!(
+        ntfs.open_path("\\Windows\\System32")
+            .unwrap()
+            .mft_record_number,
+        32
+    );
+    assert_eq!(
+        ntfs.open_path("/windows/system32/KERNEL32.DLL")
+            .unwrap()
+            .mft_record_number,
+        34
+    );
+    assert_eq!(
+        ntfs.open_path("\\Windows\\.\\notepad.exe\\")
+            .unwrap()
+            .mft_record_number,
+        33
+    );
+}
+
+#[test]
+fn resolves_dos_names() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::tree_volume())).unwrap();
+    assert_eq!(
+        ntfs.open_path("\\readme~1.txt").unwrap().mft_record_number,
+        31
+    );
+}
+
+#[test]
+fn missing_component() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::tree_volume())).unwrap();
+    match ntfs.open_path("\\Windows\\Fonts\\arial.ttf") {
+        Err(NtfsError::NotFound(path)) => assert_eq!(path, "\\Windows\\Fonts"),
+        other => panic!("expected NotFound, got {other:?}"),
+    }
+    match ntfs.open_path("\\readme.txt\\inside") {
+        Err(NtfsError::NotADirectory(path)) => assert_eq!(path, "\\readme.txt"),
+        other => panic!("expected NotADirectory, got {other:?}"),
+    }
+}
+
+#[test]
+fn resolves_record_number() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::tree_volume())).unwrap();
+    let (n, record) = ntfs.resolve_path("/Windows/System32/kernel32.dll").unwrap();
+    assert_eq!(n, 34);
+    assert_eq!(record.best_name().as_deref(), Some("kernel32.dll"));
+
+    let (n, _) = ntfs
+        .master_file_table()
+        .unwrap()
+        .resolve_path(r"\WINDOWS")
+        .unwrap();
+    assert_eq!(n, 30);
+}
+
+///Replaces the root directory of the tree volume with one whose only entry, "Big.txt",
+///is in an INDX block at cluster 0x40.
+fn volume_with_large_root() -> Vec<u8> {
+    let mut last = common::last_index_entry();
+    last.extend_from_slice(&0u64.to_le_bytes());
+    last[0x08] = 0x18;
+    last[0x0C] |= 0x01;
+    let mut root = common::index_root(&[]);
+    root.truncate(0x20);
+    root.extend_from_slice(&last);
+    root[0x14..0x18].copy_from_slice(&0x28u32.to_le_bytes());
+    root[0x18..0x1C].copy_from_slice(&0x28u32.to_le_bytes());
+    root[0x1C] = 0x01;
+
+    let record = common::record_with(
+        5,
+        0x0003,
+        &[
+            common::resident(0x30, "", &common::file_name(5, ".", 3, 0x1000_0000)),
+            common::resident(0x90, "$I30", &root),
+            common::non_resident(0xA0, "$I30", &[0x11, 0x01, 0x40], 1, 4096),
+            common::resident(0xB0, "$I30", &[0x01, 0, 0, 0, 0, 0, 0, 0]),
+        ],
+    );
+    let mut image = common::tree_volume();
+    let offset = 4 * 4096 + 5 * 1024;
+    image[offset..offset + 1024].copy_from_slice(&common::protected(record));
+    let block = common::index_block(0, &[common::child(31, 5, "Big.txt", 3, 0x20)]);
+    image[0x40 * 4096..0x41 * 4096].copy_from_slice(&block);
+    image
+}
+
+#[test]
+fn resolves_through_index_allocation() {
+    let mut ntfs = Ntfs::new(Cursor::new(volume_with_large_root())).unwrap();
+    assert_eq!(ntfs.resolve_path("/big.TXT").unwrap().0, 31);
+}
+
+///Writes an $UpCase record holding `table`, which covers the first code units only.
+fn with_upcase(mut image: Vec<u8>, table: &[u16]) -> Vec<u8> {
+    let table: Vec<u8> = table.iter().flat_map(|unit| unit.to_le_bytes()).collect();
+    let record = common::protected(common::record_with(
+        10,
+        0x0001,
+        &[
+            common::resident(0x30, "", &common::file_name(5, "$UpCase", 3, 0x06)),
+            common::resident(0x80, "", &table),
+        ],
+    ));
+    let offset = 4 * 4096 + 10 * 1024;
+    image[offset..offset + 1024].copy_from_slice(&record);
+    image
+}
+
+#[test]
+fn compares_names_with_upcase_table() {
+    //A table that folds nothing, so only exact matches of ASCII names resolve.
+    //readme.txt sorts the same with or without folding, so the root index can still be searched.
+    let identity: Vec<u16> = (0..0x80).collect();
+    let image = with_upcase(common::tree_volume(), &identity);
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    assert_eq!(ntfs.upcase_table().unwrap().0.len(), 0x80);
+    assert_eq!(ntfs.resolve_path(r"\readme.txt").unwrap().0, 31);
+    assert!(matches!(
+        ntfs.resolve_path(r"\README.TXT"),
+        Err(NtfsError::NotFound(_))
+    ));
+}
+
+#[test]
+fn upcase_table_falls_back_past_its_end() {
+    let ascii: Vec<u16> = (0..0x80u16)
+        .map(|unit| (unit as u8).to_ascii_uppercase() as u16)
+        .collect();
+    let table = UpcaseTable(ascii);
+    assert!(table.eq_ignore_case("readme.txt", "README.TXT"));
+    assert!(table.eq_ignore_case("café", "CAFÉ"));
+    assert!(!table.eq_ignore_case("café", "CAFE"));
+    assert!(UpcaseTable::default().eq_ignore_case("Straße", "STRAßE"));
+}
+
+#[test]
+fn maps_non_ascii_through_loaded_table() {
+    //Folds 'é' and nothing else, not even ASCII.
+    let mut table: Vec<u16> = (0..0x100).collect();
+    table[0xE9] = 0xC9;
+    let image = with_upcase(common::tree_volume(), &table);
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    let upcase: UpCaseTable = ntfs.load_upcase().unwrap();
+    assert_eq!(upcase.to_upper('é' as u16), 'É' as u16);
+    assert_eq!(upcase.to_upper('a' as u16), 'a' as u16);
+    assert_eq!(upcase.to_upper('ж' as u16), 'Ж' as u16);
+    assert!(upcase.eq_ignore_case("café", "cafÉ"));
+    assert!(matches!(
+        ntfs.resolve_path(r"\windows"),
+        Err(NtfsError::NotFound(_))
+    ));
+}
+
+fn follow(drive_letter: char) -> ResolveOptions {
+    ResolveOptions {
+        follow_links: true,
+        drive_letter,
+    }
+}
+
+#[test]
+fn links_are_not_followed_by_default() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::linked_volume())).unwrap();
+    let (record_number, record) = ntfs.resolve_path(r"\Documents and Settings").unwrap();
+    assert_eq!(record_number, 43);
+    assert!(record.reparse().unwrap().is_link());
+    assert!(matches!(
+        ntfs.resolve_path(r"\Documents and Settings\Public"),
+        Err(NtfsError::NotFound(path)) if path == r"\Documents and Settings\Public"
+    ));
+}
+
+#[test]
+fn follows_junctions_and_symlinks() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::linked_volume())).unwrap();
+    let options = follow('C');
+    let resolve = |ntfs: &mut Ntfs<_>, path| ntfs.resolve_path_with_options(path, options);
+    assert_eq!(
+        resolve(&mut ntfs, r"\Documents and Settings\Public\notes.txt")
+            .unwrap()
+            .0,
+        42
+    );
+    //The last component is followed too.
+    assert_eq!(
+        resolve(&mut ntfs, r"\Documents and Settings").unwrap().0,
+        40
+    );
+    //Up is relative to \Users\Public, so it points at \Users.
+    assert_eq!(
+        resolve(&mut ntfs, r"\Users\Public\Up\Public\Up\Public\notes.txt")
+            .unwrap()
+            .0,
+        42
+    );
+    //Drive letters are compared ignoring case.
+    assert_eq!(
+        ntfs.resolve_path_with_options(r"\documents and settings", follow('c'))
+            .unwrap()
+            .0,
+        40
+    );
+}
+
+#[test]
+fn links_outside_the_volume() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::linked_volume())).unwrap();
+    match ntfs.resolve_path_with_options(r"\Users\link\file.txt", follow('C')) {
+        Err(NtfsError::LinkOutsideVolume { link, target }) => {
+            assert_eq!(link, r"\Users\link");
+            assert_eq!(target, r"\??\D:\Data");
+        }
+        other => panic!("expected LinkOutsideVolume, got {other:?}"),
+    }
+    assert!(matches!(
+        ntfs.resolve_path_with_options(r"\Users\escape", follow('C')),
+        Err(NtfsError::LinkOutsideVolume { .. })
+    ));
+
+    //As D: the link stays on the volume, but the junction to C: doesn't.
+    assert!(matches!(
+        ntfs.resolve_path_with_options(r"\Users\link", follow('D')),
+        Err(NtfsError::NotFound(path)) if path == r"\Data"
+    ));
+    assert!(matches!(
+        ntfs.resolve_path_with_options(r"\Documents and Settings", follow('D')),
+        Err(NtfsError::LinkOutsideVolume { .. })
+    ));
+}
+
+#[test]
+fn link_loops_stop_after_the_hop_limit() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::linked_volume())).unwrap();
+    assert!(matches!(
+        ntfs.resolve_path_with_options(r"\a", follow('C')),
+        Err(NtfsError::TooManyLinks(_))
+    ));
+}
+
+#[test]
+fn second_lookup_hits_the_record_cache() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::tree_volume())).unwrap();
+    let path = "\\Windows\\System32\\kernel32.dll";
+    assert_eq!(ntfs.open_path(path).unwrap().mft_record_number, 34);
+    assert_eq!(ntfs.cache_stats(), (0, 4));
+    assert_eq!(ntfs.open_path(path).unwrap().mft_record_number, 34);
+    assert_eq!(ntfs.cache_stats(), (4, 4));
+}
+
+#[test]
+fn record_cache_evicts_least_recently_used() {
+    let volume = common::tree_volume();
+    let mut ntfs = Ntfs::new(Cursor::new(volume.clone()))
+        .unwrap()
+        .with_cache_capacity(2);
+    ntfs.open_path("\\Windows\\System32").unwrap();
+    ntfs.open_path("\\readme.txt").unwrap();
+    assert_eq!(ntfs.cache_stats(), (0, 5));
+    //\ is still cached, but readme.txt pushed Windows out.
+    ntfs.open_path("\\Windows").unwrap();
+    assert_eq!(ntfs.cache_stats(), (1, 6));
+
+    let mut ntfs = Ntfs::new(Cursor::new(volume))
+        .unwrap()
+        .with_cache_capacity(0);
+    ntfs.open_path("\\Windows").unwrap();
+    ntfs.open_path("\\Windows").unwrap();
+    assert_eq!(ntfs.cache_stats(), (0, 4));
+}
+
+#[test]
+fn walking_fills_the_record_cache() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::tree_volume())).unwrap();
+    ntfs.walk().for_each(drop);
+    let (_, misses) = ntfs.cache_stats();
+    ntfs.open_path("\\Windows\\notepad.exe").unwrap();
+    assert_eq!(ntfs.cache_stats().1, misses);
+}
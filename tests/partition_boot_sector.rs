@@ -0,0 +1,338 @@
+mod common;
+
+use ntfs::*;
+use std::io::Cursor;
+
+fn parse(bytes: &[u8]) -> Result<PartitionBootSector, NtfsError> {
+    pbs(&mut Cursor::new(bytes))
+}
+
+#[test]
+fn parses_boot_sector() {
+    let pbs = parse(&common::boot_sector()).unwrap();
+    assert_eq!(pbs.bytes_per_sector, 512);
+    assert_eq!(pbs.sectors_per_cluster, 8);
+    assert_eq!(pbs.total_sectors, 0x7FF54A);
+    assert_eq!(pbs.mft_cluster_number, 4);
+    assert_eq!(pbs.mft_mirror_cluster_number, 0x7FF54);
+    assert_eq!(pbs.file_record_segment, Size::Bytes(1024));
+    assert_eq!(pbs.index_buffer, Size::Clusters(1));
+    assert_eq!(pbs.volume_serial_number, 0x1C741BC9741BA514);
+}
+
+#[test]
+fn rejects_bad_oem_id() {
+    let mut buf = common::boot_sector();
+    buf[3..11].copy_from_slice(b"EXFAT   ");
+    assert!(matches!(parse(&buf), Err(NtfsError::BadOemId)));
+}
+
+#[test]
+fn rejects_missing_end_marker() {
+    let mut buf = common::boot_sector();
+    buf[510] = 0;
+    assert!(matches!(parse(&buf), Err(NtfsError::BadEndMarker)));
+}
+
+#[test]
+fn parses_from_slice_reader() {
+    let buf = common::boot_sector();
+    let mut reader: &[u8] = &buf;
+    assert!(pbs(&mut reader).is_ok());
+}
+
+#[test]
+fn reports_failing_field_and_offset() {
+    let mut buf = common::boot_sector();
+    buf[21] = 0xF0;
+    match parse(&buf) {
+        Err(NtfsError::UnexpectedValue {
+            field,
+            offset,
+            expected,
+            found,
+        }) => {
+            assert_eq!(field, "media descriptor");
+            assert_eq!(offset, 0x15);
+            assert_eq!(expected, 0xF8);
+            assert_eq!(found, 0xF0);
+        }
+        other => panic!("unexpected result: {other:?}"),
+    }
+}
+
+#[test]
+fn parses_from_bytes_without_io() {
+    let pbs = PartitionBootSector::from_bytes(&common::boot_sector()).unwrap();
+    assert_eq!(pbs.mft_cluster_number, 4);
+}
+
+#[test]
+fn parses_4kn_boot_sector() {
+    let pbs = parse(&common::boot_sector_4kn()).unwrap();
+    assert_eq!(pbs.bytes_per_sector, 4096);
+    assert_eq!(pbs.sectors_per_cluster, 1);
+    assert_eq!(pbs.cluster_size_bytes(), 4096);
+    assert_eq!(pbs.file_record_segment, Size::Bytes(4096));
+}
+
+#[test]
+fn rejects_invalid_sector_size() {
+    for size in [0u16, 256, 1000, 8192] {
+        let mut buf = common::boot_sector();
+        buf[11..13].copy_from_slice(&size.to_le_bytes());
+        assert!(matches!(parse(&buf), Err(NtfsError::InvalidSectorSize(s)) if s == size));
+    }
+}
+
+fn with_sectors_per_cluster(value: u8) -> Result<PartitionBootSector, NtfsError> {
+    let mut buf = common::boot_sector();
+    buf[13] = value;
+    parse(&buf)
+}
+
+#[test]
+fn decodes_plain_sectors_per_cluster() {
+    assert_eq!(with_sectors_per_cluster(8).unwrap().sectors_per_cluster, 8);
+    assert_eq!(
+        with_sectors_per_cluster(0x80).unwrap().sectors_per_cluster,
+        128
+    );
+}
+
+#[test]
+fn decodes_large_sectors_per_cluster() {
+    assert_eq!(
+        with_sectors_per_cluster(0xF4).unwrap().sectors_per_cluster,
+        4096
+    );
+}
+
+#[test]
+fn rejects_invalid_sectors_per_cluster() {
+    assert!(matches!(
+        with_sectors_per_cluster(0),
+        Err(NtfsError::InvalidClusterSize(0))
+    ));
+    assert!(matches!(
+        with_sectors_per_cluster(0x81),
+        Err(NtfsError::InvalidClusterSize(0x81))
+    ));
+}
+
+#[test]
+fn cluster_size_bytes() {
+    //4K, 64K and 2MB clusters on 512 byte sectors.
+    for (value, size) in [(0x08, 4096), (0x80, 64 * 1024), (0xF4, 2 * 1024 * 1024)] {
+        let pbs = with_sectors_per_cluster(value).unwrap();
+        assert_eq!(pbs.cluster_size_bytes(), size);
+    }
+}
+
+#[test]
+fn rejects_invalid_size_encoding() {
+    let mut buf = common::boot_sector();
+    buf[64] = 0x80;
+    assert!(matches!(
+        parse(&buf),
+        Err(NtfsError::InvalidSizeEncoding { value: 0x80, .. })
+    ));
+}
+
+#[test]
+fn accepts_unusual_geometry() {
+    let mut buf = common::boot_sector();
+    buf[24..26].copy_from_slice(&0x0020u16.to_le_bytes());
+    buf[26..28].copy_from_slice(&0x0040u16.to_le_bytes());
+    buf[28..32].copy_from_slice(&0x0000_0800u32.to_le_bytes());
+    buf[36..40].copy_from_slice(&0x8000_8000u32.to_le_bytes());
+    let pbs = parse(&buf).unwrap();
+    assert_eq!(pbs.sectors_per_track, 0x20);
+    assert_eq!(pbs.number_of_heads, 0x40);
+    assert_eq!(pbs.hidden_sectors, 0x800);
+}
+
+#[test]
+fn lenient_mode_collects_warnings() {
+    let mut buf = common::boot_sector();
+    buf[0] = 0xE9;
+    buf[21] = 0xF0;
+    buf[32] = 1;
+
+    let lenient = ParseOptions {
+        strict: false,
+        ..ParseOptions::default()
+    };
+    let (pbs, warnings) = pbs_with_options(&mut Cursor::new(&buf), lenient).unwrap();
+    assert_eq!(pbs.mft_cluster_number, 4);
+    assert_eq!(warnings.len(), 3);
+    assert!(matches!(warnings[0].0, NtfsError::BadMagic));
+    assert!(matches!(
+        warnings[2].0,
+        NtfsError::UnexpectedValue { offset: 32, .. }
+    ));
+
+    let strict = ParseOptions {
+        strict: true,
+        ..ParseOptions::default()
+    };
+    assert!(matches!(
+        pbs_with_options(&mut Cursor::new(&buf), strict),
+        Err(NtfsError::BadMagic)
+    ));
+}
+
+#[test]
+fn unusual_media_descriptor() {
+    //0xF0 is the media descriptor of a 1.44 MB floppy, written by some imaging tools.
+    let mut buf = common::boot_sector();
+    buf[21] = 0xF0;
+
+    let strict = ParseOptions {
+        strict: true,
+        ..ParseOptions::default()
+    };
+    assert!(matches!(
+        pbs_with_options(&mut Cursor::new(&buf), strict),
+        Err(NtfsError::UnexpectedValue {
+            field: "media descriptor",
+            offset: 21,
+            expected: 0xF8,
+            found: 0xF0,
+        })
+    ));
+
+    let lenient = ParseOptions {
+        strict: false,
+        ..ParseOptions::default()
+    };
+    let (pbs, warnings) = pbs_with_options(&mut Cursor::new(&buf), lenient).unwrap();
+    assert_eq!(pbs.media_descriptor, 0xF0);
+    assert_eq!(pbs.cluster_size(), 4096);
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].to_string().contains("media descriptor"));
+}
+
+#[test]
+fn lenient_mode_requires_oem_id_and_end_marker() {
+    let lenient = ParseOptions {
+        strict: false,
+        ..ParseOptions::default()
+    };
+    let mut buf = common::boot_sector();
+    buf[3..11].copy_from_slice(b"EXFAT   ");
+    assert!(matches!(
+        pbs_with_options(&mut Cursor::new(&buf), lenient),
+        Err(NtfsError::BadOemId)
+    ));
+
+    let mut buf = common::boot_sector();
+    buf[510] = 0;
+    assert!(matches!(
+        pbs_with_options(&mut Cursor::new(&buf), lenient),
+        Err(NtfsError::BadEndMarker)
+    ));
+}
+
+#[test]
+fn lenient_mode_still_requires_sector_size() {
+    let mut buf = common::boot_sector();
+    buf[11..13].copy_from_slice(&0u16.to_le_bytes());
+    let lenient = ParseOptions {
+        strict: false,
+        ..ParseOptions::default()
+    };
+    assert!(matches!(
+        pbs_with_options(&mut Cursor::new(&buf), lenient),
+        Err(NtfsError::InvalidSectorSize(0))
+    ));
+}
+
+#[test]
+fn geometry() {
+    let pbs = parse(&common::boot_sector()).unwrap();
+    assert_eq!(pbs.cluster_size(), 4096);
+    assert_eq!(pbs.mft_offset(), 4 * 4096);
+    assert_eq!(pbs.mft_mirror_offset(), 0x7FF54 * 4096);
+    assert_eq!(pbs.volume_size(), 0x7FF54A * 512);
+    assert_eq!(pbs.file_record_size(), 1024);
+    assert_eq!(pbs.index_buffer_size(), 4096);
+}
+
+#[test]
+fn geometry_resolves_both_size_variants() {
+    let mut buf = common::boot_sector();
+    buf[64] = 0x01;
+    buf[68] = 0xF4;
+    let pbs = parse(&buf).unwrap();
+    assert_eq!(pbs.file_record_segment, Size::Clusters(1));
+    assert_eq!(pbs.file_record_size(), 4096);
+    assert_eq!(pbs.index_buffer, Size::Bytes(4096));
+    assert_eq!(pbs.index_buffer_size(), 4096);
+}
+
+#[test]
+fn geometry_handles_large_volumes() {
+    //A 256 TB volume with 64K clusters.
+    let mut buf = common::boot_sector();
+    buf[13] = 0x80;
+    buf[40..48].copy_from_slice(&(1u64 << 39).to_le_bytes());
+    buf[48..56].copy_from_slice(&(1u64 << 31).to_le_bytes());
+    let pbs = parse(&buf).unwrap();
+    assert_eq!(pbs.volume_size(), 1u64 << 48);
+    assert_eq!(pbs.mft_offset(), 1u64 << 47);
+}
+
+#[test]
+fn cluster_conversions() {
+    let pbs = parse(&common::boot_sector()).unwrap();
+    assert_eq!(pbs.bytes_per_sector, 512);
+    assert_eq!(pbs.sectors_per_cluster, 8);
+    assert_eq!(pbs.bytes_per_cluster(), 4096);
+    assert_eq!(pbs.cluster_to_byte_offset(4), 16384);
+    assert_eq!(pbs.total_bytes(), 0x7FF54A * 512);
+}
+
+#[test]
+fn mft_byte_offsets() {
+    let pbs = parse(&common::boot_sector()).unwrap();
+    assert_eq!(pbs.mft_byte_offset(), 16384);
+    assert_eq!(pbs.mft_mirror_byte_offset(), 0x7FF54 * 4096);
+}
+
+#[test]
+fn keeps_bootstrap_code_and_checksum() {
+    let mut buf = common::boot_sector();
+    let code: Vec<u8> = (0..BOOTSTRAP_CODE_SIZE).map(|i| i as u8).collect();
+    buf[0x54..0x54 + BOOTSTRAP_CODE_SIZE].copy_from_slice(&code);
+    let pbs = parse(&buf).unwrap();
+    assert_eq!(pbs.checksum, 0);
+    assert_eq!(pbs.bootstrap_code[..], code[..]);
+
+    //A non-zero checksum is reported as it is, in strict mode too.
+    buf[0x50..0x54].copy_from_slice(&0xDEADBEEFu32.to_le_bytes());
+    assert_eq!(parse(&buf).unwrap().checksum, 0xDEADBEEF);
+    let lenient = ParseOptions {
+        strict: false,
+        ..ParseOptions::default()
+    };
+    let (pbs, warnings) = pbs_with_options(&mut Cursor::new(&buf), lenient).unwrap();
+    assert_eq!(pbs.checksum, 0xDEADBEEF);
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn boot_sectors_compare_and_hash() {
+    use std::collections::HashSet;
+
+    let pbs = parse(&common::boot_sector()).unwrap();
+    let mut buf = common::boot_sector();
+    buf[72] ^= 1;
+    let other = parse(&buf).unwrap();
+    assert_eq!(pbs.clone(), pbs);
+    assert_ne!(other, pbs);
+    assert_eq!(pbs.file_record_segment, Size::Bytes(1024));
+
+    let serials: HashSet<PartitionBootSector> = [pbs.clone(), other, pbs].into_iter().collect();
+    assert_eq!(serials.len(), 2);
+}
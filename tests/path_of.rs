@@ -0,0 +1,105 @@
+mod common;
+
+use ntfs::*;
+use std::{io::Cursor, path::PathBuf};
+
+const SEQ: u64 = 0x0001_0000_0000_0000;
+
+fn write_record(image: &mut [u8], record_number: usize, record: Vec<u8>) {
+    let offset = 4 * 4096 + record_number * 1024;
+    image[offset..offset + 1024].copy_from_slice(&record);
+}
+
+///The tree volume where notepad.exe has a DOS name and a second hard link in System32.
+fn volume() -> Vec<u8> {
+    let mut image = common::tree_volume();
+    let notepad = common::record_with(
+        33,
+        0x0001,
+        &[
+            common::resident(
+                0x30,
+                "",
+                &common::file_name(SEQ | 30, "NOTEPAD.EXE", 2, 0x20),
+            ),
+            common::resident(0x30, "", &common::file_name(SEQ | 32, "np.exe", 0, 0x20)),
+            common::resident(
+                0x30,
+                "",
+                &common::file_name(SEQ | 30, "notepad.exe", 1, 0x20),
+            ),
+        ],
+    );
+    write_record(&mut image, 33, common::protected(notepad));
+    image
+}
+
+#[test]
+fn reconstructs_paths() {
+    let mut ntfs = Ntfs::new(Cursor::new(volume())).unwrap();
+    assert_eq!(
+        ntfs.path_of(34).unwrap(),
+        Some(PathBuf::from_iter(["Windows", "System32", "kernel32.dll"]))
+    );
+    assert_eq!(ntfs.path_of(30).unwrap(), Some(PathBuf::from("Windows")));
+    assert_eq!(ntfs.path_of(5).unwrap(), Some(PathBuf::new()));
+    assert_eq!(
+        ntfs.master_file_table().unwrap().path_of(31).unwrap(),
+        Some(PathBuf::from("readme.txt"))
+    );
+}
+
+#[test]
+fn hard_links() {
+    let mut ntfs = Ntfs::new(Cursor::new(volume())).unwrap();
+    assert_eq!(
+        ntfs.path_of(33).unwrap(),
+        Some(PathBuf::from_iter(["Windows", "notepad.exe"]))
+    );
+    assert_eq!(
+        ntfs.all_paths_of(33).unwrap(),
+        [
+            PathBuf::from_iter(["Windows", "System32", "np.exe"]),
+            PathBuf::from_iter(["Windows", "notepad.exe"]),
+        ]
+    );
+}
+
+#[test]
+fn orphans_have_no_path() {
+    let mut image = volume();
+    //The parent reference expects the second use of record 30.
+    write_record(
+        &mut image,
+        40,
+        common::file_in(40, 0x0002_0000_0000_001E, "lost.txt"),
+    );
+    //Record 41 was deleted.
+    let mut deleted = common::file_in(41, SEQ | 30, "deleted.txt");
+    deleted[0x16] = 0;
+    write_record(&mut image, 41, common::protected(deleted));
+
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    assert_eq!(ntfs.path_of(40).unwrap(), None);
+    assert!(ntfs.all_paths_of(40).unwrap().is_empty());
+    assert_eq!(ntfs.path_of(41).unwrap(), None);
+}
+
+#[test]
+fn parent_cycle_is_an_error() {
+    let mut image = volume();
+    write_record(
+        &mut image,
+        40,
+        common::directory_record(40, SEQ | 41, "a", &[]),
+    );
+    write_record(
+        &mut image,
+        41,
+        common::directory_record(41, SEQ | 40, "b", &[]),
+    );
+    write_record(&mut image, 42, common::file_in(42, SEQ | 40, "c.txt"));
+
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    assert!(matches!(ntfs.path_of(42), Err(NtfsError::Corrupt(_))));
+}
@@ -0,0 +1,205 @@
+mod common;
+
+use ntfs::*;
+use std::{io::Cursor, str::FromStr};
+
+const SEQ: u64 = 0x0001_0000_0000_0000;
+
+fn sid(s: &str) -> Sid {
+    Sid::from_str(s).unwrap()
+}
+
+///A view index entry with `key` followed by `data`.
+fn view_entry(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let data_offset = (0x10 + key.len()).next_multiple_of(4);
+    let mut entry = vec![0u8; data_offset];
+    entry[0x10..0x10 + key.len()].copy_from_slice(key);
+    entry.extend_from_slice(data);
+    entry.resize(entry.len().next_multiple_of(8), 0);
+    entry[0x00..0x02].copy_from_slice(&(data_offset as u16).to_le_bytes());
+    entry[0x02..0x04].copy_from_slice(&(data.len() as u16).to_le_bytes());
+    let len = entry.len() as u16;
+    entry[0x08..0x0A].copy_from_slice(&len.to_le_bytes());
+    entry[0x0A..0x0C].copy_from_slice(&(key.len() as u16).to_le_bytes());
+    entry
+}
+
+fn owner_entry(sid: &Sid, owner_id: u32) -> Vec<u8> {
+    view_entry(&sid.to_bytes(), &owner_id.to_le_bytes())
+}
+
+fn quota_entry(owner_id: u32, flags: u32, bytes_used: u64, sid: Option<&Sid>) -> Vec<u8> {
+    let mut data = vec![0u8; 0x30];
+    data[0x00..0x04].copy_from_slice(&2u32.to_le_bytes());
+    data[0x04..0x08].copy_from_slice(&flags.to_le_bytes());
+    data[0x08..0x10].copy_from_slice(&bytes_used.to_le_bytes());
+    data[0x10..0x18].copy_from_slice(&0x01D0_0000_0000_0000u64.to_le_bytes());
+    data[0x18..0x20].copy_from_slice(&u64::MAX.to_le_bytes());
+    data[0x20..0x28].copy_from_slice(&(1u64 << 30).to_le_bytes());
+    if let Some(sid) = sid {
+        data.extend_from_slice(&sid.to_bytes());
+    }
+    view_entry(&owner_id.to_le_bytes(), &data)
+}
+
+fn with_sub_node(mut entry: Vec<u8>, vcn: u64) -> Vec<u8> {
+    entry.extend_from_slice(&vcn.to_le_bytes());
+    let len = entry.len() as u16;
+    entry[0x08..0x0A].copy_from_slice(&len.to_le_bytes());
+    entry[0x0C] |= IndexEntry::SUB_NODE;
+    entry
+}
+
+///A view $INDEX_ROOT value holding `entries` with `collation`, large if the last entry has a sub-node.
+fn view_root(entries: &[Vec<u8>], collation: u32, last_sub_node: Option<u64>) -> Vec<u8> {
+    let mut value = common::index_root(entries);
+    value[0x00..0x04].copy_from_slice(&0u32.to_le_bytes());
+    value[0x04..0x08].copy_from_slice(&collation.to_le_bytes());
+    if let Some(vcn) = last_sub_node {
+        let last = value.len() - 0x10;
+        value.truncate(last);
+        value.extend_from_slice(&with_sub_node(common::last_index_entry(), vcn));
+        let entries_size = (value.len() - 0x10) as u32;
+        value[0x14..0x18].copy_from_slice(&entries_size.to_le_bytes());
+        value[0x18..0x1C].copy_from_slice(&entries_size.to_le_bytes());
+        value[0x1C] = IndexRoot::LARGE_INDEX;
+    }
+    value
+}
+
+///A volume with \$Extend\$Quota (24). Its $O index holds three owners in its root.
+///Its $Q index holds owner 0x101 in the root, the defaults and owner 0x100 in the INDX block
+///at cluster 0x80 and owner 0x102, which has no SID of its own, in the block at 0x81.
+fn quota_volume() -> Vec<u8> {
+    const DIR: u32 = 0x1000_0000;
+    let mut records = vec![Vec::new(); 25];
+    records[0] = common::mft_record_0();
+    records[5] = common::directory_record(
+        5,
+        SEQ | 5,
+        ".",
+        &[common::child(11, 5, "$Extend", 3, DIR | 0x06)],
+    );
+    records[11] = common::directory_record(
+        11,
+        SEQ | 5,
+        "$Extend",
+        &[common::child(24, 11, "$Quota", 3, 0x2000_0006)],
+    );
+    let owners = [
+        owner_entry(&sid("S-1-5-18"), 0x100),
+        owner_entry(&sid("S-1-5-21-1-2-3-1001"), 0x101),
+        owner_entry(&sid("S-1-5-32-544"), 0x102),
+    ];
+    let root = [with_sub_node(
+        quota_entry(0x101, 0x0001, 5_000_000, Some(&sid("S-1-5-21-1-2-3-1001"))),
+        0,
+    )];
+    records[24] = common::protected(common::record_with(
+        24,
+        0x000D,
+        &[
+            common::resident(0x30, "", &common::file_name(SEQ | 11, "$Quota", 3, 0x06)),
+            common::resident(0x90, "$O", &view_root(&owners, 0x11, None)),
+            common::resident(0x90, "$Q", &view_root(&root, 0x10, Some(1))),
+            common::non_resident(0xA0, "$Q", &[0x21, 0x02, 0x80, 0x00], 2, 2 * 4096),
+            common::resident(0xB0, "$Q", &[0x03, 0, 0, 0, 0, 0, 0, 0]),
+        ],
+    ));
+
+    let mut image = common::volume_with_mft(&records);
+    let first = common::index_block(
+        0,
+        &[
+            quota_entry(1, 0x0010, 0, None),
+            quota_entry(0x100, 0x0001, 123_456, Some(&sid("S-1-5-18"))),
+        ],
+    );
+    let second = common::index_block(1, &[quota_entry(0x102, 0x0002, 42, None)]);
+    image[0x80 * 4096..0x81 * 4096].copy_from_slice(&first);
+    image[0x81 * 4096..0x82 * 4096].copy_from_slice(&second);
+    image
+}
+
+#[test]
+fn parses_quota_entry() {
+    let entry =
+        QuotaEntry::parse(&quota_entry(0x100, 0x0003, 4096, Some(&sid("S-1-5-18")))).unwrap();
+    assert_eq!(entry.owner_id, 0x100);
+    assert_eq!(entry.version, 2);
+    assert_eq!(entry.flags.names(), ["DEFAULT_LIMITS", "LIMIT_REACHED"]);
+    assert_eq!(entry.bytes_used, 4096);
+    assert_eq!(entry.change_time, NtfsTime(0x01D0_0000_0000_0000));
+    assert_eq!(entry.warning_limit, u64::MAX);
+    assert_eq!(entry.hard_limit, 1 << 30);
+    assert_eq!(entry.sid, Some(sid("S-1-5-18")));
+    assert!(!entry.is_defaults());
+
+    let defaults = QuotaEntry::parse(&quota_entry(1, 0x0030, 0, None)).unwrap();
+    assert!(defaults.is_defaults());
+    assert_eq!(defaults.sid, None);
+    assert_eq!(
+        defaults.flags.names(),
+        ["TRACKING_ENABLED", "ENFORCEMENT_ENABLED"]
+    );
+
+    let owner = QuotaOwner::parse(&owner_entry(&sid("S-1-5-32-544"), 0x102)).unwrap();
+    assert_eq!(owner.sid, sid("S-1-5-32-544"));
+    assert_eq!(owner.owner_id, 0x102);
+}
+
+#[test]
+fn rejects_short_quota_entries() {
+    let mut entry = quota_entry(0x100, 0, 0, None);
+    entry[0x02..0x04].copy_from_slice(&0x20u16.to_le_bytes());
+    assert!(matches!(
+        QuotaEntry::parse(&entry),
+        Err(NtfsError::Corrupt(_))
+    ));
+}
+
+#[test]
+fn reads_both_indexes() {
+    let mut ntfs = Ntfs::new(Cursor::new(quota_volume())).unwrap();
+
+    let owners: Vec<u32> = ntfs
+        .quota_owners()
+        .unwrap()
+        .iter()
+        .map(|owner| owner.owner_id)
+        .collect();
+    assert_eq!(owners, [0x100, 0x101, 0x102]);
+
+    let entries: Vec<u32> = ntfs
+        .quota_entries()
+        .unwrap()
+        .iter()
+        .map(|entry| entry.owner_id)
+        .collect();
+    assert_eq!(entries, [1, 0x100, 0x101, 0x102]);
+}
+
+#[test]
+fn pairs_entries_with_their_owners() {
+    let mut ntfs = Ntfs::new(Cursor::new(quota_volume())).unwrap();
+    let quotas: Vec<(String, u64)> = ntfs
+        .quotas()
+        .unwrap()
+        .into_iter()
+        .map(|(sid, entry)| (sid.to_string(), entry.bytes_used))
+        .collect();
+    assert_eq!(
+        quotas,
+        [
+            ("S-1-5-18".to_string(), 123_456),
+            ("S-1-5-21-1-2-3-1001".to_string(), 5_000_000),
+            ("S-1-5-32-544".to_string(), 42),
+        ]
+    );
+}
+
+#[test]
+fn volume_without_quota() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::tree_volume())).unwrap();
+    assert!(matches!(ntfs.quotas(), Err(NtfsError::NotFound(_))));
+}
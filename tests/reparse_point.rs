@@ -0,0 +1,283 @@
+mod common;
+
+use ntfs::*;
+use std::{io::Cursor, path::PathBuf};
+
+const SEQ: u64 = 0x0001_0000_0000_0000;
+
+#[test]
+fn symlink() {
+    let value = common::reparse_link(
+        ReparsePoint::SYMLINK,
+        r"\??\C:\Users\Public",
+        r"C:\Users\Public",
+        Some(0),
+    );
+    let reparse_point = ReparsePoint::parse(&value).unwrap();
+    assert!(reparse_point.is_symlink());
+    assert!(reparse_point.is_microsoft());
+    assert!(!reparse_point.relative);
+    assert_eq!(
+        reparse_point.substitute_name.as_deref(),
+        Some(r"\??\C:\Users\Public")
+    );
+    assert_eq!(reparse_point.target(), Some(r"C:\Users\Public"));
+
+    let value = common::reparse_link(ReparsePoint::SYMLINK, r"..\notes.txt", "", Some(1));
+    let reparse_point = ReparsePoint::parse(&value).unwrap();
+    assert!(reparse_point.relative);
+    assert_eq!(reparse_point.target(), Some(r"..\notes.txt"));
+}
+
+#[test]
+fn junction() {
+    let value = common::reparse_link(ReparsePoint::MOUNT_POINT, r"\??\D:\Data", r"D:\Data", None);
+    let reparse_point = ReparsePoint::parse(&value).unwrap();
+    assert!(reparse_point.is_mount_point());
+    assert!(!reparse_point.relative);
+    assert_eq!(
+        reparse_point.substitute_name.as_deref(),
+        Some(r"\??\D:\Data")
+    );
+    assert_eq!(reparse_point.print_name.as_deref(), Some(r"D:\Data"));
+}
+
+#[test]
+fn third_party_tag_has_guid() {
+    let mut value = 0x0000_1234u32.to_le_bytes().to_vec();
+    value.extend_from_slice(&3u16.to_le_bytes());
+    value.extend_from_slice(&[0; 2]);
+    value.extend_from_slice(&[0xAB; 16]);
+    value.extend_from_slice(&[1, 2, 3]);
+    let reparse_point = ReparsePoint::parse(&value).unwrap();
+    assert!(!reparse_point.is_microsoft());
+    assert_eq!(reparse_point.data, [1, 2, 3]);
+    assert_eq!(reparse_point.target(), None);
+}
+
+#[test]
+fn rejects_names_past_buffer() {
+    let mut value =
+        common::reparse_link(ReparsePoint::MOUNT_POINT, r"\??\D:\Data", r"D:\Data", None);
+    value[0x0E] = 0xFF;
+    assert!(matches!(
+        ReparsePoint::parse(&value),
+        Err(NtfsError::Corrupt(_))
+    ));
+    assert!(matches!(
+        ReparsePoint::parse(&value[..0x0A]),
+        Err(NtfsError::BufferTooSmall { .. })
+    ));
+}
+
+#[test]
+fn reparse_target_of_record() {
+    let value = common::reparse_link(ReparsePoint::MOUNT_POINT, r"\??\C:\", r"C:\", None);
+    let record = common::protected(common::record_with(
+        35,
+        0x0003,
+        &[
+            common::resident(0x30, "", &common::file_name(32, "loop", 3, 0x1000_0400)),
+            common::resident(0xC0, "", &value),
+        ],
+    ));
+    let record = FileRecord::parse(&record).unwrap();
+    assert_eq!(record.reparse_target().as_deref(), Some(r"C:\"));
+
+    let plain = FileRecord::parse(&common::file_in(31, 5, "readme.txt")).unwrap();
+    assert!(plain.reparse_point().unwrap().is_none());
+    assert_eq!(plain.reparse_target(), None);
+}
+
+///A Microsoft reparse point with an opaque payload.
+fn tagged(tag: u32, data: &[u8]) -> Vec<u8> {
+    let mut value = tag.to_le_bytes().to_vec();
+    value.extend_from_slice(&(data.len() as u16).to_le_bytes());
+    value.extend_from_slice(&[0; 2]);
+    value.extend_from_slice(data);
+    value
+}
+
+#[test]
+fn typed_links() {
+    let value = common::reparse_link(ReparsePoint::SYMLINK, r"..\notes.txt", "", Some(1));
+    let data = ReparseData::from(ReparsePoint::parse(&value).unwrap());
+    assert_eq!(
+        data,
+        ReparseData::SymbolicLink {
+            substitute_name: r"..\notes.txt".to_string(),
+            print_name: String::new(),
+            relative: true,
+        }
+    );
+    assert!(data.is_link());
+    assert_eq!(data.target(), Some(r"..\notes.txt"));
+
+    let value = common::reparse_link(ReparsePoint::MOUNT_POINT, r"\??\D:\Data", r"D:\Data", None);
+    let data = ReparseData::from(ReparsePoint::parse(&value).unwrap());
+    assert!(matches!(data, ReparseData::MountPoint { .. }));
+    assert_eq!(data.target(), Some(r"D:\Data"));
+}
+
+#[test]
+fn tag_only_payloads() {
+    let typed = |tag| ReparseData::from(ReparsePoint::parse(&tagged(tag, &[1, 0, 0, 0])).unwrap());
+    assert_eq!(typed(ReparsePoint::WOF), ReparseData::Wof);
+    assert_eq!(typed(ReparsePoint::APP_EXEC_LINK), ReparseData::AppExecLink);
+    assert_eq!(
+        typed(ReparsePoint::CLOUD),
+        ReparseData::CloudFiles { tag: 0x9000_001A }
+    );
+    //OneDrive uses the numbered Cloud Files tags.
+    assert_eq!(
+        typed(0x9000_601A),
+        ReparseData::CloudFiles { tag: 0x9000_601A }
+    );
+    assert!(ReparsePoint::parse(&tagged(0x9000_601A, &[]))
+        .unwrap()
+        .is_cloud_files());
+    assert_eq!(
+        typed(0x8000_0013),
+        ReparseData::Other {
+            tag: 0x8000_0013,
+            data: vec![1, 0, 0, 0]
+        }
+    );
+    assert_eq!(typed(ReparsePoint::WOF).target(), None);
+    assert!(!typed(ReparsePoint::APP_EXEC_LINK).is_link());
+}
+
+#[test]
+fn reparse_of_record() {
+    let value = common::reparse_link(ReparsePoint::MOUNT_POINT, r"\??\D:\Data", r"D:\Data", None);
+    let record = common::protected(common::record_with(
+        36,
+        0x0003,
+        &[
+            common::resident(0x30, "", &common::file_name(5, "Data", 3, 0x1000_0400)),
+            common::resident(0xC0, "", &value),
+        ],
+    ));
+    let record = FileRecord::parse(&record).unwrap();
+    let data = record.reparse().unwrap();
+    assert!(data.is_link());
+    assert_eq!(data.target(), Some(r"D:\Data"));
+
+    let plain = FileRecord::parse(&common::file_in(31, 5, "readme.txt")).unwrap();
+    assert_eq!(plain.reparse(), None);
+}
+
+///An $R index entry for `tag` on `file_reference`, which has no data.
+fn r_entry(tag: u32, file_reference: u64) -> Vec<u8> {
+    let mut entry = vec![0u8; 0x20];
+    entry[0x00..0x02].copy_from_slice(&0x1Cu16.to_le_bytes());
+    entry[0x08..0x0A].copy_from_slice(&0x20u16.to_le_bytes());
+    entry[0x0A..0x0C].copy_from_slice(&0x0Cu16.to_le_bytes());
+    entry[0x10..0x14].copy_from_slice(&tag.to_le_bytes());
+    entry[0x14..0x1C].copy_from_slice(&file_reference.to_le_bytes());
+    entry
+}
+
+///A volume with \$Extend\$Reparse (26), whose $R index lists a Cloud Files placeholder at
+///\Docs\report.docx (41), a junction at \Data (40) and a symbolic link in record 42,
+///which has been reused since.
+fn reparse_volume() -> Vec<u8> {
+    const DIR: u32 = 0x1000_0000;
+    let mut records = vec![Vec::new(); 43];
+    records[0] = common::mft_record_0();
+    records[5] = common::directory_record(
+        5,
+        SEQ | 5,
+        ".",
+        &[common::child(11, 5, "$Extend", 3, DIR | 0x06)],
+    );
+    records[11] = common::directory_record(
+        11,
+        SEQ | 5,
+        "$Extend",
+        &[common::child(26, 11, "$Reparse", 3, 0x2000_0006)],
+    );
+    let mut root = common::index_root(&[
+        r_entry(0x9000_601A, SEQ | 41),
+        r_entry(ReparsePoint::MOUNT_POINT, SEQ | 40),
+        r_entry(ReparsePoint::SYMLINK, 0x0002_0000_0000_0000 | 42),
+    ]);
+    root[0x00..0x04].copy_from_slice(&0u32.to_le_bytes());
+    root[0x04..0x08].copy_from_slice(&0x13u32.to_le_bytes());
+    records[26] = common::protected(common::record_with(
+        26,
+        0x000D,
+        &[
+            common::resident(0x30, "", &common::file_name(SEQ | 11, "$Reparse", 3, 0x06)),
+            common::resident(0x90, "$R", &root),
+        ],
+    ));
+    records[30] = common::directory_record(30, SEQ | 5, "Docs", &[]);
+    records[40] = common::directory_record(40, SEQ | 5, "Data", &[]);
+    records[41] = common::file_in(41, SEQ | 30, "report.docx");
+    records[42] = common::file_in(42, SEQ | 5, "new.txt");
+    common::volume_with_mft(&records)
+}
+
+#[test]
+fn typed_tags() {
+    assert_eq!(
+        ReparseTag::from(ReparsePoint::SYMLINK),
+        ReparseTag::SymbolicLink
+    );
+    assert_eq!(
+        ReparseTag::from(0x9000_601A),
+        ReparseTag::CloudFiles(0x9000_601A)
+    );
+    assert_eq!(
+        ReparseTag::from(0x8000_0013),
+        ReparseTag::Other(0x8000_0013)
+    );
+    assert_eq!(ReparseTag::MountPoint.bits(), ReparsePoint::MOUNT_POINT);
+    assert!(ReparseTag::MountPoint.is_link());
+    assert!(!ReparseTag::Wof.is_link());
+    assert!(!ReparseTag::Other(0x0000_1234).is_microsoft());
+}
+
+#[test]
+fn lists_reparse_points_from_the_index() {
+    let mut ntfs = Ntfs::new(Cursor::new(reparse_volume())).unwrap();
+    let entries: Vec<(ReparseTag, u64)> = ntfs
+        .reparse_points()
+        .unwrap()
+        .into_iter()
+        .map(|entry| (entry.tag, entry.file_reference.record_number))
+        .collect();
+    assert_eq!(
+        entries,
+        [
+            (ReparseTag::CloudFiles(0x9000_601A), 41),
+            (ReparseTag::MountPoint, 40),
+            (ReparseTag::SymbolicLink, 42),
+        ]
+    );
+}
+
+#[test]
+fn resolves_reparse_point_paths() {
+    let mut ntfs = Ntfs::new(Cursor::new(reparse_volume())).unwrap();
+    let paths: Vec<Option<PathBuf>> = ntfs
+        .reparse_points_with_options(ReparsePointOptions {
+            resolve_paths: true,
+        })
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.path)
+        .collect();
+    assert_eq!(
+        paths,
+        [
+            Some(PathBuf::from("Docs").join("report.docx")),
+            Some(PathBuf::from("Data")),
+            None,
+        ]
+    );
+
+    let mut ntfs = Ntfs::new(Cursor::new(common::tree_volume())).unwrap();
+    assert!(matches!(ntfs.reparse_points(), Err(NtfsError::NotFound(_))));
+}
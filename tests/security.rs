@@ -0,0 +1,275 @@
+mod common;
+
+use ntfs::*;
+use std::io::Cursor;
+
+const SEQ: u64 = 0x0001_0000_0000_0000;
+
+fn sid(authority: u8, sub_authorities: &[u32]) -> Vec<u8> {
+    let mut sid = vec![1, sub_authorities.len() as u8, 0, 0, 0, 0, 0, authority];
+    for sub_authority in sub_authorities {
+        sid.extend_from_slice(&sub_authority.to_le_bytes());
+    }
+    sid
+}
+
+fn ace(ace_type: u8, flags: u8, access_mask: u32, sid: &[u8]) -> Vec<u8> {
+    let mut ace = vec![ace_type, flags, 0, 0];
+    ace.extend_from_slice(&access_mask.to_le_bytes());
+    ace.extend_from_slice(sid);
+    let len = ace.len() as u16;
+    ace[0x02..0x04].copy_from_slice(&len.to_le_bytes());
+    ace
+}
+
+///A self-relative descriptor owned by the administrators with a DACL giving the system
+///full control and the users read access.
+fn descriptor() -> Vec<u8> {
+    let owner = sid(5, &[32, 544]);
+    let group = sid(5, &[18]);
+    let mut acl = vec![2, 0, 0, 0, 2, 0, 0, 0];
+    acl.extend_from_slice(&ace(0, 0x13, Ace::FILE_ALL_ACCESS, &sid(5, &[18])));
+    acl.extend_from_slice(&ace(0, 0x10, Ace::FILE_GENERIC_READ, &sid(5, &[32, 545])));
+    let acl_len = acl.len() as u16;
+    acl[0x02..0x04].copy_from_slice(&acl_len.to_le_bytes());
+
+    let control = SecurityDescriptor::SELF_RELATIVE | SecurityDescriptor::DACL_PRESENT;
+    let mut descriptor = vec![1, 0];
+    descriptor.extend_from_slice(&control.to_le_bytes());
+    let owner_offset = 0x14u32;
+    let group_offset = owner_offset + owner.len() as u32;
+    let dacl_offset = group_offset + group.len() as u32;
+    for offset in [owner_offset, group_offset, 0, dacl_offset] {
+        descriptor.extend_from_slice(&offset.to_le_bytes());
+    }
+    descriptor.extend_from_slice(&owner);
+    descriptor.extend_from_slice(&group);
+    descriptor.extend_from_slice(&acl);
+    descriptor
+}
+
+fn sds_entry(security_id: u32, offset: u64) -> Vec<u8> {
+    let descriptor = descriptor();
+    let mut entry = vec![0u8; 0x14];
+    entry[0x00..0x04].copy_from_slice(&(0xABCD_0000 | security_id).to_le_bytes());
+    entry[0x04..0x08].copy_from_slice(&security_id.to_le_bytes());
+    entry[0x08..0x10].copy_from_slice(&offset.to_le_bytes());
+    entry[0x10..0x14].copy_from_slice(&(0x14 + descriptor.len() as u32).to_le_bytes());
+    entry.extend_from_slice(&descriptor);
+    entry
+}
+
+///An $SII index entry mapping `security_id` to the $SDS entry at `offset`.
+fn sii_entry(security_id: u32, offset: u64) -> Vec<u8> {
+    let mut entry = vec![0u8; 0x14];
+    entry[0x00..0x02].copy_from_slice(&0x14u16.to_le_bytes());
+    entry[0x02..0x04].copy_from_slice(&0x14u16.to_le_bytes());
+    entry[0x08..0x0A].copy_from_slice(&0x28u16.to_le_bytes());
+    entry[0x0A..0x0C].copy_from_slice(&4u16.to_le_bytes());
+    entry[0x10..0x14].copy_from_slice(&security_id.to_le_bytes());
+    entry.extend_from_slice(&sds_entry(security_id, offset)[..0x14]);
+    entry
+}
+
+///A volume whose $Secure (9) has security ids 0x100 and 0x101 in $SDS at cluster 0x41,
+///mirrored 256K further on, and record 20 is a file with security id 0x101.
+fn secure_volume() -> Vec<u8> {
+    let mut sii = common::index_root(&[sii_entry(0x100, 0), sii_entry(0x101, 0x80)]);
+    sii[0x00..0x04].copy_from_slice(&0u32.to_le_bytes());
+    sii[0x04..0x08].copy_from_slice(&0x10u32.to_le_bytes());
+
+    let mut records = vec![Vec::new(); 21];
+    records[0] = common::mft_record_0();
+    records[9] = common::protected(common::record_with(
+        9,
+        0x0001,
+        &[
+            common::resident(0x30, "", &common::file_name(SEQ | 5, "$Secure", 3, 0x06)),
+            common::non_resident(0x80, "$SDS", &[0x22, 0x80, 0x00, 0x41, 0x00], 0x80, 0x80000),
+            common::resident(0x90, "$SII", &sii),
+        ],
+    ));
+    let mut standard_information = common::standard_information(0, 0x20);
+    standard_information[0x34..0x38].copy_from_slice(&0x101u32.to_le_bytes());
+    records[20] = common::protected(common::record_with(
+        20,
+        0x0001,
+        &[
+            common::resident(0x10, "", &standard_information),
+            common::resident(0x30, "", &common::file_name(SEQ | 5, "secret.txt", 1, 0x20)),
+        ],
+    ));
+
+    let mut image = common::volume_with_mft(&records);
+    let sds = 0x41 * 4096;
+    for mirror in [0, 0x40000] {
+        for (id, offset) in [(0x100, 0), (0x101, 0x80)] {
+            let entry = sds_entry(id, offset + mirror as u64);
+            let start = sds + mirror + offset as usize;
+            image[start..start + entry.len()].copy_from_slice(&entry);
+        }
+    }
+    image
+}
+
+#[test]
+fn parses_security_descriptor() {
+    let descriptor = SecurityDescriptor::parse(&descriptor()).unwrap();
+    assert_eq!(descriptor.owner.unwrap().to_string(), "S-1-5-32-544");
+    assert_eq!(descriptor.group.unwrap().to_string(), "S-1-5-18");
+    assert!(descriptor.sacl.is_none());
+
+    let dacl = descriptor.dacl.unwrap();
+    assert_eq!(dacl.aces.len(), 2);
+    let system = &dacl.aces[0];
+    assert!(system.is_access_allowed() && !system.is_access_denied());
+    assert!(system.is_inherited());
+    assert!(system.covers(Ace::FILE_WRITE_DATA | Ace::DELETE));
+    let users = &dacl.aces[1];
+    assert_eq!(users.sid.to_string(), "S-1-5-32-545");
+    assert!(users.covers(Ace::FILE_READ_DATA));
+    assert!(!users.covers(Ace::FILE_WRITE_DATA));
+
+    let mut absolute = self::descriptor();
+    absolute[0x03] = 0;
+    assert!(matches!(
+        SecurityDescriptor::parse(&absolute),
+        Err(NtfsError::Unsupported(_))
+    ));
+}
+
+#[test]
+fn parses_object_ace() {
+    let mut object_ace = vec![Ace::ACCESS_ALLOWED_OBJECT, 0, 0, 0];
+    object_ace.extend_from_slice(&Ace::GENERIC_READ.to_le_bytes());
+    object_ace.extend_from_slice(&2u32.to_le_bytes());
+    object_ace.extend_from_slice(&[0x42; 16]);
+    object_ace.extend_from_slice(&sid(5, &[11]));
+    let len = object_ace.len() as u16;
+    object_ace[0x02..0x04].copy_from_slice(&len.to_le_bytes());
+
+    let (ace, size) = Ace::parse(&object_ace).unwrap();
+    assert_eq!(size, object_ace.len());
+    assert_eq!(ace.object_type, None);
+    assert_eq!(ace.inherited_object_type, Some(Guid([0x42; 16])));
+    assert_eq!(ace.sid.to_string(), "S-1-5-11");
+    assert!(ace.covers(Ace::FILE_GENERIC_READ));
+}
+
+#[test]
+fn looks_up_descriptors_through_sii() {
+    let mut ntfs = Ntfs::new(Cursor::new(secure_volume())).unwrap();
+    let descriptor = ntfs.security_descriptor_by_id(0x101).unwrap().unwrap();
+    assert_eq!(
+        descriptor,
+        SecurityDescriptor::parse(&self::descriptor()).unwrap()
+    );
+    assert!(ntfs.security_descriptor_by_id(0x100).unwrap().is_some());
+    assert!(ntfs.security_descriptor_by_id(0x102).unwrap().is_none());
+
+    let record = ntfs.file_record(20).unwrap();
+    let descriptor = ntfs.security_descriptor(&record).unwrap().unwrap();
+    assert_eq!(descriptor.owner.unwrap().to_string(), "S-1-5-32-544");
+}
+
+#[test]
+fn rejects_oversized_sii_length() {
+    let mut image = secure_volume();
+    let needle = sii_entry(0x101, 0x80);
+    let position = image
+        .windows(needle.len())
+        .position(|window| window == needle.as_slice())
+        .unwrap();
+    image[position + 0x24..position + 0x28].copy_from_slice(&0xFFFF_FFF0u32.to_le_bytes());
+
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    assert!(matches!(
+        ntfs.security_descriptor_by_id(0x101),
+        Err(NtfsError::Corrupt(_))
+    ));
+    assert!(ntfs.security_descriptor_by_id(0x100).unwrap().is_some());
+}
+
+#[test]
+fn reads_sds_entries_without_mirrors() {
+    let mut ntfs = Ntfs::new(Cursor::new(secure_volume())).unwrap();
+    let entries = ntfs.sds_entries().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].security_id, 0x100);
+    assert_eq!(entries[1].security_id, 0x101);
+    assert_eq!(entries[1].offset, 0x80);
+    assert_eq!(entries[1].hash, 0xABCD_0101);
+    assert_eq!(entries[1].length as usize, sds_entry(0x101, 0x80).len());
+}
+
+///A self-relative descriptor owned by Local System, with no DACL.
+fn system_descriptor() -> Vec<u8> {
+    let control = SecurityDescriptor::SELF_RELATIVE;
+    let mut descriptor = vec![1, 0];
+    descriptor.extend_from_slice(&control.to_le_bytes());
+    for offset in [0x14u32, 0x20, 0, 0] {
+        descriptor.extend_from_slice(&offset.to_le_bytes());
+    }
+    descriptor.extend_from_slice(&sid(5, &[18]));
+    descriptor.extend_from_slice(&sid(5, &[18]));
+    descriptor
+}
+
+#[test]
+fn reads_security_descriptor_attribute() {
+    let value = system_descriptor();
+    let record = FileRecord::parse(&common::protected(common::record_with(
+        40,
+        0x0001,
+        &[
+            common::resident(0x30, "", &common::file_name(5, "boot.ini", 1, 0x06)),
+            common::resident(0x50, "", &value),
+        ],
+    )))
+    .unwrap();
+    let descriptor = record.security_descriptor().unwrap().unwrap();
+    assert_eq!(descriptor.revision, 1);
+    assert_eq!(descriptor.control, SecurityDescriptor::SELF_RELATIVE);
+    assert_eq!(descriptor.owner.as_ref().unwrap().to_string(), "S-1-5-18");
+    assert_eq!(
+        descriptor.owner.as_ref().unwrap().well_known_name(),
+        Some("SYSTEM")
+    );
+    assert_eq!(descriptor.group.unwrap().to_string(), "S-1-5-18");
+    assert!(descriptor.dacl.is_none() && descriptor.sacl.is_none());
+
+    //The attribute is used before the security id, which this volume has no $Secure for.
+    let mut ntfs = Ntfs::new(Cursor::new(common::numbered_volume())).unwrap();
+    let from_volume = ntfs.security_descriptor(&record).unwrap().unwrap();
+    assert_eq!(from_volume.owner.unwrap().to_string(), "S-1-5-18");
+
+    let plain = FileRecord::parse(&common::file_in(31, 5, "readme.txt")).unwrap();
+    assert_eq!(plain.security_descriptor().unwrap(), None);
+}
+
+#[test]
+fn reads_non_resident_security_descriptor_attribute() {
+    let value = system_descriptor();
+    let record = FileRecord::parse(&common::protected(common::record_with(
+        40,
+        0x0001,
+        &[common::non_resident(
+            0x50,
+            "",
+            &[0x11, 0x01, 0x30],
+            1,
+            value.len() as u64,
+        )],
+    )))
+    .unwrap();
+    assert!(matches!(
+        record.security_descriptor(),
+        Err(NtfsError::Unsupported(_))
+    ));
+
+    let mut image = common::numbered_volume();
+    image[0x30 * 4096..0x30 * 4096 + value.len()].copy_from_slice(&value);
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    let descriptor = ntfs.security_descriptor(&record).unwrap().unwrap();
+    assert_eq!(descriptor.owner.unwrap().to_string(), "S-1-5-18");
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "serde")]
+mod common;
+
+use ntfs::*;
+use std::io::Cursor;
+
+#[test]
+fn size_is_externally_tagged() {
+    assert_eq!(
+        serde_json::to_string(&Size::Bytes(1024)).unwrap(),
+        r#"{"Bytes":1024}"#
+    );
+    assert_eq!(
+        serde_json::to_string(&Size::Clusters(8)).unwrap(),
+        r#"{"Clusters":8}"#
+    );
+    let size: Size = serde_json::from_str(r#"{"Clusters":2}"#).unwrap();
+    assert_eq!(size, Size::Clusters(2));
+}
+
+#[test]
+fn boot_sector_round_trip() {
+    let pbs = pbs(&mut Cursor::new(common::boot_sector())).unwrap();
+    let json = serde_json::to_string(&pbs).unwrap();
+    let parsed: PartitionBootSector = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, pbs);
+
+    let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+    value["bootstrap_code"] = serde_json::json!([0, 1, 2]);
+    assert!(serde_json::from_value::<PartitionBootSector>(value).is_err());
+}
+
+#[test]
+fn file_record_round_trip() {
+    let record = FileRecord::parse(&common::mft_record_0()).unwrap();
+    let json = serde_json::to_string(&record).unwrap();
+    let parsed: FileRecord = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, record);
+    assert_eq!(parsed.file_names().unwrap()[0].name, "$MFT");
+
+    let standard_information = record.standard_information().unwrap().unwrap();
+    let json = serde_json::to_string(&standard_information).unwrap();
+    assert_eq!(
+        serde_json::from_str::<StandardInformation>(&json).unwrap(),
+        standard_information
+    );
+
+    let file_name = record.file_names().unwrap().remove(0);
+    let json = serde_json::to_string(&file_name).unwrap();
+    assert_eq!(serde_json::from_str::<FileName>(&json).unwrap(), file_name);
+
+    for attribute in record.attributes() {
+        let header = attribute.unwrap().header().clone();
+        let json = serde_json::to_string(&header).unwrap();
+        assert_eq!(
+            serde_json::from_str::<AttributeHeader>(&json).unwrap(),
+            header
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use ntfs::*;
+
+fn sid(authority: u8, sub_authorities: &[u32]) -> Vec<u8> {
+    let mut sid = vec![1, sub_authorities.len() as u8, 0, 0, 0, 0, 0, authority];
+    for sub_authority in sub_authorities {
+        sid.extend_from_slice(&sub_authority.to_le_bytes());
+    }
+    sid
+}
+
+#[test]
+fn formats_sids() {
+    let (parsed, len) = Sid::parse(&sid(5, &[21, 1004336348, 1177238915, 682003330, 512])).unwrap();
+    assert_eq!(len, 28);
+    assert_eq!(
+        parsed.to_string(),
+        "S-1-5-21-1004336348-1177238915-682003330-512"
+    );
+    assert_eq!(parsed.rid(), Some(512));
+    assert_eq!(Sid::parse(&sid(1, &[0])).unwrap().0.to_string(), "S-1-1-0");
+
+    let mut large = sid(0, &[7]);
+    large[2] = 0x01;
+    assert_eq!(
+        Sid::parse(&large).unwrap().0.to_string(),
+        "S-1-0x010000000000-7"
+    );
+
+    assert!(matches!(
+        Sid::parse(&sid(5, &[32, 544])[..12]),
+        Err(NtfsError::BufferTooSmall { needed: 16, .. })
+    ));
+}
+
+#[test]
+fn round_trips_between_binary_and_string() {
+    let max: Vec<u32> = (0..MAX_SUB_AUTHORITIES as u32)
+        .map(|i| u32::MAX - i)
+        .collect();
+    for bytes in [
+        sid(5, &[18]),
+        sid(5, &[21, 1004336348, 1177238915, 682003330, 1001]),
+        sid(16, &[12288]),
+        sid(5, &[]),
+        sid(5, &max),
+    ] {
+        let (parsed, len) = Sid::parse(&bytes).unwrap();
+        assert_eq!(len, bytes.len());
+        assert_eq!(parsed.to_bytes(), bytes);
+        let string = parsed.to_string();
+        assert_eq!(string.parse::<Sid>().unwrap(), parsed);
+    }
+
+    let string = "S-1-0x010000000000-7";
+    assert_eq!(string.parse::<Sid>().unwrap().to_string(), string);
+    assert_eq!(
+        "s-1-5-32-544".parse::<Sid>().unwrap().to_string(),
+        "S-1-5-32-544"
+    );
+}
+
+#[test]
+fn rejects_invalid_sids() {
+    let mut too_many = sid(5, &[0; 15]);
+    too_many[1] = 16;
+    too_many.extend_from_slice(&[0; 4]);
+    assert!(matches!(Sid::parse(&too_many), Err(NtfsError::Corrupt(_))));
+
+    let sixteen = format!("S-1-5{}", "-1".repeat(16));
+    for invalid in [
+        "",
+        "S",
+        "X-1-5-18",
+        "S-1",
+        "S-1-5-",
+        "S-1-5-abc",
+        "S-1-5-4294967296",
+        "S-1-0x1000000000000-1",
+        sixteen.as_str(),
+    ] {
+        assert!(
+            matches!(invalid.parse::<Sid>(), Err(NtfsError::InvalidSid(s)) if s == invalid),
+            "{invalid}"
+        );
+    }
+}
+
+#[test]
+fn names_well_known_sids() {
+    for (sid, name) in [
+        ("S-1-1-0", "Everyone"),
+        ("S-1-5-18", "SYSTEM"),
+        ("S-1-5-32-544", "Administrators"),
+        ("S-1-5-32-545", "Users"),
+        ("S-1-3-0", "CREATOR OWNER"),
+        (
+            "S-1-5-21-1004336348-1177238915-682003330-500",
+            "Administrator",
+        ),
+        ("S-1-16-12288", "High Mandatory Level"),
+    ] {
+        assert_eq!(sid.parse::<Sid>().unwrap().well_known_name(), Some(name));
+    }
+    for sid in [
+        "S-1-5-21-1004336348-1177238915-682003330-1001",
+        "S-1-5-32",
+        "S-2-1-0",
+    ] {
+        assert_eq!(sid.parse::<Sid>().unwrap().well_known_name(), None);
+    }
+}
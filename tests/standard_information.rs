@@ -0,0 +1,68 @@
+mod common;
+
+use ntfs::*;
+
+#[test]
+fn parses_timestamps_and_flags() {
+    let mut value = common::standard_information(0x01D9_1234_5678_9ABC, 0x0023);
+    value[0x18..0x20].copy_from_slice(&0x01DA_0000_0000_0000u64.to_le_bytes());
+    let info = StandardInformation::parse(&value).unwrap();
+    assert_eq!(info.creation_time.raw(), 0x01D9_1234_5678_9ABC);
+    assert_eq!(info.modified_time.raw(), 0x01D9_1234_5678_9ABC);
+    assert_eq!(info.mft_changed_time.raw(), 0x01D9_1234_5678_9ABC);
+    assert_eq!(info.accessed_time.raw(), 0x01DA_0000_0000_0000);
+    assert!(info.is_read_only());
+    assert!(info.is_hidden());
+    assert!(!info.is_system());
+    assert!(info.is_archive());
+}
+
+#[test]
+fn rejects_short_value() {
+    assert!(matches!(
+        StandardInformation::parse(&[0; 0x20]),
+        Err(NtfsError::BufferTooSmall { .. })
+    ));
+}
+
+#[test]
+fn parses_v3_fields() {
+    let mut value = common::standard_information(0, 0x0800);
+    value[0x30..0x34].copy_from_slice(&7u32.to_le_bytes());
+    value[0x34..0x38].copy_from_slice(&0x100u32.to_le_bytes());
+    value[0x38..0x40].copy_from_slice(&4096u64.to_le_bytes());
+    value[0x40..0x48].copy_from_slice(&0x1234_5678u64.to_le_bytes());
+    let info = StandardInformation::parse(&value).unwrap();
+    assert_eq!(info.owner_id, Some(7));
+    assert_eq!(info.security_id, Some(0x100));
+    assert_eq!(info.quota_charged, Some(4096));
+    assert_eq!(info.usn, Some(0x1234_5678));
+    assert!(info.dos_flags.contains(FileAttributes::COMPRESSED));
+    assert_eq!(info.dos_flags.bits(), 0x0800);
+}
+
+#[test]
+fn parses_v1_layout() {
+    let value = common::standard_information(0x01D9_0000_0000_0000, 0x0004);
+    let info = StandardInformation::parse(&value[..STANDARD_INFORMATION_SIZE]).unwrap();
+    assert_eq!(info.creation_time, NtfsTime(0x01D9_0000_0000_0000));
+    assert!(info.is_system());
+    assert_eq!(info.owner_id, None);
+    assert_eq!(info.security_id, None);
+    assert_eq!(info.quota_charged, None);
+    assert_eq!(info.usn, None);
+}
+
+#[test]
+fn record_standard_information() {
+    let si = common::resident(0x10, "", &common::standard_information(42, 0x0020));
+    let record = common::protected(common::record_with(64, 1, &[si]));
+    let record = FileRecord::parse(&record).unwrap();
+    let info = record.standard_information().unwrap().unwrap();
+    assert_eq!(info.creation_time.raw(), 42);
+    assert!(info.is_archive());
+
+    let record = common::protected(common::record_with(65, 1, &[]));
+    let record = FileRecord::parse(&record).unwrap();
+    assert!(record.standard_information().unwrap().is_none());
+}
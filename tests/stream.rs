@@ -0,0 +1,102 @@
+mod common;
+
+use ntfs::*;
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+
+///Two clusters at 0x20, a two cluster hole, then one cluster at 0x30, with 10 bytes used in the last cluster.
+fn fragmented() -> Vec<u8> {
+    common::non_resident(
+        0x80,
+        "",
+        &[0x11, 0x02, 0x20, 0x01, 0x02, 0x11, 0x01, 0x10],
+        5,
+        4 * 4096 + 10,
+    )
+}
+
+#[test]
+fn sequential_reads() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::numbered_volume())).unwrap();
+    let attr = fragmented();
+    let attr = Attribute::parse(&attr).unwrap();
+    let expected = ntfs.read_attribute(&attr).unwrap();
+
+    let mut stream = ntfs.stream_reader(&attr).unwrap();
+    assert_eq!(stream.len(), 4 * 4096 + 10);
+    let mut content = Vec::new();
+    let mut buf = [0u8; 1000];
+    loop {
+        let n = stream.read(&mut buf).unwrap();
+        if n == 0 {
+            break;
+        }
+        content.extend_from_slice(&buf[..n]);
+    }
+    assert_eq!(content, expected);
+
+    let mut copied = Vec::new();
+    stream.seek(SeekFrom::Start(0)).unwrap();
+    io::copy(&mut stream, &mut copied).unwrap();
+    assert_eq!(copied, expected);
+}
+
+#[test]
+fn seeks_across_run_boundaries() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::numbered_volume())).unwrap();
+    let attr = fragmented();
+    let attr = Attribute::parse(&attr).unwrap();
+    let mut stream = ntfs.stream_reader(&attr).unwrap();
+
+    let mut buf = [0u8; 8];
+    stream.seek(SeekFrom::Start(4096 - 4)).unwrap();
+    stream.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [0x20, 0x20, 0x20, 0x20, 0x21, 0x21, 0x21, 0x21]);
+
+    stream.seek(SeekFrom::End(-4)).unwrap();
+    let mut tail = Vec::new();
+    stream.read_to_end(&mut tail).unwrap();
+    assert_eq!(tail, [0x30; 4]);
+
+    stream.seek(SeekFrom::Current(-8)).unwrap();
+    assert_eq!(stream.position(), 4 * 4096 + 2);
+    assert!(stream.seek(SeekFrom::Current(-100_000)).is_err());
+}
+
+#[test]
+fn read_from_hole_into_allocated_run() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::numbered_volume())).unwrap();
+    let attr = fragmented();
+    let attr = Attribute::parse(&attr).unwrap();
+    let mut stream = ntfs.stream_reader(&attr).unwrap();
+
+    let mut buf = [0xFFu8; 8];
+    stream.seek(SeekFrom::Start(4 * 4096 - 4)).unwrap();
+    stream.read_exact(&mut buf).unwrap();
+    assert_eq!(buf, [0, 0, 0, 0, 0x30, 0x30, 0x30, 0x30]);
+}
+
+#[test]
+fn honors_initialized_size() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::numbered_volume())).unwrap();
+    let mut attr = common::non_resident(0x80, "", &[0x11, 0x02, 0x20], 2, 6000);
+    attr[0x38..0x40].copy_from_slice(&5000u64.to_le_bytes());
+    let attr = Attribute::parse(&attr).unwrap();
+    let mut stream = ntfs.stream_reader(&attr).unwrap();
+
+    let mut content = Vec::new();
+    stream.read_to_end(&mut content).unwrap();
+    assert_eq!(content.len(), 6000);
+    assert!(content[4096..5000].iter().all(|b| *b == 0x21));
+    assert!(content[5000..].iter().all(|b| *b == 0));
+}
+
+#[test]
+fn resident_attributes_are_not_streamed() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::numbered_volume())).unwrap();
+    let attr = common::resident(0x80, "", b"small");
+    let attr = Attribute::parse(&attr).unwrap();
+    assert!(matches!(
+        ntfs.stream_reader(&attr),
+        Err(NtfsError::Unsupported(_))
+    ));
+}
@@ -0,0 +1,72 @@
+use ntfs::*;
+use std::time::{Duration, UNIX_EPOCH};
+
+//2024-01-01 00:00:00 UTC
+const NEW_YEAR_2024: u64 = 133_485_408_000_000_000;
+
+#[test]
+fn zero_is_1601() {
+    assert_eq!(filetime_to_unix_seconds(0), -11_644_473_600);
+    assert!(matches!(
+        filetime_to_system_time(0),
+        Err(NtfsError::TimeBeforeUnixEpoch(0))
+    ));
+}
+
+#[test]
+fn unix_epoch() {
+    let epoch = 116_444_736_000_000_000;
+    assert_eq!(filetime_to_unix_seconds(epoch), 0);
+    assert_eq!(filetime_to_system_time(epoch).unwrap(), UNIX_EPOCH);
+}
+
+#[test]
+fn modern_timestamp() {
+    assert_eq!(filetime_to_unix_seconds(NEW_YEAR_2024), 1_704_067_200);
+    assert_eq!(
+        filetime_to_system_time(NEW_YEAR_2024 + 5).unwrap(),
+        UNIX_EPOCH + Duration::new(1_704_067_200, 500)
+    );
+}
+
+#[test]
+fn ntfs_time_unix_seconds() {
+    assert_eq!(NtfsTime(NEW_YEAR_2024).to_unix_seconds(), 1_704_067_200);
+    assert_eq!(NtfsTime(0).to_unix_seconds(), -11_644_473_600);
+    assert_eq!(NtfsTime(NEW_YEAR_2024).raw(), NEW_YEAR_2024);
+}
+
+#[test]
+fn ntfs_time_system_time() {
+    assert_eq!(
+        NtfsTime(NEW_YEAR_2024).to_system_time(),
+        Some(UNIX_EPOCH + Duration::from_secs(1_704_067_200))
+    );
+    //1969-12-31 23:59:59
+    let before_epoch = NtfsTime(116_444_736_000_000_000 - 10_000_000);
+    assert_eq!(
+        before_epoch.to_system_time(),
+        UNIX_EPOCH.checked_sub(Duration::from_secs(1))
+    );
+    //Must not panic, whether or not the platform can represent it.
+    let _ = NtfsTime(0).to_system_time();
+    let _ = NtfsTime(u64::MAX).to_system_time();
+}
+
+#[test]
+fn ntfs_time_display() {
+    assert_eq!(
+        NtfsTime(NEW_YEAR_2024 + 1234567).to_string(),
+        "2024-01-01T00:00:00.1234567Z"
+    );
+    assert_eq!(NtfsTime(0).to_string(), "1601-01-01T00:00:00.0000000Z");
+    //2000-02-29 12:34:56
+    assert_eq!(
+        NtfsTime(125_963_012_960_000_000).to_string(),
+        "2000-02-29T12:34:56.0000000Z"
+    );
+    assert_eq!(
+        NtfsTime(u64::MAX).to_string(),
+        "60056-05-28T05:36:10.9551615Z"
+    );
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use ntfs::*;
+use std::{io::Cursor, path::PathBuf};
+
+const SEQ: u64 = 0x0001_0000_0000_0000;
+
+fn record(record_number: u32, flags: u16, sequence: u16, attributes: &[Vec<u8>]) -> Vec<u8> {
+    let mut record = common::record_with(record_number, flags, attributes);
+    record[0x10..0x12].copy_from_slice(&sequence.to_le_bytes());
+    common::protected(record)
+}
+
+///A volume where cluster 0x70 is the only one in use, with:
+///- record 40, "lost.bin" deleted from the root, 2 clusters at 0x60 and 1 reallocated at 0x70
+///- record 41, the directory "olddir", deleted and its sequence number bumped to 2
+///- record 42, "note.txt" deleted from "olddir", resident
+///- record 43, "alive.txt", in use
+fn volume() -> Vec<u8> {
+    let mut records = vec![Vec::new(); 44];
+    records[0] = common::mft_record_0();
+    let mut bitmap = [0u8; 32];
+    bitmap[0x70 / 8] = 0x01;
+    records[6] = record(
+        6,
+        0x0001,
+        1,
+        &[
+            common::resident(0x30, "", &common::file_name(SEQ | 5, "$Bitmap", 3, 0x06)),
+            common::resident(0x80, "", &bitmap),
+        ],
+    );
+    records[40] = record(
+        40,
+        0x0000,
+        2,
+        &[
+            common::resident(
+                0x10,
+                "",
+                &common::standard_information(0x01D0_0000_0000_0000, 0x20),
+            ),
+            common::resident(0x30, "", &common::file_name(SEQ | 5, "lost.bin", 1, 0x20)),
+            common::non_resident(0x80, "", &[0x11, 0x02, 0x60, 0x11, 0x01, 0x10], 3, 8292),
+        ],
+    );
+    records[41] = record(
+        41,
+        0x0002,
+        2,
+        &[common::resident(
+            0x30,
+            "",
+            &common::file_name(SEQ | 5, "olddir", 1, 0x1000_0000),
+        )],
+    );
+    records[42] = record(
+        42,
+        0x0000,
+        2,
+        &[
+            common::resident(0x30, "", &common::file_name(SEQ | 41, "note.txt", 1, 0x20)),
+            common::resident(0x80, "", b"hello"),
+        ],
+    );
+    records[43] = record(
+        43,
+        0x0001,
+        1,
+        &[
+            common::resident(0x30, "", &common::file_name(SEQ | 5, "alive.txt", 1, 0x20)),
+            common::resident(0x80, "", b"still here"),
+        ],
+    );
+    let mut image = common::volume_with_mft(&records);
+    image[0x60 * 4096..0x62 * 4096].fill(0xAB);
+    image[0x70 * 4096..0x71 * 4096].fill(0xCD);
+    image
+}
+
+#[test]
+fn finds_deleted_files() {
+    let mut ntfs = Ntfs::new(Cursor::new(volume())).unwrap();
+    let mut mft = ntfs.master_file_table().unwrap();
+    let files: Vec<DeletedFile> = mft.deleted_files().unwrap().map(Result::unwrap).collect();
+    let numbers: Vec<u64> = files.iter().map(|file| file.record_number).collect();
+    assert_eq!(numbers, [40, 42]);
+
+    let lost = &files[0];
+    assert_eq!(lost.path, Some(PathBuf::from("lost.bin")));
+    assert!(!lost.parent_reused);
+    assert_eq!(lost.size, 8292);
+    assert!(!lost.is_resident());
+    assert_eq!(lost.reallocated, [false, true]);
+    assert_eq!(lost.recoverable_percent(), 50.0);
+    assert!(lost.standard_information.is_some());
+
+    let note = &files[1];
+    assert_eq!(note.path, Some(PathBuf::from("olddir").join("note.txt")));
+    assert!(note.parent_reused);
+    assert!(note.is_resident());
+    assert_eq!(note.recoverable_percent(), 100.0);
+}
+
+#[test]
+fn extracts_what_is_left() {
+    let mut ntfs = Ntfs::new(Cursor::new(volume())).unwrap();
+    let files: Vec<DeletedFile> = ntfs
+        .master_file_table()
+        .unwrap()
+        .deleted_files()
+        .unwrap()
+        .map(Result::unwrap)
+        .collect();
+
+    let mut out = Vec::new();
+    assert_eq!(ntfs.extract_deleted(&files[0], &mut out).unwrap(), 8192);
+    assert_eq!(out.len(), 8292);
+    assert!(out[..8192].iter().all(|b| *b == 0xAB));
+    assert!(out[8192..].iter().all(|b| *b == 0));
+
+    let mut out = Vec::new();
+    assert_eq!(ntfs.extract_deleted(&files[1], &mut out).unwrap(), 5);
+    assert_eq!(out, b"hello");
+}
@@ -0,0 +1,215 @@
+mod common;
+
+use ntfs::*;
+use std::io::Cursor;
+
+const SEQ: u64 = 0x0001_0000_0000_0000;
+
+fn usn_record(
+    major_version: u16,
+    usn: u64,
+    file_reference: u64,
+    reason: UsnReason,
+    name: &str,
+) -> Vec<u8> {
+    let name: Vec<u8> = name.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    let (size, shift) = if major_version == 3 {
+        (USN_RECORD_V3_SIZE, 0x10)
+    } else {
+        (USN_RECORD_V2_SIZE, 0)
+    };
+    let mut record = vec![0u8; size];
+    record.extend_from_slice(&name);
+    record.resize(record.len().next_multiple_of(8), 0);
+    let len = record.len() as u32;
+    record[0x00..0x04].copy_from_slice(&len.to_le_bytes());
+    record[0x04..0x06].copy_from_slice(&major_version.to_le_bytes());
+    record[0x08..0x10].copy_from_slice(&file_reference.to_le_bytes());
+    let parent = if major_version == 3 { 0x18 } else { 0x10 };
+    record[parent..parent + 8].copy_from_slice(&(SEQ | 5).to_le_bytes());
+    record[0x18 + shift..0x20 + shift].copy_from_slice(&usn.to_le_bytes());
+    record[0x20 + shift..0x28 + shift].copy_from_slice(&0x01D9_0000_0000_0000u64.to_le_bytes());
+    record[0x28 + shift..0x2C + shift].copy_from_slice(&reason.bits().to_le_bytes());
+    record[0x34 + shift..0x38 + shift].copy_from_slice(&0x20u32.to_le_bytes());
+    record[0x38 + shift..0x3A + shift].copy_from_slice(&(name.len() as u16).to_le_bytes());
+    record[0x3A + shift..0x3C + shift].copy_from_slice(&(size as u16).to_le_bytes());
+    record
+}
+
+fn max(lowest_valid_usn: u64) -> Vec<u8> {
+    let mut value = vec![0u8; 0x20];
+    value[0x00..0x08].copy_from_slice(&0x0200_0000u64.to_le_bytes());
+    value[0x08..0x10].copy_from_slice(&0x0080_0000u64.to_le_bytes());
+    value[0x10..0x18].copy_from_slice(&0x01D9_1234_5678_9ABCu64.to_le_bytes());
+    value[0x18..0x20].copy_from_slice(&lowest_valid_usn.to_le_bytes());
+    value
+}
+
+///A volume with \$Extend\$UsnJrnl (30), whose $J starts with two sparse clusters followed by
+///two clusters at 0x60. The first page holds a V2, a V3 and a V4 record, the second a V2 record.
+fn journal_volume() -> Vec<u8> {
+    const DIR: u32 = 0x1000_0000;
+    let mut records = vec![Vec::new(); 31];
+    records[0] = common::mft_record_0();
+    records[5] = common::directory_record(
+        5,
+        SEQ | 5,
+        ".",
+        &[common::child(11, 5, "$Extend", 3, DIR | 0x06)],
+    );
+    records[11] = common::directory_record(
+        11,
+        SEQ | 5,
+        "$Extend",
+        &[common::child(30, 11, "$UsnJrnl", 3, 0x26)],
+    );
+    records[30] = common::protected(common::record_with(
+        30,
+        0x0001,
+        &[
+            common::resident(0x30, "", &common::file_name(SEQ | 11, "$UsnJrnl", 3, 0x26)),
+            common::resident(0x80, "$Max", &max(0x1000)),
+            common::non_resident(0x80, "$J", &[0x01, 0x02, 0x11, 0x02, 0x60], 4, 0x4000),
+        ],
+    ));
+
+    let mut page = usn_record(2, 0x2000, SEQ | 40, UsnReason::FILE_CREATE, "a.txt");
+    let usn = 0x2000 + page.len() as u64;
+    page.extend_from_slice(&usn_record(
+        3,
+        usn,
+        SEQ | 40,
+        UsnReason::RENAME_NEW_NAME | UsnReason::CLOSE,
+        "b.txt",
+    ));
+    let mut v4 = vec![0u8; 0x40];
+    v4[0x00] = 0x40;
+    v4[0x04] = 4;
+    page.extend_from_slice(&v4);
+
+    let mut image = common::volume_with_mft(&records);
+    image[0x60 * 4096..0x60 * 4096 + page.len()].copy_from_slice(&page);
+    let last = usn_record(
+        2,
+        0x3000,
+        SEQ | 40,
+        UsnReason::FILE_DELETE | UsnReason::CLOSE,
+        "b.txt",
+    );
+    image[0x61 * 4096..0x61 * 4096 + last.len()].copy_from_slice(&last);
+    image
+}
+
+#[test]
+fn parses_v2_and_v3_records() {
+    let record = UsnRecord::parse(&usn_record(
+        2,
+        0x2000,
+        SEQ | 40,
+        UsnReason::FILE_CREATE,
+        "a.txt",
+    ))
+    .unwrap();
+    assert_eq!(record.major_version, 2);
+    assert_eq!(record.usn, 0x2000);
+    assert_eq!(record.file_reference.record_number, 40);
+    assert_eq!(record.parent_reference.record_number, 5);
+    assert_eq!(record.timestamp, NtfsTime(0x01D9_0000_0000_0000));
+    assert_eq!(record.reason, UsnReason::FILE_CREATE);
+    assert!(record.file_attributes.contains(FileAttributes::ARCHIVE));
+    assert_eq!(record.file_name, "a.txt");
+
+    let bytes = usn_record(3, 0x2050, SEQ | 41, UsnReason::CLOSE, "report.docx");
+    let record = UsnRecord::parse(&bytes).unwrap();
+    assert_eq!(record.major_version, 3);
+    assert_eq!(record.record_length as usize, bytes.len());
+    assert_eq!(record.usn, 0x2050);
+    assert_eq!(record.file_reference.record_number, 41);
+    assert_eq!(record.parent_reference.record_number, 5);
+    assert_eq!(record.file_name, "report.docx");
+
+    let mut v4 = bytes.clone();
+    v4[0x04] = 4;
+    assert!(matches!(
+        UsnRecord::parse(&v4),
+        Err(NtfsError::Unsupported(_))
+    ));
+    assert!(matches!(
+        UsnRecord::parse(&bytes[..0x50]),
+        Err(NtfsError::Corrupt(_))
+    ));
+}
+
+#[test]
+fn decodes_reasons() {
+    let reason = UsnReason::FILE_CREATE | UsnReason::CLOSE | UsnReason(0x0100_0000);
+    assert!(reason.contains(UsnReason::FILE_CREATE | UsnReason::CLOSE));
+    assert!(!reason.contains(UsnReason::FILE_CREATE | UsnReason::FILE_DELETE));
+    assert!(reason.intersects(UsnReason::FILE_CREATE | UsnReason::FILE_DELETE));
+    assert_eq!(reason.names(), ["FILE_CREATE", "CLOSE"]);
+    assert_eq!(reason.bits(), 0x8100_0100);
+}
+
+#[test]
+fn reads_max() {
+    let mut ntfs = Ntfs::new(Cursor::new(journal_volume())).unwrap();
+    let journal = ntfs.usn_journal().unwrap();
+    let info = journal.info();
+    assert_eq!(info.maximum_size, 0x0200_0000);
+    assert_eq!(info.allocation_delta, 0x0080_0000);
+    assert_eq!(info.journal_id, 0x01D9_1234_5678_9ABC);
+    assert_eq!(info.lowest_valid_usn, 0x1000);
+    assert_eq!(journal.next_usn(), 0x4000);
+}
+
+#[test]
+fn iterates_records_skipping_holes_and_padding() {
+    let mut ntfs = Ntfs::new(Cursor::new(journal_volume())).unwrap();
+    let mut journal = ntfs.usn_journal().unwrap();
+    let records: Vec<UsnRecord> = journal
+        .records()
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    let names: Vec<(u64, &str)> = records
+        .iter()
+        .map(|record| (record.usn, record.file_name.as_str()))
+        .collect();
+    assert_eq!(
+        names,
+        [(0x2000, "a.txt"), (0x2048, "b.txt"), (0x3000, "b.txt")]
+    );
+    assert_eq!(records[1].major_version, 3);
+
+    let since: Vec<u64> = journal
+        .records_since(0x2048)
+        .unwrap()
+        .map(|record| record.unwrap().usn)
+        .collect();
+    assert_eq!(since, [0x2048, 0x3000]);
+}
+
+#[test]
+fn filters_by_reason() {
+    let mut ntfs = Ntfs::new(Cursor::new(journal_volume())).unwrap();
+    let mut journal = ntfs.usn_journal().unwrap();
+    let changes: Vec<UsnReason> = journal
+        .records_since(0)
+        .unwrap()
+        .with_reasons(UsnReason::FILE_CREATE | UsnReason::FILE_DELETE)
+        .map(|record| record.unwrap().reason)
+        .collect();
+    assert_eq!(
+        changes,
+        [
+            UsnReason::FILE_CREATE,
+            UsnReason::FILE_DELETE | UsnReason::CLOSE
+        ]
+    );
+}
+
+#[test]
+fn volume_without_journal() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::tree_volume())).unwrap();
+    assert!(matches!(ntfs.usn_journal(), Err(NtfsError::NotFound(_))));
+}
@@ -0,0 +1,110 @@
+mod common;
+
+use ntfs::*;
+use std::io::Cursor;
+
+#[test]
+fn new_reads_boot_sector() {
+    let mut image = common::boot_sector().to_vec();
+    image.resize(64 * 1024, 0);
+    common::fit_total_sectors(&mut image);
+    let ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    assert_eq!(ntfs.boot_sector().mft_cluster_number, 4);
+}
+
+#[test]
+fn new_rejects_non_ntfs() {
+    let image = vec![0u8; 4096];
+    assert!(Ntfs::new(Cursor::new(image)).is_err());
+}
+
+#[test]
+fn new_rejects_truncated_images() {
+    let mut image = common::numbered_volume();
+    image.truncate(512 * 1024);
+    assert!(matches!(
+        Ntfs::new(Cursor::new(image.clone())),
+        Err(NtfsError::TruncatedVolume {
+            declared: 0xFFE00,
+            available: 0x80000,
+        })
+    ));
+
+    let options = ParseOptions {
+        check_length: false,
+        ..ParseOptions::default()
+    };
+    let mut ntfs = Ntfs::new_with_options(Cursor::new(image), options).unwrap();
+    assert_eq!(ntfs.read_cluster(5).unwrap()[0], 5);
+}
+
+#[test]
+fn offset_is_excluded_from_the_available_length() {
+    let mut image = vec![0u8; 2048 * 512];
+    image.extend(&common::numbered_volume()[..1024 * 1024 - 4096]);
+    assert!(matches!(
+        Ntfs::new_at_offset(Cursor::new(image), 2048 * 512),
+        Err(NtfsError::TruncatedVolume {
+            available: 0xFF000,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn reads_sectors_and_clusters() {
+    let mut volume = Volume::new(Cursor::new(common::numbered_volume())).unwrap();
+
+    let sectors = volume.read_sectors(0, 1).unwrap();
+    assert_eq!(sectors, common::numbered_volume()[..512]);
+
+    let sectors = volume.read_sectors(17, 2).unwrap();
+    assert_eq!(sectors.len(), 1024);
+    assert!(sectors.iter().all(|&b| b == 2));
+
+    let cluster = volume.read_cluster(5).unwrap();
+    assert_eq!(cluster.len(), 4096);
+    assert!(cluster.iter().all(|&b| b == 5));
+
+    let clusters = volume.read_clusters(6, 3).unwrap();
+    assert_eq!(clusters.len(), 3 * 4096);
+    assert_eq!(clusters[0], 6);
+    assert_eq!(clusters[3 * 4096 - 1], 8);
+}
+
+#[test]
+fn read_past_end_fails() {
+    let mut volume = Volume::new(Cursor::new(common::numbered_volume())).unwrap();
+    assert!(matches!(volume.read_cluster(256), Err(NtfsError::Io(_))));
+}
+
+///A disk image with the tree volume in a partition starting at sector 2048.
+fn partitioned_image(hidden_sectors: u32) -> Vec<u8> {
+    let mut volume = common::tree_volume();
+    volume[0x1C..0x20].copy_from_slice(&hidden_sectors.to_le_bytes());
+    let mut image = vec![0u8; 2048 * 512];
+    image.extend(volume);
+    image
+}
+
+#[test]
+fn opens_volume_at_partition_offset() {
+    let image = partitioned_image(2048);
+    let mut ntfs = Ntfs::new_at_offset(Cursor::new(image), 2048 * 512).unwrap();
+    assert_eq!(ntfs.offset(), 2048 * 512);
+    assert_eq!(ntfs.boot_sector().hidden_sectors, 2048);
+
+    let record = ntfs.open_path(r"\Windows\System32\kernel32.dll").unwrap();
+    assert_eq!(record.mft_record_number, 34);
+    assert_eq!(ntfs.read_sectors(0, 1).unwrap()[3..11], *b"NTFS    ");
+}
+
+#[test]
+fn ignores_stale_hidden_sectors() {
+    for hidden_sectors in [0, 63] {
+        let image = partitioned_image(hidden_sectors);
+        let mut ntfs = Ntfs::new_at_offset(Cursor::new(image), 2048 * 512).unwrap();
+        assert_eq!(ntfs.boot_sector().hidden_sectors, hidden_sectors);
+        assert_eq!(ntfs.open_path(r"\Windows").unwrap().mft_record_number, 30);
+    }
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use ntfs::*;
+use std::io::Cursor;
+
+#[test]
+fn parses_volume_information() {
+    let info = VolumeInformation::parse(&common::volume_information(3, 1, 0x8001)).unwrap();
+    assert_eq!(info.major_version, 3);
+    assert_eq!(info.minor_version, 1);
+    assert_eq!(info.flags, 0x8001);
+    assert!(matches!(
+        VolumeInformation::parse(&[0; 8]),
+        Err(NtfsError::BufferTooSmall { needed: 0x0C, .. })
+    ));
+}
+
+#[test]
+fn reads_volume_info() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::labelled_volume("Windows", 0x0000))).unwrap();
+    let info = ntfs.info().unwrap();
+    assert_eq!(
+        info,
+        VolumeInfo {
+            label: "Windows".to_string(),
+            version: (3, 1),
+            dirty: false,
+            flags: 0,
+        }
+    );
+}
+
+#[test]
+fn dirty_volume_without_label() {
+    let flags = VolumeInformation::DIRTY | VolumeInformation::MODIFIED_BY_CHKDSK;
+    let mut ntfs = Ntfs::new(Cursor::new(common::labelled_volume("", flags))).unwrap();
+    let info = ntfs.info().unwrap();
+    assert_eq!(info.label, "");
+    assert!(info.dirty);
+    assert_eq!(info.flags, 0x8001);
+}
+
+#[test]
+fn parses_volume_name() {
+    let value: Vec<u8> = "OS".encode_utf16().flat_map(u16::to_le_bytes).collect();
+    assert_eq!(VolumeName::parse(&value).unwrap().label, "OS");
+    assert_eq!(VolumeName::parse(&[]).unwrap().label, "");
+    assert!(VolumeName::parse(&[0x4F]).is_err());
+}
+
+#[test]
+fn volume_info_of_dirty_volume() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::labelled_volume("OS", 0x0001))).unwrap();
+    let (label, information) = ntfs.volume_info().unwrap();
+    assert_eq!(label.as_deref(), Some("OS"));
+    assert!(information.is_dirty());
+    assert_eq!(
+        (information.major_version, information.minor_version),
+        (3, 1)
+    );
+
+    let mut ntfs = Ntfs::new(Cursor::new(common::labelled_volume("", 0))).unwrap();
+    let (label, information) = ntfs.volume_info().unwrap();
+    assert_eq!(label, None);
+    assert!(!information.is_dirty());
+}
@@ -0,0 +1,259 @@
+mod common;
+
+use ntfs::*;
+use std::{io::Cursor, path::PathBuf};
+
+#[test]
+fn walks_depth_first() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::tree_volume())).unwrap();
+    let walked: Vec<(PathBuf, u32)> = ntfs
+        .walk()
+        .map(|item| {
+            let entry = item.unwrap();
+            (entry.path, entry.record.mft_record_number)
+        })
+        .collect();
+
+    let expected = [
+        ("readme.txt", 31),
+        ("Windows", 30),
+        ("Windows/notepad.exe", 33),
+        ("Windows/System32", 32),
+        ("Windows/System32/kernel32.dll", 34),
+        ("Windows/System32/loop", 35),
+    ];
+    let expected: Vec<(PathBuf, u32)> = expected
+        .iter()
+        .map(|(path, n)| (PathBuf::from(path), *n))
+        .collect();
+    assert_eq!(walked, expected);
+}
+
+#[test]
+fn lists_directory_without_dos_names() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::tree_volume())).unwrap();
+    let root = ntfs.file_record(5).unwrap();
+    let names: Vec<String> = ntfs
+        .read_directory(&root)
+        .unwrap()
+        .into_iter()
+        .map(|entry| entry.file_name.name)
+        .collect();
+    assert_eq!(names, [".", "readme.txt", "Windows"]);
+}
+
+#[test]
+fn reports_unreadable_root() {
+    let image = common::volume_with_mft(&[common::mft_record_0()]);
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    let mut walk = ntfs.walk();
+    assert!(walk.next().unwrap().is_err());
+    assert!(walk.next().is_none());
+}
+
+///The path, record number and depth of every entry of `walk`.
+fn walked_paths(walk: Walk<'_, Cursor<Vec<u8>>>) -> Vec<(String, u64, usize)> {
+    walk.map(|item| {
+        let entry = item.unwrap();
+        let path = entry.path.to_str().unwrap().replace('\\', "/");
+        (path, entry.record_number, entry.depth)
+    })
+    .collect()
+}
+
+fn owned(expected: &[(&str, u64, usize)]) -> Vec<(String, u64, usize)> {
+    expected
+        .iter()
+        .map(|(path, n, depth)| (path.to_string(), *n, *depth))
+        .collect()
+}
+
+#[test]
+fn walks_from_a_subdirectory() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::tree_volume())).unwrap();
+    let walked = walked_paths(ntfs.walk_from(30).unwrap());
+    assert_eq!(
+        walked,
+        owned(&[
+            ("notepad.exe", 33, 1),
+            ("System32", 32, 1),
+            ("System32/kernel32.dll", 34, 2),
+            ("System32/loop", 35, 2),
+        ])
+    );
+}
+
+#[test]
+fn walk_from_follows_links() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::linked_volume())).unwrap();
+    let options = ResolveOptions {
+        follow_links: true,
+        drive_letter: 'C',
+    };
+    //Up is relative to Users\Public, so it lists Users even though the walk starts below it.
+    let mut paths = Vec::new();
+    let mut errors = Vec::new();
+    for item in ntfs
+        .walk_from_with_options(41, options)
+        .unwrap()
+        .max_depth(2)
+    {
+        match item {
+            Ok(entry) => paths.push((entry.path, entry.record.mft_record_number)),
+            Err(err) => errors.push(err),
+        }
+    }
+    let expected = [
+        ("notes.txt", 42),
+        ("Up", 44),
+        ("Up/escape", 46),
+        ("Up/link", 45),
+        ("Up/Public", 41),
+    ];
+    let expected: Vec<(PathBuf, u32)> = expected
+        .iter()
+        .map(|(path, n)| (PathBuf::from(path), *n))
+        .collect();
+    assert_eq!(paths, expected);
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(errors[0], NtfsError::LinkOutsideVolume { .. }));
+}
+
+#[test]
+fn walk_yields_file_names() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::tree_volume())).unwrap();
+    let entries: Vec<WalkEntry> = ntfs.walk().map(Result::unwrap).collect();
+    assert_eq!(entries.len(), 6);
+    let loop_entry = entries.iter().find(|e| e.file_name.name == "loop").unwrap();
+    assert_eq!(loop_entry.record_number, 35);
+    assert_eq!(loop_entry.depth, 3);
+    assert!(loop_entry.file_name.is_directory());
+    assert_eq!(loop_entry.file_name.parent_directory.record_number, 32);
+}
+
+#[test]
+fn walk_from_max_depth() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::tree_volume())).unwrap();
+    let walked = walked_paths(ntfs.walk_from(5).unwrap().max_depth(1));
+    assert_eq!(walked, owned(&[("readme.txt", 31, 1), ("Windows", 30, 1)]));
+
+    let walked = walked_paths(ntfs.walk_from(5).unwrap().max_depth(0));
+    assert!(walked.is_empty());
+}
+
+#[test]
+fn walk_from_filter_entry() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::tree_volume())).unwrap();
+    let walk = ntfs
+        .walk_from(5)
+        .unwrap()
+        .filter_entry(|entry| entry.file_name.name != "System32");
+    assert_eq!(
+        walked_paths(walk),
+        owned(&[
+            ("readme.txt", 31, 1),
+            ("Windows", 30, 1),
+            ("Windows/notepad.exe", 33, 2),
+        ])
+    );
+}
+
+#[test]
+fn walk_from_a_file() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::tree_volume())).unwrap();
+    assert!(matches!(
+        ntfs.walk_from(31),
+        Err(NtfsError::NotADirectory(_))
+    ));
+}
+
+#[test]
+fn walk_from_survives_directory_loop() {
+    //System32\loop is a plain directory instead of a junction, but it still lists the root.
+    const DIR: u32 = 0x1000_0000;
+    let mut image = common::tree_volume();
+    let system32 = common::directory_record(
+        32,
+        0x0001_0000_0000_001E,
+        "System32",
+        &[
+            common::child(34, 32, "kernel32.dll", 3, 0x20),
+            common::child(35, 32, "loop", 3, DIR),
+        ],
+    );
+    let offset = 4 * 4096 + 32 * 1024;
+    image[offset..offset + 1024].copy_from_slice(&system32);
+
+    let mut ntfs = Ntfs::new(Cursor::new(image)).unwrap();
+    let walked = walked_paths(ntfs.walk_from(5).unwrap());
+    assert_eq!(
+        walked,
+        owned(&[
+            ("readme.txt", 31, 1),
+            ("Windows", 30, 1),
+            ("Windows/notepad.exe", 33, 2),
+            ("Windows/System32", 32, 2),
+            ("Windows/System32/kernel32.dll", 34, 3),
+            ("Windows/System32/loop", 35, 3),
+            ("Windows/System32/loop/root", 5, 4),
+        ])
+    );
+}
+
+#[test]
+fn walk_follows_links_without_looping() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::linked_volume())).unwrap();
+    let options = ResolveOptions {
+        follow_links: true,
+        drive_letter: 'C',
+    };
+    let mut paths = Vec::new();
+    let mut errors = Vec::new();
+    for item in ntfs.walk_with_options(options) {
+        match item {
+            Ok(entry) => paths.push((entry.path, entry.record.mft_record_number)),
+            Err(err) => errors.push(err),
+        }
+    }
+
+    let expected = [
+        ("a", 47),
+        ("b", 48),
+        ("Documents and Settings", 43),
+        ("Documents and Settings/escape", 46),
+        ("Documents and Settings/link", 45),
+        ("Documents and Settings/Public", 41),
+        ("Documents and Settings/Public/notes.txt", 42),
+        ("Documents and Settings/Public/Up", 44),
+        ("Users", 40),
+        ("Users/escape", 46),
+        ("Users/link", 45),
+        ("Users/Public", 41),
+        ("Users/Public/notes.txt", 42),
+        ("Users/Public/Up", 44),
+    ];
+    let expected: Vec<(PathBuf, u32)> = expected
+        .iter()
+        .map(|(path, n)| (PathBuf::from(path), *n))
+        .collect();
+    assert_eq!(paths, expected);
+
+    //a and b point at each other, escape and link point off the volume, twice each.
+    assert_eq!(errors.len(), 6);
+    assert!(matches!(errors[0], NtfsError::TooManyLinks(_)));
+    assert!(matches!(errors[1], NtfsError::TooManyLinks(_)));
+    assert!(errors[2..]
+        .iter()
+        .all(|err| matches!(err, NtfsError::LinkOutsideVolume { .. })));
+}
+
+#[test]
+fn walk_lists_links_without_following_by_default() {
+    let mut ntfs = Ntfs::new(Cursor::new(common::linked_volume())).unwrap();
+    let paths: Vec<PathBuf> = ntfs.walk().map(|item| item.unwrap().path).collect();
+    assert!(paths.contains(&PathBuf::from("Documents and Settings")));
+    assert!(!paths
+        .iter()
+        .any(|path| path.starts_with("Documents and Settings/Public")));
+    assert_eq!(paths.len(), 9);
+}